@@ -0,0 +1,87 @@
+//! Throughput of proving many presentations under one policy: one after the
+//! other with `present`, and in parallel with `present_batch`, sharing the
+//! params and proving key, with one prover in flight per thread. See
+//! `common` for the parameters.
+
+mod common;
+
+use common::{list, mean_ns, threads, timed, Report};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use halo2_proofs::halo2curves::bn256::Fr;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use recreds::sizing::estimate_k;
+use recreds::{
+    keygen, present, present_batch, Backend, Credential, IssuerSecretKey, Kzg, PresentationPolicy,
+};
+use serde_json::json;
+use std::time::Duration;
+
+fn prove_batch(c: &mut Criterion) {
+    let mut report = Report::new("prove_batch");
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let policy = PresentationPolicy::new(4, vec![1, 3]);
+    let k = estimate_k::<Fr>(&policy, None).unwrap().k;
+    let params = Kzg::setup(k, &mut rng);
+    let pk = keygen::<Kzg>(&params, &policy).unwrap();
+    let sk = IssuerSecretKey::random(&mut rng);
+
+    let mut group = c.benchmark_group("prove_batch");
+    group.sample_size(10);
+    for n in list("RECREDS_BENCH_BATCH").unwrap_or_else(|| vec![8]) {
+        let credentials: Vec<Credential<Fr>> = (0..n as u64)
+            .map(|i| Credential::issue(&sk, (i..i + 4).map(Fr::from).collect(), &mut rng))
+            .collect();
+        let requests: Vec<_> = credentials
+            .iter()
+            .zip(0u64..)
+            .map(|(credential, nonce)| (credential, Fr::from(nonce)))
+            .collect();
+
+        group.throughput(Throughput::Elements(n as u64));
+        for threads in threads() {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .unwrap();
+            let id = format!("n{}/k{}/t{}", n, k, threads);
+            let mut sequential = (Duration::ZERO, 0);
+            let mut batch = (Duration::ZERO, 0);
+
+            group.bench_function(BenchmarkId::new("sequential", &id), |b| {
+                b.iter_custom(timed(&mut sequential, || {
+                    pool.install(|| {
+                        for (credential, nonce) in requests.iter() {
+                            present::<Kzg>(&params, &pk, credential, &policy, *nonce, &mut rng)
+                                .unwrap();
+                        }
+                    })
+                }))
+            });
+            group.bench_function(BenchmarkId::new("batch", &id), |b| {
+                b.iter_custom(timed(&mut batch, || {
+                    let presentations = pool.install(|| {
+                        present_batch::<Kzg>(&params, &pk, &requests, &policy, threads, &mut rng)
+                    });
+                    assert!(presentations.iter().all(Result::is_ok));
+                }))
+            });
+
+            let per_second = |total| n as f64 * 1e9 / mean_ns(total) as f64;
+            report.push(json!({
+                "presentations": n,
+                "k": k,
+                "threads": threads,
+                "sequential_ns": mean_ns(sequential),
+                "batch_ns": mean_ns(batch),
+                "sequential_per_second": per_second(sequential),
+                "batch_per_second": per_second(batch),
+            }));
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(benches, prove_batch);
+criterion_main!(benches);
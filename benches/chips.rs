@@ -0,0 +1,369 @@
+//! Key generation, proving and verifying of the chips the credentials are
+//! built from: native and emulated scalar multiplication, ECDSA verification
+//! and the Poseidon sponge. See `common` for the parameters.
+
+mod common;
+
+use common::{bench_circuit, fit, ks, Report};
+use criterion::{criterion_group, criterion_main, Criterion};
+use ecc::integer::{IntegerInstructions, Range};
+use ecc::maingate::{
+    MainGate, MainGateConfig, RangeChip, RangeConfig, RangeInstructions, RegionCtx,
+};
+use ecc::{EccConfig, GeneralEccChip};
+use ecdsa::credential::sign_prehashed;
+use ecdsa::ecdsa::{AssignedEcdsaSig, AssignedPublicKey, EcdsaChip};
+use halo2_native_ecc::{ECChip, ECConfig, NativeECOps};
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::halo2curves::group::{Curve, Group};
+use halo2_proofs::halo2curves::grumpkin::{Fr as GrumpkinScalar, G1Affine as Grumpkin, G1};
+use halo2_proofs::halo2curves::secp256k1::{Fq as Secp256k1Scalar, Secp256k1, Secp256k1Affine};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use poseidon_circuit::poseidon::Pow5Chip;
+use poseidon_circuit::{hash::*, DEFAULT_STEP};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use recreds::sizing::measure;
+
+const NUMBER_OF_LIMBS: usize = 4;
+const BIT_LEN_LIMB: usize = 68;
+const WINDOW_SIZE: usize = 4;
+
+/// `n` multiplications of a grumpkin point in its base field, BN254's
+/// scalar field, checked against the result computed out of circuit
+#[derive(Clone, Debug)]
+struct NativeMul(Vec<(Grumpkin, GrumpkinScalar, Grumpkin)>);
+
+impl NativeMul {
+    fn new(n: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(0);
+        Self(
+            (0..n)
+                .map(|_| {
+                    let p = G1::random(&mut rng).to_affine();
+                    let s = GrumpkinScalar::random(&mut rng);
+                    (p, s, (p * s).to_affine())
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Circuit<Fr> for NativeMul {
+    type Config = ECConfig<Grumpkin, Fr>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        ECChip::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let ec_chip = ECChip::construct(config.clone());
+
+        layouter.assign_region(
+            || "point mul",
+            |mut region| {
+                let mut offset = 0;
+                for (p, s, q) in self.0.iter() {
+                    let q = ec_chip.load_private_point(&mut region, &config, q, &mut offset)?;
+                    let r = ec_chip.point_mul(&mut region, &config, p, s, &mut offset)?;
+                    region.constrain_equal(q.x.cell(), r.x.cell())?;
+                    region.constrain_equal(q.y.cell(), r.y.cell())?;
+                }
+                ec_chip.pad(&mut region, &config, &mut offset)
+            },
+        )
+    }
+}
+
+#[derive(Clone, Debug)]
+struct EmulatedConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+impl EmulatedConfig {
+    fn new(meta: &mut ConstraintSystem<Fr>) -> Self {
+        let (rns_base, rns_scalar) =
+            GeneralEccChip::<Secp256k1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+        let main_gate_config = MainGate::<Fr>::configure(meta);
+        let mut overflow_bit_lens: Vec<usize> = vec![];
+        overflow_bit_lens.extend(rns_base.overflow_lengths());
+        overflow_bit_lens.extend(rns_scalar.overflow_lengths());
+        let composition_bit_lens = vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS];
+
+        let range_config = RangeChip::<Fr>::configure(
+            meta,
+            &main_gate_config,
+            composition_bit_lens,
+            overflow_bit_lens,
+        );
+        EmulatedConfig {
+            main_gate_config,
+            range_config,
+        }
+    }
+
+    /// The chip with its auxiliary points for `aux` multiplications
+    fn ecc_chip(
+        &self,
+        layouter: &mut impl Layouter<Fr>,
+        aux: usize,
+    ) -> Result<GeneralEccChip<Secp256k1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+        let mut ecc_chip = GeneralEccChip::new(EccConfig::new(
+            self.range_config.clone(),
+            self.main_gate_config.clone(),
+        ));
+        let aux_generator = Secp256k1::random(StdRng::seed_from_u64(1)).to_affine();
+        layouter.assign_region(
+            || "assign aux values",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                ecc_chip.assign_aux_generator(ctx, Value::known(aux_generator))?;
+                ecc_chip.assign_aux(ctx, WINDOW_SIZE, aux)?;
+                Ok(())
+            },
+        )?;
+        Ok(ecc_chip)
+    }
+
+    fn config_range(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        let range_chip = RangeChip::<Fr>::new(self.range_config.clone());
+        range_chip.load_table(layouter)?;
+        Ok(())
+    }
+}
+
+/// `n` multiplications of a secp256k1 point emulated over BN254's scalar
+/// field, as in the issuer signature check
+#[derive(Clone, Debug)]
+struct EmulatedMul(Vec<(Secp256k1Affine, Secp256k1Scalar)>);
+
+impl EmulatedMul {
+    fn new(n: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(0);
+        Self(
+            (0..n)
+                .map(|_| {
+                    (
+                        Secp256k1::random(&mut rng).to_affine(),
+                        Secp256k1Scalar::random(&mut rng),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Circuit<Fr> for EmulatedMul {
+    type Config = EmulatedConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        EmulatedConfig::new(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let ecc_chip = config.ecc_chip(&mut layouter, 1)?;
+        let scalar_chip = ecc_chip.scalar_field_chip();
+
+        layouter.assign_region(
+            || "region mul",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                for (base, s) in self.0.iter() {
+                    let result = (*base * s).to_affine();
+                    let base = ecc_chip.assign_point(ctx, Value::known(*base))?;
+                    let s = ecc_chip.new_unassigned_scalar(Value::known(*s));
+                    let s = scalar_chip.assign_integer(ctx, s, Range::Remainder)?;
+                    let result_0 = ecc_chip.assign_point(ctx, Value::known(result))?;
+                    let result_1 = ecc_chip.mul(ctx, &base, &s, WINDOW_SIZE)?;
+                    ecc_chip.assert_equal(ctx, &result_0, &result_1)?;
+                }
+                Ok(())
+            },
+        )?;
+
+        config.config_range(&mut layouter)
+    }
+}
+
+/// `n` secp256k1 ECDSA verifications of prehashed messages
+#[derive(Clone, Debug)]
+#[allow(clippy::type_complexity)]
+struct EcdsaVerify(
+    Vec<(
+        Secp256k1Affine,
+        Secp256k1Scalar,
+        (Secp256k1Scalar, Secp256k1Scalar),
+    )>,
+);
+
+impl EcdsaVerify {
+    fn new(n: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(0);
+        Self(
+            (0..n)
+                .map(|_| {
+                    let sk = Secp256k1Scalar::random(&mut rng);
+                    let msg_hash = Secp256k1Scalar::random(&mut rng);
+                    let k = Secp256k1Scalar::random(&mut rng);
+                    let public_key = (Secp256k1::generator() * sk).to_affine();
+                    (
+                        public_key,
+                        msg_hash,
+                        sign_prehashed::<Secp256k1Affine>(sk, msg_hash, k),
+                    )
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Circuit<Fr> for EcdsaVerify {
+    type Config = EmulatedConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        EmulatedConfig::new(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let ecc_chip = config.ecc_chip(&mut layouter, 2)?;
+        let ecdsa_chip = EcdsaChip::new(ecc_chip.clone());
+        let scalar_chip = ecc_chip.scalar_field_chip();
+
+        layouter.assign_region(
+            || "region ecdsa",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                for (public_key, msg_hash, (r, s)) in self.0.iter() {
+                    let mut assign = |value: &Secp256k1Scalar| {
+                        let integer = ecc_chip.new_unassigned_scalar(Value::known(*value));
+                        scalar_chip.assign_integer(ctx, integer, Range::Remainder)
+                    };
+                    let sig = AssignedEcdsaSig {
+                        r: assign(r)?,
+                        s: assign(s)?,
+                    };
+                    let msg_hash = assign(msg_hash)?;
+                    let pk = AssignedPublicKey {
+                        point: ecc_chip.assign_point(ctx, Value::known(*public_key))?,
+                    };
+                    ecdsa_chip.verify(ctx, &sig, &pk, &msg_hash)?;
+                }
+                Ok(())
+            },
+        )?;
+
+        config.config_range(&mut layouter)
+    }
+}
+
+/// `n` Poseidon hashes of two field elements by the sponge of the hash table
+#[derive(Clone, Debug)]
+struct Sponge(PoseidonHashTable<Fr>, usize);
+
+impl Sponge {
+    fn new(n: usize) -> Self {
+        Self(
+            PoseidonHashTable {
+                inputs: (0..n as u64)
+                    .map(|i| [Fr::from(2 * i), Fr::from(2 * i + 1)])
+                    .collect(),
+                ..Default::default()
+            },
+            n,
+        )
+    }
+}
+
+impl Circuit<Fr> for Sponge {
+    type Config = SpongeConfig<Fr, Pow5Chip<Fr, 3, 2>>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self(PoseidonHashTable::default(), self.1)
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let hash_tbl = [0; 6].map(|_| meta.advice_column());
+        let q_enable = meta.fixed_column();
+        SpongeConfig::configure_sub(meta, (q_enable, hash_tbl), DEFAULT_STEP)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let chip =
+            SpongeChip::<Fr, DEFAULT_STEP, Pow5Chip<Fr, 3, 2>>::construct(config, &self.0, self.1);
+        chip.load(&mut layouter)
+    }
+}
+
+/// Benchmarks `build(n)` for the largest `n` that fits each `k`
+fn bench_chip<C: Circuit<Fr> + Clone + Send + Sync>(
+    c: &mut Criterion,
+    name: &'static str,
+    build: impl Fn(usize) -> C,
+) {
+    let mut report = Report::new(name);
+    let min_k = measure(&build(1)).unwrap().k;
+    for k in ks(min_k) {
+        let (n, circuit) = fit(k, &build).unwrap();
+        bench_circuit(c, &mut report, k, &format!("n{}", n), n, circuit, vec![]);
+    }
+}
+
+fn native_point_mul(c: &mut Criterion) {
+    bench_chip(c, "native_point_mul", NativeMul::new);
+}
+
+fn emulated_mul(c: &mut Criterion) {
+    bench_chip(c, "emulated_mul", EmulatedMul::new);
+}
+
+fn ecdsa_verify(c: &mut Criterion) {
+    bench_chip(c, "ecdsa_verify", EcdsaVerify::new);
+}
+
+fn poseidon_sponge(c: &mut Criterion) {
+    bench_chip(c, "poseidon_sponge", Sponge::new);
+}
+
+criterion_group!(
+    benches,
+    native_point_mul,
+    emulated_mul,
+    ecdsa_verify,
+    poseidon_sponge
+);
+criterion_main!(benches);
@@ -0,0 +1,203 @@
+//! Shared setup of the benches.
+//!
+//! Every circuit is benchmarked for key generation, proving and verifying
+//! with the KZG backend, once per `k` and thread count:
+//!
+//! - `RECREDS_BENCH_K`: comma separated `k`, by default the smallest `k` of
+//!   each circuit. Chip circuits repeat their operation as many times as
+//!   fits in `2^k` rows, doubling from one
+//! - `RECREDS_BENCH_THREADS`: comma separated thread counts of the rayon
+//!   pool the prover runs on, by default the available parallelism
+//! - `RECREDS_BENCH_ATTRIBUTES`: comma separated attribute counts of the
+//!   credential benches, by default 4 and 16
+//! - `RECREDS_BENCH_BATCH`: comma separated numbers of presentations of
+//!   the batch proving bench, by default 8
+//! - `RECREDS_BENCH_JSON`: directory of the JSON results, by default
+//!   `target/recreds-bench`
+//!
+//! Criterion keeps its statistics under `target/criterion`. The JSON file of
+//! a bench adds what criterion does not know: `k`, rows, repetitions and
+//! proof size, with the mean time per iteration of every measurement.
+
+// Not every bench uses every helper
+#![allow(dead_code)]
+
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::Circuit;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use recreds::sizing::{measure, CircuitSize};
+use recreds::{Backend, Kzg};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use criterion::{BenchmarkId, Criterion};
+
+pub fn list<T: std::str::FromStr>(name: &str) -> Option<Vec<T>> {
+    let value = std::env::var(name).ok()?;
+    let values = value
+        .split(',')
+        .map(|v| v.trim().parse())
+        .collect::<Result<Vec<T>, _>>()
+        .unwrap_or_else(|_| panic!("{} must be a comma separated list", name));
+    Some(values)
+}
+
+/// The `k` to benchmark a circuit whose smallest `k` is `min_k`
+pub fn ks(min_k: u32) -> Vec<u32> {
+    match list::<u32>("RECREDS_BENCH_K") {
+        Some(ks) => ks.into_iter().filter(|k| *k >= min_k).collect(),
+        None => vec![min_k],
+    }
+}
+
+pub fn threads() -> Vec<usize> {
+    list("RECREDS_BENCH_THREADS").unwrap_or_else(|| {
+        vec![std::thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)]
+    })
+}
+
+/// Builds the circuit of the most repetitions, doubling from one, that fits
+/// in `2^k` rows. `None` if one repetition does not
+pub fn fit<C: Circuit<Fr>>(k: u32, build: impl Fn(usize) -> C) -> Option<(usize, C)> {
+    let fits = |n: usize| measure(&build(n)).unwrap().k <= k;
+    if !fits(1) {
+        return None;
+    }
+    let mut n = 1;
+    while fits(2 * n) {
+        n *= 2;
+    }
+    Some((n, build(n)))
+}
+
+/// Results of one bench, written to its JSON file on drop
+pub struct Report {
+    name: &'static str,
+    records: Vec<Value>,
+}
+
+impl Report {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            records: vec![],
+        }
+    }
+
+    pub fn push(&mut self, record: Value) {
+        self.records.push(record);
+    }
+}
+
+impl Drop for Report {
+    fn drop(&mut self) {
+        let dir = std::env::var_os("RECREDS_BENCH_JSON")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| PathBuf::from("target/recreds-bench"));
+        std::fs::create_dir_all(&dir).unwrap();
+        let report = json!({
+            "bench": self.name,
+            "backend": Kzg::NAME,
+            "results": self.records,
+        });
+        std::fs::write(
+            dir.join(format!("{}.json", self.name)),
+            serde_json::to_string_pretty(&report).unwrap(),
+        )
+        .unwrap();
+    }
+}
+
+/// Times `routine` and keeps the mean time per iteration
+pub fn timed(
+    total: &mut (Duration, u64),
+    mut routine: impl FnMut(),
+) -> impl FnMut(u64) -> Duration + '_ {
+    move |iters| {
+        let start = Instant::now();
+        for _ in 0..iters {
+            routine();
+        }
+        let elapsed = start.elapsed();
+        total.0 += elapsed;
+        total.1 += iters;
+        elapsed
+    }
+}
+
+pub fn mean_ns(total: (Duration, u64)) -> u64 {
+    (total.0.as_nanos() / u128::from(total.1.max(1))) as u64
+}
+
+/// Benchmarks key generation, proving and verifying of `circuit` with
+/// parameters of `k`, on every thread count. `repetitions` is the number of
+/// operations of the circuit
+pub fn bench_circuit<C: Circuit<Fr> + Clone + Send + Sync>(
+    c: &mut Criterion,
+    report: &mut Report,
+    k: u32,
+    label: &str,
+    repetitions: usize,
+    circuit: C,
+    instances: Vec<Vec<Fr>>,
+) {
+    let size: CircuitSize = measure(&circuit).unwrap();
+    assert!(size.k <= k, "{} needs k = {}", label, size.k);
+    let params = Kzg::setup(k, StdRng::seed_from_u64(k.into()));
+
+    let mut group = c.benchmark_group(report.name);
+    group.sample_size(10);
+    for threads in threads() {
+        // The prover and key generation parallelise with rayon, so running
+        // them in a pool bounds the threads they use
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads)
+            .build()
+            .unwrap();
+        let id = format!("{}/k{}/t{}", label, k, threads);
+        let mut keygen = (Duration::ZERO, 0);
+        let mut prove = (Duration::ZERO, 0);
+        let mut verify = (Duration::ZERO, 0);
+
+        group.bench_function(BenchmarkId::new("keygen", &id), |b| {
+            b.iter_custom(timed(&mut keygen, || {
+                pool.install(|| Kzg::keygen(&params, &circuit)).unwrap();
+            }))
+        });
+        let pk = pool.install(|| Kzg::keygen(&params, &circuit)).unwrap();
+
+        let mut rng = StdRng::seed_from_u64(0);
+        group.bench_function(BenchmarkId::new("prove", &id), |b| {
+            b.iter_custom(timed(&mut prove, || {
+                pool.install(|| Kzg::prove(&params, &pk, circuit.clone(), &instances, &mut rng))
+                    .unwrap();
+            }))
+        });
+        let proof = Kzg::prove(&params, &pk, circuit.clone(), &instances, &mut rng).unwrap();
+
+        group.bench_function(BenchmarkId::new("verify", &id), |b| {
+            b.iter_custom(timed(&mut verify, || {
+                pool.install(|| Kzg::verify(&params, pk.get_vk(), &instances, &proof))
+                    .unwrap();
+            }))
+        });
+
+        println!("{}: {} rows, proof of {} bytes", id, size.rows, proof.len());
+        report.push(json!({
+            "circuit": label,
+            "k": k,
+            "threads": threads,
+            "rows": size.rows,
+            "repetitions": repetitions,
+            "proof_size": proof.len(),
+            "keygen_ns": mean_ns(keygen),
+            "prove_ns": mean_ns(prove),
+            "verify_ns": mean_ns(verify),
+        }));
+    }
+    group.finish();
+}
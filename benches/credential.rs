@@ -0,0 +1,58 @@
+//! Key generation, proving and verifying of a full credential show: the
+//! presentation circuit of a policy that discloses every other attribute.
+//! See `common` for the parameters.
+//!
+//! The predicate update bench is still open: predicate updates have no
+//! circuit in this tree, so the benchmark request stays open until they do.
+//! See "Open work" in the README.
+
+mod common;
+
+use common::{bench_circuit, ks, list, Report};
+use criterion::{criterion_group, criterion_main, Criterion};
+use halo2_proofs::halo2curves::bn256::Fr;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use recreds::sizing::estimate_k;
+use recreds::{Credential, IssuerSecretKey, Presentation, PresentationCircuit, PresentationPolicy};
+
+fn credential_show(c: &mut Criterion) {
+    let mut report = Report::new("credential_show");
+    let mut rng = StdRng::seed_from_u64(0);
+    let sk = IssuerSecretKey::random(&mut rng);
+
+    for n in list("RECREDS_BENCH_ATTRIBUTES").unwrap_or_else(|| vec![4, 16]) {
+        let policy = PresentationPolicy::new(n, (0..n).step_by(2).collect());
+        let attributes: Vec<Fr> = (1..=n as u64).map(Fr::from).collect();
+        let credential = Credential::issue(&sk, attributes, &mut rng);
+        let nonce = Fr::from(42);
+        let presentation = Presentation {
+            policy: policy.clone(),
+            issuer: credential.issuer,
+            nonce,
+            disclosed: policy
+                .disclosed
+                .iter()
+                .map(|i| credential.attributes[*i])
+                .collect(),
+            proof: vec![],
+        };
+
+        let circuit = PresentationCircuit::from_credential(&credential, &policy, nonce);
+        let min_k = estimate_k::<Fr>(&policy, None).unwrap().k;
+        for k in ks(min_k) {
+            bench_circuit(
+                c,
+                &mut report,
+                k,
+                &format!("a{}", n),
+                1,
+                circuit.clone(),
+                vec![presentation.public_inputs()],
+            );
+        }
+    }
+}
+
+criterion_group!(benches, credential_show);
+criterion_main!(benches);
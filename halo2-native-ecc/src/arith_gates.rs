@@ -1,8 +1,8 @@
 use ff::Field;
+use ff::PrimeField;
 use halo2_proofs::circuit::AssignedCell;
 use halo2_proofs::circuit::Region;
 use halo2_proofs::circuit::Value;
-use ff::PrimeField;
 use halo2_proofs::halo2curves::CurveAffine;
 use halo2_proofs::plonk::Error;
 
@@ -54,6 +54,37 @@ pub trait ArithOps<F: Field> {
         offset: &mut usize,
     ) -> Result<AssignedCell<F, F>, Error>;
 
+    /// Load a field element that is fixed as a constant of the circuit
+    fn load_constant(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        f: &F,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Add two assigned cells and return the sum.
+    /// The inputs are copied into the gate.
+    fn add_cells(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Multiply two assigned cells and return the product.
+    /// The inputs are copied into the gate.
+    fn mul_cells(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error>;
+
     /// Input x1, y1, x2, y2, x3, y3
     /// Assert that
     /// - x3 = x1 + 2y1 + 4x2 + 8y2 + 16y3
@@ -78,6 +109,33 @@ pub trait ArithOps<F: Field> {
         input: &u128,
         offset: &mut usize,
     ) -> Result<(Vec<AssignedCell<F, F>>, AssignedCell<F, F>), Error>;
+
+    /// Input a u128,
+    /// Output the cell that contains u128, constrained to be less than 2^128.
+    ///
+    /// Uses table lookups over limbs if the chip is configured with
+    /// `DecompositionMode::Lookup`, and `decompose_u128` otherwise.
+    fn range_check_u128(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        input: &u128,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Input a u128,
+    /// Output its bit decomposition cells in little endian.
+    ///
+    /// Only available with `DecompositionMode::Lookup`: the bits are checked
+    /// against the range table, two per row. Unlike `decompose_u128`, the bits
+    /// are not accumulated into a cell that contains the u128.
+    fn lookup_bits_u128(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        input: &u128,
+        offset: &mut usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error>;
 }
 
 impl<C, F> ArithOps<F> for ECChip<C, F>
@@ -189,6 +247,75 @@ where
         res
     }
 
+    /// Load a field element that is fixed as a constant of the circuit
+    fn load_constant(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        f: &F,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let res = self.load_private_field(region, config, f, offset)?;
+        region.constrain_constant(res.cell(), *f)?;
+        Ok(res)
+    }
+
+    /// Add two assigned cells and return the sum.
+    /// The inputs are copied into the gate.
+    fn add_cells(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        // |         add |   2  |       0      | 0  | 1  | 0  | a1 = a0 + b0
+        config.q2.enable(region, *offset)?;
+        a.copy_advice(|| "field element", region, config.a, *offset)?;
+        b.copy_advice(|| "field element", region, config.b, *offset)?;
+
+        let c = a.value().copied() + b.value().copied();
+        let res = region.assign_advice(|| "field element", config.a, *offset + 1, || c);
+        let _ = region.assign_advice(
+            || "field element",
+            config.b,
+            *offset + 1,
+            || Value::known(F::ZERO),
+        );
+
+        *offset += 2;
+        res
+    }
+
+    /// Multiply two assigned cells and return the product.
+    /// The inputs are copied into the gate.
+    fn mul_cells(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        a: &AssignedCell<F, F>,
+        b: &AssignedCell<F, F>,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        // |         mul |   2  |       0      | 0  | 0  | 1  | a1 = a0 * b0
+        config.q3.enable(region, *offset)?;
+        a.copy_advice(|| "field element", region, config.a, *offset)?;
+        b.copy_advice(|| "field element", region, config.b, *offset)?;
+
+        let c = a.value().copied() * b.value().copied();
+        let res = region.assign_advice(|| "field element", config.a, *offset + 1, || c);
+        let _ = region.assign_advice(
+            || "field element",
+            config.b,
+            *offset + 1,
+            || Value::known(F::ZERO),
+        );
+
+        *offset += 2;
+        res
+    }
+
     /// Input x1, y1, x2, y2, x3, y3
     /// Assert that
     /// - x3 = x1 + 2y1 + 4x2 + 8y2 + 16y3
@@ -327,6 +454,7 @@ where
         assert_eq!(prev_acc, F::from_u128(*input));
 
         // constrain the accumulators are well-formed
+        region.constrain_constant(acc_cells[0].cell(), F::ZERO)?;
         for i in 0..31 {
             region.constrain_equal(
                 // acc in the previous round
@@ -341,4 +469,101 @@ where
 
         Ok((res, acc_cells.last().unwrap().clone()))
     }
+
+    /// Input a u128,
+    /// Output the cell that contains u128, constrained to be less than 2^128.
+    ///
+    /// Uses table lookups over limbs if the chip is configured with
+    /// `DecompositionMode::Lookup`, and `decompose_u128` otherwise.
+    fn range_check_u128(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        input: &u128,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let range = match &config.range {
+            Some(range) => range,
+            None => return Ok(self.decompose_u128(region, config, input, offset)?.1),
+        };
+
+        //  index  |  a     |  b
+        //  -------|--------|------
+        //         | limb_n | 0
+        //         | ...    | ...
+        //         | limb_1 | acc_1
+        //  offset | 0      | acc_0 = input
+        //
+        // acc_i = acc_{i+1} * 2^limb_bits + limb_{i+1}, and each limb is in the table
+        let limb_bits = range.limb_bits;
+        let mask = (1u128 << limb_bits) - 1;
+        let base = F::from_u128(1 << limb_bits);
+
+        let mut acc = F::ZERO;
+        for i in (0..128 / limb_bits).rev() {
+            let limb = F::from_u128((*input >> (i * limb_bits)) & mask);
+
+            range.q_range.enable(region, *offset)?;
+            region.assign_advice(|| "limb", config.a, *offset, || Value::known(limb))?;
+            let acc_cell =
+                region.assign_advice(|| "acc", config.b, *offset, || Value::known(acc))?;
+            if i == 128 / limb_bits - 1 {
+                // the running sum starts from 0
+                region.constrain_constant(acc_cell.cell(), F::ZERO)?;
+            }
+
+            acc = acc * base + limb;
+            *offset += 1;
+        }
+
+        // sanity check
+        assert_eq!(acc, F::from_u128(*input));
+
+        region.assign_advice(|| "limb", config.a, *offset, || Value::known(F::ZERO))?;
+        let res = region.assign_advice(|| "acc", config.b, *offset, || Value::known(acc))?;
+        *offset += 1;
+
+        Ok(res)
+    }
+
+    fn lookup_bits_u128(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        input: &u128,
+        offset: &mut usize,
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let range = config
+            .range
+            .as_ref()
+            .expect("bit lookups require DecompositionMode::Lookup");
+
+        //  index  |  a         |  b
+        //  -------|------------|------------
+        //         | bit_0      | bit_1
+        //         | ...        | ...
+        //         | bit_126    | bit_127
+        //
+        // a cell x is a bit iff both x and 2^(limb_bits - 1) * x are in the table
+        let mut res = vec![];
+        for i in 0..64 {
+            range.q_bits.enable(region, *offset)?;
+            let bit = |j: usize| F::from(((*input >> j) & 1) as u64);
+            res.push(region.assign_advice(
+                || "bit",
+                config.a,
+                *offset,
+                || Value::known(bit(2 * i)),
+            )?);
+            res.push(region.assign_advice(
+                || "bit",
+                config.b,
+                *offset,
+                || Value::known(bit(2 * i + 1)),
+            )?);
+            *offset += 1;
+        }
+
+        Ok(res)
+    }
 }
@@ -1,7 +1,9 @@
 use ark_std::test_rng;
+use ff::PrimeField;
 use halo2_proofs::arithmetic::Field;
 use halo2_proofs::circuit::Layouter;
 use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::circuit::Value;
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::plonk::Circuit;
 use halo2_proofs::plonk::ConstraintSystem;
@@ -11,6 +13,8 @@ use halo2curves::grumpkin::G1Affine;
 
 use crate::arith_gates::ArithOps;
 use crate::chip::ECChip;
+use crate::compression::PointCompressionOps;
+use crate::config::DecompositionMode;
 use crate::config::ECConfig;
 use crate::ec_gates::NativeECOps;
 
@@ -190,3 +194,322 @@ fn test_field_ops() {
         assert!(prover.verify().is_err());
     }
 }
+
+/// Decomposes `input`, then reassigns the accumulators as if the first one
+/// was `init` rather than zero
+#[derive(Default, Debug, Clone, Copy)]
+struct DecomposeTestCircuit {
+    input: u128,
+    init: Fq,
+}
+
+impl Circuit<Fq> for DecomposeTestCircuit {
+    type Config = ECConfig<G1Affine, Fq>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        ECChip::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let field_chip = ECChip::construct(config.clone());
+
+        layouter.assign_region(
+            || "test decompose circuit",
+            |mut region| {
+                let mut offset = 0;
+                field_chip.decompose_u128(&mut region, &config, &self.input, &mut offset)?;
+
+                // every round absorbs 4 bits into acc = 16 * prev_acc + bits,
+                // so a non-zero start shifts the i-th accumulator by init * 16^(i + 1)
+                let mut prev = self.init;
+                let mut shift = self.init;
+                for i in 0..32 {
+                    shift *= Fq::from(16);
+                    let acc = Fq::from_u128(self.input >> (124 - 4 * i)) + shift;
+                    region.assign_advice(|| "a3", config.a, 3 * i + 2, || Value::known(prev))?;
+                    region.assign_advice(|| "b3", config.b, 3 * i + 2, || Value::known(acc))?;
+                    prev = acc;
+                }
+
+                // pad the last two rows
+                field_chip.pad(&mut region, &config, &mut offset)?;
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_decompose_u128() {
+    let k = 8;
+    let input = u128::from_le_bytes((0..16).collect::<Vec<u8>>().try_into().unwrap());
+
+    let circuit = DecomposeTestCircuit {
+        input,
+        init: Fq::zero(),
+    };
+    let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+    prover.assert_satisfied();
+
+    // Error case: the accumulators start from a non-zero value, so the last
+    // one is input + 2^128 and not a u128. This was accepted before the first
+    // accumulator was constrained to zero
+    let circuit = DecomposeTestCircuit {
+        input,
+        init: Fq::one(),
+    };
+    let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+    assert!(prover.verify().is_err());
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+struct RangeCheckTestCircuit<const LIMB_BITS: usize> {
+    input: u128,
+    expected: Fq, // the field element of input
+    y: Fq,
+    // move 2^LIMB_BITS from the top limb into the next one,
+    // which keeps the running sum but overflows the limb
+    overflow_limb: bool,
+    // replace the first scalar bit with 2
+    non_bit: bool,
+}
+
+impl<const LIMB_BITS: usize> RangeCheckTestCircuit<LIMB_BITS> {
+    // rows used by a single `range_check_u128`
+    fn range_check_rows() -> usize {
+        if LIMB_BITS == 0 {
+            // 32 partial decompositions of 3 rows
+            96
+        } else {
+            128 / LIMB_BITS + 1
+        }
+    }
+}
+
+impl<const LIMB_BITS: usize> Circuit<Fq> for RangeCheckTestCircuit<LIMB_BITS> {
+    type Config = ECConfig<G1Affine, Fq>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        let mode = if LIMB_BITS == 0 {
+            DecompositionMode::Bits
+        } else {
+            DecompositionMode::Lookup {
+                limb_bits: LIMB_BITS,
+            }
+        };
+        ECChip::configure_with_mode(meta, mode)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let field_chip = ECChip::construct(config.clone());
+        field_chip.load_range_table(&mut layouter)?;
+
+        layouter.assign_region(
+            || "test range check circuit",
+            |mut region| {
+                let mut offset = 0;
+
+                // unit test: range check
+                {
+                    let start = offset;
+                    let cell = field_chip.range_check_u128(
+                        &mut region,
+                        &config,
+                        &self.input,
+                        &mut offset,
+                    )?;
+                    let rows = offset - start;
+                    println!(
+                        "range check with {} bit limbs uses {} rows",
+                        LIMB_BITS, rows
+                    );
+                    assert_eq!(rows, Self::range_check_rows());
+
+                    if self.overflow_limb {
+                        let mask = (1u128 << LIMB_BITS) - 1;
+                        let top = Fq::from_u128(self.input >> (128 - LIMB_BITS)) - Fq::one();
+                        let next = Fq::from_u128((self.input >> (128 - 2 * LIMB_BITS)) & mask)
+                            + Fq::from_u128(1 << LIMB_BITS);
+                        region.assign_advice(|| "limb", config.a, start, || Value::known(top))?;
+                        region.assign_advice(
+                            || "limb",
+                            config.a,
+                            start + 1,
+                            || Value::known(next),
+                        )?;
+                        region.assign_advice(
+                            || "acc",
+                            config.b,
+                            start + 1,
+                            || Value::known(top),
+                        )?;
+                    }
+
+                    let expected = field_chip.load_private_field(
+                        &mut region,
+                        &config,
+                        &self.expected,
+                        &mut offset,
+                    )?;
+                    region.constrain_equal(cell.cell(), expected.cell())?;
+                }
+
+                // unit test: y parity, which range checks three limbs
+                {
+                    let y = field_chip.load_private_field(
+                        &mut region,
+                        &config,
+                        &self.y,
+                        &mut offset,
+                    )?;
+                    let start = offset;
+                    let _sign = field_chip.y_parity(&mut region, &config, &y, &mut offset)?;
+                    println!(
+                        "y parity with {} bit limbs uses {} rows",
+                        LIMB_BITS,
+                        offset - start
+                    );
+                    assert_eq!(offset - start, 96 + 3 * Self::range_check_rows() + 19);
+                }
+
+                // unit test: bits of a scalar, as consumed by double-then-add
+                if LIMB_BITS != 0 {
+                    let start = offset;
+                    let bits = field_chip.lookup_bits_u128(
+                        &mut region,
+                        &config,
+                        &self.input,
+                        &mut offset,
+                    )?;
+                    println!(
+                        "scalar bits with {} bit limbs use {} rows, against 96 bit by bit",
+                        LIMB_BITS,
+                        offset - start
+                    );
+                    assert_eq!(offset - start, 64);
+
+                    for (i, bit) in bits.iter().enumerate() {
+                        let expected = Fq::from(((self.input >> i) & 1) as u64);
+                        bit.value().assert_if_known(|&&v| v == expected);
+                    }
+
+                    if self.non_bit {
+                        region.assign_advice(
+                            || "bit",
+                            config.a,
+                            start,
+                            || Value::known(Fq::from(2)),
+                        )?;
+                    }
+                }
+
+                // pad the last two rows
+                field_chip.pad(&mut region, &config, &mut offset)?;
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_range_check() {
+    fn run<const LIMB_BITS: usize>(k: u32) {
+        let mut rng = test_rng();
+        let input = u128::from_le_bytes([0xab; 16]);
+        let y = Fq::random(&mut rng);
+
+        let circuit = RangeCheckTestCircuit::<LIMB_BITS> {
+            input,
+            expected: Fq::from_u128(input),
+            y,
+            ..Default::default()
+        };
+        {
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            prover.assert_satisfied();
+        }
+
+        // Error case: the range checked cell is not the input
+        {
+            let circuit = RangeCheckTestCircuit::<LIMB_BITS> {
+                expected: Fq::from_u128(input) + Fq::one(),
+                ..circuit
+            };
+            let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        if LIMB_BITS != 0 {
+            // Error case: a limb is not less than 2^LIMB_BITS
+            {
+                let circuit = RangeCheckTestCircuit::<LIMB_BITS> {
+                    overflow_limb: true,
+                    ..circuit
+                };
+                let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+
+            // Error case: a scalar bit is 2, which is in the table
+            {
+                let circuit = RangeCheckTestCircuit::<LIMB_BITS> {
+                    non_bit: true,
+                    ..circuit
+                };
+                let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+        }
+    }
+
+    // bit by bit decomposition
+    run::<0>(10);
+    // 8 bit limbs
+    run::<8>(10);
+    // 16 bit limbs; the table alone needs 2^16 rows
+    run::<16>(17);
+}
+
+#[test]
+#[should_panic(expected = "limb bits must divide 128 and be at most 16")]
+fn test_range_check_rejects_32_bit_limbs() {
+    let mut meta = ConstraintSystem::<Fq>::default();
+    ECChip::<G1Affine, Fq>::configure_with_mode(
+        &mut meta,
+        DecompositionMode::Lookup { limb_bits: 32 },
+    );
+}
+
+#[test]
+#[should_panic(expected = "limb bits must divide 128 and be at most 16")]
+fn test_range_check_rejects_128_bit_limbs() {
+    let mut meta = ConstraintSystem::<Fq>::default();
+    ECChip::<G1Affine, Fq>::configure_with_mode(
+        &mut meta,
+        DecompositionMode::Lookup { limb_bits: 128 },
+    );
+}
@@ -1,13 +1,19 @@
 use std::marker::PhantomData;
 
 use ff::Field;
-use halo2_proofs::circuit::Chip;
 use ff::PrimeField;
+use halo2_proofs::circuit::Chip;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Value;
 use halo2_proofs::halo2curves::CurveAffine;
 use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
 use halo2_proofs::plonk::Expression;
+use halo2_proofs::poly::Rotation;
 
+use crate::config::DecompositionMode;
 use crate::config::ECConfig;
+use crate::config::RangeLookupConfig;
 
 #[derive(Clone, Debug)]
 pub struct ECChip<C, F>
@@ -51,6 +57,16 @@ where
     }
 
     pub fn configure(meta: &mut ConstraintSystem<F>) -> <Self as Chip<F>>::Config {
+        Self::configure_with_mode(meta, DecompositionMode::Bits)
+    }
+
+    /// Configure the chip with the given mode for `ArithOps::range_check_u128`
+    /// and `NativeECOps::decompose_scalar`.
+    /// With `DecompositionMode::Lookup`, the circuit must call `load_range_table`.
+    pub fn configure_with_mode(
+        meta: &mut ConstraintSystem<F>,
+        mode: DecompositionMode,
+    ) -> <Self as Chip<F>>::Config {
         let a = meta.advice_column();
         meta.enable_equality(a);
         let b = meta.advice_column();
@@ -68,13 +84,14 @@ where
         // ec on curve
         let q3 = meta.complex_selector();
 
-        let config = ECConfig {
+        let mut config = ECConfig {
             a,
             b,
             q_ec_enable,
             q1,
             q2,
             q3,
+            range: None,
             _phantom: PhantomData::default(),
         };
 
@@ -120,8 +137,79 @@ where
                     + mul_gate * (one - q_ec_enable) * q3,
             ]
         });
+
+        if let DecompositionMode::Lookup { limb_bits } = mode {
+            // the table has 2^limb_bits rows, and 2^limb_bits must fit the shifts below
+            assert!(
+                limb_bits > 0 && limb_bits <= 16 && 128 % limb_bits == 0,
+                "limb bits must divide 128 and be at most 16"
+            );
+
+            let q_range = meta.complex_selector();
+            let q_bits = meta.complex_selector();
+            let table = meta.lookup_table_column();
+            let range = RangeLookupConfig {
+                q_range,
+                q_bits,
+                table,
+                limb_bits,
+            };
+
+            meta.create_gate("native ec chip running sum", |meta| {
+                let q_range = meta.query_selector(range.q_range);
+                vec![q_range * config.running_sum_gate(meta, limb_bits)]
+            });
+
+            meta.lookup("native ec chip range check", |meta| {
+                let q_range = meta.query_selector(range.q_range);
+                let limb = meta.query_advice(config.a, Rotation::cur());
+                vec![(q_range * limb, range.table)]
+            });
+
+            // x < 2^limb_bits and 2^(limb_bits - 1) * x < 2^limb_bits imply x is 0 or 1
+            let shift = Expression::Constant(F::from_u128(1 << (limb_bits - 1)));
+            for column in [config.a, config.b] {
+                meta.lookup("native ec chip scalar bit", |meta| {
+                    let q_bits = meta.query_selector(range.q_bits);
+                    let bit = meta.query_advice(column, Rotation::cur());
+                    vec![(q_bits * bit, range.table)]
+                });
+                meta.lookup("native ec chip shifted scalar bit", |meta| {
+                    let q_bits = meta.query_selector(range.q_bits);
+                    let bit = meta.query_advice(column, Rotation::cur());
+                    vec![(q_bits * shift.clone() * bit, range.table)]
+                });
+            }
+
+            config.range = Some(range);
+        }
+
         #[cfg(feature = "verbose")]
         println!("custom gate's degree {}", meta.degree());
         config
     }
+
+    /// Load the fixed table for lookup based range checks.
+    /// Does nothing if the chip decomposes bit by bit.
+    pub fn load_range_table(&self, layouter: &mut impl Layouter<F>) -> Result<(), Error> {
+        let range = match &self.config.range {
+            Some(range) => range,
+            None => return Ok(()),
+        };
+
+        layouter.assign_table(
+            || "range table",
+            |mut table| {
+                for i in 0..1u64 << range.limb_bits {
+                    table.assign_cell(
+                        || "range table",
+                        range.table,
+                        i as usize,
+                        || Value::known(F::from(i)),
+                    )?;
+                }
+                Ok(())
+            },
+        )
+    }
 }
@@ -0,0 +1,227 @@
+use ff::PrimeField;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Instance;
+
+use crate::chip::ECChip;
+use crate::config::ECConfig;
+use crate::hash_to_curve::map_to_point;
+use crate::util::field_decompose_u128;
+use crate::util::leak;
+use crate::ArithOps;
+use crate::AssignedECPoint;
+use crate::NativeECOps;
+
+#[cfg(test)]
+mod tests;
+
+/// Split a point into its x coordinate and the parity of y
+pub fn compress_point<C, F>(p: &C) -> (F, bool)
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    let coordinates = p.coordinates().unwrap();
+    (*coordinates.x(), coordinates.y().to_repr()[0] & 1 == 1)
+}
+
+/// Recover a point from its x coordinate and the parity of y.
+/// Returns None if x is not the x coordinate of any point.
+pub fn decompress_point<C, F>(x: &F, sign: bool) -> Option<C>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    // `map_to_point` always returns the point with an even y
+    map_to_point::<C, F>(x).map(|p| if sign { -p } else { p })
+}
+
+/// Encode a point into 32 bytes:
+/// x in little endian, with the parity of y stored in the most significant bit.
+pub fn encode_point<C, F>(p: &C) -> [u8; 32]
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    assert!(F::NUM_BITS < 256, "no spare bit for the sign");
+
+    let (x, sign) = compress_point(p);
+    let mut res = x.to_repr();
+    res[31] |= (sign as u8) << 7;
+    res
+}
+
+/// Decode a point from `encode_point`.
+/// Returns None for non-canonical x or if x is not on curve.
+pub fn decode_point<C, F>(bytes: &[u8; 32]) -> Option<C>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    let mut repr = *bytes;
+    let sign = repr[31] >> 7 == 1;
+    repr[31] &= 0x7f;
+    let x = Option::<F>::from(F::from_repr(repr))?;
+    decompress_point(&x, sign)
+}
+
+/// The instance cells written by `expose_compressed`:
+/// the x coordinates, followed by one cell that packs the parities of y as
+///     \sum sign_i * 2^i
+pub fn compressed_instance<C, F>(points: &[C]) -> Vec<F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    assert!(points.len() <= F::CAPACITY as usize, "too many points");
+
+    let mut res = vec![];
+    let mut signs = F::ZERO;
+    for (i, p) in points.iter().enumerate() {
+        let (x, sign) = compress_point(p);
+        res.push(x);
+        if sign {
+            signs += F::from(2).pow_vartime([i as u64]);
+        }
+    }
+    res.push(signs);
+    res
+}
+
+pub trait PointCompressionOps<C, F>
+where
+    // the embedded curve, i.e., Grumpkin
+    C: CurveAffine<Base = F>,
+    // the field for circuit, i.e., BN::Scalar
+    F: PrimeField,
+{
+    type Config;
+    type AssignedECPoint;
+
+    /// Returns the parity of y, i.e., the least significant bit of its canonical form.
+    ///
+    /// y is decomposed into two 128 bits limbs, and (p - 1 - y) is decomposed
+    /// likewise so that the limbs cannot wrap around the modulus.
+    fn y_parity(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        y: &AssignedCell<F, F>,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error>;
+
+    /// Expose points as public inputs in compressed form;
+    /// see `compressed_instance` for the layout starting at `row`.
+    ///
+    /// The points may be assigned in other regions;
+    /// equality must be enabled on the instance column.
+    fn expose_compressed(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        config: &Self::Config,
+        instance: Column<Instance>,
+        points: &[Self::AssignedECPoint],
+        row: usize,
+    ) -> Result<(), Error>;
+}
+
+impl<C, F> PointCompressionOps<C, F> for ECChip<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    type Config = ECConfig<C, F>;
+    type AssignedECPoint = AssignedECPoint<C, F>;
+
+    fn y_parity(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        y: &AssignedCell<F, F>,
+        offset: &mut usize,
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let y_value = leak(&y.value());
+        let (high, low) = field_decompose_u128(&y_value);
+        // the complement (p - 1 - y)
+        let (high_rem, low_rem) = field_decompose_u128(&(-F::ONE - y_value));
+        let (p_high, p_low) = field_decompose_u128(&(-F::ONE));
+        let carry = low.overflowing_add(low_rem).1;
+
+        // only the low limb of y needs its bits
+        let (low_bits, low_cell) = self.decompose_u128(region, config, &low, offset)?;
+        let high_cell = self.range_check_u128(region, config, &high, offset)?;
+        let low_rem_cell = self.range_check_u128(region, config, &low_rem, offset)?;
+        let high_rem_cell = self.range_check_u128(region, config, &high_rem, offset)?;
+
+        // y = high * 2^128 + low
+        let two_to_128 =
+            self.load_constant(region, config, &F::from_u128(1 << 127).double(), offset)?;
+        let high_shifted = self.mul_cells(region, config, &high_cell, &two_to_128, offset)?;
+        let y_rec = self.add_cells(region, config, &high_shifted, &low_cell, offset)?;
+        region.constrain_equal(y_rec.cell(), y.cell())?;
+
+        // y + (p - 1 - y) = p - 1 limb by limb, which ensures y < p
+        let carry = self.load_private_field(region, config, &F::from(carry as u64), offset)?;
+        let carry_square = self.mul_cells(region, config, &carry, &carry, offset)?;
+        region.constrain_equal(carry_square.cell(), carry.cell())?;
+
+        // low + low_rem = p_low + carry * 2^128
+        let low_sum = self.add_cells(region, config, &low_cell, &low_rem_cell, offset)?;
+        let carry_shifted = self.mul_cells(region, config, &carry, &two_to_128, offset)?;
+        let p_low = self.load_constant(region, config, &F::from_u128(p_low), offset)?;
+        let low_rhs = self.add_cells(region, config, &carry_shifted, &p_low, offset)?;
+        region.constrain_equal(low_sum.cell(), low_rhs.cell())?;
+
+        // high + high_rem + carry = p_high
+        let high_sum = self.add_cells(region, config, &high_cell, &high_rem_cell, offset)?;
+        let high_sum = self.add_cells(region, config, &high_sum, &carry, offset)?;
+        region.constrain_constant(high_sum.cell(), F::from_u128(p_high))?;
+
+        Ok(low_bits[0].clone())
+    }
+
+    fn expose_compressed(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        config: &Self::Config,
+        instance: Column<Instance>,
+        points: &[Self::AssignedECPoint],
+        row: usize,
+    ) -> Result<(), Error> {
+        assert!(points.len() <= F::CAPACITY as usize, "too many points");
+
+        let signs = layouter.assign_region(
+            || "compress points",
+            |mut region| {
+                let mut offset = 0;
+
+                // signs = \sum sign_i * 2^i
+                let mut signs = self.load_constant(&mut region, config, &F::ZERO, &mut offset)?;
+                let mut power = F::ONE;
+                for p in points.iter() {
+                    let sign = self.y_parity(&mut region, config, &p.y, &mut offset)?;
+                    let power_cell =
+                        self.load_constant(&mut region, config, &power, &mut offset)?;
+                    let term =
+                        self.mul_cells(&mut region, config, &sign, &power_cell, &mut offset)?;
+                    signs = self.add_cells(&mut region, config, &signs, &term, &mut offset)?;
+                    power = power.double();
+                }
+
+                // pad the last two rows
+                self.pad(&mut region, config, &mut offset)?;
+
+                Ok(signs)
+            },
+        )?;
+
+        for (i, p) in points.iter().enumerate() {
+            layouter.constrain_instance(p.x.cell(), instance, row + i)?;
+        }
+        layouter.constrain_instance(signs.cell(), instance, row + points.len())
+    }
+}
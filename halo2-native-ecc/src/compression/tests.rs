@@ -0,0 +1,159 @@
+use ark_std::test_rng;
+use ff::PrimeField;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::group::Curve;
+use halo2_proofs::halo2curves::group::Group;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Instance;
+use halo2curves::grumpkin::Fq;
+use halo2curves::grumpkin::G1Affine;
+use halo2curves::grumpkin::G1;
+
+use crate::chip::ECChip;
+use crate::compression::compress_point;
+use crate::compression::compressed_instance;
+use crate::compression::decode_point;
+use crate::compression::decompress_point;
+use crate::compression::encode_point;
+use crate::compression::PointCompressionOps;
+use crate::config::ECConfig;
+use crate::ec_gates::NativeECOps;
+
+#[test]
+fn test_encode_decode() {
+    let mut rng = test_rng();
+
+    // the generator (1, y) has an even y
+    let g = G1Affine::generator();
+    let mut expected = [0u8; 32];
+    expected[0] = 1;
+    assert_eq!(encode_point(&g), expected);
+    expected[31] = 0x80;
+    assert_eq!(encode_point(&-g), expected);
+
+    for _ in 0..10 {
+        let p = G1::random(&mut rng).to_affine();
+        let (x, sign) = compress_point(&p);
+        assert_eq!(decompress_point::<G1Affine, Fq>(&x, sign), Some(p));
+        assert_eq!(decompress_point::<G1Affine, Fq>(&x, !sign), Some(-p));
+        assert_eq!(decode_point::<G1Affine, Fq>(&encode_point(&p)), Some(p));
+    }
+
+    // Error case: x is not canonical
+    {
+        let mut bytes = (-Fq::one()).to_repr();
+        bytes[0] = bytes[0].wrapping_add(1);
+        assert_eq!(decode_point::<G1Affine, Fq>(&bytes), None);
+    }
+
+    // Error case: x is not on curve; x^3 - 17 = -17 is not a square
+    {
+        let bytes = Fq::zero().to_repr();
+        assert_eq!(decode_point::<G1Affine, Fq>(&bytes), None);
+    }
+}
+
+#[derive(Default, Debug, Clone)]
+struct CompressionTestCircuit {
+    points: Vec<G1Affine>,
+}
+
+impl Circuit<Fq> for CompressionTestCircuit {
+    type Config = (ECConfig<G1Affine, Fq>, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        (ECChip::configure(meta), instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let (config, instance) = config;
+        let ec_chip = ECChip::construct(config.clone());
+
+        let points = layouter.assign_region(
+            || "test compression circuit",
+            |mut region| {
+                let mut offset = 0;
+                let mut points = vec![];
+                for p in self.points.iter() {
+                    points.push(ec_chip.load_private_point(
+                        &mut region,
+                        &config,
+                        p,
+                        &mut offset,
+                    )?);
+                }
+
+                // pad the last two rows
+                ec_chip.pad(&mut region, &config, &mut offset)?;
+
+                Ok(points)
+            },
+        )?;
+
+        ec_chip.expose_compressed(&mut layouter, &config, instance, &points, 0)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_expose_compressed() {
+    let k = 11;
+
+    let mut rng = test_rng();
+    let points = (0..3)
+        .map(|_| G1::random(&mut rng).to_affine())
+        .collect::<Vec<_>>();
+
+    {
+        let instance = compressed_instance(&points);
+        // 4 instance cells instead of 6 for the uncompressed coordinates
+        assert_eq!(instance.len(), points.len() + 1);
+
+        let circuit = CompressionTestCircuit {
+            points: points.clone(),
+        };
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    // Error case: the negated point shares x but not the sign
+    {
+        let mut negated = points.clone();
+        negated[1] = -negated[1];
+        let instance = compressed_instance(&negated);
+
+        let circuit = CompressionTestCircuit {
+            points: points.clone(),
+        };
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: wrong x
+    {
+        let mut instance = compressed_instance(&points);
+        instance[0] += Fq::one();
+
+        let circuit = CompressionTestCircuit { points };
+        let prover = MockProver::run(k, &circuit, vec![instance]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
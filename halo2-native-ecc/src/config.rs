@@ -7,6 +7,7 @@ use halo2_proofs::plonk::Advice;
 use halo2_proofs::plonk::Column;
 use halo2_proofs::plonk::Expression;
 use halo2_proofs::plonk::Selector;
+use halo2_proofs::plonk::TableColumn;
 use halo2_proofs::plonk::VirtualCells;
 use halo2_proofs::poly::Rotation;
 
@@ -29,9 +30,34 @@ where
     pub(crate) q2: Selector,          // ec double
     pub(crate) q3: Selector,          // ec on curve
 
+    // lookup based range checks; None if the chip decomposes bit by bit
+    pub(crate) range: Option<RangeLookupConfig>,
+
     pub(crate) _phantom: PhantomData<C>,
 }
 
+/// How `ArithOps::range_check_u128` constrains a u128,
+/// and how `NativeECOps::decompose_scalar` constrains the bits of a scalar
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecompositionMode {
+    /// one bit per cell via the partial bit decomposition gate
+    Bits,
+    /// limbs of `limb_bits` bits checked against a fixed table;
+    /// scalar bits are checked against the same table, two per row.
+    /// `limb_bits` must divide 128 and be at most 16, and the circuit needs k > limb_bits
+    Lookup { limb_bits: usize },
+}
+
+/// Running sum over limbs that are looked up in a fixed table
+#[derive(Clone, Debug)]
+pub(crate) struct RangeLookupConfig {
+    pub(crate) q_range: Selector,
+    // both cells of the row are bits
+    pub(crate) q_bits: Selector,
+    pub(crate) table: TableColumn,
+    pub(crate) limb_bits: usize,
+}
+
 impl<C, F> ECConfig<C, F>
 where
     C: CurveAffine<Base = F>,
@@ -154,6 +180,22 @@ where
         a0 + b0 - a1
     }
 
+    /// running sum gate
+    /// - b1 = b0 * 2^limb_bits + a0
+    pub(crate) fn running_sum_gate(
+        &self,
+        meta: &mut VirtualCells<F>,
+        limb_bits: usize,
+    ) -> Expression<F> {
+        let base = Expression::Constant(F::from_u128(1 << limb_bits));
+
+        let a0 = meta.query_advice(self.a, Rotation::cur());
+        let b0 = meta.query_advice(self.b, Rotation::cur());
+        let b1 = meta.query_advice(self.b, Rotation::next());
+
+        b0 * base + a0 - b1
+    }
+
     /// additional gate
     pub(crate) fn mul_gate(&self, meta: &mut VirtualCells<F>) -> Expression<F> {
         let a0 = meta.query_advice(self.a, Rotation::cur());
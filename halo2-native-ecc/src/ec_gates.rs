@@ -1,7 +1,7 @@
+use ff::PrimeField;
 use halo2_proofs::circuit::AssignedCell;
 use halo2_proofs::circuit::Region;
 use halo2_proofs::circuit::Value;
-use ff::PrimeField;
 use halo2_proofs::halo2curves::group::Curve;
 use halo2_proofs::halo2curves::CurveAffine;
 use halo2_proofs::plonk::Error;
@@ -55,6 +55,17 @@ where
         offset: &mut usize,
     ) -> Result<Self::AssignedECPoint, Error>;
 
+    /// Loads a point whose coordinates are fixed constants of the circuit.
+    ///
+    /// Will allocate the (x, y) to columns (a, b)
+    fn load_constant_point(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        p: &C,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedECPoint, Error>;
+
     /// For an input pair (x, y), enforces the point is on curve.
     fn enforce_on_curve(
         &self,
@@ -116,6 +127,32 @@ where
         S: PrimeField<Repr = [u8; 32]>,
         C: CurveAffine<ScalarExt = S>;
 
+    /// Point mul via double-then-add method, for a point that is already assigned.
+    /// The point may live in another region.
+    ///
+    /// Caller must check p is on curve.
+    fn point_mul_assigned<S>(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        p: &Self::AssignedECPoint,
+        s: &C::ScalarExt,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedECPoint, Error>
+    where
+        S: PrimeField<Repr = [u8; 32]>,
+        C: CurveAffine<ScalarExt = S>;
+
+    /// Copy an assigned point into the current row.
+    /// The point may live in another region.
+    fn copy_point(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        p: &Self::AssignedECPoint,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedECPoint, Error>;
+
     /// Pad the row with empty cells.
     fn pad(
         &self,
@@ -152,6 +189,23 @@ where
         Ok(res)
     }
 
+    /// Loads a point whose coordinates are fixed constants of the circuit.
+    ///
+    /// Will allocate the (x, y) to columns (a, b)
+    fn load_constant_point(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        p: &C,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedECPoint, Error> {
+        let coordinates = p.coordinates().unwrap();
+        let res = self.load_private_point_unchecked(region, config, p, offset)?;
+        region.constrain_constant(res.x.cell(), *coordinates.x())?;
+        region.constrain_constant(res.y.cell(), *coordinates.y())?;
+        Ok(res)
+    }
+
     /// For an input pair (x, y), enforces the point is on curve.
     /// The point must locate at (offset - 1) row
     fn enforce_on_curve(
@@ -290,15 +344,24 @@ where
         C: CurveAffine<ScalarExt = S>,
     {
         let (high, low) = field_decompose_u128(s);
-        let (low_cells, _res) = self.decompose_u128(region, config, &low, offset)?;
-        let (high_cells, _res) = self.decompose_u128(region, config, &high, offset)?;
+        // double-then-add only consumes the bits, so the lookup mode skips the accumulators
+        let (low_cells, high_cells) = if config.range.is_some() {
+            (
+                self.lookup_bits_u128(region, config, &low, offset)?,
+                self.lookup_bits_u128(region, config, &high, offset)?,
+            )
+        } else {
+            (
+                self.decompose_u128(region, config, &low, offset)?.0,
+                self.decompose_u128(region, config, &high, offset)?.0,
+            )
+        };
         let res = [low_cells.as_slice(), high_cells.as_slice()].concat();
 
         Ok(res)
     }
 
     /// Point mul via double-then-add method
-    // todo: assigned point -> point
     fn point_mul<S>(
         &self,
         region: &mut Region<F>,
@@ -307,6 +370,26 @@ where
         s: &C::ScalarExt,
         offset: &mut usize,
     ) -> Result<Self::AssignedECPoint, Error>
+    where
+        S: PrimeField<Repr = [u8; 32]>,
+        C: CurveAffine<ScalarExt = S>,
+    {
+        let p_assigned = self.load_private_point(region, config, p, offset)?;
+        self.point_mul_assigned(region, config, &p_assigned, s, offset)
+    }
+
+    /// Point mul via double-then-add method, for a point that is already assigned.
+    /// The point may live in another region.
+    ///
+    /// Caller must check p is on curve.
+    fn point_mul_assigned<S>(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        p: &Self::AssignedECPoint,
+        s: &C::ScalarExt,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedECPoint, Error>
     where
         S: PrimeField<Repr = [u8; 32]>,
         C: CurveAffine<ScalarExt = S>,
@@ -314,7 +397,6 @@ where
         let generator = C::generator();
         let bits = self.decompose_scalar(region, config, s, offset)?;
 
-        let p_assigned = self.load_private_point(region, config, p, offset)?;
         let gen_assigned = self.load_private_point(region, config, &generator, offset)?;
 
         // we do not have a cell representation for infinity point
@@ -330,17 +412,9 @@ where
 
             // conditional add depending on the bit b
             res = {
-                let p_copied = if leak(&b.value()) == F::ONE {
-                    // copy the base point cells
-                    let p_copied: AssignedECPoint<C, F> =
-                        self.load_private_point_unchecked(region, config, p, offset)?;
-                    region.constrain_equal(p_copied.x.cell(), p_assigned.x.cell())?;
-                    region.constrain_equal(p_copied.y.cell(), p_assigned.y.cell())?;
-                    p_copied
-                } else {
-                    // the point here doesn't matter but we do need to fill in the cells
-                    self.load_private_point_unchecked(region, config, &generator, offset)?
-                };
+                // copy the base point cells regardless of the bit,
+                // so that the copy constraints do not depend on the witness
+                let p_copied = self.copy_point(region, config, p, offset)?;
 
                 // copy the bit cell; already constraint `bit` is either 0 or 1
                 let bit = self.load_two_private_fields(
@@ -358,9 +432,10 @@ where
         }
 
         // now we subtract 2^256 * generator from res
-        let (offset_generator, x, y) = neg_generator_times_2_to_256::<C, C::Base>();
+        // the `subtract 2^256 * generator` cells are fixed constants
+        let (offset_generator, _, _) = neg_generator_times_2_to_256::<C, C::Base>();
         let offset_generator_assigned =
-            self.load_private_point_unchecked(region, config, &offset_generator, offset)?;
+            self.load_constant_point(region, config, &offset_generator, offset)?;
         let bit = self.load_two_private_fields(region, config, &F::ONE, &F::ZERO, offset)?;
         res = self.conditional_point_add(
             region,
@@ -370,13 +445,26 @@ where
             &bit[0],
             offset,
         )?;
-        // ensure the `subtract 2^256 * generator` cells are fixed constants
-        region.constrain_constant(offset_generator_assigned.x.cell(), x)?;
-        region.constrain_constant(offset_generator_assigned.y.cell(), y)?;
 
         Ok(res)
     }
 
+    /// Copy an assigned point into the current row.
+    /// The point may live in another region.
+    fn copy_point(
+        &self,
+        region: &mut Region<F>,
+        config: &Self::Config,
+        p: &Self::AssignedECPoint,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedECPoint, Error> {
+        let x = p.x.copy_advice(|| "x", region, config.a, *offset)?;
+        let y = p.y.copy_advice(|| "y", region, config.b, *offset)?;
+        let res = Self::AssignedECPoint::new(x, y, *offset);
+        *offset += 1;
+        Ok(res)
+    }
+
     /// Pad the row with empty cells.
     fn pad(
         &self,
@@ -14,7 +14,8 @@ where
 {
     pub x: AssignedCell<F, F>,
     pub y: AssignedCell<F, F>,
-    // the index of the ec point: the two cells is always stored in a same row
+    // the index of the ec point: the two cells is always stored in a same row.
+    // the index is relative to the region the point is assigned in
     pub offset: usize,
     _phantom: PhantomData<C>,
 }
@@ -0,0 +1,233 @@
+use ff::Field;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Chip;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use num_bigint::BigUint;
+use poseidon_circuit::poseidon::PoseidonInstructions;
+use poseidon_circuit::poseidon::Pow5Chip;
+use poseidon_circuit::poseidon::Pow5Config;
+use poseidon_circuit::poseidon::StateWord;
+
+use crate::edwards::AssignedEdwardsPoint;
+use crate::edwards::BabyJubJub;
+use crate::edwards::EdwardsChip;
+use crate::edwards::EdwardsConfig;
+use crate::edwards::EdwardsOps;
+use crate::edwards::EdwardsPoint;
+use crate::edwards::TwistedEdwardsCurve;
+use crate::util::big_to_fe;
+use crate::util::fe_to_big;
+use crate::util::modulus;
+
+mod circom;
+#[cfg(test)]
+mod tests;
+
+pub use circom::poseidon;
+pub use circom::EdDSAHashSpec;
+use circom::RATE;
+use circom::WIDTH;
+
+/// An EdDSA signature (R8, S) over BabyJubJub
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct EdDSASignature {
+    pub r8: EdwardsPoint<BabyJubJub>,
+    pub s: BigUint,
+}
+
+/// The challenge h = Poseidon(R8.x, R8.y, A.x, A.y, msg) with circomlib's
+/// width-6 instance, so signatures from circomlib's `signPoseidon` verify here.
+pub fn challenge(r8: &EdwardsPoint<BabyJubJub>, pk: &EdwardsPoint<BabyJubJub>, msg: Fr) -> Fr {
+    poseidon([r8.x, r8.y, pk.x, pk.y, msg])
+}
+
+/// The public key A = sk * Base8
+pub fn public_key(sk: &BigUint) -> EdwardsPoint<BabyJubJub> {
+    BabyJubJub::generator().mul(sk)
+}
+
+/// Sign `msg` with the nonce `r`:
+///     R8 = r * Base8, S = r + 8 * h * sk mod l
+///
+/// The factor 8 matches circomlib, whose pruned secret is 8 * sk;
+/// with circomlib's nonce, this reproduces `signPoseidon`.
+pub fn sign(sk: &BigUint, msg: Fr, r: &BigUint) -> EdDSASignature {
+    let order = BabyJubJub::subgroup_order();
+    let pk = public_key(sk);
+    let r8 = BabyJubJub::generator().mul(r);
+    let h = fe_to_big(&challenge(&r8, &pk, msg));
+    let s = (r + h * sk * 8u64) % order;
+    EdDSASignature { r8, s }
+}
+
+/// Verify S < l and S * Base8 = R8 + 8 * h * A
+pub fn verify(pk: &EdwardsPoint<BabyJubJub>, msg: Fr, sig: &EdDSASignature) -> bool {
+    if sig.s >= BabyJubJub::subgroup_order()
+        || !pk.is_on_curve()
+        || !sig.r8.is_on_curve()
+        || pk.x == Fr::ZERO
+    {
+        return false;
+    }
+    let h = fe_to_big(&challenge(&sig.r8, pk, msg));
+    let left = BabyJubJub::generator().mul(&sig.s);
+    let right = sig.r8.add(&pk.mul(&(h * 8u64)));
+    left == right
+}
+
+#[derive(Clone, Debug)]
+pub struct EdDSAConfig {
+    pub edwards: EdwardsConfig<BabyJubJub>,
+    pub poseidon: Pow5Config<Fr, WIDTH, RATE>,
+}
+
+/// EdDSA-Poseidon verifier over BabyJubJub
+#[derive(Clone, Debug)]
+pub struct EdDSAChip {
+    edwards: EdwardsChip<BabyJubJub>,
+    poseidon: Pow5Chip<Fr, WIDTH, RATE>,
+}
+
+impl EdDSAChip {
+    pub fn construct(config: EdDSAConfig) -> Self {
+        Self {
+            edwards: EdwardsChip::construct(config.edwards),
+            poseidon: Pow5Chip::construct(config.poseidon),
+        }
+    }
+
+    /// The Poseidon chip relies on the constant column enabled by the Edwards chip
+    pub fn configure(meta: &mut ConstraintSystem<Fr>) -> EdDSAConfig {
+        let edwards = EdwardsChip::configure(meta);
+
+        let state = [0; WIDTH].map(|_| meta.advice_column());
+        let partial_sbox = meta.advice_column();
+        let rc_a = [0; WIDTH].map(|_| meta.fixed_column());
+        let rc_b = [0; WIDTH].map(|_| meta.fixed_column());
+        let poseidon = Pow5Chip::configure::<EdDSAHashSpec>(meta, state, partial_sbox, rc_a, rc_b);
+
+        EdDSAConfig { edwards, poseidon }
+    }
+
+    pub fn edwards_chip(&self) -> &EdwardsChip<BabyJubJub> {
+        &self.edwards
+    }
+
+    /// Verify a signature on an assigned message.
+    /// Returns the assigned public key so the caller can bind it to an issuer.
+    ///
+    /// Constraints
+    /// - A and R8 are on curve and A.x != 0
+    /// - S < l
+    /// - S * Base8 = R8 + h * (8 * A)
+    pub fn verify(
+        &self,
+        mut layouter: impl Layouter<Fr>,
+        pk: &EdwardsPoint<BabyJubJub>,
+        sig: &EdDSASignature,
+        msg: &AssignedCell<Fr, Fr>,
+    ) -> Result<AssignedEdwardsPoint<BabyJubJub>, Error> {
+        let config = self.edwards.config();
+
+        let (pk, r8, s, zero) = layouter.assign_region(
+            || "load eddsa signature",
+            |mut region| {
+                let mut offset = 0;
+                let pk = self
+                    .edwards
+                    .load_private_point(&mut region, config, pk, &mut offset)?;
+                self.edwards
+                    .assert_not_zero(&mut region, config, &pk.x, &mut offset)?;
+                let r8 =
+                    self.edwards
+                        .load_private_point(&mut region, config, &sig.r8, &mut offset)?;
+                // S + l is below the modulus, so an unreduced S is still assignable
+                let s = region.assign_advice(
+                    || "s",
+                    config.a,
+                    offset,
+                    || Value::known(big_to_fe(&sig.s)),
+                )?;
+                // the first word of circomlib's initial state
+                let zero =
+                    region.assign_advice(|| "zero", config.b, offset, || Value::known(Fr::ZERO))?;
+                region.constrain_constant(zero.cell(), Fr::ZERO)?;
+                Ok((pk, r8, s, zero))
+            },
+        )?;
+
+        let initial_state = [
+            zero,
+            r8.x.clone(),
+            r8.y.clone(),
+            pk.x.clone(),
+            pk.y.clone(),
+            msg.clone(),
+        ]
+        .map(StateWord::from);
+        let state = <Pow5Chip<Fr, WIDTH, RATE> as PoseidonInstructions<
+            Fr,
+            EdDSAHashSpec,
+            WIDTH,
+            RATE,
+        >>::permute(
+            &self.poseidon,
+            &mut layouter.namespace(|| "eddsa challenge"),
+            &initial_state,
+        )?;
+        let h: AssignedCell<Fr, Fr> = state[0].clone().into();
+
+        layouter.assign_region(
+            || "verify eddsa signature",
+            |mut region| {
+                let mut offset = 0;
+                let s_bits = self.edwards.decompose_less_than(
+                    &mut region,
+                    config,
+                    &s,
+                    &BabyJubJub::subgroup_order(),
+                    &mut offset,
+                )?;
+                // the canonical bits of h
+                let h_bits = self.edwards.decompose_less_than(
+                    &mut region,
+                    config,
+                    &h,
+                    &modulus::<Fr>(),
+                    &mut offset,
+                )?;
+
+                let base8 = self.edwards.load_constant_point(
+                    &mut region,
+                    config,
+                    &BabyJubJub::generator(),
+                    &mut offset,
+                )?;
+                let left =
+                    self.edwards
+                        .scalar_mul(&mut region, config, &base8, &s_bits, &mut offset)?;
+
+                let mut pk8 = pk.clone();
+                for _ in 0..3 {
+                    pk8 = self
+                        .edwards
+                        .double(&mut region, config, &pk8, &mut offset)?;
+                }
+                let right =
+                    self.edwards
+                        .scalar_mul(&mut region, config, &pk8, &h_bits, &mut offset)?;
+                let right = self
+                    .edwards
+                    .add(&mut region, config, &r8, &right, &mut offset)?;
+
+                self.edwards.assert_equal(&mut region, &left, &right)
+            },
+        )?;
+
+        Ok(pk)
+    }
+}
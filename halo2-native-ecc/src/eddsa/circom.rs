@@ -0,0 +1,570 @@
+//! circomlib's Poseidon instance for five inputs:
+//! width 6, x^5 S-boxes, 8 full rounds and 60 partial rounds.
+//!
+//! The constants are taken from circomlib's `poseidon_constants.json`,
+//! which circomlibjs and light-poseidon also use.
+//! circomlib starts from the state [0, inputs] and outputs the first word,
+//! unlike the sponge of poseidon-circuit that keeps the capacity last,
+//! so both the native and the in-circuit hash run the bare permutation.
+
+use ff::Field;
+use ff::PrimeField;
+use halo2_proofs::halo2curves::bn256::Fr;
+use lazy_static::lazy_static;
+use poseidon_base::primitives::permute;
+use poseidon_base::primitives::Mds;
+use poseidon_base::primitives::Spec;
+
+pub(crate) const WIDTH: usize = 6;
+pub(crate) const RATE: usize = 5;
+
+lazy_static! {
+    static ref ROUND_CONSTANTS: Vec<[Fr; WIDTH]> = {
+        let c_str = round_constants();
+        (0..c_str.len() / WIDTH)
+            .map(|i| {
+                [0, 1, 2, 3, 4, 5].map(|j| Fr::from_str_vartime(c_str[i * WIDTH + j]).unwrap())
+            })
+            .collect()
+    };
+    static ref MDS: Mds<Fr, WIDTH> = {
+        let m_str = mds();
+        [0, 1, 2, 3, 4, 5]
+            .map(|i| [0, 1, 2, 3, 4, 5].map(|j| Fr::from_str_vartime(m_str[i][j]).unwrap()))
+    };
+    static ref MDS_INV: Mds<Fr, WIDTH> = invert(&MDS);
+}
+
+/// The Poseidon spec of the EdDSA challenge hash, i.e., circomlib's width-6 instance
+#[derive(Clone, Copy, Debug)]
+pub struct EdDSAHashSpec;
+
+impl Spec<Fr, WIDTH, RATE> for EdDSAHashSpec {
+    fn full_rounds() -> usize {
+        8
+    }
+
+    fn partial_rounds() -> usize {
+        60
+    }
+
+    fn sbox(val: Fr) -> Fr {
+        val.pow_vartime([5])
+    }
+
+    fn secure_mds() -> usize {
+        unimplemented!("the constants are hard-coded")
+    }
+
+    fn constants() -> (Vec<[Fr; WIDTH]>, Mds<Fr, WIDTH>, Mds<Fr, WIDTH>) {
+        (ROUND_CONSTANTS.clone(), *MDS, *MDS_INV)
+    }
+}
+
+/// circomlib's `Poseidon(5)`
+pub fn poseidon(inputs: [Fr; RATE]) -> Fr {
+    let mut state = [Fr::ZERO; WIDTH];
+    state[1..].copy_from_slice(&inputs);
+    permute::<Fr, EdDSAHashSpec, WIDTH, RATE>(&mut state, &MDS, &ROUND_CONSTANTS);
+    state[0]
+}
+
+/// Invert a matrix via Gauss-Jordan elimination; panics if it is singular
+fn invert(m: &Mds<Fr, WIDTH>) -> Mds<Fr, WIDTH> {
+    let mut m = *m;
+    let mut res = [[Fr::ZERO; WIDTH]; WIDTH];
+    for (i, row) in res.iter_mut().enumerate() {
+        row[i] = Fr::ONE;
+    }
+
+    for col in 0..WIDTH {
+        let pivot = (col..WIDTH)
+            .find(|&row| m[row][col] != Fr::ZERO)
+            .expect("the MDS matrix is invertible");
+        m.swap(col, pivot);
+        res.swap(col, pivot);
+
+        let inv = m[col][col].invert().unwrap();
+        for j in 0..WIDTH {
+            m[col][j] *= inv;
+            res[col][j] *= inv;
+        }
+        for row in 0..WIDTH {
+            if row != col {
+                let factor = m[row][col];
+                for j in 0..WIDTH {
+                    let (m_col, res_col) = (m[col][j], res[col][j]);
+                    m[row][j] -= factor * m_col;
+                    res[row][j] -= factor * res_col;
+                }
+            }
+        }
+    }
+    res
+}
+
+fn round_constants() -> Vec<&'static str> {
+    vec![
+        "9174141306060971809979631725764298697615039980311809306145004207410652431953",
+        "4847693924685156250211477469465516228032151306221739650606132660616428517315",
+        "19669833054057639609249840291533340493211768292967819468538893000195036768991",
+        "19800508893433268850924828171290876015556093796000695603651522426066333836892",
+        "8244699449852279148780456022144420353408196866113049322676048275081354214716",
+        "1563672068712965454176533719400672258364596155638916268717470967009721945171",
+        "12723223712027468580318230235559705540011996847167975439677647504573149248849",
+        "19944398841194165937952509356635863229327574447452745793253427406349161295763",
+        "21218058308392585368594275702746106483411305671883946244077923955757637296177",
+        "18442884961885927579732373746933397748806426938144021013884176466434407012116",
+        "11138408360119814115926439449668526422561003790198269766757675305576549475808",
+        "12724564576884231109847024566806896391934587839830522481308995309797961575379",
+        "4897733190252075532660075013731462724561461746919488679609618967302541674417",
+        "4797748331306263412471031924618974997396620231469532262170060449304337691527",
+        "8626839560132907403537141283531395025838110825355541158539075100658769738351",
+        "6096293906324574249636975851522292408228519044739444932687579741964974917617",
+        "2351617695830568421216396081605990689071283678701192113347036659596049514149",
+        "3045682390398203085155257535118136303069379656645406266260961816947178911890",
+        "6935829264874515341379952008241845470659188886156484974987865751370715745075",
+        "19847439266968955911971997829840067368072860877451092633069920565944933744280",
+        "12795097343831149148337906863235678514689648096503928066579129201713661539889",
+        "10424580232112390318877053133877999442988769389050776486274146627765228950235",
+        "11651452649618223740363812212607761589812354035139843126315028745587570714609",
+        "21307929358023177131550002602820591970791247513576735567457471459920519084552",
+        "2579908580162153663820021562014873149811195641589016321720930006635393981680",
+        "8198198178555784054784079137247244121807775986273563786249987394640289859893",
+        "17176088986876377315956611075288620878117708836881362200541916957398026761276",
+        "671389874397910339333118510595007038137908096657753354622355890021074216004",
+        "19161949137729278558310070194809106779119877882343914445178348849980058405327",
+        "10827554013954037091657804154642286174226562252063767377995268439458401752538",
+        "11693672899474469123468133710607776304784343543318650064064636202512816205843",
+        "7026547767612627656560992117440221331093280829523426249915938274837157551621",
+        "14422968137896343032446633683271253661000603582016449215470992885331170459671",
+        "7685352543184863430081115767111935982586458632527708735083385591291346555502",
+        "14089009391529192464370954954330128327830078875414722902347666490457756695535",
+        "8424161061743752192085022963953944100289245618074575727145394775891645849043",
+        "9809236779073852557054640507912802523501426410996355424610807253990040160483",
+        "14100245203768962710288059230665566265892855964739454261791429988929622355986",
+        "7775683622333704945225255741567928967674629526812606133980425422182282014012",
+        "8739247215686497264451630351996892836638898510934389758205488381695687859658",
+        "9431876969679115468275053745264413939426444105271849398322497961102606290132",
+        "257914055321743732506701382989022126153391940932933566664491918941925247878",
+        "21801414068435960590201256257290267142214176965736081788536576642934903066059",
+        "9465495933537134443327560834432669768951376466867005153580146079082722525723",
+        "7862366214258716333873810314803222267215825847232397599183717032713290878315",
+        "10701164906390193792620967030790214270231326273599373762943959252633779929633",
+        "11951628827727068395937910010248864431667047516686609553745879936868276916066",
+        "14268744039571470490378560085356767818183790841094115879980723591887874138419",
+        "14468215915818797151199796266933432577607248341385185700017147731054148927023",
+        "1523824033338639123415809477892820349580561577160869448927791050266158538520",
+        "13559991428776910947424645696251487328999214391124402586267086012691140984198",
+        "18151203063828433535061866995346135260543721730169485344610433976436663085882",
+        "13436242600153492361692256644258899977135098134175123174795293078081801647137",
+        "9384556671429507406657070680351030238568956203341356106463890924933167416522",
+        "20321079285577981781556986944841048777999006905303986053275199507771332527205",
+        "13510502130738135726695195328780836716597947131948116750163533622597187969844",
+        "20903049289119144354363108865308751668897757360882852151457514926552553533040",
+        "5611953645512225417723205546533389174830971368309601830751921473015551069534",
+        "8816886019615642422040038431962872654062471314244185285424018745071289038220",
+        "16751828354835345790163611999302863949792305206769993810746019449909446216365",
+        "10421654749141018171116296259626916395875529220250947127973888230084671091757",
+        "6065225315766552671037285757918350882361743810888619479819895087632281975681",
+        "5737755346739850738724717271213687543479332312420206954339242459110768587128",
+        "14770522272891919220644639305274656491731294860310497013287297810648680944682",
+        "2777394791070450473479179489594969793054480209411136328689318984981401732197",
+        "10039559932930709555975364107098145624058027439566384376771787183526929807647",
+        "20757756003754261934858081777796652436155530474748550156383127600004580439167",
+        "13253166894715452480712170898662712132411702335275401581167208877688374856806",
+        "2037004052447343668129085129987646907388123739343356363273464870501805506884",
+        "21829471491172175426560705585746893969222010633542962882847909490991398830669",
+        "5130395545419191392223692116621486075405299333195732914002649716762739787586",
+        "20333821730990393095934147177227294218344864602777744425090741435432040213391",
+        "13629653802252084129446975515814037702423511189484562534040643669977716900228",
+        "18489091892360842692678715136565494502607711254719045543684163289077857041829",
+        "21380328601365035012832876315565064374684993115210423862017233170195286906080",
+        "2280052193465635727584791148501382679094142036232980037838088033232747821762",
+        "21415541711468815972744677841317235994302058341802530962394281077076174148777",
+        "17146992672828650459975820445250769505470616910596779130798889014378635881076",
+        "21676475584514120109058208398560066698690773910598518925936412952356431597439",
+        "18337052978997482578725645166749278142628133291693686105612531426715865276143",
+        "14864089429815580405957698645045711801464462794754089671996837547347950054532",
+        "10834607317840698149140890207826430113987295440254355899459691878793978994131",
+        "1157143498448645320415276909137008396665083714591338741616893578930275511205",
+        "5027542104048754930085470328670427788489455916338375169351586496298129661248",
+        "1922685817237874482932428650501872692326329693528175054457715565489676406535",
+        "3071473720617798005831658342971536643616129392641449174655528578463370685788",
+        "21091078808046042460442535848913779439792606439995062001271357804782672390627",
+        "19773167374024045118471391738750949555178717045037157435777574972149053404157",
+        "6418695831178793575992210834992785624340084513619644969535805236049937971859",
+        "6317875495482489567338519005308431806047606843913867465201005132273298011425",
+        "18001249545956637376455848019549801116909661454019565655561439372098476761813",
+        "15530167556609139699164228289904946047951254183080358784988008899829027775935",
+        "8702757129830652230304011519426558036441096750485189115358314568895250616455",
+        "6369986882953061252605652398893489899416599935424066958291402945530517772170",
+        "6842894437627604179732847187262933342846269043996061072487488027804029200046",
+        "20951621154051947571647917571547811655800779287153833018533872651413529893817",
+        "1219277535080749134805291725937516331501172121638812333911793209536894469364",
+        "11704605822590166851511022757496386950530399074796545751042566537118336773236",
+        "5983427701962592508775640503988144495847156070437130549832329402380170245893",
+        "20169091361583397776908351163571343158517532527313940288212943504015977979442",
+        "3347733015762117176159731683196584632702931062411889821726902331981723958255",
+        "16217509027282489850987935065936382820558307489954122630844029918951230268972",
+        "10781269196927764524006466217779648732772805761839205677745819812868343369087",
+        "10568911823766972365218731330080733630028238366288098114239172953421915095075",
+        "5568774544682750792074131352530555554984876659733959079036284517928264996437",
+        "17854353469028651373397049175548228061144941710027186166132671198740388767529",
+        "6573034112757039329551886086829829282007989555105157401271097204633906940776",
+        "14069627287078359391137554212536883450595451640858724555679971658981340584258",
+        "21119713641590541511025673864154852875977162278614553796484277752677323191505",
+        "12802116677235410441672624559825044917295689876859311183079161588690810005363",
+        "16037054471696658545113065872215787085337497333273419984439267709950724531124",
+        "11698654309680908244303850432833183602706804558317993513795996394673734185716",
+        "15147889780127043019188099948246961619198549928908180192590946633702778981583",
+        "3657342516407201801006680507925024451922115018712017224805778401726428603983",
+        "19776786467141868744713630352693556348834540992018636838044610844396164981103",
+        "7980994848490005281733955776875257044050741738176865989521982608944874160873",
+        "12415191330803073018395217955802011585094769098717180100014182475381600382452",
+        "9300986814650530426668152137665814177758578011365736727321578452726378799933",
+        "4412208980274764197258090802604347599791567698589180187154608728755887977460",
+        "2582317668924231956058541757507620542434237159213236485179804217989764223164",
+        "19860814395849792324574773787600734118308975251437485131415273418632757301303",
+        "2765909129639570206766170018363951893338720647679193401532780051354569922989",
+        "5402210382809272147099442645489124829067576777592680891367494969197685281513",
+        "21011104174655621871977821285307554463403659856745964274018020456838460357574",
+        "7018364707286303918877589672878574811337524823085078243421192184715151775983",
+        "136380103284908296988715215087018020601815024625535396780012012453684253071",
+        "15953315437474610448052466140270091879233956524793052736202793153707558909889",
+        "5912305909658884889781037379491781973092020933879206417274479331390062715252",
+        "21575635295587180789566592951559325743281772394055590203112195979769645712827",
+        "1541325805478255472079288730846072146731241030100908414806224735345400173350",
+        "17207219201921814683730773200330679841907450967511507012179337438654141678023",
+        "18266907794578843029196926509122804272900478710738403531664855427655744759655",
+        "1204224895193276222782842236712348692319665277014183965830735736728887994581",
+        "4023246588034712778784328407820569751989619386134504404739514704773521558127",
+        "9064437981037864995763386367268294611921404895425171966596873454090899491243",
+        "18733802217274421976148972926716884457128521840010001893311936746027998476583",
+        "684088380644531080099595788833220377905013807951051638705160997709156627273",
+        "11994830816367980341637110785269531718699655485484715851375754143223090344544",
+        "1831724566362300629700078416489434571462666430381219293205871349415506993475",
+        "476710745682537342427691635955087951551678644045621275039835625280220347951",
+        "3586272766499559446129476613035465343616602918105042144185864609818186807939",
+        "21220348736799044560439132291243370111879983677197111626309132298278891334631",
+        "13683795063599185801186093771702503913590598475095473714851383723199050309401",
+        "16118007386401646906425171859166434660243697555307927508268622819509657450614",
+        "20930641024767526790605168032291665313905337763598128831404465184891980632233",
+        "8098646212401100552303711812039666794078834386731698810205195111722330322418",
+        "11585783577173465460243373201831086724911159484415020913089605532852648999143",
+        "6939053275662244505087635417541857793206828446247848992283188764105131966721",
+        "12798043540382494855660472922674138947867597503468216532170157050160462426199",
+        "20713389801600667412553956346192236970217099413304167366340548074880917096741",
+        "8708207547232102069057776099666995672015399188924281674772351753887161579745",
+        "16016293152251662056020528248861487281148011452459422778601663166015837379163",
+        "14324897997637439510797191208789711173129460994362368408063402682894248793270",
+        "5652996184880208428967511742390474289004021508049280419259474250332590598159",
+        "9877106633097964013050071703002221796318046172981334418310092241450453368579",
+        "5385816971548914185604875069230499528103133871233951354186676373318036241822",
+        "8683091293306949708478955451280670950858818602696102489349595054818146782362",
+        "16854975838650963077652189417311897888852709425835763860743171659164792100482",
+        "2485160816649177905834265823672532710299580013309324666453183278408904845122",
+        "13571692148185502188613896013359942531817915076247598483272449919094247957149",
+        "11899399615412173136098732970606292047945698835588882297719609812145308198009",
+        "16827672312681684936590464376780346837611857292837989006980972390576065571472",
+        "15588237822592586948064701827497915157359094833395277985658706133691498343174",
+        "18356642512438827417103800170157877145465512961188328254773957819312191285168",
+        "21642368145757804795143182901389223409544979732781450480847315495418822041608",
+        "13104082060493963869934085622104709047787444250961437496674916673804812287386",
+        "1561532086277971111804773016487251313460788916643968126116038406859074212104",
+        "2718320602791009266532615731130512762296058687816604986701989820504700684864",
+        "6182683520717583142027400659687593712743548729948584058329789905227082638908",
+        "5757242145794370726637363237313640925174531077560764545993554185332488520899",
+        "13688467192244237790806289073845563960119021610896694359815485764764608925981",
+        "12528461541936459922472167643986446262977222390263675720335825628163511159437",
+        "4897268894447399415795897967133432014527122426051771866816059363418177665482",
+        "764332419588242767884018802335623760055144509861323437945071732931233600264",
+        "11755468878196093893190753985692714003062307843033761257593209352165323938879",
+        "6006022813561851182403581780143813226749481175437001910923100661321563995672",
+        "13901542382190510449243772206670622017835690746895066410475076631498053123535",
+        "17648853891656481911225897080296737974064729032668806126284849597245044343224",
+        "15106333841965710929952896897521673254279668876709612770907537801609875568099",
+        "20899315415025260484895459315726322363345188136910564549344894025053466430346",
+        "1409310408943258102775009950750654615881913956151269414096059752250092035807",
+        "3899088673345731523976816322438172722785832982334214339521575164464706226294",
+        "21406686765584824639201351330529610299177537976609066339927938099572420696135",
+        "9121591670793901722224770893633585291275002987585289305307167711146944200595",
+        "10711764678410479049841945177317023555168593838022414378232020467195337241279",
+        "6599257303974597452501135281719536074294806740553273627128065549267140155175",
+        "2142616913275380526921597026822750992917222975992774063376747381991404337593",
+        "16361086527663411948363284957489078505159658832010445114438602510508720771278",
+        "17122647864721668762640781848678028227021534122268561738445496382823789619088",
+        "21708018685042482318786273055293241752114005312590172460099480713746031274624",
+        "8303630654111760473056607545365338851734309857718959193970615705292826806179",
+        "3658686547507488906491014260011151850549759409901579684176172268581462329020",
+        "7720024124908065424512743488999250878143598904717873371853608249805302871508",
+        "8805244918657836956533473437651380347005779399042661429698187314657501156241",
+        "6303681354794120075893215838935586592706844702088252970663343726024171795351",
+        "21512507181643408509426104627003618425209526633080701556628608990726677651135",
+        "11835373417333287523801757951049679177935522717858158305516568595764125190183",
+        "13059698839045014411602727811400239840163533672024084777768305507840091151855",
+        "17635240655824524168378284083397931667938326555447077097306236826752492079430",
+        "3374412791113107178205006579112630099131939030015047870738873452427211677886",
+        "649711083340882271985565833699379436167716866997851102439037906608755280128",
+        "20002805138014565226408902156524463368767807620908543995020210484077706418135",
+        "11071355197960433041624284534649121637702414580710232237233568479006159191217",
+        "1105441595020980635809093220782460032826849883993030969714432603468135735502",
+        "9652765957610682812348919340146799318537766051849796416434577860126024594091",
+        "19248299650856496267902926731608572596705132576830681367365128976226233392929",
+        "15285802367070100569572399512275861017714681455564415244982064571963339715277",
+        "19970416835730683993734843405673457882587154729456022607061085470691843864556",
+        "1017865638757684714433500504002748241987153668285974836527484933462490771227",
+        "17284848056169793253916338792235498052654877955690514601079806604278964099314",
+        "11718277105372928962350331838305733149270432706448484259807630484543527733952",
+        "6670793378364949883511003949124179112275066568088468958915163969545409700112",
+        "17088789393958965094855662340742013087397643056458490270185660553870734946796",
+        "1930788514812600942005320214284180860980345276633471423966020111188605196111",
+        "8844343159753729614645407314580317697758296041737296276765583948670245312842",
+        "16657939543606018325703787748629433167511611178952563626096990460124133990109",
+        "15333343644239485619497914931918504163396626751908652058758135581206765801100",
+        "16533875915742793452819179569144271760125646811168930162441077117553849625884",
+        "19679534317472082858641184998487299940737032844519038845860980362664393659234",
+        "16385719932525604857740698205965045007053424961009717093945644387917936681719",
+        "14490521084213123170781774542655088188106794646066074998587858678154251198444",
+        "6386781978322405984893078797365492485297499058328348606653460996474947075858",
+        "17508047533433736707046937662428611868296556965172642086594091783148965906980",
+        "14904597000414815084666285064575232635645852687797347860862157463159487771060",
+        "14979972442969995336727018758631782107138089738395941038626891064816880204567",
+        "5299243186271864957800928637599294208954109271450189950375274196644046222516",
+        "16189884555052883188473617525411302750109401983487269295700675997730645714379",
+        "1645560170870292006287241616671417605853047420339675073261660626733726665673",
+        "17866745974872498136933906591373095763114066893081150553715211393380040095383",
+        "5744849574386643500716045532645657520001448510343827372577217716983339773799",
+        "14021966200238971589811034967347517039341058556783068950884921208853167419283",
+        "1201178089866013320759085637098781870734315826415474628546655403142858044361",
+        "5875644793836087035760988842421852197052681650818034527831700615895391179258",
+        "10875065950479466897559006840696567433921014267247530366235539292597441428702",
+        "2221662399199449388725697795500999209427453463134383582414172135385907744785",
+        "9758513532658579204941116584445291102215928928145103503086996542188799521709",
+        "20879593323317766577775570558015407573466986714590017262168011643343469361329",
+        "17225846522404915080676699509636264825833159640824918876741681229188434930856",
+        "15189442986691997434021855855358620506645387296294217783597931695143376252483",
+        "15973617135551858849206811241799666696907820418171736027820254766840973764431",
+        "11888113439449420418408437784450952639345990804839507528208325036625374967083",
+        "12365920814385241227394825974928370916184942218042429533600397623369545597697",
+        "11966175169612449906889690852332416255478894176917636726028104087408060623141",
+        "11163554022908212145274813635928762748847331295589087669583554722521180712379",
+        "15273476004030808005186443499782264987539818978741159793745891769358221570633",
+        "2013969196885866182480519514425192091338553670034650196068995589691938248955",
+        "5008975446746271526106846692137145404766553748264648461545948417006052208130",
+        "3926749194225734582453671614337621250954608160208554883789519551411469033731",
+        "1635544156808471185144068767649088695307748439189898784051754434524720057896",
+        "17144944482517962143604430553750908864860079758005337246916094084534304051981",
+        "13823503533305241872793740090687668844401004819859520464168798913603662683770",
+        "16335911272023134851779534303717879370955813837529588982953758998930285394340",
+        "14467284210444150699969889681308566002886261365990840091849371665183151060295",
+        "10578205764525658336257882813734672799527733392763965031628376897794294290414",
+        "18771425328697137255453620743509164311086906349726510394566012237817674245865",
+        "21804626093983212038528370352039806004465345685985435415809095637323683466452",
+        "12056805308954301132385034564357716323176447186932453788072119595595483786736",
+        "14307195735327805282612857510308008767450554777122724855715789120735513378827",
+        "6848201070063637295416045855906784325422580350462489495889308309540335269587",
+        "631364713487758647973016689203003205602593076699875191323345338325349259049",
+        "16214655556434201961140525501007839859074077768660052713461045928979956365067",
+        "20940788212183642266181811368870506130164462254923655617893660245551698033523",
+        "8257440848494309435270838240795567828478627302119374684511017376568090372435",
+        "13701089242130867705897643891164147923878521147124165292045879194108024940909",
+        "6895272953337895406509859406973110417619874994579965619097329249292199573333",
+        "530437169778092455975584310016745919549274205817234464915791595041990209639",
+        "9008612822403008353420189298381046023002474279157557733428254452507266389025",
+        "14863423501786052071018008300345884780479084379412157784789951872243409629758",
+        "20091026239041315645045502002997446404106877721183777765607724358538559881231",
+        "11103877261161399045807234470901399725912406134008627937945079980590775715243",
+        "21529163495181909351665093277427712610965764606448489357319207727176092439794",
+        "19540446772694448035410067193880900774391072899517686330271100773183944540294",
+        "17549510450820803306426739851959754252204444648959723652883552677325100583689",
+        "12252518814610348662318155253547558779974557529822012236107550517806390105567",
+        "8058115132085119666951861652409945532276905989404523986413207631657437321956",
+        "15916100116790431839835734530362130437167135501074855072245598938219364570910",
+        "14256533476494466694764843270015662315303617568641801280831873052211753536970",
+        "17865471381417606502707639037418669122823481329049436020149405646709537112534",
+        "14015711483636570179335132940981982618090553643653746531174110949872682031017",
+        "6075776171664976866533080327142904134938121198707020111533599997509054627652",
+        "6357981809351565370498807027309828058036389418343890944791766504532174516243",
+        "15145296985037303761634018005118672316118004891352906450983918852209191841446",
+        "2473672396516437070485250176897956191104549656554290725379242542480862701754",
+        "11059085933391482002269653121188853142706883316754376424538662772943167665341",
+        "14804069155713123448375113552227724310276294677318593116834685772120057819258",
+        "10146378656966122923223443263705119557842694560695035707977826044606938090895",
+        "21828309590915152213768434346306434851424116996828875020020066586363340244814",
+        "15568879616082229996551157805731419126872501425454775741945679993142071548779",
+        "17504079509060638501918729619244098692140123800571022969294759717277257664716",
+        "2998311560047298465700351970612785742605093777116697796464434026101441410385",
+        "20229972737818088327107446854254558628041027965197447598027135778783710740259",
+        "14884874200763033520375899992902136897590350894844904733314191389520252900641",
+        "9619409751736964504139815024141276029474791187139050183491749032619248817404",
+        "11534029087676783672833531415041588991838838078174102967049055562568798961925",
+        "17106297093375816944137015955705541133308466659538554159312635106186252148471",
+        "21676736161168806529097919794022110433487869702564846859065695507460463414524",
+        "12596447704589377083704857810305080195761099125652005594925931498073219198049",
+        "310943124066162607352831846280730445558498286205117614171844835745706684432",
+        "16013029710570597613246104892930389004941711962070683476555063566372534206859",
+        "14282564976066063966062366540992448474634085812789771416509095817495183298269",
+        "20757241092771652500911491636894210910134068426068355089789205706892703219255",
+        "17084251309147907751212619949757520468224028014308500329099194408342072624132",
+        "14680350698112448759886861002622963534698534998651150537754386791270019720748",
+        "17739512731440543100681958009173086667000199263945053345384367808940651002571",
+        "8967486063900234709994801661246451094429250620940593387993430620369318619734",
+        "3906067814916986286272005884942051451306945488494283077675304366798199289520",
+        "2517004675157816404807349457307096161030587393097616279110332574293494030636",
+        "9995302877359286298434340810356550712107485295049220989690824504445305103587",
+        "12849909876017357260683411536833847986127911582040960825577300322066595609115",
+        "18074515800779889507358182860997188274134395074469953155084226981497567860114",
+        "6692811728183968363967959295970424292426462800383828091752006855360167264617",
+        "17859827663908740084792157440799065184931609649811664442236242315795442091367",
+        "12243409340804252499520308602187370739653046835019551522661290645230850934962",
+        "3009118420068966587115224335717185828292538080040896739662684632413054772046",
+        "15856202298588272962175258696610233941787471472716811521132004805327415486141",
+        "7549804594729480554341356998842376772514802673462970334329441043324983960866",
+        "6390806437030742378988258255983502109201709511321162596105974797942236431761",
+        "17370236522182003753669946647208335160124999930136364231371998757664000198520",
+        "2261672244214630177095236704932243497157963117166120717011661647779055001646",
+        "17325026196605130064689259977831126468940872193987407658419640959345091161632",
+        "3631641025220845885502691330008982895233731506600778684638817282531001457735",
+        "8656561399441987116927438675277763317789561532507396244334062468892541066084",
+        "4069166732330197412844703565599514109399373916243310212229125901351402003915",
+        "19808198732373520522982274785888742523226720967259539531129335924093928174880",
+        "8555796834031869022510134190573521699378201702450788201649007358450530423866",
+        "17759660636058865290579521740750449606781204755231964378855563896473545202303",
+        "1335826395218609619260020055566056869243760115287254209950063597653055872566",
+        "21596200365241795669701682696176077888309278223833581800772036945674858315765",
+        "12619752319673193899296833725747186284394167228468888029626464753793997178599",
+        "17420588547980145067421969830249755561311178399975476925894947008643385243007",
+        "10337481272389772505654575850886249605422739785111225132545740838911222864209",
+        "17928431631046752749930349099366498612885288622404560316665023363985966878427",
+        "3075798659324203306711977985120251896073145961913793478792728028765206521425",
+        "4639500613932181914847461422373341918892878975546430906324216810326467690534",
+        "15396322795715441250300995201889120935591602515487993982711884319616897970533",
+        "6391276937505284102735701938724106665734769352007891548547667448647832351929",
+        "6811373320779057384916660178551330838095673247430496448933336925226142036083",
+        "6590973140323934807800215988687710942074412987201753370126190631819398102173",
+        "19364648614154949386936259588484266535262135334799266379433252509193375956715",
+        "4702754284612371917466042550086249683933140314858807272591351280832918881874",
+        "1081036249074169248236179367049085684430282426446509768147097371368406374049",
+        "18548093223441988703029589168425055383154624592689171393242936199350770119589",
+        "11098999608073377668352846814752381891400020647878345005629685447730764310163",
+        "16001262992680194260590639872321865154716987495605624862471107193457192704714",
+        "21696229443869118415905915570780926763029898831113534481730746953640692230062",
+        "11716215712634983607563947056324900205144202447594949676250978337464771243867",
+        "1778908113733035314726603632369389424542091991692308812147944884836647395775",
+        "4019081204388123040098634987844274011285321286777408246805308194144238418480",
+        "3473266952388383063447927231564219811787341139731701190625605897592140631276",
+        "10457881304788072618845101933412333126160339089704353596608910674508961127232",
+        "14926101732700077295531234099443522459232814784151318061435025890154852791802",
+        "4036967072197259618286839959572768559469665646019907384624959071646231971399",
+        "12776716624632228928613396031717959431597335742467953143594165782617234803915",
+        "18894783424164609284436913400522166453255844750192864579927645453695213022195",
+        "6303809107919167113924303987533838414137996606980561570652539716097058487126",
+        "4729698693443803882717817492985796053343431875965792864932005291979914613160",
+        "1645790034267553926884568714540144778649055395816210525904813567839945991808",
+        "8138260225269705405100573121045873922755899939885385491610389913906979427176",
+        "680936760009829486282006800072001712155424246576949107399338687767760991887",
+        "17240357869291182045663678468827695873425113788704614245279840174870850373113",
+        "19100963939745621863641468371111320143895293700517367016077996431570157414340",
+        "16188989656090417148189510820963186890780289777598053654241741803194118100843",
+        "18027402882394597868782011288920739982398714370069420860949975937357531046151",
+        "17780529984916796963712255733293310230026423072958099290880849386941451922559",
+        "20004531511171838591303710792081846238092292916166965045929062171308088520097",
+        "13855731634251510230399834192704620793850325654395687428672253016405315169901",
+        "16872938837392115669581040432902657478544143723662502779821325505282093696739",
+        "2541555081244462826761076743762714962901590548271316707071685417008817634653",
+        "5136424039269088350807839181761422963254683236279333039713142751702136147963",
+        "19216238128964101420135465007632926445321991494181045543846024053552797518994",
+        "18868537488540023742258053821537824724371813776839672880900985865823137839953",
+        "18246710415801024039719497716350501105591286880983169809863166130543617917249",
+        "20608694004331631709610739723463009412162748201282986294016482926528443868949",
+        "11318113915971658853560322943565673154831611543653209084299774855226816037778",
+        "16240989418312335385576389959938922684406585560688799437547298624184839261343",
+        "16171299673760267132909753100946681733778389681324959987573199154235691694977",
+        "8036823955656422391918380552495301547890420665617977624790236120392727764522",
+        "20269862530534739231936251654244170650781428788816658397167110617927916774329",
+        "2368678892744667199202318323282128737449992006513656480477288092472671147090",
+        "4618078962163037429845764284139891171861860687111566735174912070413086829215",
+        "12695350627501306162901105159009497730633599768443844225981772758225613194238",
+        "16356283146491744069785034066388746989409816380917535719898337817088223419024",
+        "6407893217596287850421377738867081146106659458551198123106454022096864887316",
+        "18168868018352364136212098098453930600797374324006271488950341490483455519349",
+        "18352629174410142476418438008157117497168118524562206830585500251463010761689",
+        "4344169393287991961961456515301754172943022039566219343212376057129143739343",
+        "19424839806870716108478074501405697296961947409763509419111261767390677718987",
+        "5796037897847804302272999466834285170265203646465480652521088328457333766863",
+        "17402105801450379889120987010453669096275392789725153915905747267778100864362",
+        "15540989618743824352651126288511222263828123668208146479603617243655978402205",
+        "945810410725426921570254447269595873973858272778720657523509910503434094174",
+        "6962323734045776666289031609372270190654631739266635759799844631053633876675",
+        "11382945272742312954364642163371436855283161775445664525053938433459897196647",
+        "18940251871958826726849623572811640436342841713786099464305053400421580490631",
+        "13969540696178305383564753026163726563325318478290740131984853424331762285147",
+        "4841983966001277917879506889862519614692143906356361564304719688757862622407",
+        "8939049562492171082419559182596894186639203815268680721033389307282239000385",
+        "19265363396776097866041313346787101192508520582744521467413665478819721956884",
+        "337106861429123598189388456471513480497137213511877011021531147545809512194",
+        "251367482782327915297484770356856386307188967585026711663629212746150191478",
+        "19506616511267234489421548744907283107923549136620297132842391511025844759064",
+        "20633589633280372440758096707466273580151526293980868749421563697429194761212",
+        "18833062060138888612708634036427140134887774731041742144004707524569102994071",
+        "2927291160590267909596732410727396533948837350308818016906834558527125752899",
+        "7095572562193114209617459307511041110255341231707924363346373597653253806883",
+        "14274988113217913224290208839851596837329960221329537670822013510325939323091",
+        "9965830780560026128320556230399915681196410289456547935188741323403719404039",
+        "10333365845496980935202034863900757172839454015352626511769637076650624839070",
+    ]
+}
+
+fn mds() -> Vec<Vec<&'static str>> {
+    vec![
+        vec![
+            "8266021233794274332054729525918686051968756165685671155584565440479247355160",
+            "7947823415909040438587565055355894256799314737783432792935458921778371169026",
+            "16508811191852041977017821887204137955816331040385276110261643892701458724933",
+            "1804800467126006102677564831888710635194614232739335985819349312754063580223",
+            "11189892034806587650995829160516587240879881493093022855087765921356611070470",
+            "20567450145123179140729389574352706949280207113956641415022972885523439610844",
+        ],
+        vec![
+            "4666756311257455192796774305229624459258864488677689058174087310651786875914",
+            "11389253665835451896363091846189307652796786468610595637047377864063404843117",
+            "18793736599347263150867965517898541872137378991464725717839931503944801692688",
+            "4206344588923325482680116848820594823631536459347642329098796888497153867720",
+            "1739462481670645248707834504605096139894257554120906850613041004917967456145",
+            "18514227342636266640333254638454588508118462110178719555586534011641424431745",
+        ],
+        vec![
+            "17887039315911403193186866703775654467672391491657957999455462537283842145802",
+            "2824959020572825365047639014537190268717891749361604043531643698340708119767",
+            "12521547103713919592301476538318318223836047611311454785951907894055964264287",
+            "8658146183671258251984364885894342376430874614261222570603159082682815800788",
+            "154390145585284450772861151318029820117470958184878116158462181541183085587",
+            "7593705166056392393963956710828665339496927193740869686529339432486182720653",
+        ],
+        vec![
+            "5529559239163081088908568555890212324771345012509269613465629182165427812002",
+            "3729910453162885538930719732708124491456460687048972152311428493400220125686",
+            "11942815243552870715777415109008273807076911177089425348095503288499102855779",
+            "498938524453430895689241565973888863905147713935369405079343247530256066618",
+            "3976257517234324421403708035200810671331954932478384823208414346189926720724",
+            "723540703523219510043977323240437576248315561543814629392162302024056718473",
+        ],
+        vec![
+            "13306548824219676333032339487546407241767961556934015003605485324283250885682",
+            "7970147269291664639740298762956131361316495463191268382513594527221399186752",
+            "20633313939958767604804835838065337107615699351647541991788258289962727735454",
+            "17162090859520817529294904484646695645841022315617926715432606252643123848792",
+            "9181379842957190051440498041153333325098774266789773971685141362947015398641",
+            "7051606617662816798224904133351061549832959857069896192072217769241273559278",
+        ],
+        vec![
+            "16619522548478824222688310091434959542211899852679631815023615875678448806029",
+            "14965311177811968100298579672135357167599499478246106482433786066289128683961",
+            "9792733250919070275775594069208673385381167169182805600474820364274865306108",
+            "2069253833779081039049908513863485270550301879399727430830923273191877809560",
+            "15847298987712771667136245955631872888473964330474501593909263901393348546986",
+            "12244443532166430060291409356011430759892629145539185535677568234713942157668",
+        ],
+    ]
+}
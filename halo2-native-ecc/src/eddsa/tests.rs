@@ -0,0 +1,240 @@
+use ff::Field;
+use ff::PrimeField;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Instance;
+use num_bigint::BigUint;
+
+use crate::eddsa::poseidon;
+use crate::eddsa::public_key;
+use crate::eddsa::sign;
+use crate::eddsa::verify;
+use crate::eddsa::EdDSAChip;
+use crate::eddsa::EdDSAConfig;
+use crate::eddsa::EdDSASignature;
+use crate::edwards::BabyJubJub;
+use crate::edwards::EdwardsPoint;
+use crate::edwards::TwistedEdwardsCurve;
+
+fn big(s: &str) -> BigUint {
+    BigUint::parse_bytes(s.as_bytes(), 10).unwrap()
+}
+
+fn keypair() -> (BigUint, EdwardsPoint<BabyJubJub>) {
+    let sk = big("1743524719893267498451023472617312468210957436152034917240195320");
+    let pk = public_key(&sk);
+    (sk, pk)
+}
+
+fn nonce() -> BigUint {
+    big("9876543210123456789098765432101234567890987654321")
+}
+
+fn fe(s: &str) -> Fr {
+    Fr::from_str_vartime(s).unwrap()
+}
+
+/// circomlibjs' `signPoseidon` test: the private key is the bytes
+/// 0001020304050607080900010203040506070809000102030405060708090001,
+/// and the message is the bytes 000102030405060708090000 read in little endian
+struct CircomlibVector {
+    // the pruned blake512 of the private key, divided by 8
+    sk: BigUint,
+    // blake512 of the second half of the private key hash and the message, mod l
+    nonce: BigUint,
+    msg: Fr,
+    pk: EdwardsPoint<BabyJubJub>,
+    sig: EdDSASignature,
+}
+
+fn circomlib_vector() -> CircomlibVector {
+    CircomlibVector {
+        sk: big("6466070937662820620902051049739362987537906109895538826186780010858059362905"),
+        nonce: big("998509002261817064039893525009363315223455691288800741227950990424097427109"),
+        msg: Fr::from_u128(42649378395939397566720),
+        pk: EdwardsPoint::new(
+            fe("13277427435165878497778222415993513565335242147425444199013288855685581939618"),
+            fe("13622229784656158136036771217484571176836296686641868549125388198837476602820"),
+        )
+        .unwrap(),
+        sig: EdDSASignature {
+            r8: EdwardsPoint::new(
+                fe("11384336176656855268977457483345535180380036354188103142384839473266348197733"),
+                fe("15383486972088797283337779941324724402501462225528836549661220478783371668959"),
+            )
+            .unwrap(),
+            s: big("1672775540645840396591609181675628451599263765380031905495115170613215233181"),
+        },
+    }
+}
+
+#[test]
+fn test_circom_poseidon() {
+    // circomlibjs' `poseidon` test vectors
+    let inputs = [1, 2, 0, 0, 0].map(Fr::from);
+    assert_eq!(
+        poseidon(inputs),
+        fe("1018317224307729531995786483840663576608797660851238720571059489595066344487")
+    );
+    let inputs = [1, 2, 3, 4, 5].map(Fr::from);
+    assert_eq!(
+        poseidon(inputs),
+        fe("6183221330272524995739186171720101788151706631170188140075976616310159254464")
+    );
+}
+
+#[test]
+fn test_eddsa_circomlib() {
+    let vector = circomlib_vector();
+    assert_eq!(public_key(&vector.sk), vector.pk);
+    assert_eq!(sign(&vector.sk, vector.msg, &vector.nonce), vector.sig);
+    assert!(verify(&vector.pk, vector.msg, &vector.sig));
+
+    // Error case: wrong message
+    assert!(!verify(&vector.pk, vector.msg + Fr::ONE, &vector.sig));
+}
+
+#[test]
+fn test_eddsa_native() {
+    let (sk, pk) = keypair();
+    let msg = Fr::from(1234);
+    let sig = sign(&sk, msg, &nonce());
+    assert!(verify(&pk, msg, &sig));
+
+    // Error case: wrong message
+    assert!(!verify(&pk, Fr::from(1235), &sig));
+
+    // Error case: S is not reduced
+    let malleated = EdDSASignature {
+        s: &sig.s + BabyJubJub::subgroup_order(),
+        ..sig.clone()
+    };
+    assert!(!verify(&pk, msg, &malleated));
+
+    // Error case: wrong key
+    let other = public_key(&(sk + 1u64));
+    assert!(!verify(&other, msg, &sig));
+}
+
+#[derive(Default, Debug, Clone)]
+struct EdDSATestCircuit {
+    pk: EdwardsPoint<BabyJubJub>,
+    sig: EdDSASignature,
+    msg: Fr,
+}
+
+impl Circuit<Fr> for EdDSATestCircuit {
+    type Config = (EdDSAConfig, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        (EdDSAChip::configure(meta), instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let (config, instance) = config;
+        let chip = EdDSAChip::construct(config.clone());
+
+        let msg = layouter.assign_region(
+            || "load message",
+            |mut region| {
+                region.assign_advice(|| "msg", config.edwards.a, 0, || Value::known(self.msg))
+            },
+        )?;
+
+        let pk = chip.verify(layouter.namespace(|| "eddsa"), &self.pk, &self.sig, &msg)?;
+
+        // the message and the issuer are public
+        layouter.constrain_instance(msg.cell(), instance, 0)?;
+        layouter.constrain_instance(pk.x.cell(), instance, 1)?;
+        layouter.constrain_instance(pk.y.cell(), instance, 2)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_eddsa_circuit() {
+    let k = 13;
+
+    let (sk, pk) = keypair();
+    let msg = Fr::from(1234);
+    let sig = sign(&sk, msg, &nonce());
+    let circuit = EdDSATestCircuit {
+        pk,
+        sig: sig.clone(),
+        msg,
+    };
+    let instance = vec![vec![msg, pk.x, pk.y]];
+
+    {
+        let prover = MockProver::run(k, &circuit, instance.clone()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    // a signature from circomlib
+    {
+        let vector = circomlib_vector();
+        let circuit = EdDSATestCircuit {
+            pk: vector.pk,
+            sig: vector.sig,
+            msg: vector.msg,
+        };
+        let instance = vec![vec![vector.msg, vector.pk.x, vector.pk.y]];
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        prover.assert_satisfied();
+    }
+
+    // Error case: wrong message
+    {
+        let circuit = EdDSATestCircuit {
+            msg: Fr::from(1235),
+            ..circuit.clone()
+        };
+        let prover = MockProver::run(k, &circuit, vec![vec![Fr::from(1235), pk.x, pk.y]]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: S + l passes the curve equation but is not reduced
+    {
+        let circuit = EdDSATestCircuit {
+            sig: EdDSASignature {
+                s: &sig.s + BabyJubJub::subgroup_order(),
+                ..sig.clone()
+            },
+            ..circuit.clone()
+        };
+        let prover = MockProver::run(k, &circuit, instance.clone()).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: tampered R8
+    {
+        let circuit = EdDSATestCircuit {
+            sig: EdDSASignature {
+                r8: sig.r8.add(&BabyJubJub::generator()),
+                ..sig.clone()
+            },
+            ..circuit.clone()
+        };
+        let prover = MockProver::run(k, &circuit, instance).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
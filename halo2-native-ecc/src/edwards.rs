@@ -0,0 +1,675 @@
+use std::fmt::Debug;
+use std::marker::PhantomData;
+
+use ff::PrimeField;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Chip;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::Advice;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Expression;
+use halo2_proofs::plonk::Fixed;
+use halo2_proofs::plonk::Selector;
+use halo2_proofs::poly::Rotation;
+use num_bigint::BigUint;
+
+use crate::util::fe_to_big;
+use crate::util::leak;
+
+#[cfg(test)]
+mod tests;
+
+/// A twisted Edwards curve a * x^2 + y^2 = 1 + d * x^2 * y^2 over the native field.
+///
+/// The addition law is complete if a is a square and d is not.
+pub trait TwistedEdwardsCurve: Clone + Copy + Debug + PartialEq + Eq {
+    /// the field for circuit, i.e., BN::Scalar
+    type Base: PrimeField;
+
+    fn a() -> Self::Base;
+
+    fn d() -> Self::Base;
+
+    /// The generator of the prime order subgroup
+    fn generator() -> EdwardsPoint<Self>;
+
+    /// The order of the prime order subgroup
+    fn subgroup_order() -> BigUint;
+}
+
+/// BabyJubJub, also known as Ed-on-BN254, with the parameters of circomlib.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BabyJubJub;
+
+impl TwistedEdwardsCurve for BabyJubJub {
+    type Base = Fr;
+
+    fn a() -> Fr {
+        Fr::from(168700)
+    }
+
+    fn d() -> Fr {
+        Fr::from(168696)
+    }
+
+    /// circomlib's Base8
+    fn generator() -> EdwardsPoint<Self> {
+        EdwardsPoint::new(
+            Fr::from_str_vartime(
+                "5299619240641551281634865583518297030282874472190772894086521144482721001553",
+            )
+            .unwrap(),
+            Fr::from_str_vartime(
+                "16950150798460657717958625567821834550301663161624707787222815936182638968203",
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    }
+
+    fn subgroup_order() -> BigUint {
+        BigUint::parse_bytes(
+            b"2736030358979909402780800718157159386076813972158567259200215660948447373041",
+            10,
+        )
+        .unwrap()
+    }
+}
+
+/// A point in affine coordinates; the identity is (0, 1).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EdwardsPoint<E: TwistedEdwardsCurve> {
+    pub x: E::Base,
+    pub y: E::Base,
+}
+
+impl<E: TwistedEdwardsCurve> Default for EdwardsPoint<E> {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl<E: TwistedEdwardsCurve> EdwardsPoint<E> {
+    /// Returns None if (x, y) is not on curve
+    pub fn new(x: E::Base, y: E::Base) -> Option<Self> {
+        let p = Self { x, y };
+        p.is_on_curve().then_some(p)
+    }
+
+    pub fn identity() -> Self {
+        Self {
+            x: E::Base::ZERO,
+            y: E::Base::ONE,
+        }
+    }
+
+    pub fn is_on_curve(&self) -> bool {
+        let x2 = self.x.square();
+        let y2 = self.y.square();
+        E::a() * x2 + y2 == E::Base::ONE + E::d() * x2 * y2
+    }
+
+    /// Complete addition
+    ///     x3 = (x1 * y2 + y1 * x2) / (1 + d * x1 * x2 * y1 * y2)
+    ///     y3 = (y1 * y2 - a * x1 * x2) / (1 - d * x1 * x2 * y1 * y2)
+    pub fn add(&self, other: &Self) -> Self {
+        let u = self.x * other.x;
+        let v = self.y * other.y;
+        let t = E::d() * u * v;
+        let x = (self.x * other.y + self.y * other.x) * (E::Base::ONE + t).invert().unwrap();
+        let y = (v - E::a() * u) * (E::Base::ONE - t).invert().unwrap();
+        Self { x, y }
+    }
+
+    pub fn double(&self) -> Self {
+        self.add(self)
+    }
+
+    pub fn neg(&self) -> Self {
+        Self {
+            x: -self.x,
+            y: self.y,
+        }
+    }
+
+    /// Double-then-add from the most significant bit
+    pub fn mul(&self, scalar: &BigUint) -> Self {
+        let mut res = Self::identity();
+        for i in (0..scalar.bits()).rev() {
+            res = res.double();
+            if scalar.bit(i) {
+                res = res.add(self);
+            }
+        }
+        res
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AssignedEdwardsPoint<E: TwistedEdwardsCurve> {
+    pub x: AssignedCell<E::Base, E::Base>,
+    pub y: AssignedCell<E::Base, E::Base>,
+}
+
+impl<E: TwistedEdwardsCurve> AssignedEdwardsPoint<E> {
+    pub fn witness(&self) -> EdwardsPoint<E> {
+        EdwardsPoint {
+            x: leak(&self.x.value()),
+            y: leak(&self.y.value()),
+        }
+    }
+}
+
+/// Four advices, one fixed column for comparison bounds and four gates
+#[derive(Clone, Debug)]
+pub struct EdwardsConfig<E: TwistedEdwardsCurve> {
+    // witnesses
+    pub(crate) a: Column<Advice>,
+    pub(crate) b: Column<Advice>,
+    pub(crate) c: Column<Advice>,
+    pub(crate) d: Column<Advice>,
+    // the bits of a comparison bound
+    pub(crate) k: Column<Fixed>,
+
+    // selectors
+    pub(crate) q_add: Selector,      // conditional add
+    pub(crate) q_on_curve: Selector, // on curve
+    pub(crate) q_bits: Selector,     // bit decomposition with comparison
+    pub(crate) q_not_zero: Selector, // non zero
+
+    pub(crate) _phantom: PhantomData<E>,
+}
+
+impl<E: TwistedEdwardsCurve> EdwardsConfig<E> {
+    /// conditional add
+    ///
+    /// | a  | b  | c    |
+    /// --------------------
+    /// | x1 | y1 | cond |
+    /// | x2 | y2 |      |
+    /// | u  | v  |      |
+    /// | x3 | y3 |      |
+    ///
+    /// p2 is replaced by the identity (0, 1) if cond is 0, then
+    /// - u = x1 * x2, v = y1 * y2
+    /// - x3 * (1 + d * u * v) = x1 * y2 + y1 * x2
+    /// - y3 * (1 - d * u * v) = v - a * u
+    fn conditional_add_gate(
+        &self,
+        meta: &mut halo2_proofs::plonk::VirtualCells<E::Base>,
+    ) -> Vec<Expression<E::Base>> {
+        let one = Expression::Constant(E::Base::ONE);
+        let a = Expression::Constant(E::a());
+        let d = Expression::Constant(E::d());
+
+        let x1 = meta.query_advice(self.a, Rotation::cur());
+        let y1 = meta.query_advice(self.b, Rotation::cur());
+        let cond = meta.query_advice(self.c, Rotation::cur());
+        let x2 = meta.query_advice(self.a, Rotation::next());
+        let y2 = meta.query_advice(self.b, Rotation::next());
+        let u = meta.query_advice(self.a, Rotation(2));
+        let v = meta.query_advice(self.b, Rotation(2));
+        let x3 = meta.query_advice(self.a, Rotation(3));
+        let y3 = meta.query_advice(self.b, Rotation(3));
+
+        let x2 = cond.clone() * x2;
+        let y2 = cond.clone() * (y2 - one.clone()) + one.clone();
+        let t = d * u.clone() * v.clone();
+
+        vec![
+            cond.clone() * (one.clone() - cond),
+            u.clone() - x1.clone() * x2.clone(),
+            v.clone() - y1.clone() * y2.clone(),
+            x3 * (one.clone() + t.clone()) - (x1 * y2 + y1 * x2),
+            y3 * (one - t) - (v - a * u),
+        ]
+    }
+
+    /// a * x^2 + y^2 = 1 + d * x^2 * y^2
+    fn on_curve_gate(
+        &self,
+        meta: &mut halo2_proofs::plonk::VirtualCells<E::Base>,
+    ) -> Vec<Expression<E::Base>> {
+        let one = Expression::Constant(E::Base::ONE);
+        let a = Expression::Constant(E::a());
+        let d = Expression::Constant(E::d());
+
+        let x = meta.query_advice(self.a, Rotation::cur());
+        let y = meta.query_advice(self.b, Rotation::cur());
+        let x2 = x.clone() * x;
+        let y2 = y.clone() * y;
+
+        vec![a * x2.clone() + y2.clone() - one - d * x2 * y2]
+    }
+
+    /// one step of a most significant bit first decomposition,
+    /// compared against the bits k of a constant bound
+    ///
+    /// | a   | b   | c   | d   | k |
+    /// -----------------------------
+    /// | bit | acc | lt  | eq  | k |
+    /// |     | acc'| lt' | eq' |   |
+    ///
+    /// - bit is binary
+    /// - acc' = 2 * acc + bit
+    /// - lt' = lt + k * eq * (1 - bit), i.e., the prefix becomes smaller
+    /// - eq' = eq * (k * bit + (1 - k) * (1 - bit)), i.e., the prefix stays equal
+    fn bits_gate(
+        &self,
+        meta: &mut halo2_proofs::plonk::VirtualCells<E::Base>,
+    ) -> Vec<Expression<E::Base>> {
+        let one = Expression::Constant(E::Base::ONE);
+        let two = Expression::Constant(E::Base::from(2));
+
+        let bit = meta.query_advice(self.a, Rotation::cur());
+        let acc = meta.query_advice(self.b, Rotation::cur());
+        let lt = meta.query_advice(self.c, Rotation::cur());
+        let eq = meta.query_advice(self.d, Rotation::cur());
+        let k = meta.query_fixed(self.k, Rotation::cur());
+        let acc_next = meta.query_advice(self.b, Rotation::next());
+        let lt_next = meta.query_advice(self.c, Rotation::next());
+        let eq_next = meta.query_advice(self.d, Rotation::next());
+
+        vec![
+            bit.clone() * (one.clone() - bit.clone()),
+            acc_next - (two * acc + bit.clone()),
+            lt_next - (lt + k.clone() * eq.clone() * (one.clone() - bit.clone())),
+            eq_next - eq * (k.clone() * bit.clone() + (one.clone() - k) * (one - bit)),
+        ]
+    }
+
+    /// a * b = 1
+    fn not_zero_gate(
+        &self,
+        meta: &mut halo2_proofs::plonk::VirtualCells<E::Base>,
+    ) -> Vec<Expression<E::Base>> {
+        let one = Expression::Constant(E::Base::ONE);
+        let a = meta.query_advice(self.a, Rotation::cur());
+        let inv = meta.query_advice(self.b, Rotation::cur());
+
+        vec![a * inv - one]
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EdwardsChip<E: TwistedEdwardsCurve> {
+    config: EdwardsConfig<E>,
+}
+
+impl<E: TwistedEdwardsCurve> Chip<E::Base> for EdwardsChip<E> {
+    type Config = EdwardsConfig<E>;
+    type Loaded = ();
+
+    fn config(&self) -> &Self::Config {
+        &self.config
+    }
+
+    fn loaded(&self) -> &Self::Loaded {
+        &()
+    }
+}
+
+impl<E: TwistedEdwardsCurve> EdwardsChip<E> {
+    pub fn construct(config: <Self as Chip<E::Base>>::Config) -> Self {
+        Self { config }
+    }
+
+    pub fn configure(meta: &mut ConstraintSystem<E::Base>) -> <Self as Chip<E::Base>>::Config {
+        let a = meta.advice_column();
+        meta.enable_equality(a);
+        let b = meta.advice_column();
+        meta.enable_equality(b);
+        let c = meta.advice_column();
+        meta.enable_equality(c);
+        let d = meta.advice_column();
+        meta.enable_equality(d);
+
+        let k = meta.fixed_column();
+        let f = meta.fixed_column();
+        meta.enable_constant(f);
+
+        let config = EdwardsConfig {
+            a,
+            b,
+            c,
+            d,
+            k,
+            q_add: meta.selector(),
+            q_on_curve: meta.selector(),
+            q_bits: meta.selector(),
+            q_not_zero: meta.selector(),
+            _phantom: PhantomData,
+        };
+
+        meta.create_gate("edwards conditional add", |meta| {
+            let q = meta.query_selector(config.q_add);
+            config
+                .conditional_add_gate(meta)
+                .into_iter()
+                .map(|e| q.clone() * e)
+                .collect::<Vec<_>>()
+        });
+        meta.create_gate("edwards on curve", |meta| {
+            let q = meta.query_selector(config.q_on_curve);
+            config
+                .on_curve_gate(meta)
+                .into_iter()
+                .map(|e| q.clone() * e)
+                .collect::<Vec<_>>()
+        });
+        meta.create_gate("edwards bits", |meta| {
+            let q = meta.query_selector(config.q_bits);
+            config
+                .bits_gate(meta)
+                .into_iter()
+                .map(|e| q.clone() * e)
+                .collect::<Vec<_>>()
+        });
+        meta.create_gate("edwards not zero", |meta| {
+            let q = meta.query_selector(config.q_not_zero);
+            config
+                .not_zero_gate(meta)
+                .into_iter()
+                .map(|e| q.clone() * e)
+                .collect::<Vec<_>>()
+        });
+
+        config
+    }
+}
+
+pub trait EdwardsOps<E: TwistedEdwardsCurve> {
+    type Config;
+    type AssignedPoint;
+
+    /// Loads a point into the circuit as a private input.
+    /// Constraints the point is on curve.
+    fn load_private_point(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p: &EdwardsPoint<E>,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error>;
+
+    /// Loads a point whose coordinates are fixed constants of the circuit.
+    fn load_constant_point(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p: &EdwardsPoint<E>,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error>;
+
+    /// Input p1 and p2 that are on the curve, and a cell b.
+    ///
+    /// Returns
+    /// - p3 = p1 + p2 if b == 1.
+    /// - p3 = p1 if b == 0.
+    ///
+    /// Constraints b is a bit. The addition is complete, so p1 and p2
+    /// may be equal or the identity.
+    fn conditional_add(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p1: &Self::AssignedPoint,
+        p2: &Self::AssignedPoint,
+        b: &AssignedCell<E::Base, E::Base>,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error>;
+
+    /// Return p3 = p1 + p2
+    fn add(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p1: &Self::AssignedPoint,
+        p2: &Self::AssignedPoint,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error>;
+
+    /// Return p2 = p1 + p1
+    fn double(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p1: &Self::AssignedPoint,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error> {
+        self.add(region, config, p1, p1, offset)
+    }
+
+    /// Point mul via double-then-add method.
+    /// The bits are the most significant first, e.g., from `decompose_less_than`.
+    fn scalar_mul(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p: &Self::AssignedPoint,
+        bits: &[AssignedCell<E::Base, E::Base>],
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error>;
+
+    /// Decompose `value` into bits, the most significant first,
+    /// and constraint `value < bound`.
+    fn decompose_less_than(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        value: &AssignedCell<E::Base, E::Base>,
+        bound: &BigUint,
+        offset: &mut usize,
+    ) -> Result<Vec<AssignedCell<E::Base, E::Base>>, Error>;
+
+    /// Constraint the cell is not zero.
+    fn assert_not_zero(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        a: &AssignedCell<E::Base, E::Base>,
+        offset: &mut usize,
+    ) -> Result<(), Error>;
+
+    /// Constraint the two points are equal.
+    fn assert_equal(
+        &self,
+        region: &mut Region<E::Base>,
+        p1: &Self::AssignedPoint,
+        p2: &Self::AssignedPoint,
+    ) -> Result<(), Error>;
+}
+
+impl<E: TwistedEdwardsCurve> EdwardsOps<E> for EdwardsChip<E> {
+    type Config = EdwardsConfig<E>;
+    type AssignedPoint = AssignedEdwardsPoint<E>;
+
+    fn load_private_point(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p: &EdwardsPoint<E>,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error> {
+        let x = region.assign_advice(|| "x", config.a, *offset, || Value::known(p.x))?;
+        let y = region.assign_advice(|| "y", config.b, *offset, || Value::known(p.y))?;
+        config.q_on_curve.enable(region, *offset)?;
+        *offset += 1;
+        Ok(AssignedEdwardsPoint { x, y })
+    }
+
+    fn load_constant_point(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p: &EdwardsPoint<E>,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error> {
+        let x = region.assign_advice(|| "x", config.a, *offset, || Value::known(p.x))?;
+        let y = region.assign_advice(|| "y", config.b, *offset, || Value::known(p.y))?;
+        region.constrain_constant(x.cell(), p.x)?;
+        region.constrain_constant(y.cell(), p.y)?;
+        *offset += 1;
+        Ok(AssignedEdwardsPoint { x, y })
+    }
+
+    fn conditional_add(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p1: &Self::AssignedPoint,
+        p2: &Self::AssignedPoint,
+        b: &AssignedCell<E::Base, E::Base>,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error> {
+        let bit = leak(&b.value());
+        let p1_witness = p1.witness();
+        let p2_witness = if bit == E::Base::ONE {
+            p2.witness()
+        } else {
+            EdwardsPoint::identity()
+        };
+        let u = p1_witness.x * p2_witness.x;
+        let v = p1_witness.y * p2_witness.y;
+        let p3_witness = p1_witness.add(&p2_witness);
+
+        config.q_add.enable(region, *offset)?;
+        p1.x.copy_advice(|| "x1", region, config.a, *offset)?;
+        p1.y.copy_advice(|| "y1", region, config.b, *offset)?;
+        b.copy_advice(|| "cond", region, config.c, *offset)?;
+        p2.x.copy_advice(|| "x2", region, config.a, *offset + 1)?;
+        p2.y.copy_advice(|| "y2", region, config.b, *offset + 1)?;
+        region.assign_advice(|| "u", config.a, *offset + 2, || Value::known(u))?;
+        region.assign_advice(|| "v", config.b, *offset + 2, || Value::known(v))?;
+        let x = region.assign_advice(
+            || "x3",
+            config.a,
+            *offset + 3,
+            || Value::known(p3_witness.x),
+        )?;
+        let y = region.assign_advice(
+            || "y3",
+            config.b,
+            *offset + 3,
+            || Value::known(p3_witness.y),
+        )?;
+        *offset += 4;
+
+        Ok(AssignedEdwardsPoint { x, y })
+    }
+
+    fn add(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p1: &Self::AssignedPoint,
+        p2: &Self::AssignedPoint,
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error> {
+        // column d is free on the first row of the add gate
+        let one =
+            region.assign_advice(|| "one", config.d, *offset, || Value::known(E::Base::ONE))?;
+        region.constrain_constant(one.cell(), E::Base::ONE)?;
+        self.conditional_add(region, config, p1, p2, &one, offset)
+    }
+
+    fn scalar_mul(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        p: &Self::AssignedPoint,
+        bits: &[AssignedCell<E::Base, E::Base>],
+        offset: &mut usize,
+    ) -> Result<Self::AssignedPoint, Error> {
+        // the addition is complete, so we can start from the identity
+        let mut res =
+            self.load_constant_point(region, config, &EdwardsPoint::identity(), offset)?;
+        for bit in bits.iter() {
+            res = self.double(region, config, &res, offset)?;
+            res = self.conditional_add(region, config, &res, p, bit, offset)?;
+        }
+        Ok(res)
+    }
+
+    fn decompose_less_than(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        value: &AssignedCell<E::Base, E::Base>,
+        bound: &BigUint,
+        offset: &mut usize,
+    ) -> Result<Vec<AssignedCell<E::Base, E::Base>>, Error> {
+        let n = bound.bits();
+        let value_witness = fe_to_big(&leak(&value.value()));
+
+        let mut acc = E::Base::ZERO;
+        let mut lt = E::Base::ZERO;
+        let mut eq = E::Base::ONE;
+
+        let acc_cell = region.assign_advice(|| "acc", config.b, *offset, || Value::known(acc))?;
+        region.constrain_constant(acc_cell.cell(), E::Base::ZERO)?;
+        let lt_cell = region.assign_advice(|| "lt", config.c, *offset, || Value::known(lt))?;
+        region.constrain_constant(lt_cell.cell(), E::Base::ZERO)?;
+        let eq_cell = region.assign_advice(|| "eq", config.d, *offset, || Value::known(eq))?;
+        region.constrain_constant(eq_cell.cell(), E::Base::ONE)?;
+
+        let mut bits = vec![];
+        let mut lt_cell = lt_cell;
+        for i in (0..n).rev() {
+            let bit = E::Base::from(value_witness.bit(i) as u64);
+            let k = E::Base::from(bound.bit(i) as u64);
+
+            config.q_bits.enable(region, *offset)?;
+            region.assign_fixed(|| "k", config.k, *offset, || Value::known(k))?;
+            bits.push(region.assign_advice(|| "bit", config.a, *offset, || Value::known(bit))?);
+
+            acc = acc.double() + bit;
+            lt += k * eq * (E::Base::ONE - bit);
+            eq *= k * bit + (E::Base::ONE - k) * (E::Base::ONE - bit);
+            *offset += 1;
+
+            let acc_cell =
+                region.assign_advice(|| "acc", config.b, *offset, || Value::known(acc))?;
+            lt_cell = region.assign_advice(|| "lt", config.c, *offset, || Value::known(lt))?;
+            region.assign_advice(|| "eq", config.d, *offset, || Value::known(eq))?;
+            if i == 0 {
+                region.constrain_equal(acc_cell.cell(), value.cell())?;
+            }
+        }
+        // the last row holds the final accumulator and comparison flags
+        region.constrain_constant(lt_cell.cell(), E::Base::ONE)?;
+        *offset += 1;
+
+        Ok(bits)
+    }
+
+    fn assert_not_zero(
+        &self,
+        region: &mut Region<E::Base>,
+        config: &Self::Config,
+        a: &AssignedCell<E::Base, E::Base>,
+        offset: &mut usize,
+    ) -> Result<(), Error> {
+        let inv = leak(&a.value()).invert().unwrap_or(E::Base::ZERO);
+
+        config.q_not_zero.enable(region, *offset)?;
+        a.copy_advice(|| "a", region, config.a, *offset)?;
+        region.assign_advice(|| "inv", config.b, *offset, || Value::known(inv))?;
+        *offset += 1;
+        Ok(())
+    }
+
+    fn assert_equal(
+        &self,
+        region: &mut Region<E::Base>,
+        p1: &Self::AssignedPoint,
+        p2: &Self::AssignedPoint,
+    ) -> Result<(), Error> {
+        region.constrain_equal(p1.x.cell(), p2.x.cell())?;
+        region.constrain_equal(p1.y.cell(), p2.y.cell())
+    }
+}
@@ -0,0 +1,246 @@
+use ff::PrimeField;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Instance;
+use num_bigint::BigUint;
+
+use crate::edwards::BabyJubJub;
+use crate::edwards::EdwardsChip;
+use crate::edwards::EdwardsConfig;
+use crate::edwards::EdwardsOps;
+use crate::edwards::EdwardsPoint;
+use crate::edwards::TwistedEdwardsCurve;
+use crate::util::big_to_fe;
+use crate::util::modulus;
+
+type Point = EdwardsPoint<BabyJubJub>;
+
+fn point(x: &str, y: &str) -> Point {
+    Point {
+        x: Fr::from_str_vartime(x).unwrap(),
+        y: Fr::from_str_vartime(y).unwrap(),
+    }
+}
+
+fn big(s: &str) -> BigUint {
+    BigUint::parse_bytes(s.as_bytes(), 10).unwrap()
+}
+
+// test vectors from circomlib/test/babyjub.js
+fn p1() -> Point {
+    point(
+        "17777552123799933955779906779655732241715742912184938656739573121738514868268",
+        "2626589144620713026669568689430873010625803728049924121243784502389097019475",
+    )
+}
+
+fn p2() -> Point {
+    point(
+        "16540640123574156134436876038791482806971768689494387082833631921987005038935",
+        "20819045374670962167435360035096875258406992893633759881276124905556507972311",
+    )
+}
+
+fn p1_plus_p1() -> Point {
+    point(
+        "6890855772600357754907169075114257697580319025794532037257385534741338397365",
+        "4338620300185947561074059802482547481416142213883829469920100239455078257889",
+    )
+}
+
+fn p1_plus_p2() -> Point {
+    point(
+        "7916061937171219682591368294088513039687205273691143098332585753343424131937",
+        "14035240266687799601661095864649209771790948434046947201833777492504781204499",
+    )
+}
+
+fn scalar() -> BigUint {
+    big("20819045374670962167435360035096875258406992893633759881276124905556507972311")
+}
+
+fn p1_times_scalar() -> Point {
+    point(
+        "13563888653650925984868671744672725781658357821216877865297235725727006259983",
+        "8442587202676550862664528699803615547505326611544120184665036919364004251662",
+    )
+}
+
+#[test]
+fn test_babyjub_native() {
+    let p1 = p1();
+    let p2 = p2();
+    assert!(p1.is_on_curve());
+    assert!(p2.is_on_curve());
+    assert!(BabyJubJub::generator().is_on_curve());
+
+    assert_eq!(p1.add(&p1), p1_plus_p1());
+    assert_eq!(p1.double(), p1_plus_p1());
+    assert_eq!(p1.add(&p2), p1_plus_p2());
+    assert_eq!(
+        p1.mul(&BigUint::from(3u64)),
+        point(
+            "19372461775513343691590086534037741906533799473648040012278229434133483800898",
+            "9458658722007214007257525444427903161243386465067105737478306991484593958249",
+        )
+    );
+    assert_eq!(p1.mul(&scalar()), p1_times_scalar());
+
+    // the identity is neutral and the addition is complete
+    assert_eq!(p1.add(&Point::identity()), p1);
+    assert_eq!(p1.add(&p1.neg()), Point::identity());
+
+    // Base8 generates the subgroup of order l
+    let g = BabyJubJub::generator();
+    assert_eq!(g.mul(&BabyJubJub::subgroup_order()), Point::identity());
+    assert_ne!(g, Point::identity());
+
+    // off curve
+    assert!(Point::new(p1.x, p2.y).is_none());
+}
+
+#[derive(Default, Debug, Clone)]
+struct EdwardsTestCircuit {
+    p1: Point,
+    p2: Point,
+    s: Fr,
+    bound: BigUint,
+}
+
+impl Circuit<Fr> for EdwardsTestCircuit {
+    type Config = (EdwardsConfig<BabyJubJub>, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        (EdwardsChip::configure(meta), instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let (config, instance) = config;
+        let chip = EdwardsChip::construct(config.clone());
+
+        let (sum, double, mul) = layouter.assign_region(
+            || "test edwards circuit",
+            |mut region| {
+                let mut offset = 0;
+
+                let p1 = chip.load_private_point(&mut region, &config, &self.p1, &mut offset)?;
+                let p2 = chip.load_private_point(&mut region, &config, &self.p2, &mut offset)?;
+
+                // unit test: complete addition and doubling
+                let sum = chip.add(&mut region, &config, &p1, &p2, &mut offset)?;
+                let double = chip.double(&mut region, &config, &p1, &mut offset)?;
+
+                // unit test: scalar multiplication
+                let s = region.assign_advice(|| "s", config.a, offset, || Value::known(self.s))?;
+                offset += 1;
+                let start = offset;
+                let bits =
+                    chip.decompose_less_than(&mut region, &config, &s, &self.bound, &mut offset)?;
+                let mul = chip.scalar_mul(&mut region, &config, &p1, &bits, &mut offset)?;
+                println!("edwards scalar mul uses {} rows", offset - start);
+
+                Ok((sum, double, mul))
+            },
+        )?;
+
+        for (i, p) in [sum, double, mul].iter().enumerate() {
+            layouter.constrain_instance(p.x.cell(), instance, 2 * i)?;
+            layouter.constrain_instance(p.y.cell(), instance, 2 * i + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+fn instance(points: &[Point]) -> Vec<Vec<Fr>> {
+    vec![points.iter().flat_map(|p| [p.x, p.y]).collect()]
+}
+
+#[test]
+fn test_edwards_chip() {
+    let k = 12;
+    let circuit = EdwardsTestCircuit {
+        p1: p1(),
+        p2: p2(),
+        s: big_to_fe(&scalar()),
+        bound: modulus::<Fr>(),
+    };
+    let expected = instance(&[p1_plus_p2(), p1_plus_p1(), p1_times_scalar()]);
+
+    {
+        let prover = MockProver::run(k, &circuit, expected.clone()).unwrap();
+        prover.assert_satisfied();
+    }
+
+    // 3 * p1 with a small bound
+    {
+        let circuit = EdwardsTestCircuit {
+            s: Fr::from(3),
+            bound: BigUint::from(4u64),
+            ..circuit.clone()
+        };
+        let p1_times_3 = p1().mul(&BigUint::from(3u64));
+        let prover = MockProver::run(
+            k,
+            &circuit,
+            instance(&[p1_plus_p2(), p1_plus_p1(), p1_times_3]),
+        )
+        .unwrap();
+        prover.assert_satisfied();
+    }
+
+    // Error case: wrong result
+    {
+        let wrong = instance(&[p1_plus_p1(), p1_plus_p1(), p1_times_scalar()]);
+        let prover = MockProver::run(k, &circuit, wrong).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: the scalar is not less than the bound
+    {
+        let circuit = EdwardsTestCircuit {
+            s: Fr::from(3),
+            bound: BigUint::from(3u64),
+            ..circuit.clone()
+        };
+        let p1_times_3 = p1().mul(&BigUint::from(3u64));
+        let prover = MockProver::run(
+            k,
+            &circuit,
+            instance(&[p1_plus_p2(), p1_plus_p1(), p1_times_3]),
+        )
+        .unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: point not on curve
+    {
+        let circuit = EdwardsTestCircuit {
+            p2: Point {
+                x: p2().x,
+                y: p1().y,
+            },
+            ..circuit.clone()
+        };
+        let prover = MockProver::run(k, &circuit, expected).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
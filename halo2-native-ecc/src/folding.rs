@@ -0,0 +1,591 @@
+use ff::Field;
+use ff::PrimeField;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::halo2curves::group::prime::PrimeCurveAffine;
+use halo2_proofs::halo2curves::group::Curve;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use poseidon_base::hash::MessageHashable;
+use poseidon_circuit::poseidon::Pow5Config;
+
+use crate::chip::ECChip;
+use crate::config::ECConfig;
+use crate::pedersen::PedersenParams;
+use crate::sponge::configure_poseidon;
+use crate::sponge::MessageSponge;
+use crate::util::field_decompose_u128;
+use crate::util::leak;
+use crate::util::neg_generator_times_2_to_256;
+use crate::ArithOps;
+use crate::AssignedECPoint;
+use crate::NativeECOps;
+
+#[cfg(test)]
+mod tests;
+
+/// Domain tag for the folding challenge; exactly 16 bytes so it fits a u128.
+const FOLDING_DOMAIN: &[u8; 16] = b"recreds-nifs-chl";
+
+/// A sparse matrix given by its non-zero (row, column, value) entries
+pub type SparseMatrix<S> = Vec<(usize, usize, S)>;
+
+/// A rank-1 constraint system (A z) * (B z) = C z for z = (W, 1, x),
+/// where W has `num_vars` and x has `num_io` elements.
+///
+/// Folding works on the relaxed form (A z) * (B z) = u * (C z) + E
+/// for z = (W, u, x), which is closed under random linear combinations.
+#[derive(Clone, Debug)]
+pub struct R1CS<S> {
+    pub num_vars: usize,
+    pub num_io: usize,
+    pub num_constraints: usize,
+    pub a: SparseMatrix<S>,
+    pub b: SparseMatrix<S>,
+    pub c: SparseMatrix<S>,
+}
+
+/// A committed R1CS instance
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1CSInstance<C: CurveAffine> {
+    pub comm_w: C,
+    pub x: Vec<C::ScalarExt>,
+}
+
+/// The witness of an `R1CSInstance`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct R1CSWitness<S> {
+    pub w: Vec<S>,
+}
+
+/// A committed relaxed R1CS instance; the running instance of the folding
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedR1CSInstance<C: CurveAffine> {
+    pub comm_w: C,
+    pub comm_e: C,
+    pub u: C::ScalarExt,
+    pub x: Vec<C::ScalarExt>,
+}
+
+/// The witness of a `RelaxedR1CSInstance`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RelaxedR1CSWitness<S> {
+    pub w: Vec<S>,
+    pub e: Vec<S>,
+}
+
+/// Commitments are Pedersen commitments without blinding, as in Nova.
+fn commit<C, F>(ck: &PedersenParams<C>, v: &[C::ScalarExt]) -> C
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    ck.commit(v, &C::ScalarExt::ZERO)
+}
+
+impl<S: PrimeField> R1CS<S> {
+    fn multiply(&self, m: &SparseMatrix<S>, z: &[S]) -> Vec<S> {
+        let mut res = vec![S::ZERO; self.num_constraints];
+        for &(row, col, value) in m.iter() {
+            res[row] += value * z[col];
+        }
+        res
+    }
+
+    /// Returns (A z, B z, C z) for z = (w, u, x)
+    fn products(&self, w: &[S], u: S, x: &[S]) -> (Vec<S>, Vec<S>, Vec<S>) {
+        let z = [w, &[u], x].concat();
+        (
+            self.multiply(&self.a, &z),
+            self.multiply(&self.b, &z),
+            self.multiply(&self.c, &z),
+        )
+    }
+
+    /// Check that `w` satisfies the constraints for the public input `x`
+    pub fn is_satisfied(&self, x: &[S], w: &[S]) -> bool {
+        if x.len() != self.num_io || w.len() != self.num_vars {
+            return false;
+        }
+        let (az, bz, cz) = self.products(w, S::ONE, x);
+        az.iter()
+            .zip(bz.iter())
+            .zip(cz.iter())
+            .all(|((a, b), c)| *a * b == *c)
+    }
+
+    /// Check that the relaxed `witness` satisfies the constraints and opens
+    /// the commitments of `instance`
+    pub fn is_relaxed_satisfied<C, F>(
+        &self,
+        ck: &PedersenParams<C>,
+        instance: &RelaxedR1CSInstance<C>,
+        witness: &RelaxedR1CSWitness<S>,
+    ) -> bool
+    where
+        C: CurveAffine<Base = F, ScalarExt = S>,
+        F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+    {
+        if instance.x.len() != self.num_io
+            || witness.w.len() != self.num_vars
+            || witness.e.len() != self.num_constraints
+        {
+            return false;
+        }
+        let (az, bz, cz) = self.products(&witness.w, instance.u, &instance.x);
+        let satisfied =
+            (0..self.num_constraints).all(|i| az[i] * bz[i] == instance.u * cz[i] + witness.e[i]);
+        satisfied
+            && commit(ck, &witness.w) == instance.comm_w
+            && commit(ck, &witness.e) == instance.comm_e
+    }
+}
+
+impl<C, F> R1CSInstance<C>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    /// Commit to `witness` for the public input `x`
+    pub fn new(
+        ck: &PedersenParams<C>,
+        x: Vec<C::ScalarExt>,
+        witness: &R1CSWitness<C::ScalarExt>,
+    ) -> Self {
+        Self {
+            comm_w: commit(ck, &witness.w),
+            x,
+        }
+    }
+}
+
+impl<C: CurveAffine> RelaxedR1CSInstance<C> {
+    /// The relaxed form of `instance`, with u = 1 and E = 0
+    pub fn from_r1cs(instance: R1CSInstance<C>) -> Self {
+        Self {
+            comm_w: instance.comm_w,
+            comm_e: C::identity(),
+            u: C::ScalarExt::ONE,
+            x: instance.x,
+        }
+    }
+}
+
+impl<S: PrimeField> RelaxedR1CSWitness<S> {
+    /// The relaxed form of `witness`, with E = 0
+    pub fn from_r1cs(r1cs: &R1CS<S>, witness: R1CSWitness<S>) -> Self {
+        Self {
+            w: witness.w,
+            e: vec![S::ZERO; r1cs.num_constraints],
+        }
+    }
+}
+
+/// The scalars that the folding challenge absorbs after the commitments:
+/// u of the running instance, then the public inputs of both instances,
+/// each as its (high, low) 128 bit halves in the base field.
+///
+/// `FoldingChip` takes these as witnesses; the circuit over the other half
+/// of the cycle, which folds u and x, must bind them.
+pub fn nifs_challenge_scalars<C, F, S>(
+    running: &RelaxedR1CSInstance<C>,
+    fresh: &R1CSInstance<C>,
+) -> Vec<F>
+where
+    C: CurveAffine<Base = F, ScalarExt = S>,
+    F: PrimeField,
+    S: PrimeField<Repr = [u8; 32]>,
+{
+    [&running.u]
+        .into_iter()
+        .chain(running.x.iter())
+        .chain(fresh.x.iter())
+        .flat_map(|s| {
+            let (high, low) = field_decompose_u128(s);
+            [F::from_u128(high), F::from_u128(low)]
+        })
+        .collect()
+}
+
+/// The Fiat-Shamir challenge of the folding.
+///
+/// Both instances and the cross term commitment are hashed with Poseidon
+/// over the base field; scalars enter as two 128 bit halves. The challenge
+/// is the low 128 bits of the digest, which fits both fields of the cycle.
+pub fn nifs_challenge<C, F, S>(
+    running: &RelaxedR1CSInstance<C>,
+    fresh: &R1CSInstance<C>,
+    comm_t: &C,
+) -> S
+where
+    C: CurveAffine<Base = F, ScalarExt = S>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+    S: PrimeField<Repr = [u8; 32]>,
+{
+    let mut inputs = vec![F::from_u128(u128::from_le_bytes(*FOLDING_DOMAIN))];
+    let mut absorb_point = |p: &C| {
+        // the identity is absorbed as (0, 0), which is not on the curve
+        if bool::from(p.is_identity()) {
+            inputs.extend([F::ZERO, F::ZERO]);
+        } else {
+            let coordinates = p.coordinates().unwrap();
+            inputs.extend([*coordinates.x(), *coordinates.y()]);
+        }
+    };
+    absorb_point(&running.comm_w);
+    absorb_point(&running.comm_e);
+    absorb_point(&fresh.comm_w);
+    absorb_point(comm_t);
+    inputs.extend(nifs_challenge_scalars(running, fresh));
+
+    let digest = F::hash_msg(&inputs, None);
+    let (_, low) = field_decompose_u128(&digest);
+    S::from_u128(low)
+}
+
+/// The prover of the non-interactive folding scheme: folds a fresh R1CS
+/// instance into the running relaxed one.
+///
+/// Returns the commitment to the cross term T, which is sent to the
+/// verifier, and the folded instance and witness.
+#[allow(clippy::type_complexity)]
+pub fn nifs_prove<C, F, S>(
+    ck: &PedersenParams<C>,
+    r1cs: &R1CS<S>,
+    running: (&RelaxedR1CSInstance<C>, &RelaxedR1CSWitness<S>),
+    fresh: (&R1CSInstance<C>, &R1CSWitness<S>),
+) -> (C, RelaxedR1CSInstance<C>, RelaxedR1CSWitness<S>)
+where
+    C: CurveAffine<Base = F, ScalarExt = S>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+    S: PrimeField<Repr = [u8; 32]>,
+{
+    let (running_instance, running_witness) = running;
+    let (fresh_instance, fresh_witness) = fresh;
+
+    // T = A z1 * B z2 + A z2 * B z1 - u1 * C z2 - C z1
+    let (az1, bz1, cz1) =
+        r1cs.products(&running_witness.w, running_instance.u, &running_instance.x);
+    let (az2, bz2, cz2) = r1cs.products(&fresh_witness.w, S::ONE, &fresh_instance.x);
+    let t: Vec<S> = (0..r1cs.num_constraints)
+        .map(|i| az1[i] * bz2[i] + az2[i] * bz1[i] - running_instance.u * cz2[i] - cz1[i])
+        .collect();
+    let comm_t = commit(ck, &t);
+
+    let r = nifs_challenge(running_instance, fresh_instance, &comm_t);
+    let instance = fold_instances(running_instance, fresh_instance, &comm_t, r);
+    let witness = RelaxedR1CSWitness {
+        w: running_witness
+            .w
+            .iter()
+            .zip(fresh_witness.w.iter())
+            .map(|(w1, w2)| *w1 + r * w2)
+            .collect(),
+        e: running_witness
+            .e
+            .iter()
+            .zip(t.iter())
+            .map(|(e1, t)| *e1 + r * t)
+            .collect(),
+    };
+    (comm_t, instance, witness)
+}
+
+/// The verifier of the non-interactive folding scheme. The folded instance
+/// is satisfiable iff both inputs are, except with negligible probability.
+pub fn nifs_verify<C, F, S>(
+    running: &RelaxedR1CSInstance<C>,
+    fresh: &R1CSInstance<C>,
+    comm_t: &C,
+) -> RelaxedR1CSInstance<C>
+where
+    C: CurveAffine<Base = F, ScalarExt = S>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+    S: PrimeField<Repr = [u8; 32]>,
+{
+    let r = nifs_challenge(running, fresh, comm_t);
+    fold_instances(running, fresh, comm_t, r)
+}
+
+fn fold_instances<C: CurveAffine>(
+    running: &RelaxedR1CSInstance<C>,
+    fresh: &R1CSInstance<C>,
+    comm_t: &C,
+    r: C::ScalarExt,
+) -> RelaxedR1CSInstance<C> {
+    RelaxedR1CSInstance {
+        comm_w: (running.comm_w + fresh.comm_w * r).to_affine(),
+        comm_e: (running.comm_e + *comm_t * r).to_affine(),
+        u: running.u + r,
+        x: running
+            .x
+            .iter()
+            .zip(fresh.x.iter())
+            .map(|(x1, x2)| *x1 + r * x2)
+            .collect(),
+    }
+}
+
+/// The configuration of `FoldingChip`: the ec chip and a Poseidon
+/// permutation for the challenge.
+#[derive(Clone, Debug)]
+pub struct FoldingConfig<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField,
+{
+    pub ec: ECConfig<C, F>,
+    pub poseidon: Pow5Config<F, 3, 2>,
+}
+
+/// A chip for the group side of the folding verifier:
+///     r = nifs_challenge(...), W = W1 + r * W2, E = E1 + r * T
+/// with the ec chip over the base field of the commitment curve.
+///
+/// For the BN254/Grumpkin cycle the running instance is committed over
+/// Grumpkin, so this part runs natively in a circuit over BN254's scalar
+/// field. The field side (u and x) belongs to the circuit over the other
+/// half of the cycle.
+///
+/// This is only the group side of the NIFS verifier. The augmented step
+/// circuit of Nova, which also hashes the running instance into its public
+/// inputs, and the compression of the final instance are not implemented.
+#[derive(Clone, Debug)]
+pub struct FoldingChip<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField,
+{
+    config: FoldingConfig<C, F>,
+    ec_chip: ECChip<C, F>,
+    sponge: MessageSponge<C, F>,
+}
+
+impl<C, F> FoldingChip<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    pub fn construct(config: FoldingConfig<C, F>) -> Self {
+        Self {
+            ec_chip: ECChip::construct(config.ec.clone()),
+            sponge: MessageSponge::construct(config.ec.clone(), config.poseidon.clone()),
+            config,
+        }
+    }
+
+    /// The Poseidon chip relies on the constant column enabled by the ec chip
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> FoldingConfig<C, F> {
+        let ec = ECChip::configure(meta);
+        let poseidon = configure_poseidon(meta);
+
+        FoldingConfig { ec, poseidon }
+    }
+
+    /// Fold the commitments of the running instance (`comm_w1`, `comm_e1`)
+    /// with those of the fresh instance and the cross term.
+    ///
+    /// `scalars` are the cells of `nifs_challenge_scalars`. The challenge r
+    /// is computed from them and the commitments as in `nifs_challenge`.
+    ///
+    /// Returns the cell of r and the folded (W, E).
+    /// Caller must check the points are on curve; none of them may be the
+    /// identity, so the running instance must have been folded at least once.
+    #[allow(clippy::type_complexity)]
+    pub fn fold_commitments(
+        &self,
+        mut layouter: impl Layouter<F>,
+        comm_w1: &AssignedECPoint<C, F>,
+        comm_e1: &AssignedECPoint<C, F>,
+        comm_w2: &AssignedECPoint<C, F>,
+        comm_t: &AssignedECPoint<C, F>,
+        scalars: &[AssignedCell<F, F>],
+    ) -> Result<
+        (
+            AssignedCell<F, F>,
+            AssignedECPoint<C, F>,
+            AssignedECPoint<C, F>,
+        ),
+        Error,
+    > {
+        let digest =
+            self.challenge_digest(&mut layouter, [comm_w1, comm_e1, comm_w2, comm_t], scalars)?;
+
+        let config = &self.config.ec;
+        layouter.assign_region(
+            || "fold commitments",
+            |mut region| {
+                let mut offset = 0;
+                let (r_cell, bits) =
+                    self.truncate_challenge(&mut region, config, &digest, &mut offset)?;
+
+                let r_w2 = self.mul_with_bits(&mut region, config, comm_w2, &bits, &mut offset)?;
+                let w = self.add(&mut region, config, comm_w1, &r_w2, &mut offset)?;
+                let r_t = self.mul_with_bits(&mut region, config, comm_t, &bits, &mut offset)?;
+                let e = self.add(&mut region, config, comm_e1, &r_t, &mut offset)?;
+
+                // pad the last rows
+                self.ec_chip.pad(&mut region, config, &mut offset)?;
+                Ok((r_cell, w, e))
+            },
+        )
+    }
+
+    /// The Poseidon digest of `nifs_challenge`, i.e. `hash_msg` over
+    /// the domain tag, the coordinates of `points` and `scalars`.
+    fn challenge_digest(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        points: [&AssignedECPoint<C, F>; 4],
+        scalars: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let config = &self.config.ec;
+
+        let domain = layouter.assign_region(
+            || "folding challenge domain",
+            |mut region| {
+                let mut offset = 0;
+                let domain = F::from_u128(u128::from_le_bytes(*FOLDING_DOMAIN));
+                self.ec_chip
+                    .load_constant(&mut region, config, &domain, &mut offset)
+            },
+        )?;
+
+        let mut messages = vec![domain];
+        for p in points {
+            messages.extend([p.x.clone(), p.y.clone()]);
+        }
+        messages.extend(scalars.iter().cloned());
+        self.sponge
+            .hash(&mut layouter.namespace(|| "folding challenge"), &messages)
+    }
+
+    /// Constrain r to be the low 128 bits of the digest, i.e.
+    ///     digest = high * 2^128 + low, with high * 2^128 + low < p
+    /// so that the prover can not pick another decomposition.
+    ///
+    /// Returns the cell of r and its 256 little endian bits, the high half
+    /// zero so that the loop of `mul_with_bits` matches the offset generator.
+    #[allow(clippy::type_complexity)]
+    fn truncate_challenge(
+        &self,
+        region: &mut Region<F>,
+        config: &ECConfig<C, F>,
+        digest: &AssignedCell<F, F>,
+        offset: &mut usize,
+    ) -> Result<(AssignedCell<F, F>, Vec<AssignedCell<F, F>>), Error> {
+        let chip = &self.ec_chip;
+        let (high, low) = field_decompose_u128(&leak(&digest.value()));
+        // the halves of p - 1
+        let (max_high, max_low) = field_decompose_u128(&-F::ONE);
+
+        let (low_bits, low_cell) = chip.decompose_u128(region, config, &low, offset)?;
+        let (high_bits, zero) = chip.decompose_u128(region, config, &0, offset)?;
+        region.constrain_constant(zero.cell(), F::ZERO)?;
+        let high_cell = chip.range_check_u128(region, config, &high, offset)?;
+
+        // digest = high * 2^128 + low
+        let two_to_128 = F::from_u128(u128::MAX) + F::ONE;
+        let two_to_128 = chip.load_constant(region, config, &two_to_128, offset)?;
+        let shifted = chip.mul_cells(region, config, &high_cell, &two_to_128, offset)?;
+        let sum = chip.add_cells(region, config, &shifted, &low_cell, offset)?;
+        region.constrain_equal(sum.cell(), digest.cell())?;
+
+        // high <= max_high, i.e. gap = max_high - high has 128 bits
+        let gap = chip.range_check_u128(region, config, &(max_high - high), offset)?;
+        let sum = chip.add_cells(region, config, &gap, &high_cell, offset)?;
+        region.constrain_constant(sum.cell(), F::from_u128(max_high))?;
+
+        // eq = 1 iff gap = 0: gap * inv = 1 - eq and gap * eq = 0
+        let is_eq = high == max_high;
+        let inv = F::from_u128(max_high - high).invert().unwrap_or(F::ZERO);
+        let eq_value = if is_eq { F::ONE } else { F::ZERO };
+        let [inv, eq] = chip.load_two_private_fields(region, config, &inv, &eq_value, offset)?;
+        let prod = chip.mul_cells(region, config, &gap, &inv, offset)?;
+        let sum = chip.add_cells(region, config, &prod, &eq, offset)?;
+        region.constrain_constant(sum.cell(), F::ONE)?;
+        let prod = chip.mul_cells(region, config, &gap, &eq, offset)?;
+        region.constrain_constant(prod.cell(), F::ZERO)?;
+
+        // if high = max_high then low <= max_low, i.e. for a 128 bit t
+        //     t + low + eq * (2^128 - 1 - max_low) = 2^128 - 1
+        let t = if is_eq {
+            max_low - low
+        } else {
+            u128::MAX - low
+        };
+        let t = chip.range_check_u128(region, config, &t, offset)?;
+        let slack =
+            chip.load_constant(region, config, &F::from_u128(u128::MAX - max_low), offset)?;
+        let slack = chip.mul_cells(region, config, &eq, &slack, offset)?;
+        let sum = chip.add_cells(region, config, &t, &low_cell, offset)?;
+        let sum = chip.add_cells(region, config, &sum, &slack, offset)?;
+        region.constrain_constant(sum.cell(), F::from_u128(u128::MAX))?;
+
+        Ok((low_cell, [low_bits, high_bits].concat()))
+    }
+
+    /// Return p1 + p2. The points may live in other rows or regions.
+    fn add(
+        &self,
+        region: &mut Region<F>,
+        config: &ECConfig<C, F>,
+        p1: &AssignedECPoint<C, F>,
+        p2: &AssignedECPoint<C, F>,
+        offset: &mut usize,
+    ) -> Result<AssignedECPoint<C, F>, Error> {
+        let p1 = self.ec_chip.copy_point(region, config, p1, offset)?;
+        let p2 = self.ec_chip.copy_point(region, config, p2, offset)?;
+        let bit =
+            self.ec_chip
+                .load_two_private_fields(region, config, &F::ONE, &F::ZERO, offset)?;
+        region.constrain_constant(bit[0].cell(), F::ONE)?;
+        self.ec_chip
+            .conditional_point_add(region, config, &p1, &p2, &bit[0], offset)
+    }
+
+    /// Same as `point_mul_assigned`, for a scalar that is already decomposed
+    /// into 256 little endian bits.
+    fn mul_with_bits(
+        &self,
+        region: &mut Region<F>,
+        config: &ECConfig<C, F>,
+        p: &AssignedECPoint<C, F>,
+        bits: &[AssignedCell<F, F>],
+        offset: &mut usize,
+    ) -> Result<AssignedECPoint<C, F>, Error> {
+        let mut res = self
+            .ec_chip
+            .load_constant_point(region, config, &C::generator(), offset)?;
+
+        for b in bits.iter().rev() {
+            let res_double = self.ec_chip.point_double(region, config, &res, offset)?;
+            let p_copied = self.ec_chip.copy_point(region, config, p, offset)?;
+            let bit = self.ec_chip.load_two_private_fields(
+                region,
+                config,
+                &leak(&b.value()),
+                &F::ZERO,
+                offset,
+            )?;
+            region.constrain_equal(bit[0].cell(), b.cell())?;
+            res = self.ec_chip.conditional_point_add(
+                region,
+                config,
+                &res_double,
+                &p_copied,
+                &bit[0],
+                offset,
+            )?;
+        }
+
+        // now we subtract 2^256 * generator from res
+        let (offset_generator, _, _) = neg_generator_times_2_to_256::<C, F>();
+        let offset_generator =
+            self.ec_chip
+                .load_constant_point(region, config, &offset_generator, offset)?;
+        self.add(region, config, &res, &offset_generator, offset)
+    }
+}
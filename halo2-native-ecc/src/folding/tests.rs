@@ -0,0 +1,283 @@
+use ark_std::test_rng;
+use ff::PrimeField;
+use halo2_proofs::arithmetic::Field;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Instance;
+use halo2curves::grumpkin::Fq;
+use halo2curves::grumpkin::Fr;
+use halo2curves::grumpkin::G1Affine;
+
+use crate::arith_gates::ArithOps;
+use crate::chip::ECChip;
+use crate::ec_gates::NativeECOps;
+use crate::folding::nifs_challenge;
+use crate::folding::nifs_challenge_scalars;
+use crate::folding::nifs_prove;
+use crate::folding::nifs_verify;
+use crate::folding::FoldingChip;
+use crate::folding::FoldingConfig;
+use crate::folding::R1CSInstance;
+use crate::folding::R1CSWitness;
+use crate::folding::RelaxedR1CSInstance;
+use crate::folding::RelaxedR1CSWitness;
+use crate::folding::R1CS;
+use crate::pedersen::PedersenParams;
+
+/// x_out = x^3 + x + 5 over z = (x^2, x^3, u, x, x_out)
+fn cubic() -> R1CS<Fr> {
+    R1CS {
+        num_vars: 2,
+        num_io: 2,
+        num_constraints: 3,
+        a: vec![
+            (0, 3, Fr::one()),
+            (1, 0, Fr::one()),
+            (2, 1, Fr::one()),
+            (2, 3, Fr::one()),
+            (2, 2, Fr::from(5)),
+        ],
+        b: vec![(0, 3, Fr::one()), (1, 3, Fr::one()), (2, 2, Fr::one())],
+        c: vec![(0, 0, Fr::one()), (1, 1, Fr::one()), (2, 4, Fr::one())],
+    }
+}
+
+fn step(ck: &PedersenParams<G1Affine>, x: Fr) -> (R1CSInstance<G1Affine>, R1CSWitness<Fr>) {
+    let witness = R1CSWitness {
+        w: vec![x.square(), x.square() * x],
+    };
+    let out = x.square() * x + x + Fr::from(5);
+    (R1CSInstance::new(ck, vec![x, out], &witness), witness)
+}
+
+#[allow(clippy::type_complexity)]
+fn fold_steps(
+    ck: &PedersenParams<G1Affine>,
+    r1cs: &R1CS<Fr>,
+    n: usize,
+) -> (RelaxedR1CSInstance<G1Affine>, RelaxedR1CSWitness<Fr>, Fr) {
+    let mut x = Fr::random(test_rng());
+    let (instance, witness) = step(ck, x);
+    x = instance.x[1];
+    let mut running = (
+        RelaxedR1CSInstance::from_r1cs(instance),
+        RelaxedR1CSWitness::from_r1cs(r1cs, witness),
+    );
+    for _ in 1..n {
+        let (instance, witness) = step(ck, x);
+        x = instance.x[1];
+        let (_, folded_instance, folded_witness) =
+            nifs_prove(ck, r1cs, (&running.0, &running.1), (&instance, &witness));
+        running = (folded_instance, folded_witness);
+    }
+    (running.0, running.1, x)
+}
+
+#[derive(Debug, Clone)]
+struct FoldingTestCircuit {
+    running: RelaxedR1CSInstance<G1Affine>,
+    fresh: R1CSInstance<G1Affine>,
+    comm_t: G1Affine,
+    scalars: Vec<Fq>,
+}
+
+impl Circuit<Fq> for FoldingTestCircuit {
+    type Config = (FoldingConfig<G1Affine, Fq>, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        (FoldingChip::configure(meta), instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let (config, instance) = config;
+        let ec_chip = ECChip::construct(config.ec.clone());
+        let folding_chip = FoldingChip::construct(config.clone());
+
+        let (points, scalars) = layouter.assign_region(
+            || "test folding inputs",
+            |mut region| {
+                let mut offset = 0;
+
+                let mut points = vec![];
+                for p in [
+                    &self.running.comm_w,
+                    &self.running.comm_e,
+                    &self.fresh.comm_w,
+                    &self.comm_t,
+                ] {
+                    points.push(ec_chip.load_private_point(
+                        &mut region,
+                        &config.ec,
+                        p,
+                        &mut offset,
+                    )?);
+                }
+                let mut scalars = vec![];
+                for s in self.scalars.iter() {
+                    scalars.push(ec_chip.load_private_field(
+                        &mut region,
+                        &config.ec,
+                        s,
+                        &mut offset,
+                    )?);
+                }
+
+                // pad the last two rows
+                ec_chip.pad(&mut region, &config.ec, &mut offset)?;
+
+                Ok((points, scalars))
+            },
+        )?;
+
+        let (r, w, e) = folding_chip.fold_commitments(
+            layouter.namespace(|| "fold commitments"),
+            &points[0],
+            &points[1],
+            &points[2],
+            &points[3],
+            &scalars,
+        )?;
+
+        layouter.constrain_instance(r.cell(), instance, 0)?;
+        layouter.constrain_instance(w.x.cell(), instance, 1)?;
+        layouter.constrain_instance(w.y.cell(), instance, 2)?;
+        layouter.constrain_instance(e.x.cell(), instance, 3)?;
+        layouter.constrain_instance(e.y.cell(), instance, 4)?;
+
+        Ok(())
+    }
+}
+
+fn instance(r: Fr, folded: &RelaxedR1CSInstance<G1Affine>) -> Vec<Vec<Fq>> {
+    let w = folded.comm_w.coordinates().unwrap();
+    let e = folded.comm_e.coordinates().unwrap();
+    vec![vec![
+        // r has 128 bits, so it is the same integer in both fields
+        Fq::from_repr(r.to_repr()).unwrap(),
+        *w.x(),
+        *w.y(),
+        *e.x(),
+        *e.y(),
+    ]]
+}
+
+#[test]
+fn test_nifs() {
+    let r1cs = cubic();
+    let ck = PedersenParams::<G1Affine>::new(r1cs.num_constraints);
+
+    let (x, witness) = {
+        let x = Fr::random(test_rng());
+        let (instance, witness) = step(&ck, x);
+        (instance.x, witness)
+    };
+    assert!(r1cs.is_satisfied(&x, &witness.w));
+
+    // the running instance stays satisfiable after each fold
+    let (running, running_witness, x) = fold_steps(&ck, &r1cs, 3);
+    assert!(r1cs.is_relaxed_satisfied(&ck, &running, &running_witness));
+
+    let (fresh, fresh_witness) = step(&ck, x);
+    let (comm_t, folded, folded_witness) = nifs_prove(
+        &ck,
+        &r1cs,
+        (&running, &running_witness),
+        (&fresh, &fresh_witness),
+    );
+    assert_eq!(nifs_verify(&running, &fresh, &comm_t), folded);
+    assert!(r1cs.is_relaxed_satisfied(&ck, &folded, &folded_witness));
+
+    // Error case: the fresh witness does not satisfy the constraints
+    {
+        let mut invalid = fresh_witness.clone();
+        invalid.w[0] += Fr::one();
+        assert!(!r1cs.is_satisfied(&fresh.x, &invalid.w));
+        let fresh = R1CSInstance::new(&ck, fresh.x.clone(), &invalid);
+        let (_, folded, folded_witness) =
+            nifs_prove(&ck, &r1cs, (&running, &running_witness), (&fresh, &invalid));
+        assert!(!r1cs.is_relaxed_satisfied(&ck, &folded, &folded_witness));
+    }
+
+    // Error case: the cross term commitment is not the prover's
+    {
+        let tampered = (comm_t + G1Affine::generator()).into();
+        assert_ne!(nifs_verify(&running, &fresh, &tampered), folded);
+        assert!(!r1cs.is_relaxed_satisfied(
+            &ck,
+            &nifs_verify(&running, &fresh, &tampered),
+            &folded_witness
+        ));
+    }
+}
+
+#[test]
+fn test_folding_chip() {
+    let k = 14;
+
+    let r1cs = cubic();
+    let ck = PedersenParams::<G1Affine>::new(r1cs.num_constraints);
+    let (running, running_witness, x) = fold_steps(&ck, &r1cs, 2);
+    let (fresh, fresh_witness) = step(&ck, x);
+    let (comm_t, folded, _) = nifs_prove(
+        &ck,
+        &r1cs,
+        (&running, &running_witness),
+        (&fresh, &fresh_witness),
+    );
+    let r = nifs_challenge(&running, &fresh, &comm_t);
+
+    let circuit = FoldingTestCircuit {
+        running: running.clone(),
+        fresh: fresh.clone(),
+        comm_t,
+        scalars: nifs_challenge_scalars(&running, &fresh),
+    };
+    {
+        let prover = MockProver::run(k, &circuit, instance(r, &folded)).unwrap();
+        prover.assert_satisfied();
+    }
+
+    // Error case: the challenge is not the one of the transcript
+    {
+        let prover = MockProver::run(k, &circuit, instance(r + Fr::one(), &folded)).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: the scalars are not the ones of the instances,
+    // so the challenge, and the folded commitments, differ
+    {
+        let mut scalars = circuit.scalars.clone();
+        scalars[1] += Fq::one();
+        let circuit = FoldingTestCircuit {
+            scalars,
+            ..circuit.clone()
+        };
+        let prover = MockProver::run(k, &circuit, instance(r, &folded)).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: the folded commitments are not the native ones
+    {
+        let mut wrong = folded.clone();
+        std::mem::swap(&mut wrong.comm_w, &mut wrong.comm_e);
+        let prover = MockProver::run(k, &circuit, instance(r, &wrong)).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
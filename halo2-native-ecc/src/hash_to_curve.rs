@@ -0,0 +1,308 @@
+use ff::PrimeField;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use poseidon_base::hash::MessageHashable;
+use poseidon_circuit::poseidon::Pow5Config;
+
+use crate::chip::ECChip;
+use crate::compression::PointCompressionOps;
+use crate::config::ECConfig;
+use crate::sponge::configure_poseidon;
+use crate::sponge::MessageSponge;
+use crate::util::leak;
+use crate::ArithOps;
+use crate::AssignedECPoint;
+use crate::NativeECOps;
+
+#[cfg(test)]
+mod tests;
+
+/// The number of counters that are tried, both natively and in circuit.
+/// A candidate maps to the curve with probability about 1/2,
+/// so all of them fail with probability about 2^-32.
+pub const HASH_TO_CURVE_ATTEMPTS: u64 = 32;
+
+/// Hash a message to a curve point via try-and-increment with Poseidon.
+///
+/// The candidate is x = Poseidon(domain, msg, counter) for counter = 0, 1, ...;
+/// the first x such that x^3 + b is a square is kept,
+/// and y is the root with an even least significant bit.
+///
+/// Panics if none of the first `HASH_TO_CURVE_ATTEMPTS` counters maps to the curve.
+pub fn hash_to_curve<C, F>(domain: F, msg: &[F]) -> C
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    hash_to_curve_with_counter(domain, msg).0
+}
+
+/// Same as `hash_to_curve`, and also returns the counter that was used.
+pub fn hash_to_curve_with_counter<C, F>(domain: F, msg: &[F]) -> (C, u64)
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    (0..HASH_TO_CURVE_ATTEMPTS)
+        .find_map(|counter| {
+            let x = hash_to_curve_digest(domain, msg, counter);
+            map_to_point::<C, F>(&x).map(|p| (p, counter))
+        })
+        .expect("no counter maps to the curve")
+}
+
+/// The Poseidon digest Poseidon(domain, msg, counter) that is used as the x candidate
+pub fn hash_to_curve_digest<F>(domain: F, msg: &[F], counter: u64) -> F
+where
+    F: PrimeField + MessageHashable,
+{
+    let inputs = [&[domain], msg, &[F::from(counter)]].concat();
+    F::hash_msg(&inputs, None)
+}
+
+/// Map x to the point (x, y) with an even y, if x^3 + b is a square.
+pub(crate) fn map_to_point<C, F>(x: &F) -> Option<C>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    let y2 = x.square() * x + C::b();
+    Option::<F>::from(y2.sqrt()).map(|y| {
+        let y = if y.to_repr()[0] & 1 == 0 { y } else { -y };
+        C::from_xy(*x, y).unwrap()
+    })
+}
+
+/// The configuration of `HashToCurveChip`: the ec chip and a Poseidon
+/// permutation for the digests.
+#[derive(Clone, Debug)]
+pub struct HashToCurveConfig<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField,
+{
+    pub ec: ECConfig<C, F>,
+    pub poseidon: Pow5Config<F, 3, 2>,
+}
+
+/// A chip for `hash_to_curve` over the base field of the curve.
+///
+/// The digests of all `HASH_TO_CURVE_ATTEMPTS` counters are computed in
+/// circuit, and the first one that maps to the curve is selected, so the
+/// prover can choose neither the digest nor the counter.
+#[derive(Clone, Debug)]
+pub struct HashToCurveChip<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField,
+{
+    config: HashToCurveConfig<C, F>,
+    ec_chip: ECChip<C, F>,
+    sponge: MessageSponge<C, F>,
+}
+
+impl<C, F> HashToCurveChip<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    pub fn construct(config: HashToCurveConfig<C, F>) -> Self {
+        Self {
+            ec_chip: ECChip::construct(config.ec.clone()),
+            sponge: MessageSponge::construct(config.ec.clone(), config.poseidon.clone()),
+            config,
+        }
+    }
+
+    /// The Poseidon chip relies on the constant column enabled by the ec chip
+    pub fn configure(meta: &mut ConstraintSystem<F>) -> HashToCurveConfig<C, F> {
+        let ec = ECChip::configure(meta);
+        let poseidon = configure_poseidon(meta);
+
+        HashToCurveConfig { ec, poseidon }
+    }
+
+    /// Returns `hash_to_curve(domain, msg)` for the message cells,
+    /// and enforces the point is on curve and y is even.
+    pub fn hash_to_curve(
+        &self,
+        mut layouter: impl Layouter<F>,
+        domain: F,
+        msg: &[AssignedCell<F, F>],
+    ) -> Result<AssignedECPoint<C, F>, Error> {
+        let candidates = self.candidates(&mut layouter, domain, msg)?;
+        let squares = candidates
+            .iter()
+            .map(|x| {
+                let x = leak(&x.value());
+                bool::from((x.square() * x + C::b()).sqrt().is_some())
+            })
+            .collect::<Vec<_>>();
+        self.map_to_curve(&mut layouter, &candidates, &squares)
+    }
+
+    /// The digests `hash_to_curve_digest(domain, msg, counter)` for all counters.
+    ///
+    /// The counter is the last input, so the chunks before it are absorbed
+    /// once and only the last permutation is repeated for each counter.
+    pub(crate) fn candidates(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        domain: F,
+        msg: &[AssignedCell<F, F>],
+    ) -> Result<Vec<AssignedCell<F, F>>, Error> {
+        let config = &self.config.ec;
+        let (mut state, zero) = self.sponge.initial_state(layouter, msg.len() + 2)?;
+
+        let (prefix, counters) = layouter.assign_region(
+            || "hash to curve inputs",
+            |mut region| {
+                let mut offset = 0;
+                let domain =
+                    self.ec_chip
+                        .load_constant(&mut region, config, &domain, &mut offset)?;
+                let mut prefix = vec![domain];
+                prefix.extend(msg.iter().cloned());
+
+                let mut counters = vec![];
+                for counter in 0..HASH_TO_CURVE_ATTEMPTS {
+                    counters.push(self.ec_chip.load_constant(
+                        &mut region,
+                        config,
+                        &F::from(counter),
+                        &mut offset,
+                    )?);
+                }
+                Ok((prefix, counters))
+            },
+        )?;
+
+        // the chunks that do not hold the counter
+        let shared = prefix.len() - prefix.len() % 2;
+        for chunk in prefix[..shared].chunks(2) {
+            state = self
+                .sponge
+                .absorb(layouter, &state, [&chunk[0], &chunk[1]])?;
+        }
+
+        let mut candidates = vec![];
+        for counter in counters.iter() {
+            // the counter either completes the last chunk or is padded with a zero
+            let chunk = match prefix.get(shared) {
+                Some(last) => [last, counter],
+                None => [counter, &zero],
+            };
+            let digest = self.sponge.absorb(layouter, &state, chunk)?;
+            candidates.push(digest[0].clone());
+        }
+        Ok(candidates)
+    }
+
+    /// Map the first candidate x such that x^3 + b is a square to the point
+    /// (x, y) with an even y. `squares` are the claims of the prover.
+    ///
+    /// For each candidate the prover witnesses a bit s and a w with
+    ///     w^2 = (x^3 + b) * (s + (1 - s) * z)
+    /// for the non-residue z, so that s = 1 iff x^3 + b is a square.
+    /// A candidate is selected if its s is the first one that is set,
+    /// and some candidate must be selected.
+    pub(crate) fn map_to_curve(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        candidates: &[AssignedCell<F, F>],
+        squares: &[bool],
+    ) -> Result<AssignedECPoint<C, F>, Error> {
+        let chip = &self.ec_chip;
+        let config = &self.config.ec;
+        let z = F::MULTIPLICATIVE_GENERATOR;
+
+        layouter.assign_region(
+            || "map to curve",
+            |mut region| {
+                let mut offset = 0;
+                let b = chip.load_constant(&mut region, config, &C::b(), &mut offset)?;
+                let z_cell = chip.load_constant(&mut region, config, &z, &mut offset)?;
+                let one_minus_z =
+                    chip.load_constant(&mut region, config, &(F::ONE - z), &mut offset)?;
+
+                // the product of (1 - s) over the previous candidates
+                let mut before = chip.load_constant(&mut region, config, &F::ONE, &mut offset)?;
+                let mut x = chip.load_constant(&mut region, config, &F::ZERO, &mut offset)?;
+                let mut y = x.clone();
+
+                for (candidate, is_square) in candidates.iter().zip(squares) {
+                    let square =
+                        chip.mul_cells(&mut region, config, candidate, candidate, &mut offset)?;
+                    let cube =
+                        chip.mul_cells(&mut region, config, &square, candidate, &mut offset)?;
+                    let rhs = chip.add_cells(&mut region, config, &cube, &b, &mut offset)?;
+
+                    // the even root, so that the selected one is the y of `map_to_point`
+                    let rhs_value = leak(&rhs.value());
+                    let (s, w) = if *is_square {
+                        (F::ONE, rhs_value.sqrt())
+                    } else {
+                        (F::ZERO, (rhs_value * z).sqrt())
+                    };
+                    let w = Option::<F>::from(w).unwrap_or(F::ZERO);
+                    let w = if w.to_repr()[0] & 1 == 0 { w } else { -w };
+                    let [s, w] =
+                        chip.load_two_private_fields(&mut region, config, &s, &w, &mut offset)?;
+
+                    // s is a bit
+                    let s_square = chip.mul_cells(&mut region, config, &s, &s, &mut offset)?;
+                    region.constrain_equal(s_square.cell(), s.cell())?;
+
+                    // w^2 = rhs * (z + s * (1 - z))
+                    let factor =
+                        chip.mul_cells(&mut region, config, &s, &one_minus_z, &mut offset)?;
+                    let factor =
+                        chip.add_cells(&mut region, config, &factor, &z_cell, &mut offset)?;
+                    let rhs = chip.mul_cells(&mut region, config, &rhs, &factor, &mut offset)?;
+                    let w_square = chip.mul_cells(&mut region, config, &w, &w, &mut offset)?;
+                    region.constrain_equal(w_square.cell(), rhs.cell())?;
+
+                    let selected = chip.mul_cells(&mut region, config, &before, &s, &mut offset)?;
+                    let not_s = F::ONE - leak(&s.value());
+                    let not_s =
+                        chip.load_private_field(&mut region, config, &not_s, &mut offset)?;
+                    let sum = chip.add_cells(&mut region, config, &s, &not_s, &mut offset)?;
+                    region.constrain_constant(sum.cell(), F::ONE)?;
+                    before = chip.mul_cells(&mut region, config, &before, &not_s, &mut offset)?;
+
+                    let x_selected =
+                        chip.mul_cells(&mut region, config, &selected, candidate, &mut offset)?;
+                    x = chip.add_cells(&mut region, config, &x, &x_selected, &mut offset)?;
+                    let y_selected =
+                        chip.mul_cells(&mut region, config, &selected, &w, &mut offset)?;
+                    y = chip.add_cells(&mut region, config, &y, &y_selected, &mut offset)?;
+                }
+                // some candidate is a square
+                region.constrain_constant(before.cell(), F::ZERO)?;
+
+                // y must be the even root, otherwise the prover could also use -p
+                let sign = chip.y_parity(&mut region, config, &y, &mut offset)?;
+                region.constrain_constant(sign.cell(), F::ZERO)?;
+
+                let [x_res, y_res] = chip.load_two_private_fields(
+                    &mut region,
+                    config,
+                    &leak(&x.value()),
+                    &leak(&y.value()),
+                    &mut offset,
+                )?;
+                region.constrain_equal(x_res.cell(), x.cell())?;
+                region.constrain_equal(y_res.cell(), y.cell())?;
+                let res = AssignedECPoint::new(x_res, y_res, offset - 1);
+                chip.enforce_on_curve(&mut region, config, &res, &mut offset)?;
+
+                // pad the last rows
+                chip.pad(&mut region, config, &mut offset)?;
+                Ok(res)
+            },
+        )
+    }
+}
@@ -0,0 +1,234 @@
+use ff::Field;
+use ff::PrimeField;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2curves::grumpkin::Fq;
+use halo2curves::grumpkin::G1Affine;
+
+use crate::chip::ECChip;
+use crate::ec_gates::NativeECOps;
+use crate::hash_to_curve::hash_to_curve;
+use crate::hash_to_curve::hash_to_curve_digest;
+use crate::hash_to_curve::hash_to_curve_with_counter;
+use crate::hash_to_curve::map_to_point;
+use crate::hash_to_curve::HashToCurveChip;
+use crate::hash_to_curve::HashToCurveConfig;
+use crate::hash_to_curve::HASH_TO_CURVE_ATTEMPTS;
+use crate::pedersen::PedersenParams;
+use crate::util::leak;
+use crate::ArithOps;
+
+fn domain(tag: &[u8; 16]) -> Fq {
+    Fq::from_u128(u128::from_le_bytes(*tag))
+}
+
+fn coordinates(p: &G1Affine) -> (String, String) {
+    let coordinates = p.coordinates().unwrap();
+    (
+        format!("{:?}", coordinates.x()),
+        format!("{:?}", coordinates.y()),
+    )
+}
+
+#[test]
+fn test_hash_to_curve_vectors() {
+    let domain = domain(b"recreds-h2c-test");
+
+    // empty message: counter 0 is not on curve
+    let (p, counter) = hash_to_curve_with_counter::<G1Affine, Fq>(domain, &[]);
+    assert_eq!(counter, 1);
+    assert_eq!(
+        coordinates(&p),
+        (
+            "0x0431d7f249c072f1d458ba4420ef6570863b89714a6ed1d0d9828e95a5c7f30a".to_string(),
+            "0x185041059cfa704c2066ab5220601c9870b6d9670dc6ad83f7197a364a2e8170".to_string()
+        )
+    );
+
+    let (p, counter) =
+        hash_to_curve_with_counter::<G1Affine, Fq>(domain, &[Fq::from(1), Fq::from(2)]);
+    assert_eq!(counter, 2);
+    assert_eq!(
+        coordinates(&p),
+        (
+            "0x1ae4eb067d264092c79e5c17ed8edf45441a0306b50511c462476bdd9200e42e".to_string(),
+            "0x067af9a35c376ad83975864af5d2a3d2a69cc5fe8653af3fc01409bb9696d8ac".to_string()
+        )
+    );
+    assert_eq!(
+        p,
+        hash_to_curve::<G1Affine, Fq>(domain, &[Fq::from(1), Fq::from(2)])
+    );
+}
+
+#[test]
+fn test_pedersen_generator_vectors() {
+    let params = PedersenParams::<G1Affine>::new(2);
+
+    assert_eq!(
+        coordinates(&params.blinding),
+        (
+            "0x1fec09b98c1852ac40c999fec8b84259b4b72f27611a98f20c3cf011d68ffc0e".to_string(),
+            "0x04050847a970aee9040ace042cf522d04ce2dcfca23c96ce2a89e652d3bca1a0".to_string()
+        )
+    );
+    assert_eq!(
+        coordinates(&params.generators[0]),
+        (
+            "0x2b1aa49e7c6c68df751dd1876361f22f8d28cca57fff540caf947ac7da73fb1b".to_string(),
+            "0x237bb8db27d419494f27963ba55b283d70c3790ecda14cd6d5a7e7c90fe97ae2".to_string()
+        )
+    );
+    assert_eq!(
+        coordinates(&params.generators[1]),
+        (
+            "0x0c06512b680d5619a91b2a4b444226f5546982c99b9c17a46201f226872c239d".to_string(),
+            "0x2e1de7e56fb22c6943ce86c360f853cdcd767fd4f4b90feea8df0beb4a091214".to_string()
+        )
+    );
+}
+
+#[derive(Default, Debug, Clone)]
+struct HashToCurveTestCircuit {
+    msg: Vec<Fq>,
+    p: G1Affine, // the expected point
+    // claim the first candidate that maps to the curve does not
+    skip_first: bool,
+}
+
+impl Circuit<Fq> for HashToCurveTestCircuit {
+    type Config = HashToCurveConfig<G1Affine, Fq>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        HashToCurveChip::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let ec_chip = ECChip::construct(config.ec.clone());
+        let h2c_chip = HashToCurveChip::construct(config.clone());
+
+        let msg = layouter.assign_region(
+            || "test hash to curve message",
+            |mut region| {
+                let mut offset = 0;
+                let mut msg = vec![];
+                for m in self.msg.iter() {
+                    msg.push(ec_chip.load_private_field(
+                        &mut region,
+                        &config.ec,
+                        m,
+                        &mut offset,
+                    )?);
+                }
+                Ok(msg)
+            },
+        )?;
+
+        let domain = domain(b"recreds-h2c-test");
+        let p_rec = if self.skip_first {
+            let candidates = h2c_chip.candidates(&mut layouter, domain, &msg)?;
+            let mut squares = vec![true; candidates.len()];
+            let mut skipped = false;
+            for (x, square) in candidates.iter().zip(squares.iter_mut()) {
+                let x = leak(&x.value());
+                let is_square = bool::from((x.square() * x + G1Affine::b()).sqrt().is_some());
+                *square = is_square && skipped;
+                skipped |= is_square;
+            }
+            h2c_chip.map_to_curve(&mut layouter, &candidates, &squares)?
+        } else {
+            h2c_chip.hash_to_curve(layouter.namespace(|| "hash to curve"), domain, &msg)?
+        };
+
+        layouter.assign_region(
+            || "test hash to curve output",
+            |mut region| {
+                let mut offset = 0;
+                let p =
+                    ec_chip.load_private_point(&mut region, &config.ec, &self.p, &mut offset)?;
+                region.constrain_equal(p.x.cell(), p_rec.x.cell())?;
+                region.constrain_equal(p.y.cell(), p_rec.y.cell())?;
+
+                // pad the last two rows
+                ec_chip.pad(&mut region, &config.ec, &mut offset)?;
+
+                Ok(())
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_hash_to_curve_circuit() {
+    let k = 13;
+
+    let domain = domain(b"recreds-h2c-test");
+
+    // the counter completes the last chunk, or is padded with a zero
+    for msg in [vec![], vec![Fq::from(1)], vec![Fq::from(1), Fq::from(2)]] {
+        let p = hash_to_curve::<G1Affine, Fq>(domain, &msg);
+        let circuit = HashToCurveTestCircuit {
+            msg,
+            p,
+            skip_first: false,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    let msg = vec![Fq::from(1), Fq::from(2)];
+    let p = hash_to_curve::<G1Affine, Fq>(domain, &msg);
+
+    // Error case: the point of another message
+    {
+        let circuit = HashToCurveTestCircuit {
+            msg: vec![Fq::from(2), Fq::from(1)],
+            p,
+            skip_first: false,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: the odd root
+    {
+        let circuit = HashToCurveTestCircuit {
+            msg: msg.clone(),
+            p: -p,
+            skip_first: false,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: skip the first counter that maps to the curve
+    {
+        let (_, counter) = hash_to_curve_with_counter::<G1Affine, Fq>(domain, &msg);
+        let p = (counter + 1..HASH_TO_CURVE_ATTEMPTS)
+            .find_map(|c| map_to_point::<G1Affine, Fq>(&hash_to_curve_digest(domain, &msg, c)))
+            .unwrap();
+        let circuit = HashToCurveTestCircuit {
+            msg,
+            p,
+            skip_first: true,
+        };
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
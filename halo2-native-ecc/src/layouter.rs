@@ -0,0 +1,132 @@
+use ff::PrimeField;
+use halo2_proofs::circuit::Chip;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::Error;
+
+use crate::chip::ECChip;
+use crate::ArithOps;
+use crate::AssignedECPoint;
+use crate::NativeECOps;
+
+#[cfg(test)]
+mod tests;
+
+/// Layouter level API for the native ec chip.
+///
+/// Each method assigns its own region and pads it, so the caller does not need to
+/// track offsets. Input points may be assigned in any region; they are copied in
+/// via copy constraints, and the returned points can be used in other regions likewise.
+impl<C, F> ECChip<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    /// Loads an ecpoint into its own region and constraints it is on curve.
+    pub fn load_private_point_in(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        p: &C,
+    ) -> Result<AssignedECPoint<C, F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "load private point",
+            |mut region| {
+                let mut offset = 0;
+                let res = self.load_private_point(&mut region, config, p, &mut offset)?;
+                self.pad(&mut region, config, &mut offset)?;
+                Ok(res)
+            },
+        )
+    }
+
+    /// Return p3 = p1 + p2.
+    ///
+    /// Caller must check p1 and p2 are on curve, and p1 != p2, p1 != -p2.
+    /// The add gate is the chord rule, which does not hold for equal x coordinates;
+    /// use `point_double_in` for p1 == p2. There is no cell representation for
+    /// the identity, so neither input nor the sum may be the infinity point.
+    pub fn point_add_in(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        p1: &AssignedECPoint<C, F>,
+        p2: &AssignedECPoint<C, F>,
+    ) -> Result<AssignedECPoint<C, F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "point add",
+            |mut region| {
+                let mut offset = 0;
+                let p1 = self.copy_point(&mut region, config, p1, &mut offset)?;
+                let p2 = self.copy_point(&mut region, config, p2, &mut offset)?;
+                let bit = self.load_constant(&mut region, config, &F::ONE, &mut offset)?;
+                let res =
+                    self.conditional_point_add(&mut region, config, &p1, &p2, &bit, &mut offset)?;
+                self.pad(&mut region, config, &mut offset)?;
+                Ok(res)
+            },
+        )
+    }
+
+    /// Return p2 = p1 + p1.
+    ///
+    /// Caller must check p1 is on curve.
+    pub fn point_double_in(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        p1: &AssignedECPoint<C, F>,
+    ) -> Result<AssignedECPoint<C, F>, Error> {
+        let config = self.config();
+        layouter.assign_region(
+            || "point double",
+            |mut region| {
+                let mut offset = 0;
+                let p1 = self.copy_point(&mut region, config, p1, &mut offset)?;
+                let res = self.point_double(&mut region, config, &p1, &mut offset)?;
+                self.pad(&mut region, config, &mut offset)?;
+                Ok(res)
+            },
+        )
+    }
+
+    /// Return p * s via double-then-add method.
+    ///
+    /// Caller must check p is on curve.
+    pub fn point_mul_in<S>(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        p: &AssignedECPoint<C, F>,
+        s: &C::ScalarExt,
+    ) -> Result<AssignedECPoint<C, F>, Error>
+    where
+        S: PrimeField<Repr = [u8; 32]>,
+        C: CurveAffine<ScalarExt = S>,
+    {
+        let config = self.config();
+        layouter.assign_region(
+            || "point mul",
+            |mut region| {
+                let mut offset = 0;
+                let res = self.point_mul_assigned(&mut region, config, p, s, &mut offset)?;
+                self.pad(&mut region, config, &mut offset)?;
+                Ok(res)
+            },
+        )
+    }
+
+    /// Constrain two points, possibly from different regions, to be equal.
+    pub fn assert_equal_in(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        p1: &AssignedECPoint<C, F>,
+        p2: &AssignedECPoint<C, F>,
+    ) -> Result<(), Error> {
+        layouter.assign_region(
+            || "assert equal",
+            |mut region| {
+                region.constrain_equal(p1.x.cell(), p2.x.cell())?;
+                region.constrain_equal(p1.y.cell(), p2.y.cell())
+            },
+        )
+    }
+}
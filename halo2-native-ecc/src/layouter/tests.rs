@@ -0,0 +1,130 @@
+use std::ops::Mul;
+
+use ark_std::test_rng;
+use halo2_proofs::arithmetic::Field;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::group::Curve;
+use halo2_proofs::halo2curves::group::Group;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2curves::grumpkin::Fq;
+use halo2curves::grumpkin::Fr;
+use halo2curves::grumpkin::G1Affine;
+use halo2curves::grumpkin::G1;
+
+use crate::chip::ECChip;
+use crate::config::ECConfig;
+
+#[derive(Default, Debug, Clone, Copy)]
+struct LayouterTestCircuit {
+    s: Fr,
+    p1: G1Affine,
+    p2: G1Affine,
+    p3: G1Affine, // p1 + p2
+    p4: G1Affine, // 2p1
+    p5: G1Affine, // (p1 + p2) * s
+}
+
+impl Circuit<Fq> for LayouterTestCircuit {
+    type Config = ECConfig<G1Affine, Fq>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        ECChip::configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let ec_chip = ECChip::construct(config);
+
+        // every point lives in its own region
+        let p1 = ec_chip.load_private_point_in(&mut layouter, &self.p1)?;
+        let p2 = ec_chip.load_private_point_in(&mut layouter, &self.p2)?;
+        let p3 = ec_chip.load_private_point_in(&mut layouter, &self.p3)?;
+        let p4 = ec_chip.load_private_point_in(&mut layouter, &self.p4)?;
+        let p5 = ec_chip.load_private_point_in(&mut layouter, &self.p5)?;
+
+        // unit test: point addition
+        let p3_rec = ec_chip.point_add_in(&mut layouter, &p1, &p2)?;
+        ec_chip.assert_equal_in(&mut layouter, &p3, &p3_rec)?;
+
+        // unit test: point doubling
+        let p4_rec = ec_chip.point_double_in(&mut layouter, &p1)?;
+        ec_chip.assert_equal_in(&mut layouter, &p4, &p4_rec)?;
+
+        // unit test: curve mul on a point computed in another region
+        let p5_rec = ec_chip.point_mul_in(&mut layouter, &p3_rec, &self.s)?;
+        ec_chip.assert_equal_in(&mut layouter, &p5, &p5_rec)?;
+
+        Ok(())
+    }
+}
+
+#[test]
+fn test_layouter_api() {
+    let k = 14;
+
+    let mut rng = test_rng();
+    let s = Fr::random(&mut rng);
+    let p1 = G1::random(&mut rng).to_affine();
+    let p2 = G1::random(&mut rng).to_affine();
+    let p3 = (p1 + p2).to_affine();
+    let p4 = (p1 + p1).to_affine();
+    let p5 = p3.mul(s).to_affine();
+
+    {
+        let circuit = LayouterTestCircuit {
+            s,
+            p1,
+            p2,
+            p3,
+            p4,
+            p5,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        prover.assert_satisfied();
+    }
+
+    // Error case: add not equal
+    {
+        let p3 = (p1 + p1).to_affine();
+        let circuit = LayouterTestCircuit {
+            s,
+            p1,
+            p2,
+            p3,
+            p4,
+            p5,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: mul not equal
+    {
+        let p5 = p3.mul(s + Fr::one()).to_affine();
+        let circuit = LayouterTestCircuit {
+            s,
+            p1,
+            p2,
+            p3,
+            p4,
+            p5,
+        };
+
+        let prover = MockProver::run(k, &circuit, vec![]).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -1,12 +1,58 @@
 mod arith_gates;
 mod chip;
+mod compression;
 mod config;
 mod ec_gates;
 mod ec_structs;
+mod eddsa;
+mod edwards;
+mod folding;
+mod hash_to_curve;
+mod layouter;
+mod pedersen;
+mod sponge;
 mod util;
 
 pub use arith_gates::ArithOps;
 pub use chip::ECChip;
+pub use compression::compress_point;
+pub use compression::compressed_instance;
+pub use compression::decode_point;
+pub use compression::decompress_point;
+pub use compression::encode_point;
+pub use compression::PointCompressionOps;
+pub use config::DecompositionMode;
 pub use config::ECConfig;
 pub use ec_gates::NativeECOps;
 pub use ec_structs::AssignedECPoint;
+pub use eddsa::EdDSAChip;
+pub use eddsa::EdDSAConfig;
+pub use eddsa::EdDSAHashSpec;
+pub use eddsa::EdDSASignature;
+pub use edwards::AssignedEdwardsPoint;
+pub use edwards::BabyJubJub;
+pub use edwards::EdwardsChip;
+pub use edwards::EdwardsConfig;
+pub use edwards::EdwardsOps;
+pub use edwards::EdwardsPoint;
+pub use edwards::TwistedEdwardsCurve;
+pub use folding::nifs_challenge;
+pub use folding::nifs_challenge_scalars;
+pub use folding::nifs_prove;
+pub use folding::nifs_verify;
+pub use folding::FoldingChip;
+pub use folding::FoldingConfig;
+pub use folding::R1CSInstance;
+pub use folding::R1CSWitness;
+pub use folding::RelaxedR1CSInstance;
+pub use folding::RelaxedR1CSWitness;
+pub use folding::SparseMatrix;
+pub use folding::R1CS;
+pub use hash_to_curve::hash_to_curve;
+pub use hash_to_curve::hash_to_curve_digest;
+pub use hash_to_curve::hash_to_curve_with_counter;
+pub use hash_to_curve::HashToCurveChip;
+pub use hash_to_curve::HashToCurveConfig;
+pub use hash_to_curve::HASH_TO_CURVE_ATTEMPTS;
+pub use pedersen::PedersenChip;
+pub use pedersen::PedersenParams;
@@ -0,0 +1,227 @@
+use ff::PrimeField;
+use halo2_proofs::circuit::Region;
+use halo2_proofs::halo2curves::group::Curve;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::Error;
+use poseidon_base::hash::MessageHashable;
+
+use crate::chip::ECChip;
+use crate::config::ECConfig;
+use crate::hash_to_curve::hash_to_curve;
+use crate::util::leak;
+use crate::util::neg_generator_times_2_to_256;
+use crate::ArithOps;
+use crate::AssignedECPoint;
+use crate::NativeECOps;
+
+#[cfg(test)]
+mod tests;
+
+/// Domain tag for the Pedersen generators; exactly 16 bytes so it fits a u128.
+const PEDERSEN_DOMAIN: &[u8; 16] = b"recreds-pedersen";
+
+/// Public parameters for a Pedersen vector commitment
+///     C = \sum m_i * G_i + r * H
+#[derive(Clone, Debug)]
+pub struct PedersenParams<C: CurveAffine> {
+    /// the message generators G_i
+    pub generators: Vec<C>,
+    /// the blinding generator H
+    pub blinding: C,
+}
+
+impl<C, F> PedersenParams<C>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    /// Derive parameters for up to `n` messages.
+    /// The blinding generator uses index 0 and G_i uses index i + 1.
+    pub fn new(n: usize) -> Self {
+        let blinding = nums_generator::<C, F>(0);
+        let generators = (1..=n as u64).map(nums_generator::<C, F>).collect();
+        Self {
+            generators,
+            blinding,
+        }
+    }
+
+    /// Number of messages supported by the parameters
+    pub fn len(&self) -> usize {
+        self.generators.len()
+    }
+
+    /// Whether the parameters support no messages
+    pub fn is_empty(&self) -> bool {
+        self.generators.is_empty()
+    }
+
+    /// Commit to `msgs` with blinding factor `r`
+    pub fn commit(&self, msgs: &[C::ScalarExt], r: &C::ScalarExt) -> C {
+        assert!(msgs.len() <= self.len(), "too many messages");
+
+        let mut res = self.blinding * r;
+        for (g, m) in self.generators.iter().zip(msgs.iter()) {
+            res = res + *g * m;
+        }
+        res.to_affine()
+    }
+
+    /// Rerandomize a commitment with `delta`.
+    /// The new commitment opens to the same messages with blinding factor `r + delta`.
+    pub fn rerandomize(&self, commitment: &C, delta: &C::ScalarExt) -> C {
+        (self.blinding * delta + *commitment).to_affine()
+    }
+
+    /// Check that `commitment` opens to `msgs` under blinding factor `r`
+    pub fn verify(&self, commitment: &C, msgs: &[C::ScalarExt], r: &C::ScalarExt) -> bool {
+        msgs.len() <= self.len() && self.commit(msgs, r) == *commitment
+    }
+}
+
+/// Nothing-up-my-sleeve generator: hash the index to the curve under the Pedersen domain.
+fn nums_generator<C, F>(index: u64) -> C
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    let domain = F::from_u128(u128::from_le_bytes(*PEDERSEN_DOMAIN));
+    hash_to_curve(domain, &[F::from(index)])
+}
+
+/// A chip that proves knowledge of a Pedersen opening with the native ec chip.
+#[derive(Clone, Debug)]
+pub struct PedersenChip<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField,
+{
+    ec_chip: ECChip<C, F>,
+    params: PedersenParams<C>,
+}
+
+impl<C, F> PedersenChip<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]>,
+{
+    pub fn construct(config: ECConfig<C, F>, params: PedersenParams<C>) -> Self {
+        Self {
+            ec_chip: ECChip::construct(config),
+            params,
+        }
+    }
+
+    pub fn params(&self) -> &PedersenParams<C> {
+        &self.params
+    }
+
+    /// Compute C = \sum m_i * G_i + r * H in circuit.
+    ///
+    /// All scalars share a single double-then-add loop, so the cost is
+    /// 256 doublings plus 256 conditional additions per scalar.
+    /// The generators are constrained to the fixed parameters once, and
+    /// copied into each iteration of the loop.
+    pub fn commit<S>(
+        &self,
+        region: &mut Region<F>,
+        config: &ECConfig<C, F>,
+        msgs: &[C::ScalarExt],
+        r: &C::ScalarExt,
+        offset: &mut usize,
+    ) -> Result<AssignedECPoint<C, F>, Error>
+    where
+        S: PrimeField<Repr = [u8; 32]>,
+        C: CurveAffine<ScalarExt = S>,
+    {
+        assert!(msgs.len() <= self.params.len(), "too many messages");
+
+        let mut bases = vec![];
+        for base in self.params.generators[..msgs.len()]
+            .iter()
+            .chain([&self.params.blinding])
+        {
+            bases.push(
+                self.ec_chip
+                    .load_constant_point(region, config, base, offset)?,
+            );
+        }
+
+        let mut bits = vec![];
+        for s in msgs.iter().chain([r]) {
+            bits.push(self.ec_chip.decompose_scalar(region, config, s, offset)?);
+        }
+
+        // same as `point_mul`, we start from the generator to avoid the infinity point
+        // and subtract 2^256 * generator at the end
+        let generator = C::generator();
+        let mut res = self
+            .ec_chip
+            .load_constant_point(region, config, &generator, offset)?;
+
+        for i in (0..256).rev() {
+            res = self.ec_chip.point_double(region, config, &res, offset)?;
+
+            for (base, base_bits) in bases.iter().zip(bits.iter()) {
+                let b = &base_bits[i];
+                // the base is always a fixed generator; the bit decides if it is added
+                let base_assigned = self.ec_chip.copy_point(region, config, base, offset)?;
+                let bit = self.ec_chip.load_two_private_fields(
+                    region,
+                    config,
+                    &leak(&b.value()),
+                    &F::ZERO,
+                    offset,
+                )?;
+                region.constrain_equal(bit[0].cell(), b.cell())?;
+
+                res = self.ec_chip.conditional_point_add(
+                    region,
+                    config,
+                    &res,
+                    &base_assigned,
+                    &bit[0],
+                    offset,
+                )?;
+            }
+        }
+
+        // now we subtract 2^256 * generator from res
+        let (offset_generator, _, _) = neg_generator_times_2_to_256::<C, C::Base>();
+        let offset_generator_assigned =
+            self.ec_chip
+                .load_constant_point(region, config, &offset_generator, offset)?;
+        let bit =
+            self.ec_chip
+                .load_two_private_fields(region, config, &F::ONE, &F::ZERO, offset)?;
+        region.constrain_constant(bit[0].cell(), F::ONE)?;
+        self.ec_chip.conditional_point_add(
+            region,
+            config,
+            &res,
+            &offset_generator_assigned,
+            &bit[0],
+            offset,
+        )
+    }
+
+    /// Prove that `commitment` opens to `msgs` with blinding factor `r`.
+    pub fn open<S>(
+        &self,
+        region: &mut Region<F>,
+        config: &ECConfig<C, F>,
+        commitment: &AssignedECPoint<C, F>,
+        msgs: &[C::ScalarExt],
+        r: &C::ScalarExt,
+        offset: &mut usize,
+    ) -> Result<(), Error>
+    where
+        S: PrimeField<Repr = [u8; 32]>,
+        C: CurveAffine<ScalarExt = S>,
+    {
+        let res = self.commit(region, config, msgs, r, offset)?;
+        region.constrain_equal(res.x.cell(), commitment.x.cell())?;
+        region.constrain_equal(res.y.cell(), commitment.y.cell())?;
+        Ok(())
+    }
+}
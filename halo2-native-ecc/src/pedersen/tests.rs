@@ -0,0 +1,157 @@
+use ark_std::test_rng;
+use halo2_proofs::arithmetic::Field;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::circuit::SimpleFloorPlanner;
+use halo2_proofs::dev::MockProver;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::Circuit;
+use halo2_proofs::plonk::Column;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use halo2_proofs::plonk::Instance;
+use halo2curves::grumpkin::Fq;
+use halo2curves::grumpkin::Fr;
+use halo2curves::grumpkin::G1Affine;
+
+use crate::chip::ECChip;
+use crate::config::ECConfig;
+use crate::ec_gates::NativeECOps;
+use crate::pedersen::PedersenChip;
+use crate::pedersen::PedersenParams;
+
+const NUM_MSGS: usize = 2;
+
+#[derive(Default, Debug, Clone)]
+struct PedersenTestCircuit {
+    msgs: [Fr; NUM_MSGS],
+    r: Fr,
+}
+
+impl Circuit<Fq> for PedersenTestCircuit {
+    type Config = (ECConfig<G1Affine, Fq>, Column<Instance>);
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fq>) -> Self::Config {
+        let instance = meta.instance_column();
+        meta.enable_equality(instance);
+        (ECChip::configure(meta), instance)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fq>,
+    ) -> Result<(), Error> {
+        let (config, instance) = config;
+        let pedersen_chip = PedersenChip::construct(config.clone(), PedersenParams::new(NUM_MSGS));
+
+        let commitment = layouter.assign_region(
+            || "test pedersen circuit",
+            |mut region| {
+                let mut offset = 0;
+
+                let start = offset;
+                let commitment =
+                    pedersen_chip.commit(&mut region, &config, &self.msgs, &self.r, &mut offset)?;
+                println!("pedersen commitment uses {} rows", offset - start);
+
+                // pad the last two rows
+                ECChip::construct(config.clone()).pad(&mut region, &config, &mut offset)?;
+
+                Ok(commitment)
+            },
+        )?;
+
+        // the opening is proven against a public commitment
+        layouter.constrain_instance(commitment.x.cell(), instance, 0)?;
+        layouter.constrain_instance(commitment.y.cell(), instance, 1)?;
+
+        Ok(())
+    }
+}
+
+fn instance(p: &G1Affine) -> Vec<Vec<Fq>> {
+    let coordinates = p.coordinates().unwrap();
+    vec![vec![*coordinates.x(), *coordinates.y()]]
+}
+
+#[test]
+fn test_pedersen_native() {
+    let mut rng = test_rng();
+    let params = PedersenParams::<G1Affine>::new(NUM_MSGS);
+
+    // generators are deterministic and independent
+    let params2 = PedersenParams::<G1Affine>::new(NUM_MSGS);
+    assert_eq!(params.generators, params2.generators);
+    assert_eq!(params.blinding, params2.blinding);
+    assert_ne!(params.generators[0], params.generators[1]);
+    assert_ne!(params.generators[0], params.blinding);
+
+    let msgs = [Fr::random(&mut rng), Fr::random(&mut rng)];
+    let r = Fr::random(&mut rng);
+    let commitment = params.commit(&msgs, &r);
+    assert!(params.verify(&commitment, &msgs, &r));
+    assert!(!params.verify(&commitment, &msgs, &(r + Fr::one())));
+
+    // rerandomization keeps the messages and shifts the blinding factor
+    let delta = Fr::random(&mut rng);
+    let rerandomized = params.rerandomize(&commitment, &delta);
+    assert_ne!(rerandomized, commitment);
+    assert!(params.verify(&rerandomized, &msgs, &(r + delta)));
+
+    // homomorphism
+    let msgs2 = [Fr::random(&mut rng), Fr::random(&mut rng)];
+    let r2 = Fr::random(&mut rng);
+    let sum: G1Affine = (commitment + params.commit(&msgs2, &r2)).into();
+    assert!(params.verify(&sum, &[msgs[0] + msgs2[0], msgs[1] + msgs2[1]], &(r + r2)));
+}
+
+#[test]
+fn test_pedersen_opening() {
+    let k = 13;
+
+    let mut rng = test_rng();
+    let params = PedersenParams::<G1Affine>::new(NUM_MSGS);
+    let msgs = [Fr::random(&mut rng), Fr::random(&mut rng)];
+    let r = Fr::random(&mut rng);
+    let commitment = params.commit(&msgs, &r);
+
+    {
+        let circuit = PedersenTestCircuit { msgs, r };
+        let prover = MockProver::run(k, &circuit, instance(&commitment)).unwrap();
+        prover.assert_satisfied();
+    }
+
+    // a rerandomized commitment opens with the shifted blinding factor
+    {
+        let delta = Fr::random(&mut rng);
+        let rerandomized = params.rerandomize(&commitment, &delta);
+        let circuit = PedersenTestCircuit { msgs, r: r + delta };
+        let prover = MockProver::run(k, &circuit, instance(&rerandomized)).unwrap();
+        prover.assert_satisfied();
+    }
+
+    // Error case: wrong blinding factor
+    {
+        let circuit = PedersenTestCircuit {
+            msgs,
+            r: r + Fr::one(),
+        };
+        let prover = MockProver::run(k, &circuit, instance(&commitment)).unwrap();
+        assert!(prover.verify().is_err());
+    }
+
+    // Error case: wrong message
+    {
+        let circuit = PedersenTestCircuit {
+            msgs: [msgs[0], msgs[0]],
+            r,
+        };
+        let prover = MockProver::run(k, &circuit, instance(&commitment)).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
@@ -0,0 +1,142 @@
+use ff::PrimeField;
+use halo2_proofs::circuit::AssignedCell;
+use halo2_proofs::circuit::Layouter;
+use halo2_proofs::halo2curves::CurveAffine;
+use halo2_proofs::plonk::ConstraintSystem;
+use halo2_proofs::plonk::Error;
+use poseidon_base::hash::Hashable;
+use poseidon_base::hash::MessageHashable;
+use poseidon_base::hash::HASHABLE_DOMAIN_SPEC;
+use poseidon_circuit::poseidon::PoseidonInstructions;
+use poseidon_circuit::poseidon::Pow5Chip;
+use poseidon_circuit::poseidon::Pow5Config;
+use poseidon_circuit::poseidon::StateWord;
+
+use crate::chip::ECChip;
+use crate::config::ECConfig;
+use crate::ArithOps;
+
+/// Configure the Poseidon permutation of `MessageHashable::hash_msg`.
+/// It relies on the constant column enabled by the ec chip.
+pub(crate) fn configure_poseidon<F>(meta: &mut ConstraintSystem<F>) -> Pow5Config<F, 3, 2>
+where
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    let state = [0; 3].map(|_| meta.advice_column());
+    let partial_sbox = meta.advice_column();
+    let rc_a = [0; 3].map(|_| meta.fixed_column());
+    let rc_b = [0; 3].map(|_| meta.fixed_column());
+    Pow5Chip::configure::<<F as Hashable>::SpecType>(meta, state, partial_sbox, rc_a, rc_b)
+}
+
+/// The sponge of `MessageHashable::hash_msg` over the ec chip and a
+/// Poseidon permutation.
+///
+/// The sponge keeps its capacity `len * HASHABLE_DOMAIN_SPEC` in the first
+/// word and absorbs two words per permutation into the other two; a message
+/// of odd length is padded with a zero, and the digest is the first word.
+#[derive(Clone, Debug)]
+pub(crate) struct MessageSponge<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField,
+{
+    config: ECConfig<C, F>,
+    ec_chip: ECChip<C, F>,
+    poseidon: Pow5Chip<F, 3, 2>,
+}
+
+impl<C, F> MessageSponge<C, F>
+where
+    C: CurveAffine<Base = F>,
+    F: PrimeField<Repr = [u8; 32]> + MessageHashable,
+{
+    pub(crate) fn construct(config: ECConfig<C, F>, poseidon: Pow5Config<F, 3, 2>) -> Self {
+        Self {
+            ec_chip: ECChip::construct(config.clone()),
+            poseidon: Pow5Chip::construct(poseidon),
+            config,
+        }
+    }
+
+    /// The state before absorbing a message of `len` elements,
+    /// and a zero cell to pad the message with.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn initial_state(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        len: usize,
+    ) -> Result<([AssignedCell<F, F>; 3], AssignedCell<F, F>), Error> {
+        let config = &self.config;
+        layouter.assign_region(
+            || "sponge initial state",
+            |mut region| {
+                let mut offset = 0;
+                let capacity = F::from_u128(len as u128 * HASHABLE_DOMAIN_SPEC);
+                let capacity =
+                    self.ec_chip
+                        .load_constant(&mut region, config, &capacity, &mut offset)?;
+                let zero =
+                    self.ec_chip
+                        .load_constant(&mut region, config, &F::ZERO, &mut offset)?;
+                Ok(([capacity, zero.clone(), zero.clone()], zero))
+            },
+        )
+    }
+
+    /// Add a chunk of two words to the rate of `state` and permute.
+    pub(crate) fn absorb(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        state: &[AssignedCell<F, F>; 3],
+        chunk: [&AssignedCell<F, F>; 2],
+    ) -> Result<[AssignedCell<F, F>; 3], Error> {
+        let config = &self.config;
+        let absorbed = layouter.assign_region(
+            || "sponge absorb",
+            |mut region| {
+                let mut offset = 0;
+                let s1 = self.ec_chip.add_cells(
+                    &mut region,
+                    config,
+                    &state[1],
+                    chunk[0],
+                    &mut offset,
+                )?;
+                let s2 = self.ec_chip.add_cells(
+                    &mut region,
+                    config,
+                    &state[2],
+                    chunk[1],
+                    &mut offset,
+                )?;
+                Ok([state[0].clone(), s1, s2])
+            },
+        )?;
+        let permuted = <Pow5Chip<F, 3, 2> as PoseidonInstructions<
+            F,
+            <F as Hashable>::SpecType,
+            3,
+            2,
+        >>::permute(
+            &self.poseidon,
+            &mut layouter.namespace(|| "sponge permutation"),
+            &absorbed.map(StateWord::from),
+        )?;
+        Ok(permuted.map(AssignedCell::from))
+    }
+
+    /// The digest `hash_msg(messages)` of cells that are already assigned.
+    pub(crate) fn hash(
+        &self,
+        layouter: &mut impl Layouter<F>,
+        messages: &[AssignedCell<F, F>],
+    ) -> Result<AssignedCell<F, F>, Error> {
+        let (mut state, zero) = self.initial_state(layouter, messages.len())?;
+        for chunk in messages.chunks(2) {
+            let second = chunk.get(1).unwrap_or(&zero);
+            state = self.absorb(layouter, &state, [&chunk[0], second])?;
+        }
+        Ok(state[0].clone())
+    }
+}
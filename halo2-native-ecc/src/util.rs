@@ -1,8 +1,9 @@
 use std::u128;
 
-use halo2_proofs::circuit::Value;
 use ff::PrimeField;
+use halo2_proofs::circuit::Value;
 use halo2curves::CurveAffine;
+use num_bigint::BigUint;
 
 pub(crate) fn leak<T: Copy + Default>(a: &Value<&T>) -> T {
     let mut t = T::default();
@@ -10,6 +11,21 @@ pub(crate) fn leak<T: Copy + Default>(a: &Value<&T>) -> T {
     t
 }
 
+/// Integer value of a field element with a little endian representation
+pub(crate) fn fe_to_big<F: PrimeField>(e: &F) -> BigUint {
+    BigUint::from_bytes_le(e.to_repr().as_ref())
+}
+
+/// Field element of an integer; panics if the integer is not reduced
+pub(crate) fn big_to_fe<F: PrimeField>(e: &BigUint) -> F {
+    F::from_str_vartime(&e.to_str_radix(10)).unwrap()
+}
+
+/// Modulus of a field with a little endian representation
+pub(crate) fn modulus<F: PrimeField>() -> BigUint {
+    fe_to_big(&-F::ONE) + 1u64
+}
+
 /// Split a scalar field elements into high and low and
 /// store the high and low in base field.
 pub(crate) fn field_decompose_u128<S>(e: &S) -> (u128, u128)
@@ -131,6 +131,13 @@ impl<
         Point { x, y }
     }
 
+    /// Returns emulated EC constant $a$, or `None` if it is zero
+    fn parameter_a(&self) -> Option<Integer<Emulated::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>> {
+        let a = Emulated::a();
+        let is_zero = bool::from(halo2::halo2curves::ff::Field::is_zero(&a));
+        (!is_zero).then(|| Integer::from_fe(a, self.rns_base()))
+    }
+
     /// Returns emulated EC constant $b$
     fn parameter_b(&self) -> Integer<Emulated::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
         Integer::from_fe(Emulated::b(), self.rns_base())
@@ -268,7 +275,14 @@ impl<
         let x_square = &integer_chip.square(ctx, point.x())?;
         let x_cube = &integer_chip.mul(ctx, point.x(), x_square)?;
         let x_cube_b = &integer_chip.add_constant(ctx, x_cube, &self.parameter_b())?;
-        integer_chip.assert_equal(ctx, x_cube_b, y_square)?;
+        match self.parameter_a() {
+            Some(a) => {
+                let a_x = &integer_chip.mul_constant(ctx, point.x(), &a)?;
+                let rhs = &integer_chip.add(ctx, x_cube_b, a_x)?;
+                integer_chip.assert_equal(ctx, rhs, y_square)?;
+            }
+            None => integer_chip.assert_equal(ctx, x_cube_b, y_square)?,
+        }
         Ok(())
     }
 
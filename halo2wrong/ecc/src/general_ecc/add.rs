@@ -56,9 +56,13 @@ impl<
     ) -> Result<AssignedPoint<Emulated::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
         let ch = self.base_field_chip();
 
-        // lambda = (3 * a_x^2) / 2 * a_y
+        // lambda = (3 * a_x^2 + a) / 2 * a_y
         let x_0_square = &ch.square(ctx, &point.x)?;
         let numerator = &ch.mul3(ctx, x_0_square)?;
+        let numerator = &match self.parameter_a() {
+            Some(a) => ch.add_constant(ctx, numerator, &a)?,
+            None => numerator.clone(),
+        };
         let denominator = &ch.mul2(ctx, &point.y)?;
         let lambda = &ch.div_incomplete(ctx, numerator, denominator)?;
 
@@ -0,0 +1,455 @@
+use crate::ecdsa::{AssignedEcdsaSig, AssignedPublicKey, EcdsaChip};
+use crate::halo2;
+use crate::integer;
+use crate::maingate;
+use ecc::GeneralEccChip;
+use halo2::arithmetic::CurveAffine;
+use halo2::circuit::Chip;
+use halo2::halo2curves::ff::{Field, FromUniformBytes, PrimeField};
+use halo2::halo2curves::group::Curve;
+use halo2::plonk::Error;
+use integer::{IntegerInstructions, Range};
+use maingate::{big_to_fe, fe_to_big, modulus};
+use maingate::{AssignedValue, MainGateInstructions, RegionCtx};
+use poseidon::{Poseidon, Spec};
+use rand::RngCore;
+use transcript::HasherChip;
+
+/// Poseidon width used for the attribute commitment
+pub const T: usize = 3;
+/// Poseidon rate used for the attribute commitment
+pub const RATE: usize = 2;
+/// Number of full rounds of the attribute commitment hash
+pub const R_F: usize = 8;
+/// Number of partial rounds of the attribute commitment hash
+pub const R_P: usize = 57;
+
+/// Poseidon commitment to a list of attributes
+pub fn attribute_commitment<N: FromUniformBytes<64>>(attributes: &[N]) -> N {
+    let mut hasher = Poseidon::<N, T, RATE>::new(R_F, R_P);
+    hasher.update(attributes);
+    hasher.squeeze()
+}
+
+/// Reads the attribute commitment as an integer modulo the order of `E`.
+///
+/// The native field must be smaller than the scalar field of `E` so that the
+/// integer is unchanged, which holds for BN254 against secp256k1 and P-256.
+pub fn commitment_to_msg_hash<E: CurveAffine, N: PrimeField>(commitment: N) -> E::Scalar {
+    assert!(modulus::<N>() < modulus::<E::Scalar>());
+    big_to_fe(fe_to_big(commitment))
+}
+
+/// ECDSA signature over an already hashed message
+pub fn sign_prehashed<E: CurveAffine>(
+    sk: E::Scalar,
+    msg_hash: E::Scalar,
+    k: E::Scalar,
+) -> (E::Scalar, E::Scalar) {
+    let r_point = (E::generator() * k).to_affine().coordinates().unwrap();
+    let r = big_to_fe::<E::Scalar>(fe_to_big(*r_point.x()));
+    let s = k.invert().unwrap() * (msg_hash + r * sk);
+    (r, s)
+}
+
+/// ECDSA verification over an already hashed message
+pub fn verify_prehashed<E: CurveAffine>(
+    public_key: E,
+    msg_hash: E::Scalar,
+    signature: (E::Scalar, E::Scalar),
+) -> bool {
+    let (r, s) = signature;
+    if bool::from(r.is_zero()) || bool::from(s.is_zero()) {
+        return false;
+    }
+    let s_inv = s.invert().unwrap();
+    let q = (E::generator() * (msg_hash * s_inv) + public_key * (r * s_inv)).to_affine();
+    match Option::<_>::from(q.coordinates()) {
+        Some(q) => big_to_fe::<E::Scalar>(fe_to_big(*q.x())) == r,
+        None => false,
+    }
+}
+
+/// A credential issued under an existing ECDSA key.
+///
+/// The issuer signs the Poseidon commitment to the attributes, read as an
+/// integer modulo the order of `E`.
+#[derive(Clone, Debug)]
+pub struct EcdsaCredential<E: CurveAffine, N: PrimeField> {
+    pub attributes: Vec<N>,
+    pub issuer: E,
+    pub signature: (E::Scalar, E::Scalar),
+}
+
+impl<E: CurveAffine, N: FromUniformBytes<64>> EcdsaCredential<E, N> {
+    /// Issue a credential over `attributes` with the issuer secret key `sk`
+    pub fn issue(sk: E::Scalar, attributes: Vec<N>, mut rng: impl RngCore) -> Self {
+        let msg_hash = commitment_to_msg_hash::<E, N>(attribute_commitment(&attributes));
+        let k = E::Scalar::random(&mut rng);
+        Self {
+            attributes,
+            issuer: (E::generator() * sk).to_affine(),
+            signature: sign_prehashed::<E>(sk, msg_hash, k),
+        }
+    }
+
+    /// The message hash signed by the issuer
+    pub fn msg_hash(&self) -> E::Scalar {
+        commitment_to_msg_hash::<E, N>(attribute_commitment(&self.attributes))
+    }
+
+    /// Check the issuer signature natively
+    pub fn verify(&self) -> bool {
+        verify_prehashed(self.issuer, self.msg_hash(), self.signature)
+    }
+}
+
+/// Verifies credentials signed with ECDSA on top of [`EcdsaChip`]
+pub struct EcdsaCredentialChip<
+    E: CurveAffine,
+    N: PrimeField,
+    const NUMBER_OF_LIMBS: usize,
+    const BIT_LEN_LIMB: usize,
+> {
+    ecdsa_chip: EcdsaChip<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    spec: Spec<N, T, RATE>,
+}
+
+impl<
+        E: CurveAffine,
+        N: FromUniformBytes<64>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN_LIMB: usize,
+    > EcdsaCredentialChip<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>
+{
+    pub fn new(ecc_chip: GeneralEccChip<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>) -> Self {
+        Self {
+            ecdsa_chip: EcdsaChip::new(ecc_chip),
+            spec: Spec::new(R_F, R_P),
+        }
+    }
+
+    pub fn ecdsa_chip(&self) -> &EcdsaChip<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
+        &self.ecdsa_chip
+    }
+
+    /// Verifies the issuer signature over `attributes` and returns the
+    /// attribute commitment
+    pub fn verify(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        attributes: &[AssignedValue<N>],
+        sig: &AssignedEcdsaSig<E::Scalar, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        pk: &AssignedPublicKey<E::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<AssignedValue<N>, Error> {
+        let commitment = self.commit(ctx, attributes)?;
+        self.verify_commitment(ctx, &commitment, sig, pk)?;
+        Ok(commitment)
+    }
+
+    /// Commits to `attributes` as in [`attribute_commitment`]
+    pub fn commit(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        attributes: &[AssignedValue<N>],
+    ) -> Result<AssignedValue<N>, Error> {
+        let main_gate = self.ecdsa_chip.ecc_chip().scalar_field_chip().main_gate();
+        let mut hasher = HasherChip::<N, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>::new(
+            ctx,
+            &self.spec,
+            main_gate.config(),
+        )?;
+        hasher.update(attributes);
+        hasher.hash(ctx)
+    }
+
+    /// Verifies the issuer signature over an attribute commitment, which
+    /// may be assigned in another region
+    pub fn verify_commitment(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        commitment: &AssignedValue<N>,
+        sig: &AssignedEcdsaSig<E::Scalar, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        pk: &AssignedPublicKey<E::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<(), Error> {
+        assert!(modulus::<N>() < modulus::<E::Scalar>());
+
+        let ecc_chip = self.ecdsa_chip.ecc_chip();
+        let scalar_chip = ecc_chip.scalar_field_chip();
+        let main_gate = scalar_chip.main_gate();
+
+        // 1. bind the message hash to the commitment
+        //
+        // This only fixes the message hash modulo the native field. The issuer
+        // signs canonical commitments, which are below the native modulus,
+        // so a valid signature pins the message hash to the commitment itself.
+        let msg_hash = commitment
+            .value()
+            .map(|c| commitment_to_msg_hash::<E, N>(*c));
+        let msg_hash = ecc_chip.new_unassigned_scalar(msg_hash);
+        let msg_hash = scalar_chip.assign_integer(ctx, msg_hash, Range::Remainder)?;
+        main_gate.assert_equal(ctx, msg_hash.native(), commitment)?;
+
+        // 2. verify the issuer signature
+        self.ecdsa_chip.verify(ctx, sig, pk, &msg_hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        attribute_commitment, sign_prehashed, verify_prehashed, EcdsaCredential,
+        EcdsaCredentialChip,
+    };
+    use crate::ecdsa::{AssignedEcdsaSig, AssignedPublicKey};
+    use crate::halo2;
+    use crate::integer;
+    use crate::maingate;
+    use ecc::integer::Range;
+    use ecc::maingate::big_to_fe;
+    use ecc::maingate::RegionCtx;
+    use ecc::{EccConfig, GeneralEccChip};
+    use halo2::arithmetic::CurveAffine;
+    use halo2::circuit::{Layouter, SimpleFloorPlanner, Value};
+    use halo2::dev::MockProver;
+    use halo2::halo2curves::{
+        ff::{Field, FromUniformBytes, PrimeField},
+        group::{Curve, Group},
+    };
+    use halo2::plonk::{Circuit, ConstraintSystem, Error};
+    use integer::IntegerInstructions;
+    use maingate::{mock_prover_verify, DimensionMeasurement};
+    use maingate::{MainGate, MainGateConfig, MainGateInstructions};
+    use maingate::{RangeChip, RangeConfig, RangeInstructions};
+    use num_bigint::BigUint;
+    use num_traits::Num;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    use std::marker::PhantomData;
+
+    const BIT_LEN_LIMB: usize = 68;
+    const NUMBER_OF_LIMBS: usize = 4;
+
+    fn from_hex<F: PrimeField>(hex: &str) -> F {
+        big_to_fe(BigUint::from_str_radix(hex, 16).unwrap())
+    }
+
+    #[derive(Clone, Debug)]
+    struct TestCircuitCredentialConfig {
+        main_gate_config: MainGateConfig,
+        range_config: RangeConfig,
+    }
+
+    impl TestCircuitCredentialConfig {
+        pub fn new<C: CurveAffine, N: PrimeField>(meta: &mut ConstraintSystem<N>) -> Self {
+            let (rns_base, rns_scalar) =
+                GeneralEccChip::<C, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+            let main_gate_config = MainGate::<N>::configure(meta);
+            let mut overflow_bit_lens: Vec<usize> = vec![];
+            overflow_bit_lens.extend(rns_base.overflow_lengths());
+            overflow_bit_lens.extend(rns_scalar.overflow_lengths());
+            let composition_bit_lens = vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS];
+
+            let range_config = RangeChip::<N>::configure(
+                meta,
+                &main_gate_config,
+                composition_bit_lens,
+                overflow_bit_lens,
+            );
+            TestCircuitCredentialConfig {
+                main_gate_config,
+                range_config,
+            }
+        }
+
+        pub fn ecc_chip_config(&self) -> EccConfig {
+            EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+        }
+
+        pub fn config_range<N: PrimeField>(
+            &self,
+            layouter: &mut impl Layouter<N>,
+        ) -> Result<(), Error> {
+            let range_chip = RangeChip::<N>::new(self.range_config.clone());
+            range_chip.load_table(layouter)?;
+
+            Ok(())
+        }
+    }
+
+    #[derive(Default, Clone)]
+    struct TestCircuitCredential<E: CurveAffine, N: PrimeField> {
+        attributes: Vec<Value<N>>,
+        public_key: Value<E>,
+        signature: Value<(E::Scalar, E::Scalar)>,
+        commitment: Value<N>,
+
+        aux_generator: E,
+        window_size: usize,
+        _marker: PhantomData<N>,
+    }
+
+    impl<E: CurveAffine, N: FromUniformBytes<64>> Circuit<N> for TestCircuitCredential<E, N> {
+        type Config = TestCircuitCredentialConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self {
+                attributes: vec![Value::unknown(); self.attributes.len()],
+                aux_generator: self.aux_generator,
+                window_size: self.window_size,
+                ..Default::default()
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<N>) -> Self::Config {
+            TestCircuitCredentialConfig::new::<E, N>(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<N>,
+        ) -> Result<(), Error> {
+            let mut ecc_chip = GeneralEccChip::<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+                config.ecc_chip_config(),
+            );
+
+            layouter.assign_region(
+                || "assign aux values",
+                |region| {
+                    let offset = 0;
+                    let ctx = &mut RegionCtx::new(region, offset);
+
+                    ecc_chip.assign_aux_generator(ctx, Value::known(self.aux_generator))?;
+                    ecc_chip.assign_aux(ctx, self.window_size, 2)?;
+                    Ok(())
+                },
+            )?;
+
+            let credential_chip = EcdsaCredentialChip::new(ecc_chip.clone());
+            let scalar_chip = ecc_chip.scalar_field_chip();
+            let main_gate = scalar_chip.main_gate();
+
+            layouter.assign_region(
+                || "region 0",
+                |region| {
+                    let offset = 0;
+                    let ctx = &mut RegionCtx::new(region, offset);
+
+                    let attributes = self
+                        .attributes
+                        .iter()
+                        .map(|attribute| main_gate.assign_value(ctx, *attribute))
+                        .collect::<Result<Vec<_>, Error>>()?;
+
+                    let r = self.signature.map(|signature| signature.0);
+                    let s = self.signature.map(|signature| signature.1);
+                    let integer_r = ecc_chip.new_unassigned_scalar(r);
+                    let integer_s = ecc_chip.new_unassigned_scalar(s);
+                    let r_assigned =
+                        scalar_chip.assign_integer(ctx, integer_r, Range::Remainder)?;
+                    let s_assigned =
+                        scalar_chip.assign_integer(ctx, integer_s, Range::Remainder)?;
+                    let sig = AssignedEcdsaSig {
+                        r: r_assigned,
+                        s: s_assigned,
+                    };
+
+                    let pk_in_circuit = ecc_chip.assign_point(ctx, self.public_key)?;
+                    let pk_assigned = AssignedPublicKey {
+                        point: pk_in_circuit,
+                    };
+
+                    let commitment =
+                        credential_chip.verify(ctx, &attributes, &sig, &pk_assigned)?;
+                    let expected = main_gate.assign_value(ctx, self.commitment)?;
+                    main_gate.assert_equal(ctx, &commitment, &expected)
+                },
+            )?;
+
+            config.config_range(&mut layouter)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_p256_rfc6979_vector() {
+        use crate::curves::secp256r1::{Fp, Fq, Secp256r1Affine};
+
+        // RFC 6979, A.2.5, SHA-256 with message "sample"
+        let sk: Fq = from_hex("c9afa9d845ba75166b5c215767b1d6934e50c3db36e89b127b8a622b120f6721");
+        let public_key = Secp256r1Affine::from_xy(
+            from_hex::<Fp>("60fed4ba255a9d31c961eb74c6356d68c049b8923b61fa6ce669622e60f29fb6"),
+            from_hex::<Fp>("7903fe1008b8bc99a41ae9e95628bc64f2f1b20c2d7e9f5177a3c294d4462299"),
+        )
+        .unwrap();
+        assert_eq!((Secp256r1Affine::generator() * sk).to_affine(), public_key);
+
+        let msg_hash: Fq =
+            from_hex("af2bdbe1aa9b6ec1e2ade1d694f41fc71a831d0268e9891562113d8a62add1bf");
+        let k: Fq = from_hex("a6e3c57dd01abe90086538398355dd4c3b17aa873382b0f24d6129493d8aad60");
+        let r: Fq = from_hex("efd48b2aacb6a8fd1140dd9cd45e81d69d2c877b56aaf991c34d0ea84eaf3716");
+        let s: Fq = from_hex("f7cb1c942d657c41d436c7a1b6e29f65f3e900dbb9aff4064dc4ab2f843acda8");
+
+        assert_eq!(sign_prehashed::<Secp256r1Affine>(sk, msg_hash, k), (r, s));
+        assert!(verify_prehashed(public_key, msg_hash, (r, s)));
+        assert!(!verify_prehashed(public_key, msg_hash + Fq::ONE, (r, s)));
+        assert!(!verify_prehashed(public_key, msg_hash, (s, r)));
+    }
+
+    #[test]
+    fn test_ecdsa_credential() {
+        fn run<C: CurveAffine, N: FromUniformBytes<64> + Ord>() {
+            let mut rng = StdRng::seed_from_u64(0xecd5a);
+
+            let sk = C::Scalar::random(&mut rng);
+            let attributes = (0..5).map(|_| N::random(&mut rng)).collect::<Vec<_>>();
+            let credential = EcdsaCredential::<C, N>::issue(sk, attributes, &mut rng);
+            assert!(credential.verify());
+
+            let aux_generator = C::CurveExt::random(&mut rng).to_affine();
+            let circuit = TestCircuitCredential::<C, N> {
+                attributes: credential
+                    .attributes
+                    .iter()
+                    .map(|attribute| Value::known(*attribute))
+                    .collect(),
+                public_key: Value::known(credential.issuer),
+                signature: Value::known(credential.signature),
+                commitment: Value::known(attribute_commitment(&credential.attributes)),
+                aux_generator,
+                window_size: 4,
+                ..Default::default()
+            };
+            mock_prover_verify(&circuit, vec![vec![]]);
+
+            // Error case: an attribute that was not signed
+            {
+                let mut attributes = credential.attributes.clone();
+                attributes[0] += N::ONE;
+                let tampered = EcdsaCredential::<C, N> {
+                    attributes: attributes.clone(),
+                    ..credential.clone()
+                };
+                assert!(!tampered.verify());
+
+                let circuit = TestCircuitCredential::<C, N> {
+                    attributes: attributes.iter().map(|a| Value::known(*a)).collect(),
+                    commitment: Value::known(attribute_commitment(&attributes)),
+                    ..circuit.clone()
+                };
+                let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+                let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+        }
+
+        use crate::curves::bn256::Fr as BnScalar;
+        use crate::curves::secp256k1::Secp256k1Affine as Secp256k1;
+        use crate::curves::secp256r1::Secp256r1Affine as P256;
+        run::<P256, BnScalar>();
+        run::<Secp256k1, BnScalar>();
+    }
+}
@@ -5,11 +5,17 @@ use crate::maingate;
 use ecc::maingate::RegionCtx;
 use ecc::{AssignedPoint, EccConfig, GeneralEccChip};
 use halo2::arithmetic::CurveAffine;
-use halo2::halo2curves::ff::PrimeField;
+use halo2::circuit::Chip;
+use halo2::halo2curves::ff::{Field, PrimeField};
+use halo2::halo2curves::group::Curve;
 use halo2::{circuit::Value, plonk::Error};
-use integer::rns::Integer;
-use integer::{AssignedInteger, IntegerInstructions};
-use maingate::{MainGateConfig, RangeConfig};
+use integer::rns::{Common, Integer};
+use integer::{AssignedInteger, IntegerInstructions, Range};
+use maingate::{big_to_fe, fe_to_big, modulus};
+use maingate::{AssignedCondition, AssignedValue};
+use maingate::{MainGateConfig, MainGateInstructions, RangeConfig};
+use poseidon::Spec;
+use transcript::HasherChip;
 
 #[derive(Clone, Debug)]
 pub struct EcdsaConfig {
@@ -84,7 +90,7 @@ impl<E: CurveAffine, N: PrimeField, const NUMBER_OF_LIMBS: usize, const BIT_LEN_
         self.0.scalar_field_chip()
     }
 
-    fn ecc_chip(&self) -> GeneralEccChip<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
+    pub(crate) fn ecc_chip(&self) -> GeneralEccChip<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
         self.0.clone()
     }
 }
@@ -135,31 +141,230 @@ impl<E: CurveAffine, N: PrimeField, const NUMBER_OF_LIMBS: usize, const BIT_LEN_
 
         Ok(())
     }
+
+    /// Verifies a batch of signatures with a single multi scalar
+    /// multiplication.
+    ///
+    /// For each signature the prover supplies the point
+    /// `R_i = u1_i * G + u2_i * pk_i` with `R_i.x == r_i (mod n)`.
+    /// The points are then checked at once with
+    ///     sum rho^i * (u1_i * G + u2_i * pk_i - R_i) == 0
+    /// where `rho` is squeezed from a Poseidon transcript of all the inputs.
+    ///
+    /// The multiplication runs over `2 * sigs.len()` pairs with window size
+    /// 4, so the matching aux must be assigned beforehand.
+    pub fn verify_batch<const T: usize, const RATE: usize>(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        spec: &Spec<N, T, RATE>,
+        sigs: &[AssignedEcdsaSig<E::Scalar, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>],
+        pks: &[AssignedPublicKey<E::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>],
+        msg_hashes: &[AssignedInteger<E::Scalar, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>],
+    ) -> Result<(), Error> {
+        assert!(!sigs.is_empty());
+        assert_eq!(sigs.len(), pks.len());
+        assert_eq!(sigs.len(), msg_hashes.len());
+        // the challenge is read as an integer modulo n
+        assert!(modulus::<N>() < modulus::<E::Scalar>());
+
+        let ecc_chip = self.ecc_chip();
+        let scalar_chip = ecc_chip.scalar_field_chip();
+        let base_chip = ecc_chip.base_field_chip();
+        let main_gate = ecc_chip.main_gate();
+
+        let mut u1s = vec![];
+        let mut u2s = vec![];
+        let mut r_points = vec![];
+        for ((sig, pk), msg_hash) in sigs.iter().zip(pks.iter()).zip(msg_hashes.iter()) {
+            // 1. check 0 < r, s < n
+            scalar_chip.assert_not_zero(ctx, &sig.r)?;
+            scalar_chip.assert_not_zero(ctx, &sig.s)?;
+
+            // 2. u1 = m' * s^(-1), u2 = r * s^(-1) (mod n)
+            let (s_inv, _) = scalar_chip.invert(ctx, &sig.s)?;
+            let u1 = scalar_chip.mul(ctx, msg_hash, &s_inv)?;
+            let u2 = scalar_chip.mul(ctx, &sig.r, &s_inv)?;
+
+            // 3. witness R = u1*G + u2*pk and check R.x == r (mod n)
+            let r_point = u1
+                .integer()
+                .zip(u2.integer())
+                .zip(pk.point.x().integer().zip(pk.point.y().integer()))
+                .map(|((u1, u2), (x, y))| {
+                    let u1 = big_to_fe::<E::Scalar>(u1.value());
+                    let u2 = big_to_fe::<E::Scalar>(u2.value());
+                    let pk = E::from_xy(big_to_fe(x.value()), big_to_fe(y.value())).unwrap();
+                    (E::generator() * u1 + pk * u2).to_affine()
+                });
+            let r_point = ecc_chip.assign_point(ctx, r_point)?;
+            let r_x_reduced_in_q = base_chip.reduce(ctx, r_point.x())?;
+            let r_x_reduced_in_r = scalar_chip.reduce_external(ctx, &r_x_reduced_in_q)?;
+            scalar_chip.assert_strict_equal(ctx, &r_x_reduced_in_r, &sig.r)?;
+
+            u1s.push(u1);
+            u2s.push(u2);
+            r_points.push(r_point);
+        }
+
+        // 4. rho = H(r_i, s_i, m_i, pk_i, R_i)
+        let mut hasher = HasherChip::<N, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>::new(
+            ctx,
+            spec,
+            main_gate.config(),
+        )?;
+        for (((sig, pk), msg_hash), r_point) in sigs
+            .iter()
+            .zip(pks.iter())
+            .zip(msg_hashes.iter())
+            .zip(r_points.iter())
+        {
+            for integer in [
+                &sig.r,
+                &sig.s,
+                msg_hash,
+                pk.point.x(),
+                pk.point.y(),
+                r_point.x(),
+                r_point.y(),
+            ] {
+                let limbs: Vec<AssignedValue<N>> =
+                    integer.limbs().iter().map(|limb| limb.into()).collect();
+                hasher.update(&limbs);
+            }
+        }
+        let challenge = hasher.hash(ctx)?;
+        let rho = challenge
+            .value()
+            .map(|c| big_to_fe::<E::Scalar>(fe_to_big(*c)));
+        let rho = ecc_chip.new_unassigned_scalar(rho);
+        let rho = scalar_chip.assign_integer(ctx, rho, Range::Remainder)?;
+        main_gate.assert_equal(ctx, rho.native(), &challenge)?;
+
+        // 5. Q = (sum rho^i * u1_i) * G + sum rho^i * u2_i * pk_i - sum_{i > 0} rho^i * R_i
+        let e_gen = ecc_chip.assign_point(ctx, Value::known(E::generator()))?;
+        let mut u1_sum = u1s[0].clone();
+        let mut pairs = vec![(pks[0].point.clone(), u2s[0].clone())];
+        let mut rho_i = rho.clone();
+        for i in 1..sigs.len() {
+            let u1 = scalar_chip.mul(ctx, &u1s[i], &rho_i)?;
+            u1_sum = scalar_chip.add(ctx, &u1_sum, &u1)?;
+            let u2 = scalar_chip.mul(ctx, &u2s[i], &rho_i)?;
+            pairs.push((pks[i].point.clone(), u2));
+            let neg_rho_i = scalar_chip.neg(ctx, &rho_i)?;
+            let neg_rho_i = scalar_chip.reduce(ctx, &neg_rho_i)?;
+            pairs.push((r_points[i].clone(), neg_rho_i));
+
+            if i + 1 < sigs.len() {
+                rho_i = scalar_chip.mul(ctx, &rho_i, &rho)?;
+            }
+        }
+        let u1_sum = scalar_chip.reduce(ctx, &u1_sum)?;
+        pairs.insert(0, (e_gen, u1_sum));
+        let q = ecc_chip.mul_batch_1d_horizontal(ctx, pairs, 4)?;
+
+        // 6. since rho^0 = 1, Q must be R_0
+        ecc_chip.assert_equal(ctx, &q, &r_points[0])?;
+
+        Ok(())
+    }
+
+    /// Recovers the public key that signed `msg_hash`.
+    ///
+    /// `v` is the parity of the y coordinate of the signature point `R`,
+    /// whose x coordinate must match `r` modulo n. The key is
+    ///     Q = r^(-1) * (s * R - m' * G)
+    /// which passes `verify` for the same signature and message hash.
+    ///
+    /// Uses the aux for two pairs with window size 4, as `verify` does.
+    pub fn recover(
+        &self,
+        ctx: &mut RegionCtx<'_, N>,
+        sig: &AssignedEcdsaSig<E::Scalar, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        v: &AssignedCondition<N>,
+        msg_hash: &AssignedInteger<E::Scalar, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    ) -> Result<AssignedPublicKey<E::Base, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>, Error> {
+        let ecc_chip = self.ecc_chip();
+        let scalar_chip = ecc_chip.scalar_field_chip();
+        let base_chip = ecc_chip.base_field_chip();
+        let main_gate = ecc_chip.main_gate();
+
+        // 1. check 0 < r, s < n
+        scalar_chip.assert_not_zero(ctx, &sig.r)?;
+        scalar_chip.assert_not_zero(ctx, &sig.s)?;
+
+        // 2. witness R with R.x == r and the parity of R.y given by v
+        //
+        // if r is not the x coordinate of a point we fall back to the
+        // generator, which fails the check in step 3
+        let r_point = sig.r.integer().zip(v.value()).map(|(r, v)| {
+            let x = big_to_fe::<E::Base>(r.value());
+            let y = (x.square() * x + E::a() * x + E::b()).sqrt();
+            Option::<E::Base>::from(y)
+                .map(|y| {
+                    let y = if bool::from(y.is_odd()) == (*v == N::ONE) {
+                        y
+                    } else {
+                        -y
+                    };
+                    E::from_xy(x, y).unwrap()
+                })
+                .unwrap_or(E::generator())
+        });
+        let r_point = ecc_chip.assign_point(ctx, r_point)?;
+        let parity = base_chip.sign(ctx, r_point.y())?;
+        main_gate.assert_equal(ctx, &parity, v)?;
+
+        // 3. check R.x == r (mod n)
+        let r_x_reduced_in_q = base_chip.reduce(ctx, r_point.x())?;
+        let r_x_reduced_in_r = scalar_chip.reduce_external(ctx, &r_x_reduced_in_q)?;
+        scalar_chip.assert_strict_equal(ctx, &r_x_reduced_in_r, &sig.r)?;
+
+        // 4. u1 = s * r^(-1), u2 = -m' * r^(-1) (mod n)
+        let (r_inv, _) = scalar_chip.invert(ctx, &sig.r)?;
+        let u1 = scalar_chip.mul(ctx, &sig.s, &r_inv)?;
+        let u2 = scalar_chip.mul(ctx, msg_hash, &r_inv)?;
+        let u2 = scalar_chip.neg(ctx, &u2)?;
+        let u2 = scalar_chip.reduce(ctx, &u2)?;
+
+        // 5. Q = u1*R + u2*G
+        let e_gen = ecc_chip.assign_point(ctx, Value::known(E::generator()))?;
+        let pairs = vec![(r_point, u1), (e_gen, u2)];
+        let q = ecc_chip.mul_batch_1d_horizontal(ctx, pairs, 4)?;
+        let q = ecc_chip.normalize(ctx, &q)?;
+
+        Ok(AssignedPublicKey { point: q })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::{AssignedEcdsaSig, AssignedPublicKey, EcdsaChip};
+    use crate::credential::sign_prehashed;
     use crate::halo2;
     use crate::integer;
     use crate::maingate;
     use ecc::integer::Range;
     use ecc::maingate::big_to_fe;
+    use ecc::maingate::decompose_big;
     use ecc::maingate::fe_to_big;
     use ecc::maingate::RegionCtx;
     use ecc::{EccConfig, GeneralEccChip};
     use halo2::arithmetic::CurveAffine;
     use halo2::circuit::{Layouter, SimpleFloorPlanner, Value};
+    use halo2::dev::MockProver;
     use halo2::halo2curves::{
         ff::{Field, FromUniformBytes, PrimeField},
         group::{Curve, Group},
     };
     use halo2::plonk::{Circuit, ConstraintSystem, Error};
     use integer::IntegerInstructions;
-    use maingate::mock_prover_verify;
+    use maingate::MainGateInstructions;
+    use maingate::{mock_prover_verify, DimensionMeasurement};
     use maingate::{MainGate, MainGateConfig, RangeChip, RangeConfig, RangeInstructions};
+    use poseidon::{Poseidon, Spec};
     use rand_core::OsRng;
     use std::marker::PhantomData;
+    use transcript::HasherChip;
 
     const BIT_LEN_LIMB: usize = 68;
     const NUMBER_OF_LIMBS: usize = 4;
@@ -356,4 +561,359 @@ mod tests {
         run::<Secp256k1, PastaFp>();
         run::<Secp256k1, PastaFq>();
     }
+
+    #[derive(Default, Clone)]
+    struct TestCircuitEcdsaBatchVerify<E: CurveAffine, N: PrimeField> {
+        public_keys: Vec<Value<E>>,
+        signatures: Vec<Value<(E::Scalar, E::Scalar)>>,
+        msg_hashes: Vec<Value<E::Scalar>>,
+        // verify with `verify_batch` rather than one `verify` per signature
+        batch: bool,
+
+        aux_generator: E,
+        window_size: usize,
+        _marker: PhantomData<N>,
+    }
+
+    impl<E: CurveAffine, N: FromUniformBytes<64>> Circuit<N> for TestCircuitEcdsaBatchVerify<E, N> {
+        type Config = TestCircuitEcdsaVerifyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            let n = self.public_keys.len();
+            Self {
+                public_keys: vec![Value::unknown(); n],
+                signatures: vec![Value::unknown(); n],
+                msg_hashes: vec![Value::unknown(); n],
+                batch: self.batch,
+                aux_generator: self.aux_generator,
+                window_size: self.window_size,
+                _marker: PhantomData,
+            }
+        }
+
+        fn configure(meta: &mut ConstraintSystem<N>) -> Self::Config {
+            TestCircuitEcdsaVerifyConfig::new::<E, N>(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<N>,
+        ) -> Result<(), Error> {
+            let n = self.public_keys.len();
+            let mut ecc_chip = GeneralEccChip::<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+                config.ecc_chip_config(),
+            );
+
+            layouter.assign_region(
+                || "assign aux values",
+                |region| {
+                    let offset = 0;
+                    let ctx = &mut RegionCtx::new(region, offset);
+
+                    let number_of_pairs = if self.batch { 2 * n } else { 2 };
+                    ecc_chip.assign_aux_generator(ctx, Value::known(self.aux_generator))?;
+                    ecc_chip.assign_aux(ctx, self.window_size, number_of_pairs)?;
+                    Ok(())
+                },
+            )?;
+
+            let ecdsa_chip = EcdsaChip::new(ecc_chip.clone());
+            let scalar_chip = ecc_chip.scalar_field_chip();
+            let spec = Spec::<N, 3, 2>::new(8, 57);
+
+            layouter.assign_region(
+                || "region 0",
+                |region| {
+                    let offset = 0;
+                    let ctx = &mut RegionCtx::new(region, offset);
+
+                    let mut sigs = vec![];
+                    let mut pks = vec![];
+                    let mut msg_hashes = vec![];
+                    for i in 0..n {
+                        let r = self.signatures[i].map(|signature| signature.0);
+                        let s = self.signatures[i].map(|signature| signature.1);
+                        let integer_r = ecc_chip.new_unassigned_scalar(r);
+                        let integer_s = ecc_chip.new_unassigned_scalar(s);
+                        let msg_hash = ecc_chip.new_unassigned_scalar(self.msg_hashes[i]);
+
+                        let r_assigned =
+                            scalar_chip.assign_integer(ctx, integer_r, Range::Remainder)?;
+                        let s_assigned =
+                            scalar_chip.assign_integer(ctx, integer_s, Range::Remainder)?;
+                        sigs.push(AssignedEcdsaSig {
+                            r: r_assigned,
+                            s: s_assigned,
+                        });
+
+                        let pk_in_circuit = ecc_chip.assign_point(ctx, self.public_keys[i])?;
+                        pks.push(AssignedPublicKey {
+                            point: pk_in_circuit,
+                        });
+                        msg_hashes.push(scalar_chip.assign_integer(
+                            ctx,
+                            msg_hash,
+                            Range::Remainder,
+                        )?);
+                    }
+
+                    if self.batch {
+                        ecdsa_chip.verify_batch(ctx, &spec, &sigs, &pks, &msg_hashes)
+                    } else {
+                        for ((sig, pk), msg_hash) in
+                            sigs.iter().zip(pks.iter()).zip(msg_hashes.iter())
+                        {
+                            ecdsa_chip.verify(ctx, sig, pk, msg_hash)?;
+                        }
+                        Ok(())
+                    }
+                },
+            )?;
+
+            config.config_range(&mut layouter)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_batch_verifier() {
+        fn run<C: CurveAffine, N: FromUniformBytes<64> + Ord>(n: usize) {
+            let g = C::generator();
+
+            let mut public_keys = vec![];
+            let mut signatures = vec![];
+            let mut msg_hashes = vec![];
+            for _ in 0..n {
+                let sk = <C as CurveAffine>::ScalarExt::random(OsRng);
+                let msg_hash = <C as CurveAffine>::ScalarExt::random(OsRng);
+                let k = <C as CurveAffine>::ScalarExt::random(OsRng);
+                public_keys.push(Value::known((g * sk).to_affine()));
+                signatures.push(Value::known(sign_prehashed::<C>(sk, msg_hash, k)));
+                msg_hashes.push(Value::known(msg_hash));
+            }
+
+            let aux_generator = C::CurveExt::random(OsRng).to_affine();
+            let circuit = TestCircuitEcdsaBatchVerify::<C, N> {
+                public_keys,
+                signatures,
+                msg_hashes,
+                batch: true,
+                aux_generator,
+                window_size: 4,
+                ..Default::default()
+            };
+            mock_prover_verify(&circuit, vec![vec![]]);
+
+            // Compare against one `verify` per signature
+            {
+                let individual = TestCircuitEcdsaBatchVerify::<C, N> {
+                    batch: false,
+                    ..circuit.clone()
+                };
+                mock_prover_verify(&individual, vec![vec![]]);
+
+                let individual_rows = *DimensionMeasurement::measure(&individual)
+                    .unwrap()
+                    .advice_range()
+                    .end();
+                let batch_rows = *DimensionMeasurement::measure(&circuit)
+                    .unwrap()
+                    .advice_range()
+                    .end();
+                println!(
+                    "{} signatures: {} rows with verify, {} rows with verify_batch",
+                    n, individual_rows, batch_rows
+                );
+                // a single signature pays for the transcript and R without any
+                // shared doublings
+                if n > 1 {
+                    assert!(batch_rows < individual_rows);
+                }
+            }
+
+            // Error case: the last signature is over another message
+            {
+                let mut circuit = circuit.clone();
+                circuit.msg_hashes[n - 1] =
+                    Value::known(<C as CurveAffine>::ScalarExt::random(OsRng));
+                let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+                let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+        }
+
+        use crate::curves::bn256::Fr as BnScalar;
+        use crate::curves::secp256k1::Secp256k1Affine as Secp256k1;
+        use crate::curves::secp256r1::Secp256r1Affine as P256;
+        run::<Secp256k1, BnScalar>(1);
+        run::<Secp256k1, BnScalar>(3);
+        run::<P256, BnScalar>(2);
+    }
+
+    #[derive(Default, Clone)]
+    struct TestCircuitEcdsaRecover<E: CurveAffine, N: PrimeField> {
+        signature: Value<(E::Scalar, E::Scalar)>,
+        v: Value<bool>,
+        msg_hash: Value<E::Scalar>,
+        // Poseidon hash of the limbs of the issuer key
+        issuer_id: Value<N>,
+
+        aux_generator: E,
+        window_size: usize,
+        _marker: PhantomData<N>,
+    }
+
+    impl<E: CurveAffine, N: FromUniformBytes<64>> Circuit<N> for TestCircuitEcdsaRecover<E, N> {
+        type Config = TestCircuitEcdsaVerifyConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<N>) -> Self::Config {
+            TestCircuitEcdsaVerifyConfig::new::<E, N>(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<N>,
+        ) -> Result<(), Error> {
+            let mut ecc_chip = GeneralEccChip::<E, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+                config.ecc_chip_config(),
+            );
+
+            layouter.assign_region(
+                || "assign aux values",
+                |region| {
+                    let offset = 0;
+                    let ctx = &mut RegionCtx::new(region, offset);
+
+                    ecc_chip.assign_aux_generator(ctx, Value::known(self.aux_generator))?;
+                    ecc_chip.assign_aux(ctx, self.window_size, 2)?;
+                    Ok(())
+                },
+            )?;
+
+            let ecdsa_chip = EcdsaChip::new(ecc_chip.clone());
+            let scalar_chip = ecc_chip.scalar_field_chip();
+            let main_gate = ecc_chip.main_gate();
+            let spec = Spec::<N, 3, 2>::new(8, 57);
+
+            layouter.assign_region(
+                || "region 0",
+                |region| {
+                    let offset = 0;
+                    let ctx = &mut RegionCtx::new(region, offset);
+
+                    let r = self.signature.map(|signature| signature.0);
+                    let s = self.signature.map(|signature| signature.1);
+                    let integer_r = ecc_chip.new_unassigned_scalar(r);
+                    let integer_s = ecc_chip.new_unassigned_scalar(s);
+                    let msg_hash = ecc_chip.new_unassigned_scalar(self.msg_hash);
+
+                    let r_assigned =
+                        scalar_chip.assign_integer(ctx, integer_r, Range::Remainder)?;
+                    let s_assigned =
+                        scalar_chip.assign_integer(ctx, integer_s, Range::Remainder)?;
+                    let sig = AssignedEcdsaSig {
+                        r: r_assigned,
+                        s: s_assigned,
+                    };
+                    let v = main_gate.assign_bit(ctx, self.v.map(|v| N::from(v as u64)))?;
+                    let msg_hash = scalar_chip.assign_integer(ctx, msg_hash, Range::Remainder)?;
+
+                    let pk = ecdsa_chip.recover(ctx, &sig, &v, &msg_hash)?;
+
+                    // the recovered key must hash to the issuer identifier
+                    let mut hasher = HasherChip::<N, NUMBER_OF_LIMBS, BIT_LEN_LIMB, 3, 2>::new(
+                        ctx,
+                        &spec,
+                        &config.main_gate_config,
+                    )?;
+                    for coordinate in [pk.point.x(), pk.point.y()] {
+                        let limbs: Vec<_> =
+                            coordinate.limbs().iter().map(|limb| limb.into()).collect();
+                        hasher.update(&limbs);
+                    }
+                    let issuer_id = hasher.hash(ctx)?;
+                    let expected = main_gate.assign_value(ctx, self.issuer_id)?;
+                    main_gate.assert_equal(ctx, &issuer_id, &expected)
+                },
+            )?;
+
+            config.config_range(&mut layouter)?;
+
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_ecdsa_recover() {
+        fn run<C: CurveAffine, N: FromUniformBytes<64> + Ord>() {
+            let g = C::generator();
+
+            let sk = <C as CurveAffine>::ScalarExt::random(OsRng);
+            let public_key = (g * sk).to_affine();
+            let msg_hash = <C as CurveAffine>::ScalarExt::random(OsRng);
+            let k = <C as CurveAffine>::ScalarExt::random(OsRng);
+            let signature = sign_prehashed::<C>(sk, msg_hash, k);
+            let v = bool::from((g * k).to_affine().coordinates().unwrap().y().is_odd());
+
+            let issuer_id = {
+                let coordinates = public_key.coordinates().unwrap();
+                let mut hasher = Poseidon::<N, 3, 2>::new(8, 57);
+                for coordinate in [coordinates.x(), coordinates.y()] {
+                    let limbs =
+                        decompose_big::<N>(fe_to_big(*coordinate), NUMBER_OF_LIMBS, BIT_LEN_LIMB);
+                    hasher.update(&limbs);
+                }
+                hasher.squeeze()
+            };
+
+            let aux_generator = C::CurveExt::random(OsRng).to_affine();
+            let circuit = TestCircuitEcdsaRecover::<C, N> {
+                signature: Value::known(signature),
+                v: Value::known(v),
+                msg_hash: Value::known(msg_hash),
+                issuer_id: Value::known(issuer_id),
+                aux_generator,
+                window_size: 4,
+                ..Default::default()
+            };
+            mock_prover_verify(&circuit, vec![vec![]]);
+
+            // Error case: the other parity recovers another key
+            {
+                let circuit = TestCircuitEcdsaRecover::<C, N> {
+                    v: Value::known(!v),
+                    ..circuit.clone()
+                };
+                let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+                let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+
+            // Error case: the signature is over another message
+            {
+                let circuit = TestCircuitEcdsaRecover::<C, N> {
+                    msg_hash: Value::known(msg_hash + <C as CurveAffine>::ScalarExt::ONE),
+                    ..circuit.clone()
+                };
+                let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+                let prover = MockProver::run(k, &circuit, vec![vec![]]).unwrap();
+                assert!(prover.verify().is_err());
+            }
+        }
+
+        use crate::curves::bn256::Fr as BnScalar;
+        use crate::curves::secp256k1::Secp256k1Affine as Secp256k1;
+        use crate::curves::secp256r1::Secp256r1Affine as P256;
+        run::<Secp256k1, BnScalar>();
+        run::<P256, BnScalar>();
+    }
 }
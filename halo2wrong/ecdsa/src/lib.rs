@@ -1,3 +1,4 @@
+pub mod credential;
 pub mod ecdsa;
 
 pub(crate) use ecc::halo2;
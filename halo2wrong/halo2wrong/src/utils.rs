@@ -87,7 +87,7 @@ pub struct Dimension {
 }
 
 impl Dimension {
-    fn k(&self) -> u32 {
+    pub fn k(&self) -> u32 {
         u64::BITS
             - ([self.instance, self.advice, self.fixed]
                 .into_iter()
@@ -99,7 +99,7 @@ impl Dimension {
             - 1
     }
 
-    fn advice_range(&self) -> RangeInclusive<usize> {
+    pub fn advice_range(&self) -> RangeInclusive<usize> {
         0..=self.advice as usize
     }
 }
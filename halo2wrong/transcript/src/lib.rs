@@ -1,10 +1,12 @@
 mod hasher;
+mod native;
 mod transcript;
 
 pub use ecc;
 pub use ecc::halo2;
 pub use ecc::maingate;
 pub use hasher::HasherChip;
+pub use native::{PoseidonChallenge, PoseidonTranscript, R_F, R_P};
 
 pub use crate::transcript::*;
 
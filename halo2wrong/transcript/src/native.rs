@@ -0,0 +1,548 @@
+use crate::{
+    halo2::{
+        arithmetic::CurveAffine,
+        halo2curves::{
+            ff::{FromUniformBytes, PrimeField},
+            group::GroupEncoding,
+        },
+        transcript::{
+            EncodedChallenge, Transcript, TranscriptRead, TranscriptReadBuffer, TranscriptWrite,
+            TranscriptWriterBuffer,
+        },
+    },
+    transcript::PointRepresentation,
+};
+use poseidon::Poseidon;
+use std::{
+    io::{self, Read, Write},
+    marker::PhantomData,
+};
+
+/// Number of full rounds used by `TranscriptReadBuffer::init` and
+/// `TranscriptWriterBuffer::init`
+pub const R_F: usize = 8;
+/// Number of partial rounds used by `TranscriptReadBuffer::init` and
+/// `TranscriptWriterBuffer::init`
+pub const R_P: usize = 57;
+
+/// `PoseidonChallenge` is the squeezed state word itself, so the in-circuit
+/// verifier gets the same scalar without any bit decomposition
+#[derive(Clone, Copy, Debug)]
+pub struct PoseidonChallenge<C: CurveAffine>(C::Scalar);
+
+impl<C: CurveAffine> EncodedChallenge<C> for PoseidonChallenge<C> {
+    type Input = C::Scalar;
+
+    fn new(challenge_input: &C::Scalar) -> Self {
+        PoseidonChallenge(*challenge_input)
+    }
+
+    fn get_scalar(&self) -> C::Scalar {
+        self.0
+    }
+}
+
+/// `PoseidonTranscript` is the native counterpart of `TranscriptChip`.
+/// Proof elements are read from or written to `stream` in their canonical
+/// byte encoding, while the sponge absorbs points through the same
+/// `PointRepresentation` and squeezes exactly as `TranscriptChip` does.
+pub struct PoseidonTranscript<
+    S,
+    C: CurveAffine<ScalarExt = N>,
+    N: FromUniformBytes<64>,
+    E: PointRepresentation<C, N, NUMBER_OF_LIMBS, BIT_LEN>,
+    const NUMBER_OF_LIMBS: usize,
+    const BIT_LEN: usize,
+    const T: usize,
+    const RATE: usize,
+> {
+    stream: S,
+    hasher: Poseidon<N, T, RATE>,
+    _marker: PhantomData<(C, E)>,
+}
+
+impl<
+        S,
+        C: CurveAffine<ScalarExt = N>,
+        N: FromUniformBytes<64>,
+        E: PointRepresentation<C, N, NUMBER_OF_LIMBS, BIT_LEN>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN: usize,
+        const T: usize,
+        const RATE: usize,
+    > PoseidonTranscript<S, C, N, E, NUMBER_OF_LIMBS, BIT_LEN, T, RATE>
+{
+    /// Constructs the transcript over `stream`. Round numbers must match the
+    /// `Spec` given to `TranscriptChip`
+    pub fn new(stream: S, r_f: usize, r_p: usize) -> Self {
+        Self {
+            stream,
+            hasher: Poseidon::new(r_f, r_p),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the underlying stream
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<
+        S,
+        C: CurveAffine<ScalarExt = N>,
+        N: FromUniformBytes<64>,
+        E: PointRepresentation<C, N, NUMBER_OF_LIMBS, BIT_LEN>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN: usize,
+        const T: usize,
+        const RATE: usize,
+    > Transcript<C, PoseidonChallenge<C>>
+    for PoseidonTranscript<S, C, N, E, NUMBER_OF_LIMBS, BIT_LEN, T, RATE>
+{
+    fn squeeze_challenge(&mut self) -> PoseidonChallenge<C> {
+        PoseidonChallenge::new(&self.hasher.squeeze())
+    }
+
+    fn common_point(&mut self, point: C) -> io::Result<()> {
+        // `TranscriptChip` cannot represent the identity either
+        let encoded = E::encode(point).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "cannot write points at infinity to the transcript",
+            )
+        })?;
+        self.hasher.update(&encoded[..]);
+        Ok(())
+    }
+
+    fn common_scalar(&mut self, scalar: N) -> io::Result<()> {
+        self.hasher.update(&[scalar]);
+        Ok(())
+    }
+}
+
+impl<
+        R: Read,
+        C: CurveAffine<ScalarExt = N>,
+        N: FromUniformBytes<64>,
+        E: PointRepresentation<C, N, NUMBER_OF_LIMBS, BIT_LEN>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN: usize,
+        const T: usize,
+        const RATE: usize,
+    > TranscriptRead<C, PoseidonChallenge<C>>
+    for PoseidonTranscript<R, C, N, E, NUMBER_OF_LIMBS, BIT_LEN, T, RATE>
+{
+    fn read_point(&mut self) -> io::Result<C> {
+        let mut compressed = C::Repr::default();
+        self.stream.read_exact(compressed.as_mut())?;
+        let point: C = Option::from(C::from_bytes(&compressed)).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+        })?;
+        self.common_point(point)?;
+        Ok(point)
+    }
+
+    fn read_scalar(&mut self) -> io::Result<N> {
+        let mut data = N::Repr::default();
+        self.stream.read_exact(data.as_mut())?;
+        let scalar: N = Option::from(N::from_repr(data)).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Other,
+                "invalid field element encoding in proof",
+            )
+        })?;
+        self.common_scalar(scalar)?;
+        Ok(scalar)
+    }
+}
+
+impl<
+        R: Read,
+        C: CurveAffine<ScalarExt = N>,
+        N: FromUniformBytes<64>,
+        E: PointRepresentation<C, N, NUMBER_OF_LIMBS, BIT_LEN>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN: usize,
+        const T: usize,
+        const RATE: usize,
+    > TranscriptReadBuffer<R, C, PoseidonChallenge<C>>
+    for PoseidonTranscript<R, C, N, E, NUMBER_OF_LIMBS, BIT_LEN, T, RATE>
+{
+    fn init(reader: R) -> Self {
+        Self::new(reader, R_F, R_P)
+    }
+}
+
+impl<
+        W: Write,
+        C: CurveAffine<ScalarExt = N>,
+        N: FromUniformBytes<64>,
+        E: PointRepresentation<C, N, NUMBER_OF_LIMBS, BIT_LEN>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN: usize,
+        const T: usize,
+        const RATE: usize,
+    > TranscriptWrite<C, PoseidonChallenge<C>>
+    for PoseidonTranscript<W, C, N, E, NUMBER_OF_LIMBS, BIT_LEN, T, RATE>
+{
+    fn write_point(&mut self, point: C) -> io::Result<()> {
+        self.common_point(point)?;
+        let compressed = point.to_bytes();
+        self.stream.write_all(compressed.as_ref())
+    }
+
+    fn write_scalar(&mut self, scalar: N) -> io::Result<()> {
+        self.common_scalar(scalar)?;
+        let data = scalar.to_repr();
+        self.stream.write_all(data.as_ref())
+    }
+}
+
+impl<
+        W: Write,
+        C: CurveAffine<ScalarExt = N>,
+        N: FromUniformBytes<64>,
+        E: PointRepresentation<C, N, NUMBER_OF_LIMBS, BIT_LEN>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN: usize,
+        const T: usize,
+        const RATE: usize,
+    > TranscriptWriterBuffer<W, C, PoseidonChallenge<C>>
+    for PoseidonTranscript<W, C, N, E, NUMBER_OF_LIMBS, BIT_LEN, T, RATE>
+{
+    fn init(writer: W) -> Self {
+        Self::new(writer, R_F, R_P)
+    }
+
+    fn finalize(self) -> W {
+        self.stream
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{PoseidonTranscript, R_F, R_P};
+    use crate::halo2::arithmetic::CurveAffine;
+    use crate::halo2::circuit::{Layouter, SimpleFloorPlanner, Value};
+    use crate::halo2::dev::MockProver;
+    use crate::halo2::halo2curves::bn256::{Bn256, Fr, G1Affine, G1};
+    use crate::halo2::halo2curves::ff::{Field, PrimeField};
+    use crate::halo2::halo2curves::group::{Curve, Group};
+    use crate::halo2::plonk::{
+        create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, ConstraintSystem, Error,
+    };
+    use crate::halo2::poly::commitment::ParamsProver;
+    use crate::halo2::poly::kzg::{
+        commitment::{KZGCommitmentScheme, ParamsKZG},
+        multiopen::{ProverSHPLONK, VerifierSHPLONK},
+        strategy::SingleStrategy,
+    };
+    use crate::halo2::transcript::{EncodedChallenge, Transcript, TranscriptRead, TranscriptWrite};
+    use crate::maingate::{
+        mock_prover_verify, DimensionMeasurement, MainGate, MainGateConfig, MainGateInstructions,
+        RegionCtx,
+    };
+    use crate::transcript::{LimbRepresentation, NativeRepresentation, PointRepresentation};
+    use crate::TranscriptChip;
+    use ecc::integer::rns::Rns;
+    use ecc::maingate::{RangeChip, RangeConfig, RangeInstructions};
+    use ecc::{BaseFieldEccChip, EccConfig};
+    use poseidon::Spec;
+    use rand_core::OsRng;
+
+    const NUMBER_OF_LIMBS: usize = 4;
+    const BIT_LEN_LIMB: usize = 68;
+
+    type NativeTranscript<S, E> =
+        PoseidonTranscript<S, G1Affine, Fr, E, NUMBER_OF_LIMBS, BIT_LEN_LIMB, 3, 2>;
+
+    /// Points and scalars written before each squeeze
+    type Rounds<C> = Vec<(Vec<C>, Vec<<C as CurveAffine>::ScalarExt>)>;
+
+    #[derive(Clone)]
+    struct TestCircuitConfig {
+        main_gate_config: MainGateConfig,
+        range_config: RangeConfig,
+    }
+
+    impl TestCircuitConfig {
+        fn ecc_chip_config(&self) -> EccConfig {
+            EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+        }
+
+        fn new<C: CurveAffine>(meta: &mut ConstraintSystem<C::Scalar>) -> Self {
+            let rns = Rns::<C::Base, C::Scalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct();
+
+            let main_gate_config = MainGate::<C::Scalar>::configure(meta);
+            let overflow_bit_lens = rns.overflow_lengths();
+            let composition_bit_lens = vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS];
+
+            let range_config = RangeChip::<C::Scalar>::configure(
+                meta,
+                &main_gate_config,
+                composition_bit_lens,
+                overflow_bit_lens,
+            );
+            TestCircuitConfig {
+                main_gate_config,
+                range_config,
+            }
+        }
+
+        fn config_range<N: PrimeField>(
+            &self,
+            layouter: &mut impl Layouter<N>,
+        ) -> Result<(), Error> {
+            let range_chip = RangeChip::<N>::new(self.range_config.clone());
+            range_chip.load_table(layouter)?;
+
+            Ok(())
+        }
+    }
+
+    struct TestCircuitNativeTranscript<
+        C: CurveAffine,
+        E: PointRepresentation<C, C::Scalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        const T: usize,
+        const RATE: usize,
+    > {
+        spec: Spec<C::Scalar, T, RATE>,
+        rounds: Rounds<C>,
+        challenges: Vec<C::Scalar>,
+        _point_repr: E,
+    }
+
+    impl<
+            C: CurveAffine,
+            E: PointRepresentation<C, C::Scalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+            const T: usize,
+            const RATE: usize,
+        > Circuit<C::Scalar> for TestCircuitNativeTranscript<C, E, T, RATE>
+    {
+        type Config = TestCircuitConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            unimplemented!();
+        }
+
+        fn configure(meta: &mut ConstraintSystem<C::Scalar>) -> Self::Config {
+            TestCircuitConfig::new::<C>(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<C::Scalar>,
+        ) -> Result<(), Error> {
+            let main_gate = MainGate::<C::Scalar>::new(config.main_gate_config.clone());
+            let ecc_chip =
+                BaseFieldEccChip::<C, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(config.ecc_chip_config());
+
+            layouter.assign_region(
+                || "region 0",
+                |region| {
+                    let offset = 0;
+                    let ctx = &mut RegionCtx::new(region, offset);
+
+                    let mut transcript_chip =
+                        TranscriptChip::<_, _, E, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>::new(
+                            ctx,
+                            &self.spec,
+                            ecc_chip.clone(),
+                            E::default(),
+                        )?;
+
+                    for ((points, scalars), expected) in
+                        self.rounds.iter().zip(self.challenges.iter())
+                    {
+                        for point in points.iter() {
+                            let point = ecc_chip.assign_point(ctx, Value::known(*point))?;
+                            transcript_chip.write_point(ctx, &point)?;
+                        }
+                        for scalar in scalars.iter() {
+                            let scalar = main_gate.assign_value(ctx, Value::known(*scalar))?;
+                            transcript_chip.write_scalar(&scalar);
+                        }
+                        let challenge = transcript_chip.squeeze(ctx)?;
+                        let expected = main_gate.assign_value(ctx, Value::known(*expected))?;
+                        main_gate.assert_equal(ctx, &challenge, &expected)?;
+                    }
+
+                    Ok(())
+                },
+            )?;
+
+            config.config_range(&mut layouter)?;
+
+            Ok(())
+        }
+    }
+
+    fn random_rounds() -> Rounds<G1Affine> {
+        let point = || G1::random(OsRng).to_affine();
+        let scalar = || Fr::random(OsRng);
+        vec![
+            (vec![point(), point()], vec![scalar()]),
+            (vec![], vec![scalar(), scalar(), scalar()]),
+            (vec![point()], vec![]),
+        ]
+    }
+
+    /// Writes the rounds to a native transcript, reads them back and returns
+    /// the challenges, which must agree between the prover and the verifier
+    fn native_challenges<E: PointRepresentation<G1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>>(
+        rounds: &Rounds<G1Affine>,
+    ) -> Vec<Fr> {
+        let mut writer = NativeTranscript::<_, E>::new(vec![], R_F, R_P);
+        let challenges: Vec<Fr> = rounds
+            .iter()
+            .map(|(points, scalars)| {
+                for point in points.iter() {
+                    writer.write_point(*point).unwrap();
+                }
+                for scalar in scalars.iter() {
+                    writer.write_scalar(*scalar).unwrap();
+                }
+                writer.squeeze_challenge().get_scalar()
+            })
+            .collect();
+        let proof = writer.into_inner();
+
+        let mut reader = NativeTranscript::<_, E>::new(proof.as_slice(), R_F, R_P);
+        for ((points, scalars), challenge) in rounds.iter().zip(challenges.iter()) {
+            for point in points.iter() {
+                assert_eq!(reader.read_point().unwrap(), *point);
+            }
+            for scalar in scalars.iter() {
+                assert_eq!(reader.read_scalar().unwrap(), *scalar);
+            }
+            assert_eq!(reader.squeeze_challenge().get_scalar(), *challenge);
+        }
+
+        challenges
+    }
+
+    fn run<E: PointRepresentation<G1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>>(
+        rounds: &Rounds<G1Affine>,
+        challenges: Vec<Fr>,
+    ) -> Result<(), Vec<crate::halo2::dev::VerifyFailure>> {
+        let circuit = TestCircuitNativeTranscript::<G1Affine, E, 3, 2> {
+            spec: Spec::new(R_F, R_P),
+            rounds: rounds.clone(),
+            challenges,
+            _point_repr: E::default(),
+        };
+        let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+        MockProver::run(k, &circuit, vec![vec![]]).unwrap().verify()
+    }
+
+    #[test]
+    fn test_native_transcript_matches_chip() {
+        let rounds = random_rounds();
+
+        let limb_challenges = native_challenges::<LimbRepresentation>(&rounds);
+        let native_challenges = native_challenges::<NativeRepresentation>(&rounds);
+        assert_ne!(limb_challenges, native_challenges);
+
+        assert!(run::<LimbRepresentation>(&rounds, limb_challenges.clone()).is_ok());
+        assert!(run::<NativeRepresentation>(&rounds, native_challenges.clone()).is_ok());
+
+        // Error case: challenges of the other point representation
+        assert!(run::<LimbRepresentation>(&rounds, native_challenges).is_err());
+        assert!(run::<NativeRepresentation>(&rounds, limb_challenges).is_err());
+    }
+
+    #[test]
+    fn test_native_transcript_rejects_identity() {
+        let mut transcript = NativeTranscript::<_, LimbRepresentation>::new(vec![], R_F, R_P);
+        assert!(transcript.write_point(G1::identity().to_affine()).is_err());
+    }
+
+    #[derive(Clone, Default)]
+    struct MulCircuit<F: PrimeField> {
+        a: Value<F>,
+        b: Value<F>,
+    }
+
+    impl<F: PrimeField> Circuit<F> for MulCircuit<F> {
+        type Config = MainGateConfig;
+        type FloorPlanner = SimpleFloorPlanner;
+
+        fn without_witnesses(&self) -> Self {
+            Self::default()
+        }
+
+        fn configure(meta: &mut ConstraintSystem<F>) -> Self::Config {
+            MainGate::<F>::configure(meta)
+        }
+
+        fn synthesize(
+            &self,
+            config: Self::Config,
+            mut layouter: impl Layouter<F>,
+        ) -> Result<(), Error> {
+            let main_gate = MainGate::<F>::new(config);
+            let c = layouter.assign_region(
+                || "region 0",
+                |region| {
+                    let ctx = &mut RegionCtx::new(region, 0);
+                    let a = main_gate.assign_value(ctx, self.a)?;
+                    let b = main_gate.assign_value(ctx, self.b)?;
+                    main_gate.mul(ctx, &a, &b)
+                },
+            )?;
+            main_gate.expose_public(layouter.namespace(|| "c"), c, 0)
+        }
+    }
+
+    fn prove_and_verify<E: PointRepresentation<G1Affine, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>>() {
+        let k = 5;
+        let (a, b) = (Fr::random(OsRng), Fr::random(OsRng));
+        let circuit = MulCircuit {
+            a: Value::known(a),
+            b: Value::known(b),
+        };
+        mock_prover_verify(&circuit, vec![vec![a * b]]);
+
+        let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+        let verifier_params = params.verifier_params();
+        let vk = keygen_vk(&params, &MulCircuit::default()).unwrap();
+        let pk = keygen_pk(&params, vk.clone(), &MulCircuit::default()).unwrap();
+
+        let mut transcript = NativeTranscript::<_, E>::new(vec![], R_F, R_P);
+        create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+            &params,
+            &pk,
+            &[circuit],
+            &[&[&[a * b]]],
+            OsRng,
+            &mut transcript,
+        )
+        .unwrap();
+        let proof = transcript.into_inner();
+
+        let verify = |c: Fr| {
+            let mut transcript = NativeTranscript::<_, E>::new(proof.as_slice(), R_F, R_P);
+            verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<Bn256>, _, _, _>(
+                verifier_params,
+                &vk,
+                SingleStrategy::new(verifier_params),
+                &[&[&[c]]],
+                &mut transcript,
+            )
+        };
+        assert!(verify(a * b).is_ok());
+
+        // Error case: wrong public input
+        assert!(verify(a * b + Fr::ONE).is_err());
+    }
+
+    #[test]
+    fn test_native_transcript_proof() {
+        prove_and_verify::<LimbRepresentation>();
+        prove_and_verify::<NativeRepresentation>();
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{
+    ecc::{integer::rns::Rns, Point},
+    halo2::{
+        arithmetic::CurveAffine,
+        halo2curves::{
+            bn256::{Bn256, G1Affine},
+            ff::Field,
+            pairing::Engine,
+        },
+        plonk::Error,
+        poly::kzg::commitment::ParamsKZG,
+    },
+    instructions::VerifierInstructions,
+    maingate::{big_to_fe, compose, fe_to_big, modulus},
+};
+use std::rc::Rc;
+
+#[cfg(test)]
+mod tests;
+
+/// `KzgAccumulator` defers the final pairing check of KZG openings. It is
+/// valid iff `e(lhs, [s]_2) = e(rhs, [1]_2)`. `P` is either a curve point or
+/// its assigned counterpart
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KzgAccumulator<P> {
+    pub lhs: P,
+    pub rhs: P,
+}
+
+impl<P> KzgAccumulator<P> {
+    /// Constructs the accumulator
+    pub fn new(lhs: P, rhs: P) -> Self {
+        Self { lhs, rhs }
+    }
+}
+
+impl<C: CurveAffine> KzgAccumulator<C> {
+    /// Returns limbs of `lhs` and then `rhs` coordinates, the layout
+    /// `BaseFieldEccChip::expose_public` writes to public inputs
+    pub fn public<const NUMBER_OF_LIMBS: usize, const BIT_LEN_LIMB: usize>(
+        &self,
+    ) -> Vec<C::Scalar> {
+        let rns = Rc::new(Rns::<C::Base, C::Scalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct());
+        let mut public = Point::new(Rc::clone(&rns), self.lhs).public();
+        public.extend(Point::new(rns, self.rhs).public());
+        public
+    }
+
+    /// Recovers the accumulator from the layout of `public`. Returns `None`
+    /// if limbs are out of range or do not encode points on the curve
+    pub fn from_public<const NUMBER_OF_LIMBS: usize, const BIT_LEN_LIMB: usize>(
+        public: &[C::Scalar],
+    ) -> Option<Self> {
+        if public.len() != 4 * NUMBER_OF_LIMBS {
+            return None;
+        }
+        let coordinate = |limbs: &[C::Scalar]| -> Option<C::Base> {
+            let limbs: Vec<_> = limbs.iter().map(|limb| fe_to_big(*limb)).collect();
+            if limbs.iter().any(|limb| limb.bits() > BIT_LEN_LIMB as u64) {
+                return None;
+            }
+            let value = compose(limbs, BIT_LEN_LIMB);
+            (value < modulus::<C::Base>()).then(|| big_to_fe(value))
+        };
+        let point = |limbs: &[C::Scalar]| -> Option<C> {
+            let x = coordinate(&limbs[..NUMBER_OF_LIMBS])?;
+            let y = coordinate(&limbs[NUMBER_OF_LIMBS..])?;
+            Option::from(C::from_xy(x, y))
+        };
+        Some(Self {
+            lhs: point(&public[..2 * NUMBER_OF_LIMBS])?,
+            rhs: point(&public[2 * NUMBER_OF_LIMBS..])?,
+        })
+    }
+}
+
+impl KzgAccumulator<G1Affine> {
+    /// The pairing check all folded openings are deferred to
+    pub fn decide(&self, params: &ParamsKZG<Bn256>) -> bool {
+        Bn256::pairing(&self.lhs, &params.s_g2()) == Bn256::pairing(&self.rhs, &params.g2())
+    }
+}
+
+/// Folds accumulators with a random linear combination
+/// `acc = \sum r^i acc_i`, where `r` is squeezed after absorbing all of them.
+/// Except with negligible probability the result is valid iff every
+/// accumulator is valid.
+pub fn fold_accumulators<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    accumulators: &[KzgAccumulator<L::Point>],
+) -> Result<KzgAccumulator<L::Point>, Error> {
+    assert!(!accumulators.is_empty());
+    for accumulator in accumulators.iter() {
+        loader.common_point(&accumulator.lhs)?;
+        loader.common_point(&accumulator.rhs)?;
+    }
+    let r = loader.squeeze_challenge()?;
+
+    let mut powers = vec![loader.assign_constant(C::Scalar::ONE)?];
+    for _ in 1..accumulators.len() {
+        let next = loader.mul(powers.last().unwrap(), &r)?;
+        powers.push(next);
+    }
+    let lhs = accumulators
+        .iter()
+        .zip(powers.iter())
+        .map(|(accumulator, power)| (accumulator.lhs.clone(), power.clone()))
+        .collect();
+    let rhs = accumulators
+        .iter()
+        .zip(powers.iter())
+        .map(|(accumulator, power)| (accumulator.rhs.clone(), power.clone()))
+        .collect();
+    Ok(KzgAccumulator::new(loader.msm(lhs)?, loader.msm(rhs)?))
+}
@@ -0,0 +1,538 @@
+use crate::curves::bn256::{Bn256, Fq, Fr, G1Affine, G1};
+use crate::curves::ff::Field;
+use crate::curves::group::{Curve, Group};
+use crate::ecc::integer::rns::Rns;
+use crate::ecc::BaseFieldEccChip;
+use crate::halo2::circuit::{Layouter, SimpleFloorPlanner, Value};
+use crate::halo2::dev::MockProver;
+use crate::halo2::plonk::{
+    create_proof, keygen_pk, keygen_vk, Circuit, ConstraintSystem, Error, ProvingKey, VerifyingKey,
+};
+use crate::halo2::poly::commitment::Params;
+use crate::halo2::poly::kzg::{
+    commitment::{KZGCommitmentScheme, ParamsKZG},
+    multiopen::ProverSHPLONK,
+};
+use crate::maingate::{DimensionMeasurement, MainGate, MainGateInstructions, RegionCtx};
+use crate::transcript::{LimbRepresentation, R_F, R_P};
+use crate::verifier::tests::{
+    native_accumulator, InnerCircuit, TestCircuitConfig, Transcript, Verifier, BIT_LEN_LIMB,
+    NUMBER_OF_LIMBS, WINDOW_SIZE,
+};
+use crate::{fold_accumulators, verify_proof, KzgAccumulator, VerifierChip};
+use poseidon::Spec;
+use rand_core::OsRng;
+
+type Chip<'a, 'b> =
+    VerifierChip<'a, 'b, G1Affine, LimbRepresentation, NUMBER_OF_LIMBS, BIT_LEN_LIMB, 3, 2>;
+
+fn prove<ConcreteCircuit: Circuit<Fr>>(
+    params: &ParamsKZG<Bn256>,
+    pk: &ProvingKey<G1Affine>,
+    circuit: ConcreteCircuit,
+    instances: &[Fr],
+) -> Vec<u8> {
+    let mut transcript = Transcript::new(vec![], R_F, R_P);
+    create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+        params,
+        pk,
+        &[circuit],
+        &[&[instances]],
+        OsRng,
+        &mut transcript,
+    )
+    .unwrap();
+    transcript.into_inner()
+}
+
+fn random_point() -> G1Affine {
+    G1::random(OsRng).to_affine()
+}
+
+fn configure(meta: &mut ConstraintSystem<Fr>) -> TestCircuitConfig {
+    let rns = Rns::<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct();
+    TestCircuitConfig::new(
+        meta,
+        vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS],
+        rns.overflow_lengths(),
+    )
+}
+
+/// Folds two accumulators given in public inputs and exposes the result
+/// after them
+struct FoldCircuit {
+    spec: Spec<Fr, 3, 2>,
+    accumulators: [Value<KzgAccumulator<G1Affine>>; 2],
+    aux_generator: Value<G1Affine>,
+}
+
+impl Circuit<Fr> for FoldCircuit {
+    type Config = TestCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            spec: self.spec.clone(),
+            accumulators: [Value::unknown(), Value::unknown()],
+            aux_generator: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
+        let ecc_chip = BaseFieldEccChip::<G1Affine, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+            config.ecc_chip_config(),
+        );
+
+        let (public, folded) = layouter.assign_region(
+            || "region 0",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                let public = self
+                    .accumulators
+                    .iter()
+                    .flat_map(|accumulator| {
+                        let limbs = accumulator.as_ref().map(|accumulator| {
+                            accumulator.public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>()
+                        });
+                        (0..4 * NUMBER_OF_LIMBS)
+                            .map(move |i| limbs.as_ref().map(|limbs| limbs[i]))
+                            .collect::<Vec<_>>()
+                    })
+                    .map(|limb| main_gate.assign_value(ctx, limb))
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                let mut verifier = Chip::new(
+                    ctx,
+                    &self.spec,
+                    ecc_chip.clone(),
+                    Value::known(&[][..]),
+                    self.aux_generator,
+                    WINDOW_SIZE,
+                )?;
+                let accumulators = self
+                    .accumulators
+                    .iter()
+                    .zip(public.chunks(4 * NUMBER_OF_LIMBS))
+                    .map(|(accumulator, public)| {
+                        verifier.assign_accumulator(accumulator.clone(), public)
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let folded = fold_accumulators(&mut verifier, &accumulators)?;
+                let lhs = ecc_chip.normalize(verifier.ctx(), &folded.lhs)?;
+                let rhs = ecc_chip.normalize(verifier.ctx(), &folded.rhs)?;
+                Ok((public, KzgAccumulator::new(lhs, rhs)))
+            },
+        )?;
+        config.config_range(&mut layouter)?;
+
+        for (offset, limb) in public.into_iter().enumerate() {
+            main_gate.expose_public(layouter.namespace(|| "accumulators"), limb, offset)?;
+        }
+        let offset = 8 * NUMBER_OF_LIMBS;
+        ecc_chip.expose_public(layouter.namespace(|| "lhs"), folded.lhs, offset)?;
+        ecc_chip.expose_public(
+            layouter.namespace(|| "rhs"),
+            folded.rhs,
+            offset + 2 * NUMBER_OF_LIMBS,
+        )?;
+        Ok(())
+    }
+}
+
+/// Verifies a proof of `InnerCircuit` or of a previous layer. Public inputs
+/// of every layer are the public input of `InnerCircuit` followed by the
+/// limbs of the accumulator. From the second layer on the accumulator of the
+/// verified proof is folded with the one in its public inputs.
+struct RecursionCircuit<'a> {
+    spec: Spec<Fr, 3, 2>,
+    vk: &'a VerifyingKey<G1Affine>,
+    g1: G1Affine,
+    proof: Value<Vec<u8>>,
+    instances: Vec<Value<Fr>>,
+    aux_generator: Value<G1Affine>,
+}
+
+impl<'a> RecursionCircuit<'a> {
+    fn new(
+        params: &ParamsKZG<Bn256>,
+        vk: &'a VerifyingKey<G1Affine>,
+        proof: Vec<u8>,
+        instances: &[Fr],
+    ) -> Self {
+        Self {
+            spec: Spec::new(R_F, R_P),
+            vk,
+            g1: params.get_g()[0],
+            proof: Value::known(proof),
+            instances: instances.iter().copied().map(Value::known).collect(),
+            aux_generator: Value::known(random_point()),
+        }
+    }
+}
+
+impl Circuit<Fr> for RecursionCircuit<'_> {
+    type Config = TestCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            spec: self.spec.clone(),
+            vk: self.vk,
+            g1: self.g1,
+            proof: Value::unknown(),
+            instances: vec![Value::unknown(); self.instances.len()],
+            aux_generator: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        configure(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
+        let ecc_chip = BaseFieldEccChip::<G1Affine, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+            config.ecc_chip_config(),
+        );
+
+        let (instance, accumulator) = layouter.assign_region(
+            || "region 0",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                let instances = self
+                    .instances
+                    .iter()
+                    .map(|instance| main_gate.assign_value(ctx, *instance))
+                    .collect::<Result<Vec<_>, Error>>()?;
+
+                let mut verifier = Chip::new(
+                    ctx,
+                    &self.spec,
+                    ecc_chip.clone(),
+                    self.proof.as_ref().map(|proof| proof.as_slice()),
+                    self.aux_generator,
+                    WINDOW_SIZE,
+                )?;
+                let mut accumulator =
+                    verify_proof(&mut verifier, self.vk, self.g1, &[instances.clone()])?;
+                if instances.len() > 1 {
+                    let previous = self.instances[1..]
+                        .iter()
+                        .copied()
+                        .collect::<Value<Vec<_>>>()
+                        .map(|limbs| {
+                            KzgAccumulator::from_public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>(&limbs)
+                                .expect("accumulator in public inputs")
+                        });
+                    let previous = verifier.assign_accumulator(previous, &instances[1..])?;
+                    accumulator = fold_accumulators(&mut verifier, &[accumulator, previous])?;
+                }
+                let lhs = ecc_chip.normalize(verifier.ctx(), &accumulator.lhs)?;
+                let rhs = ecc_chip.normalize(verifier.ctx(), &accumulator.rhs)?;
+                Ok((instances[0].clone(), KzgAccumulator::new(lhs, rhs)))
+            },
+        )?;
+        config.config_range(&mut layouter)?;
+
+        main_gate.expose_public(layouter.namespace(|| "instance"), instance, 0)?;
+        ecc_chip.expose_public(layouter.namespace(|| "lhs"), accumulator.lhs, 1)?;
+        ecc_chip.expose_public(
+            layouter.namespace(|| "rhs"),
+            accumulator.rhs,
+            1 + 2 * NUMBER_OF_LIMBS,
+        )?;
+        Ok(())
+    }
+}
+
+/// Exposes public inputs in the layout of a recursion layer without proving
+/// anything. Its proofs stand in for proofs of `RecursionCircuit`, so a second
+/// layer can run without proving the first one
+#[derive(Clone)]
+struct LayerStub {
+    instances: Vec<Value<Fr>>,
+}
+
+impl Circuit<Fr> for LayerStub {
+    type Config = TestCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            instances: vec![Value::unknown(); self.instances.len()],
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        TestCircuitConfig::new(meta, vec![8], vec![])
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
+        let instances = layouter.assign_region(
+            || "region 0",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                self.instances
+                    .iter()
+                    .map(|instance| main_gate.assign_value(ctx, *instance))
+                    .collect::<Result<Vec<_>, Error>>()
+            },
+        )?;
+        config.config_range(&mut layouter)?;
+
+        for (offset, instance) in instances.into_iter().enumerate() {
+            main_gate.expose_public(layouter.namespace(|| "instances"), instance, offset)?;
+        }
+        Ok(())
+    }
+}
+
+/// Native counterpart of `RecursionCircuit`
+fn layer_accumulator(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instances: &[Fr],
+) -> Result<KzgAccumulator<G1Affine>, Error> {
+    let mut verifier = Verifier::new(proof);
+    let accumulator = verify_proof(&mut verifier, vk, params.get_g()[0], &[instances.to_vec()])?;
+    if instances.len() == 1 {
+        return Ok(accumulator);
+    }
+    let previous = KzgAccumulator::from_public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>(&instances[1..])
+        .ok_or(Error::InvalidInstances)?;
+    fold_accumulators(&mut verifier, &[accumulator, previous])
+}
+
+#[test]
+fn test_accumulator_public() {
+    let accumulator = KzgAccumulator::new(random_point(), random_point());
+    let public = accumulator.public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>();
+    assert_eq!(public.len(), 4 * NUMBER_OF_LIMBS);
+    assert_eq!(
+        KzgAccumulator::<G1Affine>::from_public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>(&public),
+        Some(accumulator)
+    );
+
+    // Error case: limb out of range
+    let mut invalid = public.clone();
+    invalid[0] = Fr::from(2).pow([BIT_LEN_LIMB as u64]);
+    assert!(
+        KzgAccumulator::<G1Affine>::from_public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>(&invalid)
+            .is_none()
+    );
+
+    // Error case: not on curve
+    let mut invalid = public.clone();
+    invalid[NUMBER_OF_LIMBS] += Fr::ONE;
+    assert!(
+        KzgAccumulator::<G1Affine>::from_public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>(&invalid)
+            .is_none()
+    );
+
+    // Error case: wrong length
+    assert!(
+        KzgAccumulator::<G1Affine>::from_public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>(&public[1..])
+            .is_none()
+    );
+}
+
+#[test]
+fn test_fold_accumulators() {
+    let k = 10;
+    let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+    let vk = keygen_vk(&params, &InnerCircuit::default()).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &InnerCircuit::default()).unwrap();
+
+    let accumulators: Vec<_> = (0..2)
+        .map(|_| {
+            let (a, b) = (Fr::from(0xbeef), Fr::random(OsRng));
+            let inner = InnerCircuit {
+                a: Value::known(a),
+                b: Value::known(b),
+            };
+            let proof = prove(&params, &pk, inner, &[a * b]);
+            native_accumulator(&params, &vk, &proof, a * b).unwrap()
+        })
+        .collect();
+    let fold = |accumulators: &[KzgAccumulator<G1Affine>]| {
+        let mut verifier = Verifier::new(&[]);
+        fold_accumulators(&mut verifier, accumulators).unwrap()
+    };
+    let folded = fold(&accumulators);
+    assert!(folded.decide(&params));
+
+    // Error case: one of accumulators is invalid
+    {
+        let invalid = KzgAccumulator::new(accumulators[1].lhs, random_point());
+        assert!(!fold(&[accumulators[0].clone(), invalid]).decide(&params));
+    }
+
+    let public_inputs = |accumulators: &[KzgAccumulator<G1Affine>],
+                         folded: &KzgAccumulator<G1Affine>| {
+        let mut public_inputs: Vec<_> = accumulators
+            .iter()
+            .flat_map(|accumulator| accumulator.public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>())
+            .collect();
+        public_inputs.extend(folded.public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>());
+        vec![public_inputs]
+    };
+    let circuit = FoldCircuit {
+        spec: Spec::new(R_F, R_P),
+        accumulators: [
+            Value::known(accumulators[0].clone()),
+            Value::known(accumulators[1].clone()),
+        ],
+        aux_generator: Value::known(random_point()),
+    };
+    let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+    let prover = MockProver::run(k, &circuit, public_inputs(&accumulators, &folded)).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Error case: folding is not the native one
+    {
+        let reversed = [accumulators[1].clone(), accumulators[0].clone()];
+        let prover =
+            MockProver::run(k, &circuit, public_inputs(&accumulators, &fold(&reversed))).unwrap();
+        assert!(prover.verify().is_err());
+    }
+}
+
+/// Three layers of `RecursionCircuit` with `MockProver`. The first layer
+/// verifies an `InnerCircuit` proof. Proving a layer is what makes
+/// `test_recursion` slow, so each further layer verifies a `LayerStub` proof
+/// with the public inputs of the previous layer, which folds the accumulator
+/// in them
+#[test]
+fn test_recursion_mock() {
+    let params = ParamsKZG::<Bn256>::setup(10, OsRng);
+
+    let (a, b) = (Fr::from(0xbeef), Fr::random(OsRng));
+    let c = a * b;
+    let inner = InnerCircuit {
+        a: Value::known(a),
+        b: Value::known(b),
+    };
+    let vk = keygen_vk(&params, &InnerCircuit::default()).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &InnerCircuit::default()).unwrap();
+    let proof = prove(&params, &pk, inner, &[c]);
+
+    let mut layer = (vk, proof, vec![c]);
+    for depth in 1..=3 {
+        let (vk, proof, instances) = &layer;
+        let accumulator = layer_accumulator(&params, vk, proof, instances).unwrap();
+        assert!(accumulator.decide(&params));
+        let mut next_instances = vec![c];
+        next_instances.extend(accumulator.public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>());
+
+        let circuit = RecursionCircuit::new(&params, vk, proof.clone(), instances);
+        let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+        let prover = MockProver::run(k, &circuit, vec![next_instances.clone()]).unwrap();
+        assert_eq!(prover.verify(), Ok(()), "recursion layer {}", depth);
+
+        // Error case: the accumulator of the previous layer is not the one folded
+        if depth > 1 {
+            let mut tampered = vec![c];
+            tampered.extend(
+                KzgAccumulator::new(random_point(), random_point())
+                    .public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>(),
+            );
+            let circuit = RecursionCircuit::new(&params, vk, proof.clone(), &tampered);
+            let prover = MockProver::run(k, &circuit, vec![next_instances.clone()]).unwrap();
+            assert!(prover.verify().is_err());
+        }
+
+        // a stand-in proof with the public inputs of this layer
+        let stub = LayerStub {
+            instances: next_instances.iter().copied().map(Value::known).collect(),
+        };
+        let stub_vk = keygen_vk(&params, &stub).unwrap();
+        let stub_pk = keygen_pk(&params, stub_vk.clone(), &stub).unwrap();
+        let stub_proof = prove(&params, &stub_pk, stub, &next_instances);
+        layer = (stub_vk, stub_proof, next_instances);
+    }
+
+    // A single pairing check decides the whole chain
+    let (vk, proof, instances) = &layer;
+    let accumulator = layer_accumulator(&params, vk, proof, instances).unwrap();
+    assert!(accumulator.decide(&params));
+}
+
+/// `test_recursion_mock` with real proofs of every layer over params of
+/// k = 22, so it only runs with `cargo test --release -- --ignored`
+#[test]
+#[ignore = "proves three layers of in-circuit verifiers"]
+fn test_recursion() {
+    const K: u32 = 22;
+    let params = ParamsKZG::<Bn256>::setup(K, OsRng);
+    let downsized = |k: u32| {
+        assert!(k <= K);
+        let mut params = params.clone();
+        params.downsize(k);
+        params
+    };
+
+    let (a, b) = (Fr::from(0xbeef), Fr::random(OsRng));
+    let c = a * b;
+    let inner = InnerCircuit {
+        a: Value::known(a),
+        b: Value::known(b),
+    };
+    let inner_params = downsized(10);
+    let vk = keygen_vk(&inner_params, &InnerCircuit::default()).unwrap();
+    let pk = keygen_pk(&inner_params, vk.clone(), &InnerCircuit::default()).unwrap();
+    let proof = prove(&inner_params, &pk, inner, &[c]);
+
+    let mut layer = (inner_params, vk, proof, vec![c]);
+    for depth in 1..=3 {
+        let (params, vk, proof, instances) = &layer;
+        let accumulator = layer_accumulator(params, vk, proof, instances).unwrap();
+        assert!(accumulator.decide(params));
+
+        let circuit = RecursionCircuit::new(params, vk, proof.clone(), instances);
+        let mut next_instances = vec![c];
+        next_instances.extend(accumulator.public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>());
+        let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+        println!("recursion layer {} k = {}", depth, k);
+
+        let next_params = downsized(k);
+        let next_vk = keygen_vk(&next_params, &circuit).unwrap();
+        let next_pk = keygen_pk(&next_params, next_vk.clone(), &circuit).unwrap();
+        let next_proof = prove(&next_params, &next_pk, circuit, &next_instances);
+        layer = (next_params, next_vk, next_proof, next_instances);
+    }
+
+    // A single pairing check decides the whole chain
+    let (params, vk, proof, instances) = &layer;
+    let accumulator = layer_accumulator(params, vk, proof, instances).unwrap();
+    assert!(accumulator.decide(params));
+
+    // Error case: tampered accumulator in public inputs
+    {
+        let mut tampered = instances[..1].to_vec();
+        tampered.extend(
+            KzgAccumulator::new(random_point(), random_point())
+                .public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>(),
+        );
+        let accumulator = layer_accumulator(params, vk, proof, &tampered);
+        assert!(accumulator.map_or(true, |accumulator| !accumulator.decide(params)));
+    }
+}
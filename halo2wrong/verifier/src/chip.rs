@@ -0,0 +1,252 @@
+use crate::{
+    accumulator::KzgAccumulator,
+    ecc::{AssignedPoint, BaseFieldEccChip},
+    halo2::{
+        arithmetic::CurveAffine,
+        circuit::Value,
+        halo2curves::{
+            ff::{Field, PrimeField},
+            group::GroupEncoding,
+        },
+        plonk::Error,
+    },
+    instructions::VerifierInstructions,
+    maingate::{AssignedValue, MainGateInstructions, RegionCtx, Term},
+    transcript::{PointRepresentation, TranscriptChip},
+};
+use poseidon::Spec;
+use std::io::{self, Read};
+
+/// `VerifierChip` constrains `verify_proof` in a single region. Proof
+/// elements are witnessed from `proof` and absorbed by `TranscriptChip`;
+/// verifying key commitments are assigned as constants.
+pub struct VerifierChip<
+    'a,
+    'b,
+    C: CurveAffine,
+    E: PointRepresentation<C, C::ScalarExt, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    const NUMBER_OF_LIMBS: usize,
+    const BIT_LEN_LIMB: usize,
+    const T: usize,
+    const RATE: usize,
+> {
+    ctx: &'a mut RegionCtx<'b, C::Scalar>,
+    ecc_chip: BaseFieldEccChip<C, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    transcript_chip: TranscriptChip<C, C::ScalarExt, E, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>,
+    proof: Value<&'a [u8]>,
+    window_size: usize,
+}
+
+impl<
+        'a,
+        'b,
+        C: CurveAffine,
+        E: PointRepresentation<C, C::ScalarExt, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN_LIMB: usize,
+        const T: usize,
+        const RATE: usize,
+    > VerifierChip<'a, 'b, C, E, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>
+{
+    /// Constructs the verifier chip. `aux_generator` is the auxiliary point
+    /// of the final multi scalar multiplication, `window_size` its window
+    pub fn new(
+        ctx: &'a mut RegionCtx<'b, C::Scalar>,
+        spec: &Spec<C::Scalar, T, RATE>,
+        mut ecc_chip: BaseFieldEccChip<C, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        proof: Value<&'a [u8]>,
+        aux_generator: Value<C>,
+        window_size: usize,
+    ) -> Result<Self, Error> {
+        ecc_chip.assign_aux_generator(ctx, aux_generator)?;
+        let transcript_chip = TranscriptChip::new(ctx, spec, ecc_chip.clone(), E::default())?;
+        Ok(Self {
+            ctx,
+            ecc_chip,
+            transcript_chip,
+            proof,
+            window_size,
+        })
+    }
+
+    /// Returns the region context to continue assigning after verification
+    pub fn ctx(&mut self) -> &mut RegionCtx<'b, C::Scalar> {
+        self.ctx
+    }
+
+    /// Returns the underlying ecc chip
+    pub fn ecc_chip(&self) -> &BaseFieldEccChip<C, NUMBER_OF_LIMBS, BIT_LEN_LIMB> {
+        &self.ecc_chip
+    }
+
+    /// Assigns an accumulator carried in the public inputs of a previous
+    /// proof and constrains it to `public`, its limbs laid out as in
+    /// `KzgAccumulator::public`
+    pub fn assign_accumulator(
+        &mut self,
+        accumulator: Value<KzgAccumulator<C>>,
+        public: &[AssignedValue<C::Scalar>],
+    ) -> Result<
+        KzgAccumulator<AssignedPoint<C::Base, C::Scalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB>>,
+        Error,
+    > {
+        assert_eq!(public.len(), 4 * NUMBER_OF_LIMBS);
+        let lhs = self
+            .ecc_chip
+            .assign_point(self.ctx, accumulator.as_ref().map(|acc| acc.lhs))?;
+        let rhs = self
+            .ecc_chip
+            .assign_point(self.ctx, accumulator.as_ref().map(|acc| acc.rhs))?;
+        let limbs = [lhs.x(), lhs.y(), rhs.x(), rhs.y()]
+            .into_iter()
+            .flat_map(|integer| integer.limbs().iter());
+        let main_gate = self.ecc_chip.main_gate();
+        for (limb, public) in limbs.zip(public.iter()) {
+            main_gate.assert_equal(self.ctx, &limb.into(), public)?;
+        }
+        Ok(KzgAccumulator::new(lhs, rhs))
+    }
+
+    /// Decodes the next element of the proof stream with `decode`. A
+    /// malformed proof is reported only when the proof is known
+    fn read<V>(
+        &mut self,
+        decode: impl FnOnce(&mut &'a [u8]) -> io::Result<V>,
+    ) -> Result<Value<V>, Error> {
+        let mut error = None;
+        let value = self.proof.as_mut().map(|proof| match decode(proof) {
+            Ok(value) => Some(value),
+            Err(e) => {
+                error = Some(e);
+                None
+            }
+        });
+        match error {
+            Some(e) => Err(Error::Transcript(e)),
+            None => Ok(value.map(Option::unwrap)),
+        }
+    }
+}
+
+impl<
+        'a,
+        'b,
+        C: CurveAffine,
+        E: PointRepresentation<C, C::ScalarExt, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN_LIMB: usize,
+        const T: usize,
+        const RATE: usize,
+    > VerifierInstructions<C>
+    for VerifierChip<'a, 'b, C, E, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>
+{
+    type Scalar = AssignedValue<C::Scalar>;
+    type Point = AssignedPoint<C::Base, C::Scalar, NUMBER_OF_LIMBS, BIT_LEN_LIMB>;
+
+    fn assign_constant(&mut self, constant: C::Scalar) -> Result<Self::Scalar, Error> {
+        self.ecc_chip
+            .main_gate()
+            .assign_constant(self.ctx, constant)
+    }
+
+    fn assign_constant_point(&mut self, point: C) -> Result<Self::Point, Error> {
+        self.ecc_chip.assign_constant(self.ctx, point)
+    }
+
+    fn add(&mut self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, Error> {
+        self.ecc_chip.main_gate().add(self.ctx, a, b)
+    }
+
+    fn sub(&mut self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, Error> {
+        self.ecc_chip.main_gate().sub(self.ctx, a, b)
+    }
+
+    fn mul(&mut self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, Error> {
+        self.ecc_chip.main_gate().mul(self.ctx, a, b)
+    }
+
+    fn add_constant(
+        &mut self,
+        a: &Self::Scalar,
+        constant: C::Scalar,
+    ) -> Result<Self::Scalar, Error> {
+        self.ecc_chip
+            .main_gate()
+            .add_constant(self.ctx, a, constant)
+    }
+
+    fn mul_constant(
+        &mut self,
+        a: &Self::Scalar,
+        constant: C::Scalar,
+    ) -> Result<Self::Scalar, Error> {
+        self.ecc_chip
+            .main_gate()
+            .compose(self.ctx, &[Term::Assigned(a, constant)], C::Scalar::ZERO)
+    }
+
+    fn neg_with_constant(
+        &mut self,
+        a: &Self::Scalar,
+        constant: C::Scalar,
+    ) -> Result<Self::Scalar, Error> {
+        self.ecc_chip
+            .main_gate()
+            .neg_with_constant(self.ctx, a, constant)
+    }
+
+    fn invert(&mut self, a: &Self::Scalar) -> Result<Self::Scalar, Error> {
+        self.ecc_chip.main_gate().invert_unsafe(self.ctx, a)
+    }
+
+    fn msm(&mut self, pairs: Vec<(Self::Point, Self::Scalar)>) -> Result<Self::Point, Error> {
+        self.ecc_chip
+            .assign_aux(self.ctx, self.window_size, pairs.len())?;
+        self.ecc_chip
+            .mul_batch_1d_horizontal(self.ctx, pairs, self.window_size)
+    }
+
+    fn common_point(&mut self, point: &Self::Point) -> Result<(), Error> {
+        // the transcript absorbs limbs, which must match the native encoding
+        let point = self.ecc_chip.normalize(self.ctx, point)?;
+        self.transcript_chip.write_point(self.ctx, &point)
+    }
+
+    fn common_scalar(&mut self, scalar: &Self::Scalar) -> Result<(), Error> {
+        self.transcript_chip.write_scalar(scalar);
+        Ok(())
+    }
+
+    fn read_scalar(&mut self) -> Result<Self::Scalar, Error> {
+        let scalar = self.read(|proof| {
+            let mut repr = <C::Scalar as PrimeField>::Repr::default();
+            proof.read_exact(repr.as_mut())?;
+            Option::from(C::Scalar::from_repr(repr)).ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::Other,
+                    "invalid field element encoding in proof",
+                )
+            })
+        })?;
+        let scalar = self.ecc_chip.main_gate().assign_value(self.ctx, scalar)?;
+        self.transcript_chip.write_scalar(&scalar);
+        Ok(scalar)
+    }
+
+    fn read_point(&mut self) -> Result<Self::Point, Error> {
+        let point = self.read(|proof| {
+            let mut repr = C::Repr::default();
+            proof.read_exact(repr.as_mut())?;
+            Option::from(C::from_bytes(&repr)).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::Other, "invalid point encoding in proof")
+            })
+        })?;
+        let point = self.ecc_chip.assign_point(self.ctx, point)?;
+        self.transcript_chip.write_point(self.ctx, &point)?;
+        Ok(point)
+    }
+
+    fn squeeze_challenge(&mut self) -> Result<Self::Scalar, Error> {
+        self.transcript_chip.squeeze(self.ctx)
+    }
+}
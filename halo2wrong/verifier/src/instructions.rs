@@ -0,0 +1,69 @@
+use crate::halo2::{arithmetic::CurveAffine, plonk::Error};
+
+/// Arithmetic and transcript operations `verify_proof` is written against.
+/// `NativeVerifier` computes them on field elements and points while
+/// `VerifierChip` constrains them over the scalar field of the proof's curve,
+/// so both run the exact same verification steps.
+pub trait VerifierInstructions<C: CurveAffine> {
+    /// Scalar field element or its assigned counterpart
+    type Scalar: Clone;
+    /// Curve point or its assigned counterpart
+    type Point: Clone;
+
+    /// Loads a constant scalar
+    fn assign_constant(&mut self, constant: C::Scalar) -> Result<Self::Scalar, Error>;
+
+    /// Loads a constant point. Point at infinity is not allowed
+    fn assign_constant_point(&mut self, point: C) -> Result<Self::Point, Error>;
+
+    /// Returns `a + b`
+    fn add(&mut self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, Error>;
+
+    /// Returns `a - b`
+    fn sub(&mut self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, Error>;
+
+    /// Returns `a * b`
+    fn mul(&mut self, a: &Self::Scalar, b: &Self::Scalar) -> Result<Self::Scalar, Error>;
+
+    /// Returns `a + constant`
+    fn add_constant(
+        &mut self,
+        a: &Self::Scalar,
+        constant: C::Scalar,
+    ) -> Result<Self::Scalar, Error>;
+
+    /// Returns `a * constant`
+    fn mul_constant(
+        &mut self,
+        a: &Self::Scalar,
+        constant: C::Scalar,
+    ) -> Result<Self::Scalar, Error>;
+
+    /// Returns `constant - a`
+    fn neg_with_constant(
+        &mut self,
+        a: &Self::Scalar,
+        constant: C::Scalar,
+    ) -> Result<Self::Scalar, Error>;
+
+    /// Returns `1 / a`. `a` is expected to be non zero
+    fn invert(&mut self, a: &Self::Scalar) -> Result<Self::Scalar, Error>;
+
+    /// Returns `P_0 * e_0 + P_1 * e_1 + ...`
+    fn msm(&mut self, pairs: Vec<(Self::Point, Self::Scalar)>) -> Result<Self::Point, Error>;
+
+    /// Absorbs a point that is known to the verifier
+    fn common_point(&mut self, point: &Self::Point) -> Result<(), Error>;
+
+    /// Absorbs a scalar that is known to the verifier
+    fn common_scalar(&mut self, scalar: &Self::Scalar) -> Result<(), Error>;
+
+    /// Reads and absorbs the next scalar of the proof
+    fn read_scalar(&mut self) -> Result<Self::Scalar, Error>;
+
+    /// Reads and absorbs the next point of the proof
+    fn read_point(&mut self) -> Result<Self::Point, Error>;
+
+    /// Squeezes a challenge
+    fn squeeze_challenge(&mut self) -> Result<Self::Scalar, Error>;
+}
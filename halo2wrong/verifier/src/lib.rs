@@ -0,0 +1,20 @@
+mod accumulator;
+mod chip;
+mod instructions;
+mod native;
+mod verifier;
+
+pub use ecc;
+pub use ecc::halo2;
+pub use ecc::maingate;
+pub use poseidon::Spec;
+pub use transcript;
+
+pub use accumulator::{fold_accumulators, KzgAccumulator};
+pub use chip::VerifierChip;
+pub use instructions::VerifierInstructions;
+pub use native::NativeVerifier;
+pub use verifier::verify_proof;
+
+#[cfg(test)]
+use halo2::halo2curves as curves;
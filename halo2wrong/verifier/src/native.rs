@@ -0,0 +1,140 @@
+use crate::{
+    halo2::{
+        arithmetic::CurveAffine,
+        halo2curves::{
+            ff::{Field, FromUniformBytes},
+            group::{prime::PrimeCurveAffine, Curve, Group},
+        },
+        plonk::Error,
+        transcript::{EncodedChallenge, Transcript, TranscriptRead},
+    },
+    instructions::VerifierInstructions,
+    transcript::{PointRepresentation, PoseidonTranscript, R_F, R_P},
+};
+
+/// `NativeVerifier` runs `verify_proof` outside of a circuit. It reads the
+/// proof with `PoseidonTranscript`, so it accepts exactly the proofs
+/// `VerifierChip` accepts and yields the same accumulator.
+pub struct NativeVerifier<
+    'a,
+    C: CurveAffine,
+    E: PointRepresentation<C, C::ScalarExt, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+    const NUMBER_OF_LIMBS: usize,
+    const BIT_LEN_LIMB: usize,
+    const T: usize,
+    const RATE: usize,
+> where
+    C::ScalarExt: FromUniformBytes<64>,
+{
+    transcript:
+        PoseidonTranscript<&'a [u8], C, C::ScalarExt, E, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>,
+}
+
+impl<
+        'a,
+        C: CurveAffine,
+        E: PointRepresentation<C, C::ScalarExt, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN_LIMB: usize,
+        const T: usize,
+        const RATE: usize,
+    > NativeVerifier<'a, C, E, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>
+where
+    C::ScalarExt: FromUniformBytes<64>,
+{
+    /// Constructs a verifier reading `proof`
+    pub fn new(proof: &'a [u8]) -> Self {
+        Self {
+            transcript: PoseidonTranscript::new(proof, R_F, R_P),
+        }
+    }
+}
+
+impl<
+        'a,
+        C: CurveAffine,
+        E: PointRepresentation<C, C::ScalarExt, NUMBER_OF_LIMBS, BIT_LEN_LIMB>,
+        const NUMBER_OF_LIMBS: usize,
+        const BIT_LEN_LIMB: usize,
+        const T: usize,
+        const RATE: usize,
+    > VerifierInstructions<C> for NativeVerifier<'a, C, E, NUMBER_OF_LIMBS, BIT_LEN_LIMB, T, RATE>
+where
+    C::ScalarExt: FromUniformBytes<64>,
+{
+    type Scalar = C::Scalar;
+    type Point = C;
+
+    fn assign_constant(&mut self, constant: C::Scalar) -> Result<C::Scalar, Error> {
+        Ok(constant)
+    }
+
+    fn assign_constant_point(&mut self, point: C) -> Result<C, Error> {
+        Ok(point)
+    }
+
+    fn add(&mut self, a: &C::Scalar, b: &C::Scalar) -> Result<C::Scalar, Error> {
+        Ok(*a + b)
+    }
+
+    fn sub(&mut self, a: &C::Scalar, b: &C::Scalar) -> Result<C::Scalar, Error> {
+        Ok(*a - b)
+    }
+
+    fn mul(&mut self, a: &C::Scalar, b: &C::Scalar) -> Result<C::Scalar, Error> {
+        Ok(*a * b)
+    }
+
+    fn add_constant(&mut self, a: &C::Scalar, constant: C::Scalar) -> Result<C::Scalar, Error> {
+        Ok(*a + constant)
+    }
+
+    fn mul_constant(&mut self, a: &C::Scalar, constant: C::Scalar) -> Result<C::Scalar, Error> {
+        Ok(*a * constant)
+    }
+
+    fn neg_with_constant(
+        &mut self,
+        a: &C::Scalar,
+        constant: C::Scalar,
+    ) -> Result<C::Scalar, Error> {
+        Ok(constant - a)
+    }
+
+    fn invert(&mut self, a: &C::Scalar) -> Result<C::Scalar, Error> {
+        Option::from(a.invert()).ok_or(Error::Synthesis)
+    }
+
+    fn msm(&mut self, pairs: Vec<(C, C::Scalar)>) -> Result<C, Error> {
+        Ok(pairs
+            .into_iter()
+            .fold(C::CurveExt::identity(), |acc, (point, scalar)| {
+                acc + point.to_curve() * scalar
+            })
+            .to_affine())
+    }
+
+    fn common_point(&mut self, point: &C) -> Result<(), Error> {
+        self.transcript
+            .common_point(*point)
+            .map_err(Error::Transcript)
+    }
+
+    fn common_scalar(&mut self, scalar: &C::Scalar) -> Result<(), Error> {
+        self.transcript
+            .common_scalar(*scalar)
+            .map_err(Error::Transcript)
+    }
+
+    fn read_scalar(&mut self) -> Result<C::Scalar, Error> {
+        self.transcript.read_scalar().map_err(Error::Transcript)
+    }
+
+    fn read_point(&mut self) -> Result<C, Error> {
+        self.transcript.read_point().map_err(Error::Transcript)
+    }
+
+    fn squeeze_challenge(&mut self) -> Result<C::Scalar, Error> {
+        Ok(self.transcript.squeeze_challenge().get_scalar())
+    }
+}
@@ -0,0 +1,852 @@
+use crate::{
+    accumulator::KzgAccumulator,
+    halo2::{
+        arithmetic::CurveAffine,
+        halo2curves::{
+            ff::{Field, PrimeField},
+            group::prime::PrimeCurveAffine,
+        },
+        plonk::{Any, Column, ColumnType, ConstraintSystem, Error, Expression, VerifyingKey},
+        poly::Rotation,
+        transcript::Transcript,
+    },
+    instructions::VerifierInstructions,
+    transcript::PoseidonChallenge,
+};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io,
+};
+
+#[cfg(test)]
+pub(crate) mod tests;
+
+/// Polynomial expression of the constraint system with queries resolved to
+/// their position among the evaluations of the proof
+#[derive(Clone, Debug)]
+enum Expr<F> {
+    Constant(F),
+    Fixed(usize),
+    Advice(usize),
+    Instance(usize),
+    Challenge(usize),
+    Negated(Box<Expr<F>>),
+    Sum(Box<Expr<F>>, Box<Expr<F>>),
+    Product(Box<Expr<F>>, Box<Expr<F>>),
+    Scaled(Box<Expr<F>>, F),
+}
+
+/// Committed polynomials that are opened by the multiopen argument
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Committed {
+    Advice(usize),
+    Fixed(usize),
+    PermutationCommon(usize),
+    PermutationProduct(usize),
+    LookupPermutedInput(usize),
+    LookupPermutedTable(usize),
+    LookupProduct(usize),
+    VanishingQuotient,
+    VanishingRandom,
+}
+
+/// A commitment as a linear combination of points. `None` stands for a unit
+/// coefficient
+type Msm<P, S> = Vec<(P, Option<S>)>;
+
+struct Evaluations<S> {
+    advice: Vec<S>,
+    fixed: Vec<S>,
+    instance: Vec<S>,
+    challenges: Vec<S>,
+}
+
+struct PermutationEvaluations<S> {
+    product: S,
+    product_next: S,
+    product_last: Option<S>,
+}
+
+struct LookupEvaluations<S> {
+    product: S,
+    product_next: S,
+    permuted_input: S,
+    permuted_input_inv: S,
+    permuted_table: S,
+}
+
+/// Collects the scalars `VerifyingKey::hash_into` writes to the transcript
+struct VerifyingKeyRepr<C: CurveAffine>(Vec<C::Scalar>);
+
+impl<C: CurveAffine> Transcript<C, PoseidonChallenge<C>> for VerifyingKeyRepr<C> {
+    fn squeeze_challenge(&mut self) -> PoseidonChallenge<C> {
+        unreachable!("the verifying key is only absorbed")
+    }
+
+    fn common_point(&mut self, _: C) -> io::Result<()> {
+        unreachable!("the verifying key is absorbed as a scalar")
+    }
+
+    fn common_scalar(&mut self, scalar: C::Scalar) -> io::Result<()> {
+        self.0.push(scalar);
+        Ok(())
+    }
+}
+
+fn query_index<T: ColumnType>(
+    queries: &[(Column<T>, Rotation)],
+    column_index: usize,
+    rotation: Rotation,
+) -> usize {
+    queries
+        .iter()
+        .position(|(column, at)| column.index() == column_index && *at == rotation)
+        .expect("column must be queried")
+}
+
+fn symbolic<F: Field>(cs: &ConstraintSystem<F>, expression: &Expression<F>) -> Expr<F> {
+    expression.evaluate(
+        &Expr::Constant,
+        &|_| panic!("virtual selectors are removed during optimization"),
+        &|query| {
+            Expr::Fixed(query_index(
+                cs.fixed_queries(),
+                query.column_index(),
+                query.rotation(),
+            ))
+        },
+        &|query| {
+            Expr::Advice(query_index(
+                cs.advice_queries(),
+                query.column_index(),
+                query.rotation(),
+            ))
+        },
+        &|query| {
+            Expr::Instance(query_index(
+                cs.instance_queries(),
+                query.column_index(),
+                query.rotation(),
+            ))
+        },
+        &|challenge| Expr::Challenge(challenge.index()),
+        &|a| Expr::Negated(Box::new(a)),
+        &|a, b| Expr::Sum(Box::new(a), Box::new(b)),
+        &|a, b| Expr::Product(Box::new(a), Box::new(b)),
+        &|a, scalar| Expr::Scaled(Box::new(a), scalar),
+    )
+}
+
+fn evaluate<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    expr: &Expr<C::Scalar>,
+    evaluations: &Evaluations<L::Scalar>,
+) -> Result<L::Scalar, Error> {
+    match expr {
+        Expr::Constant(constant) => loader.assign_constant(*constant),
+        Expr::Fixed(index) => Ok(evaluations.fixed[*index].clone()),
+        Expr::Advice(index) => Ok(evaluations.advice[*index].clone()),
+        Expr::Instance(index) => Ok(evaluations.instance[*index].clone()),
+        Expr::Challenge(index) => Ok(evaluations.challenges[*index].clone()),
+        Expr::Negated(a) => {
+            let a = evaluate(loader, a, evaluations)?;
+            loader.neg_with_constant(&a, C::Scalar::ZERO)
+        }
+        Expr::Sum(a, b) => match (a.as_ref(), b.as_ref()) {
+            (Expr::Constant(constant), other) | (other, Expr::Constant(constant)) => {
+                let other = evaluate(loader, other, evaluations)?;
+                loader.add_constant(&other, *constant)
+            }
+            _ => {
+                let a = evaluate(loader, a, evaluations)?;
+                let b = evaluate(loader, b, evaluations)?;
+                loader.add(&a, &b)
+            }
+        },
+        Expr::Product(a, b) => match (a.as_ref(), b.as_ref()) {
+            (Expr::Constant(constant), other) | (other, Expr::Constant(constant)) => {
+                let other = evaluate(loader, other, evaluations)?;
+                loader.mul_constant(&other, *constant)
+            }
+            _ => {
+                let a = evaluate(loader, a, evaluations)?;
+                let b = evaluate(loader, b, evaluations)?;
+                loader.mul(&a, &b)
+            }
+        },
+        Expr::Scaled(a, scalar) => {
+            let a = evaluate(loader, a, evaluations)?;
+            loader.mul_constant(&a, *scalar)
+        }
+    }
+}
+
+fn read_scalars<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    n: usize,
+) -> Result<Vec<L::Scalar>, Error> {
+    (0..n).map(|_| loader.read_scalar()).collect()
+}
+
+fn read_points<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    n: usize,
+) -> Result<Vec<L::Point>, Error> {
+    (0..n).map(|_| loader.read_point()).collect()
+}
+
+fn sum<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    terms: &[L::Scalar],
+) -> Result<L::Scalar, Error> {
+    match terms.split_first() {
+        Some((first, rest)) => rest
+            .iter()
+            .try_fold(first.clone(), |acc, term| loader.add(&acc, term)),
+        None => loader.assign_constant(C::Scalar::ZERO),
+    }
+}
+
+/// Returns `acc * base + term` folded over `terms`
+fn horner<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    terms: &[L::Scalar],
+    base: &L::Scalar,
+) -> Result<L::Scalar, Error> {
+    match terms.split_first() {
+        Some((first, rest)) => rest.iter().try_fold(first.clone(), |acc, term| {
+            let acc = loader.mul(&acc, base)?;
+            loader.add(&acc, term)
+        }),
+        None => loader.assign_constant(C::Scalar::ZERO),
+    }
+}
+
+/// Multiplies factors where `None` stands for one
+fn product<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    factors: &[Option<&L::Scalar>],
+) -> Result<Option<L::Scalar>, Error> {
+    factors
+        .iter()
+        .flatten()
+        .try_fold(None, |acc: Option<L::Scalar>, factor| {
+            Ok(Some(match acc {
+                Some(acc) => loader.mul(&acc, factor)?,
+                None => (*factor).clone(),
+            }))
+        })
+}
+
+/// Returns `[1, base, base^2, ...]` of length `n` where `None` stands for one
+fn powers<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    base: &L::Scalar,
+    n: usize,
+) -> Result<Vec<Option<L::Scalar>>, Error> {
+    let mut powers = vec![None];
+    for _ in 1..n {
+        let last = powers.last().unwrap();
+        let next = product(loader, &[last.as_ref(), Some(base)])?;
+        powers.push(next);
+    }
+    Ok(powers)
+}
+
+fn constant_commitment<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    commitment: &C,
+) -> Result<Msm<L::Point, L::Scalar>, Error> {
+    // commitment to the zero polynomial has nothing to contribute
+    if bool::from(commitment.is_identity()) {
+        Ok(vec![])
+    } else {
+        Ok(vec![(loader.assign_constant_point(*commitment)?, None)])
+    }
+}
+
+/// Verifies a proof created with `ProverSHPLONK` over the KZG commitment
+/// scheme and the Poseidon transcript, following the steps of halo2's
+/// `verify_proof` for a single circuit instance. Gates, the permutation
+/// argument and lookups are supported.
+///
+/// `g1` is the first point of the commitment key, `params.get_g()[0]`, and
+/// `instances` are the public inputs per instance column.
+///
+/// The final pairing check is left to the caller, see `KzgAccumulator`.
+pub fn verify_proof<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    vk: &VerifyingKey<C>,
+    g1: C,
+    instances: &[Vec<L::Scalar>],
+) -> Result<KzgAccumulator<L::Point>, Error> {
+    let cs = vk.cs();
+    let domain = vk.get_domain();
+    if instances.len() != cs.num_instance_columns() {
+        return Err(Error::InvalidInstances);
+    }
+
+    // The verifying key and the public inputs are absorbed first
+    let mut vk_repr = VerifyingKeyRepr::<C>(vec![]);
+    vk.hash_into(&mut vk_repr).map_err(Error::Transcript)?;
+    for scalar in vk_repr.0 {
+        let scalar = loader.assign_constant(scalar)?;
+        loader.common_scalar(&scalar)?;
+    }
+    for value in instances.iter().flatten() {
+        loader.common_scalar(value)?;
+    }
+
+    // Advice commitments and challenges phase by phase
+    let advice_phases = cs.advice_column_phase();
+    let challenge_phases = cs.challenge_phase();
+    let number_of_phases = advice_phases
+        .iter()
+        .chain(challenge_phases.iter())
+        .max()
+        .map_or(1, |phase| *phase as usize + 1);
+    let mut advice_commitments = vec![None; cs.num_advice_columns()];
+    let mut challenges = vec![None; cs.num_challenges()];
+    for phase in 0..number_of_phases {
+        for (commitment, _) in advice_commitments
+            .iter_mut()
+            .zip(advice_phases.iter())
+            .filter(|(_, at)| **at as usize == phase)
+        {
+            *commitment = Some(loader.read_point()?);
+        }
+        for (challenge, _) in challenges
+            .iter_mut()
+            .zip(challenge_phases.iter())
+            .filter(|(_, at)| **at as usize == phase)
+        {
+            *challenge = Some(loader.squeeze_challenge()?);
+        }
+    }
+    let advice_commitments: Vec<L::Point> = advice_commitments
+        .into_iter()
+        .map(|commitment| commitment.expect("every advice column has a phase"))
+        .collect();
+    let challenges: Vec<L::Scalar> = challenges
+        .into_iter()
+        .map(|challenge| challenge.expect("every challenge has a phase"))
+        .collect();
+
+    let theta = loader.squeeze_challenge()?;
+    let lookup_permuted_commitments = cs
+        .lookups()
+        .iter()
+        .map(|_| Ok((loader.read_point()?, loader.read_point()?)))
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let beta = loader.squeeze_challenge()?;
+    let gamma = loader.squeeze_challenge()?;
+    let chunk_len = cs.degree() - 2;
+    let permutation_columns = cs.permutation().get_columns();
+    let number_of_sets = permutation_columns.chunks(chunk_len).len();
+    let permutation_product_commitments = read_points(loader, number_of_sets)?;
+    let lookup_product_commitments = read_points(loader, cs.lookups().len())?;
+
+    let random_commitment = loader.read_point()?;
+    let y = loader.squeeze_challenge()?;
+    let h_commitments = read_points(loader, domain.get_quotient_poly_degree())?;
+    let x = loader.squeeze_challenge()?;
+
+    let advice_evals = read_scalars(loader, cs.advice_queries().len())?;
+    let fixed_evals = read_scalars(loader, cs.fixed_queries().len())?;
+    let random_eval = loader.read_scalar()?;
+    let permutation_common_evals = read_scalars(loader, vk.permutation().commitments().len())?;
+    let permutation_evals = (0..number_of_sets)
+        .map(|i| {
+            Ok(PermutationEvaluations {
+                product: loader.read_scalar()?,
+                product_next: loader.read_scalar()?,
+                product_last: if i + 1 < number_of_sets {
+                    Some(loader.read_scalar()?)
+                } else {
+                    None
+                },
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+    let lookup_evals = cs
+        .lookups()
+        .iter()
+        .map(|_| {
+            Ok(LookupEvaluations {
+                product: loader.read_scalar()?,
+                product_next: loader.read_scalar()?,
+                permuted_input: loader.read_scalar()?,
+                permuted_input_inv: loader.read_scalar()?,
+                permuted_table: loader.read_scalar()?,
+            })
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    // x^n
+    let mut xn = x.clone();
+    for _ in 0..domain.k() {
+        xn = loader.mul(&xn, &xn)?;
+    }
+    let xn_minus_one = loader.add_constant(&xn, -C::Scalar::ONE)?;
+
+    // L_i(x) = ω^i (x^n - 1) / (n (x - ω^i)) for the blinding rows and the
+    // rows that carry public inputs
+    let blinding_factors = cs.blinding_factors() as i32;
+    let mut rows: BTreeSet<i32> = (-(blinding_factors + 1)..=0).collect();
+    for (column, rotation) in cs.instance_queries() {
+        let len = instances[column.index()].len() as i32;
+        rows.extend((0..len).map(|j| j - rotation.0));
+    }
+    let n_inv = C::Scalar::from(1u64 << domain.k()).invert().unwrap();
+    let common = loader.mul_constant(&xn_minus_one, n_inv)?;
+    let mut lagrange = BTreeMap::new();
+    for i in rows {
+        let omega_i = domain.rotate_omega(C::Scalar::ONE, Rotation(i));
+        let denominator = loader.add_constant(&x, -omega_i)?;
+        let denominator = loader.invert(&denominator)?;
+        let l_i = loader.mul(&common, &denominator)?;
+        lagrange.insert(i, loader.mul_constant(&l_i, omega_i)?);
+    }
+    let l_last = lagrange[&-(blinding_factors + 1)].clone();
+    let l_blind: Vec<L::Scalar> = (-blinding_factors..0)
+        .map(|i| lagrange[&i].clone())
+        .collect();
+    let l_blind = sum(loader, &l_blind)?;
+    let l_0 = lagrange[&0].clone();
+
+    // Public inputs are not committed but evaluated by the verifier
+    let instance_evals = cs
+        .instance_queries()
+        .iter()
+        .map(|(column, rotation)| {
+            let terms = instances[column.index()]
+                .iter()
+                .enumerate()
+                .map(|(j, value)| loader.mul(value, &lagrange[&(j as i32 - rotation.0)]))
+                .collect::<Result<Vec<_>, Error>>()?;
+            sum(loader, &terms)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let evaluations = Evaluations {
+        advice: advice_evals,
+        fixed: fixed_evals,
+        instance: instance_evals,
+        challenges,
+    };
+    let column_eval = |column: &Column<Any>| -> L::Scalar {
+        match column.column_type() {
+            Any::Advice(_) => evaluations.advice
+                [query_index(cs.advice_queries(), column.index(), Rotation::cur())]
+            .clone(),
+            Any::Fixed => evaluations.fixed
+                [query_index(cs.fixed_queries(), column.index(), Rotation::cur())]
+            .clone(),
+            Any::Instance => evaluations.instance
+                [query_index(cs.instance_queries(), column.index(), Rotation::cur())]
+            .clone(),
+        }
+    };
+
+    let mut expressions = vec![];
+
+    // Custom gates
+    for gate in cs.gates() {
+        for poly in gate.polynomials() {
+            expressions.push(evaluate(loader, &symbolic(cs, poly), &evaluations)?);
+        }
+    }
+
+    // Permutation argument
+    let active_rows = loader.add(&l_last, &l_blind)?;
+    let active_rows = loader.neg_with_constant(&active_rows, C::Scalar::ONE)?;
+    // l_0(X) * (1 - z_0(X)) = 0
+    if let Some(first) = permutation_evals.first() {
+        let t = loader.neg_with_constant(&first.product, C::Scalar::ONE)?;
+        expressions.push(loader.mul(&l_0, &t)?);
+    }
+    // l_last(X) * (z_l(X)^2 - z_l(X)) = 0
+    if let Some(last) = permutation_evals.last() {
+        let t = loader.mul(&last.product, &last.product)?;
+        let t = loader.sub(&t, &last.product)?;
+        expressions.push(loader.mul(&l_last, &t)?);
+    }
+    // l_0(X) * (z_i(X) - z_{i-1}(\omega^(last) X)) = 0
+    for (set, previous) in permutation_evals
+        .iter()
+        .skip(1)
+        .zip(permutation_evals.iter())
+    {
+        let previous_last = previous.product_last.as_ref().unwrap();
+        let t = loader.sub(&set.product, previous_last)?;
+        expressions.push(loader.mul(&l_0, &t)?);
+    }
+    // (1 - (l_last(X) + l_blind(X))) * (
+    //   z_i(\omega X) \prod (p(X) + \beta s_i(X) + \gamma)
+    // - z_i(X) \prod (p(X) + \delta^i \beta X + \gamma)
+    // ) = 0
+    let beta_x = loader.mul(&beta, &x)?;
+    for (chunk_index, ((set, columns), common_evals)) in permutation_evals
+        .iter()
+        .zip(permutation_columns.chunks(chunk_len))
+        .zip(permutation_common_evals.chunks(chunk_len))
+        .enumerate()
+    {
+        let mut left = set.product_next.clone();
+        let mut right = set.product.clone();
+        let mut delta = C::Scalar::DELTA.pow_vartime([(chunk_index * chunk_len) as u64]);
+        for (column, common_eval) in columns.iter().zip(common_evals.iter()) {
+            let eval = column_eval(column);
+
+            let t = loader.mul(&beta, common_eval)?;
+            let t = loader.add(&t, &eval)?;
+            let t = loader.add(&t, &gamma)?;
+            left = loader.mul(&left, &t)?;
+
+            let t = loader.mul_constant(&beta_x, delta)?;
+            let t = loader.add(&t, &eval)?;
+            let t = loader.add(&t, &gamma)?;
+            right = loader.mul(&right, &t)?;
+
+            delta *= C::Scalar::DELTA;
+        }
+        let t = loader.sub(&left, &right)?;
+        expressions.push(loader.mul(&t, &active_rows)?);
+    }
+
+    // Lookup arguments
+    for (argument, evals) in cs.lookups().iter().zip(lookup_evals.iter()) {
+        // l_0(X) * (1 - z(X)) = 0
+        let t = loader.neg_with_constant(&evals.product, C::Scalar::ONE)?;
+        expressions.push(loader.mul(&l_0, &t)?);
+        // l_last(X) * (z(X)^2 - z(X)) = 0
+        let t = loader.mul(&evals.product, &evals.product)?;
+        let t = loader.sub(&t, &evals.product)?;
+        expressions.push(loader.mul(&l_last, &t)?);
+        // (1 - (l_last(X) + l_blind(X))) * (
+        //   z(\omega X) (a'(X) + \beta) (s'(X) + \gamma)
+        // - z(X) (\theta^{m-1} a_0(X) + ... + a_{m-1}(X) + \beta)
+        //        (\theta^{m-1} s_0(X) + ... + s_{m-1}(X) + \gamma)
+        // ) = 0
+        let mut compress = |expressions: &[Expression<C::Scalar>]| {
+            let values = expressions
+                .iter()
+                .map(|expression| evaluate(loader, &symbolic(cs, expression), &evaluations))
+                .collect::<Result<Vec<_>, Error>>()?;
+            horner(loader, &values, &theta)
+        };
+        let input = compress(argument.input_expressions())?;
+        let table = compress(argument.table_expressions())?;
+
+        let t = loader.add(&evals.permuted_input, &beta)?;
+        let left = loader.mul(&evals.product_next, &t)?;
+        let t = loader.add(&evals.permuted_table, &gamma)?;
+        let left = loader.mul(&left, &t)?;
+
+        let t = loader.add(&input, &beta)?;
+        let right = loader.mul(&evals.product, &t)?;
+        let t = loader.add(&table, &gamma)?;
+        let right = loader.mul(&right, &t)?;
+
+        let t = loader.sub(&left, &right)?;
+        expressions.push(loader.mul(&t, &active_rows)?);
+        // l_0(X) * (a'(X) - s'(X)) = 0
+        let diff = loader.sub(&evals.permuted_input, &evals.permuted_table)?;
+        expressions.push(loader.mul(&l_0, &diff)?);
+        // (1 - (l_last(X) + l_blind(X))) * (a′(X) − s′(X))⋅(a′(X) − a′(\omega^{-1} X)) = 0
+        let t = loader.sub(&evals.permuted_input, &evals.permuted_input_inv)?;
+        let t = loader.mul(&diff, &t)?;
+        expressions.push(loader.mul(&t, &active_rows)?);
+    }
+
+    // Vanishing argument: h(x) = \sum y^i expression_i(x) / (x^n - 1)
+    let expected_h_eval = horner(loader, &expressions, &y)?;
+    let xn_minus_one_inv = loader.invert(&xn_minus_one)?;
+    let expected_h_eval = loader.mul(&expected_h_eval, &xn_minus_one_inv)?;
+
+    // Openings in the order halo2 queries them
+    let mut queries: Vec<(Committed, i32, L::Scalar)> = vec![];
+    for (i, (column, rotation)) in cs.advice_queries().iter().enumerate() {
+        queries.push((
+            Committed::Advice(column.index()),
+            rotation.0,
+            evaluations.advice[i].clone(),
+        ));
+    }
+    for (i, set) in permutation_evals.iter().enumerate() {
+        queries.push((Committed::PermutationProduct(i), 0, set.product.clone()));
+        queries.push((
+            Committed::PermutationProduct(i),
+            1,
+            set.product_next.clone(),
+        ));
+    }
+    for (i, set) in permutation_evals.iter().enumerate().rev().skip(1) {
+        queries.push((
+            Committed::PermutationProduct(i),
+            -(blinding_factors + 1),
+            set.product_last.clone().unwrap(),
+        ));
+    }
+    for (i, evals) in lookup_evals.iter().enumerate() {
+        queries.push((Committed::LookupProduct(i), 0, evals.product.clone()));
+        queries.push((
+            Committed::LookupPermutedInput(i),
+            0,
+            evals.permuted_input.clone(),
+        ));
+        queries.push((
+            Committed::LookupPermutedTable(i),
+            0,
+            evals.permuted_table.clone(),
+        ));
+        queries.push((
+            Committed::LookupPermutedInput(i),
+            -1,
+            evals.permuted_input_inv.clone(),
+        ));
+        queries.push((Committed::LookupProduct(i), 1, evals.product_next.clone()));
+    }
+    for (i, (column, rotation)) in cs.fixed_queries().iter().enumerate() {
+        queries.push((
+            Committed::Fixed(column.index()),
+            rotation.0,
+            evaluations.fixed[i].clone(),
+        ));
+    }
+    for (i, eval) in permutation_common_evals.iter().enumerate() {
+        queries.push((Committed::PermutationCommon(i), 0, eval.clone()));
+    }
+    queries.push((Committed::VanishingQuotient, 0, expected_h_eval));
+    queries.push((Committed::VanishingRandom, 0, random_eval));
+
+    let fixed_commitments = vk
+        .fixed_commitments()
+        .iter()
+        .map(|commitment| constant_commitment(loader, commitment))
+        .collect::<Result<Vec<_>, Error>>()?;
+    let permutation_commitments = vk
+        .permutation()
+        .commitments()
+        .iter()
+        .map(|commitment| constant_commitment(loader, commitment))
+        .collect::<Result<Vec<_>, Error>>()?;
+    // \sum x^{ni} h_i
+    let mut h_commitment: Msm<L::Point, L::Scalar> = vec![];
+    let mut power = None;
+    for h in h_commitments.iter() {
+        h_commitment.push((h.clone(), power.clone()));
+        power = Some(match power {
+            Some(power) => loader.mul(&power, &xn)?,
+            None => xn.clone(),
+        });
+    }
+    let commitment = |committed: Committed| -> Msm<L::Point, L::Scalar> {
+        let single = |point: &L::Point| vec![(point.clone(), None)];
+        match committed {
+            Committed::Advice(i) => single(&advice_commitments[i]),
+            Committed::Fixed(i) => fixed_commitments[i].clone(),
+            Committed::PermutationCommon(i) => permutation_commitments[i].clone(),
+            Committed::PermutationProduct(i) => single(&permutation_product_commitments[i]),
+            Committed::LookupPermutedInput(i) => single(&lookup_permuted_commitments[i].0),
+            Committed::LookupPermutedTable(i) => single(&lookup_permuted_commitments[i].1),
+            Committed::LookupProduct(i) => single(&lookup_product_commitments[i]),
+            Committed::VanishingQuotient => h_commitment.clone(),
+            Committed::VanishingRandom => single(&random_commitment),
+        }
+    };
+
+    shplonk(loader, x, queries, commitment, g1, |rotation| {
+        domain.rotate_omega(C::Scalar::ONE, Rotation(rotation))
+    })
+}
+
+/// SHPLONK multiopen verifier. Queries are grouped into rotation sets in
+/// order of first appearance, as in halo2's `construct_intermediate_sets`
+fn shplonk<C: CurveAffine, L: VerifierInstructions<C>>(
+    loader: &mut L,
+    x: L::Scalar,
+    queries: Vec<(Committed, i32, L::Scalar)>,
+    commitment: impl Fn(Committed) -> Msm<L::Point, L::Scalar>,
+    g1: C,
+    omega: impl Fn(i32) -> C::Scalar,
+) -> Result<KzgAccumulator<L::Point>, Error> {
+    let y = loader.squeeze_challenge()?;
+    let v = loader.squeeze_challenge()?;
+    let h1 = loader.read_point()?;
+    let u = loader.squeeze_challenge()?;
+    let h2 = loader.read_point()?;
+
+    let mut super_point_set = BTreeSet::new();
+    let mut committed_rotations: Vec<(Committed, BTreeSet<i32>)> = vec![];
+    for (committed, rotation, _) in queries.iter() {
+        super_point_set.insert(*rotation);
+        match committed_rotations
+            .iter_mut()
+            .find(|(other, _)| other == committed)
+        {
+            Some((_, rotations)) => {
+                rotations.insert(*rotation);
+            }
+            None => committed_rotations.push((*committed, BTreeSet::from([*rotation]))),
+        }
+    }
+    let mut rotation_sets: Vec<(BTreeSet<i32>, Vec<Committed>)> = vec![];
+    for (committed, rotations) in committed_rotations {
+        match rotation_sets
+            .iter_mut()
+            .find(|(other, _)| *other == rotations)
+        {
+            Some((_, commitments)) => commitments.push(committed),
+            None => rotation_sets.push((rotations, vec![committed])),
+        }
+    }
+    let eval = |committed: Committed, rotation: i32| {
+        queries
+            .iter()
+            .find(|(other, at, _)| *other == committed && *at == rotation)
+            .map(|(_, _, eval)| eval.clone())
+            .unwrap()
+    };
+
+    // u - x ω^r for every opening point
+    let mut u_minus_points = BTreeMap::new();
+    for rotation in super_point_set.iter() {
+        let point = loader.mul_constant(&x, omega(*rotation))?;
+        u_minus_points.insert(*rotation, loader.sub(&u, &point)?);
+    }
+    // x^{-i} for the denominators of the lagrange basis
+    let max_set_len = rotation_sets
+        .iter()
+        .map(|(rotations, _)| rotations.len())
+        .max()
+        .unwrap_or_default();
+    let x_inv = loader.invert(&x)?;
+    let x_inv_powers = powers(loader, &x_inv, max_set_len)?;
+    let max_commitments = rotation_sets
+        .iter()
+        .map(|(_, commitments)| commitments.len())
+        .max()
+        .unwrap_or_default();
+    let y_powers = powers(loader, &y, max_commitments)?;
+    let number_of_sets = rotation_sets.len();
+    let v_powers = powers(loader, &v, number_of_sets)?;
+
+    let mut z_0 = None;
+    let mut z_0_diff_inv = None;
+    let mut r_outer: Option<L::Scalar> = None;
+    let mut outer_msm: Vec<(L::Point, L::Scalar)> = vec![];
+    for (i, (rotations, commitments)) in rotation_sets.iter().enumerate() {
+        let diffs: Vec<&L::Scalar> = super_point_set
+            .iter()
+            .filter(|rotation| !rotations.contains(rotation))
+            .map(|rotation| &u_minus_points[rotation])
+            .collect();
+        let diffs: Vec<Option<&L::Scalar>> = diffs.into_iter().map(Some).collect();
+        let z_diff = product(loader, &diffs)?;
+
+        // normalize coefficients by the coefficient of the first set
+        let z_diff = if i == 0 {
+            let points: Vec<Option<&L::Scalar>> = rotations
+                .iter()
+                .map(|rotation| Some(&u_minus_points[rotation]))
+                .collect();
+            z_0 = product(loader, &points)?;
+            z_0_diff_inv = match z_diff {
+                Some(z_diff) => Some(loader.invert(&z_diff)?),
+                None => None,
+            };
+            None
+        } else {
+            product(loader, &[z_diff.as_ref(), z_0_diff_inv.as_ref()])?
+        };
+
+        // lagrange basis of the set at u:
+        // \prod_{k != j} (u - x ω^{r_k}) / (x^{t-1} \prod_{k != j} (ω^{r_j} - ω^{r_k}))
+        let rotations: Vec<i32> = rotations.iter().copied().collect();
+        let basis = rotations
+            .iter()
+            .map(|r_j| {
+                let numerator: Vec<Option<&L::Scalar>> = rotations
+                    .iter()
+                    .filter(|r_k| *r_k != r_j)
+                    .map(|r_k| Some(&u_minus_points[r_k]))
+                    .collect();
+                let numerator = product(loader, &numerator)?;
+                let denominator = rotations
+                    .iter()
+                    .filter(|r_k| *r_k != r_j)
+                    .fold(C::Scalar::ONE, |acc, r_k| acc * (omega(*r_j) - omega(*r_k)));
+                let basis = product(
+                    loader,
+                    &[
+                        numerator.as_ref(),
+                        x_inv_powers[rotations.len() - 1].as_ref(),
+                    ],
+                )?;
+                let inv = denominator.invert().unwrap();
+                match basis {
+                    Some(basis) => loader.mul_constant(&basis, inv),
+                    None => loader.assign_constant(inv),
+                }
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
+
+        let mut r_inner: Option<L::Scalar> = None;
+        for (j, committed) in commitments.iter().enumerate() {
+            let terms = rotations
+                .iter()
+                .zip(basis.iter())
+                .map(|(rotation, basis)| loader.mul(&eval(*committed, *rotation), basis))
+                .collect::<Result<Vec<_>, Error>>()?;
+            let r_eval = sum(loader, &terms)?;
+            r_inner = Some(match r_inner {
+                Some(r_inner) => {
+                    let t = loader.mul(&r_inner, &y)?;
+                    loader.add(&t, &r_eval)?
+                }
+                None => r_eval,
+            });
+
+            // y^{n-1-j} z_diff v^{m-1-i}
+            let coeff = product(
+                loader,
+                &[
+                    y_powers[commitments.len() - 1 - j].as_ref(),
+                    z_diff.as_ref(),
+                    v_powers[number_of_sets - 1 - i].as_ref(),
+                ],
+            )?;
+            for (point, scalar) in commitment(*committed) {
+                let scalar = match product(loader, &[coeff.as_ref(), scalar.as_ref()])? {
+                    Some(scalar) => scalar,
+                    None => loader.assign_constant(C::Scalar::ONE)?,
+                };
+                outer_msm.push((point, scalar));
+            }
+        }
+
+        let r_inner = r_inner.expect("rotation sets are not empty");
+        let r_inner = product(loader, &[Some(&r_inner), z_diff.as_ref()])?.unwrap();
+        r_outer = Some(match r_outer {
+            Some(r_outer) => {
+                let t = loader.mul(&r_outer, &v)?;
+                loader.add(&t, &r_inner)?
+            }
+            None => r_inner,
+        });
+    }
+
+    let r_outer = r_outer.expect("at least one query");
+    let z_0 = z_0.expect("at least one query");
+    let g1 = loader.assign_constant_point(g1)?;
+    let neg_r_outer = loader.neg_with_constant(&r_outer, C::Scalar::ZERO)?;
+    let neg_z_0 = loader.neg_with_constant(&z_0, C::Scalar::ZERO)?;
+    outer_msm.push((g1, neg_r_outer));
+    outer_msm.push((h1, neg_z_0));
+    outer_msm.push((h2.clone(), u));
+
+    let rhs = loader.msm(outer_msm)?;
+    Ok(KzgAccumulator::new(h2, rhs))
+}
@@ -0,0 +1,284 @@
+use crate::curves::bn256::{Bn256, Fq, Fr, G1Affine, G1};
+use crate::curves::ff::Field;
+use crate::curves::group::{Curve, Group};
+use crate::ecc::integer::rns::Rns;
+use crate::ecc::{BaseFieldEccChip, EccConfig};
+use crate::halo2::circuit::{Layouter, SimpleFloorPlanner, Value};
+use crate::halo2::dev::MockProver;
+use crate::halo2::plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof as halo2_verify_proof, Circuit,
+    ConstraintSystem, Error, VerifyingKey,
+};
+use crate::halo2::poly::kzg::{
+    commitment::{KZGCommitmentScheme, ParamsKZG},
+    multiopen::{ProverSHPLONK, VerifierSHPLONK},
+    strategy::SingleStrategy,
+};
+use crate::maingate::{
+    DimensionMeasurement, MainGate, MainGateConfig, MainGateInstructions, RangeChip, RangeConfig,
+    RangeInstructions, RegionCtx,
+};
+use crate::transcript::{LimbRepresentation, PoseidonTranscript, R_F, R_P};
+use crate::{verify_proof, KzgAccumulator, NativeVerifier, VerifierChip};
+use poseidon::Spec;
+use rand_core::OsRng;
+
+pub(crate) const NUMBER_OF_LIMBS: usize = 4;
+pub(crate) const BIT_LEN_LIMB: usize = 68;
+pub(crate) const WINDOW_SIZE: usize = 4;
+
+pub(crate) type Transcript<S> =
+    PoseidonTranscript<S, G1Affine, Fr, LimbRepresentation, NUMBER_OF_LIMBS, BIT_LEN_LIMB, 3, 2>;
+pub(crate) type Verifier<'a> =
+    NativeVerifier<'a, G1Affine, LimbRepresentation, NUMBER_OF_LIMBS, BIT_LEN_LIMB, 3, 2>;
+
+#[derive(Clone)]
+pub(crate) struct TestCircuitConfig {
+    pub(crate) main_gate_config: MainGateConfig,
+    pub(crate) range_config: RangeConfig,
+}
+
+impl TestCircuitConfig {
+    pub(crate) fn new(
+        meta: &mut ConstraintSystem<Fr>,
+        composition_bit_lens: Vec<usize>,
+        overflow_bit_lens: Vec<usize>,
+    ) -> Self {
+        let main_gate_config = MainGate::<Fr>::configure(meta);
+        let range_config = RangeChip::<Fr>::configure(
+            meta,
+            &main_gate_config,
+            composition_bit_lens,
+            overflow_bit_lens,
+        );
+        TestCircuitConfig {
+            main_gate_config,
+            range_config,
+        }
+    }
+
+    pub(crate) fn ecc_chip_config(&self) -> EccConfig {
+        EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+    }
+
+    pub(crate) fn config_range(&self, layouter: &mut impl Layouter<Fr>) -> Result<(), Error> {
+        let range_chip = RangeChip::<Fr>::new(self.range_config.clone());
+        range_chip.load_table(layouter)?;
+        Ok(())
+    }
+}
+
+/// Proves `c = a * b` for a public `c` and a 16 bit `a`, so that the proof
+/// carries custom gates, copy constraints and lookups
+#[derive(Clone, Default)]
+pub(crate) struct InnerCircuit {
+    pub(crate) a: Value<Fr>,
+    pub(crate) b: Value<Fr>,
+}
+
+impl Circuit<Fr> for InnerCircuit {
+    type Config = TestCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::default()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        TestCircuitConfig::new(meta, vec![8], vec![])
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
+        let range_chip = RangeChip::<Fr>::new(config.range_config.clone());
+        let c = layouter.assign_region(
+            || "region 0",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                let a = range_chip.assign(ctx, self.a, 8, 16)?;
+                let b = main_gate.assign_value(ctx, self.b)?;
+                main_gate.mul(ctx, &a, &b)
+            },
+        )?;
+        config.config_range(&mut layouter)?;
+        main_gate.expose_public(layouter.namespace(|| "c"), c, 0)
+    }
+}
+
+/// Verifies an `InnerCircuit` proof and exposes its public input followed by
+/// the limbs of the accumulator
+struct OuterCircuit<'a> {
+    spec: Spec<Fr, 3, 2>,
+    vk: &'a VerifyingKey<G1Affine>,
+    g1: G1Affine,
+    proof: Value<Vec<u8>>,
+    instance: Value<Fr>,
+    aux_generator: Value<G1Affine>,
+}
+
+impl Circuit<Fr> for OuterCircuit<'_> {
+    type Config = TestCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            spec: self.spec.clone(),
+            vk: self.vk,
+            g1: self.g1,
+            proof: Value::unknown(),
+            instance: Value::unknown(),
+            aux_generator: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let rns = Rns::<Fq, Fr, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct();
+        TestCircuitConfig::new(
+            meta,
+            vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS],
+            rns.overflow_lengths(),
+        )
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = MainGate::<Fr>::new(config.main_gate_config.clone());
+        let ecc_chip = BaseFieldEccChip::<G1Affine, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+            config.ecc_chip_config(),
+        );
+
+        let (instance, lhs, rhs) = layouter.assign_region(
+            || "region 0",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                let instance = main_gate.assign_value(ctx, self.instance)?;
+
+                let mut verifier = VerifierChip::<
+                    _,
+                    LimbRepresentation,
+                    NUMBER_OF_LIMBS,
+                    BIT_LEN_LIMB,
+                    3,
+                    2,
+                >::new(
+                    ctx,
+                    &self.spec,
+                    ecc_chip.clone(),
+                    self.proof.as_ref().map(|proof| proof.as_slice()),
+                    self.aux_generator,
+                    WINDOW_SIZE,
+                )?;
+                let accumulator =
+                    verify_proof(&mut verifier, self.vk, self.g1, &[vec![instance.clone()]])?;
+                let lhs = ecc_chip.normalize(verifier.ctx(), &accumulator.lhs)?;
+                let rhs = ecc_chip.normalize(verifier.ctx(), &accumulator.rhs)?;
+                Ok((instance, lhs, rhs))
+            },
+        )?;
+        config.config_range(&mut layouter)?;
+
+        main_gate.expose_public(layouter.namespace(|| "instance"), instance, 0)?;
+        ecc_chip.expose_public(layouter.namespace(|| "lhs"), lhs, 1)?;
+        ecc_chip.expose_public(layouter.namespace(|| "rhs"), rhs, 1 + 2 * NUMBER_OF_LIMBS)?;
+        Ok(())
+    }
+}
+
+pub(crate) fn native_accumulator(
+    params: &ParamsKZG<Bn256>,
+    vk: &VerifyingKey<G1Affine>,
+    proof: &[u8],
+    instance: Fr,
+) -> Result<KzgAccumulator<G1Affine>, Error> {
+    let mut verifier = Verifier::new(proof);
+    verify_proof(&mut verifier, vk, params.get_g()[0], &[vec![instance]])
+}
+
+#[test]
+fn test_verifier() {
+    let k = 10;
+    let (a, b) = (Fr::from(0xbeef), Fr::random(OsRng));
+    let c = a * b;
+    let inner = InnerCircuit {
+        a: Value::known(a),
+        b: Value::known(b),
+    };
+
+    let params = ParamsKZG::<Bn256>::setup(k, OsRng);
+    let vk = keygen_vk(&params, &InnerCircuit::default()).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &InnerCircuit::default()).unwrap();
+    let mut transcript = Transcript::new(vec![], R_F, R_P);
+    create_proof::<KZGCommitmentScheme<_>, ProverSHPLONK<_>, _, _, _, _>(
+        &params,
+        &pk,
+        &[inner],
+        &[&[&[c]]],
+        OsRng,
+        &mut transcript,
+    )
+    .unwrap();
+    let proof = transcript.into_inner();
+
+    // the proof is valid for halo2's own verifier
+    {
+        let mut transcript = Transcript::new(proof.as_slice(), R_F, R_P);
+        halo2_verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<Bn256>, _, _, _>(
+            &params,
+            &vk,
+            SingleStrategy::new(&params),
+            &[&[&[c]]],
+            &mut transcript,
+        )
+        .unwrap();
+    }
+
+    let accumulator = native_accumulator(&params, &vk, &proof, c).unwrap();
+    assert!(accumulator.decide(&params));
+
+    // Error case: wrong public input
+    {
+        let accumulator = native_accumulator(&params, &vk, &proof, c + Fr::ONE).unwrap();
+        assert!(!accumulator.decide(&params));
+    }
+
+    // Error case: tampered evaluation, the last one before `h1` and `h2`
+    {
+        let mut tampered = proof.clone();
+        let offset = tampered.len() - 3 * 32;
+        tampered[offset] ^= 1;
+        let accumulator = native_accumulator(&params, &vk, &tampered, c);
+        assert!(accumulator.map_or(true, |accumulator| !accumulator.decide(&params)));
+    }
+
+    let public_inputs = |instance: Fr| {
+        let mut public_inputs = vec![instance];
+        public_inputs.extend(accumulator.public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>());
+        vec![public_inputs]
+    };
+    let outer = |instance: Fr| OuterCircuit {
+        spec: Spec::new(R_F, R_P),
+        vk: &vk,
+        g1: params.get_g()[0],
+        proof: Value::known(proof.clone()),
+        instance: Value::known(instance),
+        aux_generator: Value::known(G1::random(OsRng).to_affine()),
+    };
+
+    let circuit = outer(c);
+    let k = DimensionMeasurement::measure(&circuit).unwrap().k();
+    println!("in-circuit verifier k = {}", k);
+    let prover = MockProver::run(k, &circuit, public_inputs(c)).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Error case: the proof does not verify against another public input
+    let circuit = outer(c + Fr::ONE);
+    let prover = MockProver::run(k, &circuit, public_inputs(c + Fr::ONE)).unwrap();
+    assert!(prover.verify().is_err());
+}
@@ -1,9 +1,10 @@
+use halo2_proofs::circuit::Value;
 use halo2_proofs::dev::MockProver;
 use halo2_proofs::halo2curves::{
-    bn256::{Bn256, Fr as Fp, G1Affine},
-    group::ff::PrimeField,
+    bn256::{Bn256, Fq, Fr as Fp, G1Affine, G1},
+    group::{ff::PrimeField, Curve, Group},
 };
-use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof};
+use halo2_proofs::plonk::{create_proof, keygen_pk, keygen_vk, verify_proof, VerifyingKey};
 use halo2_proofs::poly::commitment::ParamsProver;
 use halo2_proofs::poly::kzg::commitment::{
     KZGCommitmentScheme, ParamsKZG as Params, ParamsVerifierKZG as ParamsVerifier,
@@ -21,6 +22,14 @@ use poseidon_circuit::poseidon::Pow5Chip;
 use poseidon_circuit::{hash::*, DEFAULT_STEP};
 use rand::SeedableRng;
 use rand_chacha::ChaCha8Rng;
+use recreds::sizing::measure;
+use transcript::{LimbRepresentation, R_F, R_P};
+use verifier::ecc::integer::rns::Rns;
+use verifier::ecc::{BaseFieldEccChip, EccConfig};
+use verifier::maingate::{
+    MainGate, MainGateConfig, RangeChip, RangeConfig, RangeInstructions, RegionCtx,
+};
+use verifier::{NativeVerifier, Spec, VerifierChip};
 
 struct TestCircuit(PoseidonHashTable<Fp>, usize);
 
@@ -61,7 +70,6 @@ fn hash_circuit() {
         Fp::from_str_vartime("1").unwrap(),
     ];
 
-    let k = 7;
     let circuit = TestCircuit(
         PoseidonHashTable {
             inputs: vec![message1, message2],
@@ -69,6 +77,9 @@ fn hash_circuit() {
         },
         3,
     );
+    let size = measure(&circuit).unwrap();
+    assert!(size.rows >= circuit.0.minimum_row_require());
+    let k = size.k;
     let prover = MockProver::run(k, &circuit, vec![]).unwrap();
     assert_eq!(prover.verify(), Ok(()));
 }
@@ -77,7 +88,8 @@ fn hash_circuit() {
 fn vk_validity() {
     use halo2_proofs::SerdeFormat;
 
-    let params = Params::<Bn256>::unsafe_setup(8);
+    let k = measure(&TestCircuit(PoseidonHashTable::default(), 3)).unwrap().k;
+    let params = Params::<Bn256>::unsafe_setup(k);
 
     let circuit = TestCircuit(PoseidonHashTable::default(), 3);
     let vk1 = keygen_vk(&params, &circuit).unwrap();
@@ -113,7 +125,7 @@ fn vk_validity() {
 
 #[test]
 fn proof_and_verify() {
-    let k = 8;
+    let k = measure(&TestCircuit(PoseidonHashTable::default(), 4)).unwrap().k;
 
     let params = Params::<Bn256>::unsafe_setup(k);
     let os_rng = ChaCha8Rng::from_seed([101u8; 32]);
@@ -173,3 +185,172 @@ fn proof_and_verify() {
     );
 }
 
+const NUMBER_OF_LIMBS: usize = 4;
+const BIT_LEN_LIMB: usize = 68;
+
+type PoseidonTranscript<S> = transcript::PoseidonTranscript<
+    S,
+    G1Affine,
+    Fp,
+    LimbRepresentation,
+    NUMBER_OF_LIMBS,
+    BIT_LEN_LIMB,
+    3,
+    2,
+>;
+type Verifier<'a> =
+    NativeVerifier<'a, G1Affine, LimbRepresentation, NUMBER_OF_LIMBS, BIT_LEN_LIMB, 3, 2>;
+
+#[derive(Clone)]
+struct VerifierCircuitConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+/// Verifies a `TestCircuit` proof and exposes the limbs of the accumulator
+struct VerifierCircuit<'a> {
+    vk: &'a VerifyingKey<G1Affine>,
+    g1: G1Affine,
+    proof: Value<Vec<u8>>,
+    aux_generator: Value<G1Affine>,
+}
+
+impl Circuit<Fp> for VerifierCircuit<'_> {
+    type Config = VerifierCircuitConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            vk: self.vk,
+            g1: self.g1,
+            proof: Value::unknown(),
+            aux_generator: Value::unknown(),
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fp>) -> Self::Config {
+        let rns = Rns::<Fq, Fp, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct();
+        let main_gate_config = MainGate::<Fp>::configure(meta);
+        let range_config = RangeChip::<Fp>::configure(
+            meta,
+            &main_gate_config,
+            vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS],
+            rns.overflow_lengths(),
+        );
+        VerifierCircuitConfig {
+            main_gate_config,
+            range_config,
+        }
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fp>,
+    ) -> Result<(), Error> {
+        let ecc_chip = BaseFieldEccChip::<G1Affine, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+            EccConfig::new(config.range_config.clone(), config.main_gate_config.clone()),
+        );
+        let spec = Spec::<Fp, 3, 2>::new(R_F, R_P);
+
+        let (lhs, rhs) = layouter.assign_region(
+            || "verifier",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                let mut verifier = VerifierChip::<
+                    _,
+                    LimbRepresentation,
+                    NUMBER_OF_LIMBS,
+                    BIT_LEN_LIMB,
+                    3,
+                    2,
+                >::new(
+                    ctx,
+                    &spec,
+                    ecc_chip.clone(),
+                    self.proof.as_ref().map(|proof| proof.as_slice()),
+                    self.aux_generator,
+                    4,
+                )?;
+                let accumulator = verifier::verify_proof(&mut verifier, self.vk, self.g1, &[])?;
+                let lhs = ecc_chip.normalize(verifier.ctx(), &accumulator.lhs)?;
+                let rhs = ecc_chip.normalize(verifier.ctx(), &accumulator.rhs)?;
+                Ok((lhs, rhs))
+            },
+        )?;
+
+        let range_chip = RangeChip::<Fp>::new(config.range_config);
+        range_chip.load_table(&mut layouter)?;
+
+        ecc_chip.expose_public(layouter.namespace(|| "lhs"), lhs, 0)?;
+        ecc_chip.expose_public(layouter.namespace(|| "rhs"), rhs, 2 * NUMBER_OF_LIMBS)
+    }
+}
+
+#[test]
+fn proof_and_verify_in_circuit() {
+    let k = measure(&TestCircuit(PoseidonHashTable::default(), 4)).unwrap().k;
+
+    let params = Params::<Bn256>::unsafe_setup(k);
+    let os_rng = ChaCha8Rng::from_seed([101u8; 32]);
+    let circuit = TestCircuit(
+        PoseidonHashTable {
+            inputs: vec![
+                [
+                    Fp::from_str_vartime("1").unwrap(),
+                    Fp::from_str_vartime("2").unwrap(),
+                ],
+                [
+                    Fp::from_str_vartime("30").unwrap(),
+                    Fp::from_str_vartime("1").unwrap(),
+                ],
+                [Fp::from_str_vartime("65536").unwrap(), Fp::zero()],
+            ],
+            controls: vec![0, 46, 14],
+            ..Default::default()
+        },
+        4,
+    );
+
+    let vk = keygen_vk(&params, &circuit).unwrap();
+    let pk = keygen_pk(&params, vk.clone(), &circuit).unwrap();
+
+    let mut transcript = PoseidonTranscript::new(vec![], R_F, R_P);
+    create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+        &params,
+        &pk,
+        &[circuit],
+        &[&[]],
+        os_rng,
+        &mut transcript,
+    )
+    .unwrap();
+    let proof = transcript.into_inner();
+
+    // the accumulator of the native run passes the pairing check
+    let g1 = params.get_g()[0];
+    let accumulator = verifier::verify_proof(&mut Verifier::new(&proof), &vk, g1, &[]).unwrap();
+    assert!(accumulator.decide(&params));
+
+    let public_inputs = accumulator.public::<NUMBER_OF_LIMBS, BIT_LEN_LIMB>();
+
+    let verifier_circuit = |proof: Vec<u8>| VerifierCircuit {
+        vk: &vk,
+        g1,
+        proof: Value::known(proof),
+        aux_generator: Value::known(G1::random(ChaCha8Rng::from_seed([7u8; 32])).to_affine()),
+    };
+
+    let circuit = verifier_circuit(proof.clone());
+    let k = measure(&circuit).unwrap().k;
+    let prover = MockProver::run(k, &circuit, vec![public_inputs.clone()]).unwrap();
+    assert_eq!(prover.verify(), Ok(()));
+
+    // Error case: tampered evaluation, the last one before `h1` and `h2`
+    let mut tampered = proof;
+    let offset = tampered.len() - 3 * 32;
+    tampered[offset] ^= 1;
+    let circuit = verifier_circuit(tampered);
+    assert!(MockProver::run(k, &circuit, vec![public_inputs])
+        .map_or(true, |prover| prover.verify().is_err()));
+}
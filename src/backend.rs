@@ -0,0 +1,284 @@
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr as BnScalar, G1Affine};
+use halo2_proofs::halo2curves::ff::FromUniformBytes;
+use halo2_proofs::halo2curves::pasta::{EqAffine, Fp as PastaFp};
+use halo2_proofs::plonk::{
+    create_proof, keygen_pk, keygen_vk, verify_proof, Circuit, Error, ProvingKey, VerifyingKey,
+};
+use halo2_proofs::poly::commitment::{Params, ParamsProver};
+use halo2_proofs::poly::ipa::{
+    commitment::{IPACommitmentScheme, ParamsIPA},
+    multiopen::{ProverIPA, VerifierIPA},
+    strategy::AccumulatorStrategy as IpaAccumulatorStrategy,
+};
+use halo2_proofs::poly::kzg::{
+    commitment::{KZGCommitmentScheme, ParamsKZG},
+    multiopen::{ProverSHPLONK, VerifierSHPLONK},
+    strategy::AccumulatorStrategy as KzgAccumulatorStrategy,
+};
+use halo2_proofs::poly::VerificationStrategy;
+use halo2_proofs::transcript::{
+    Blake2bRead, Blake2bWrite, Challenge255, TranscriptReadBuffer, TranscriptWriterBuffer,
+};
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use rand_core::RngCore;
+use std::sync::Mutex;
+
+/// A polynomial commitment scheme and the curve it commits over.
+///
+/// Circuits are written over `Self::Scalar` and proofs use a Blake2b
+/// transcript, so a credential circuit that is generic over its field runs
+/// unchanged on every backend.
+pub trait Backend {
+    /// Name of the backend in artifacts and reports
+    const NAME: &'static str;
+
+    type Scalar: FromUniformBytes<64> + Ord;
+    type Curve: CurveAffine<ScalarExt = Self::Scalar>;
+    type Params: Clone + Sync;
+
+    /// Generates parameters for circuits of `2^k` rows
+    fn setup(k: u32, rng: impl RngCore) -> Self::Params;
+
+    /// Returns `k` of the parameters
+    fn k(params: &Self::Params) -> u32;
+
+    fn keygen<ConcreteCircuit: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        circuit: &ConcreteCircuit,
+    ) -> Result<ProvingKey<Self::Curve>, Error>;
+
+    fn prove<ConcreteCircuit: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: ConcreteCircuit,
+        instances: &[Vec<Self::Scalar>],
+        rng: impl RngCore,
+    ) -> Result<Vec<u8>, Error>;
+
+    /// Proves `circuits` with their instances in parallel, sharing `params`
+    /// and `pk`, and returns the result of each in order.
+    ///
+    /// `max_in_flight` provers, at least one, take circuits from a queue
+    /// over the iterator and take the next one as soon as their proof is
+    /// done, so at most that many hold their polynomials at once whatever
+    /// the size of the rayon pool they run in. Each proof draws its
+    /// randomness from its own generator, seeded from `rng` in the order of
+    /// `circuits`.
+    fn prove_batch<ConcreteCircuit, Circuits>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuits: Circuits,
+        max_in_flight: usize,
+        rng: impl RngCore + Send,
+    ) -> Vec<Result<Vec<u8>, Error>>
+    where
+        ConcreteCircuit: Circuit<Self::Scalar> + Send,
+        Circuits: IntoIterator<Item = (ConcreteCircuit, Vec<Vec<Self::Scalar>>)>,
+        Circuits::IntoIter: Send,
+    {
+        let queue = Mutex::new((circuits.into_iter().enumerate(), rng));
+        let proofs = Mutex::new(vec![]);
+        rayon::scope(|scope| {
+            for _ in 0..max_in_flight.max(1) {
+                scope.spawn(|_| loop {
+                    let next = {
+                        let (circuits, rng) = &mut *queue.lock().unwrap();
+                        circuits.next().map(|(i, (circuit, instances))| {
+                            let mut seed = [0u8; 32];
+                            rng.fill_bytes(&mut seed);
+                            (i, circuit, instances, seed)
+                        })
+                    };
+                    let (i, circuit, instances, seed) = match next {
+                        Some(next) => next,
+                        None => break,
+                    };
+                    let proof = Self::prove(
+                        params,
+                        pk,
+                        circuit,
+                        &instances,
+                        ChaCha20Rng::from_seed(seed),
+                    );
+                    proofs.lock().unwrap().push((i, proof));
+                });
+            }
+        });
+        let mut proofs = proofs.into_inner().unwrap();
+        proofs.sort_by_key(|(i, _)| *i);
+        proofs.into_iter().map(|(_, proof)| proof).collect()
+    }
+
+    /// Verifies proofs of the same circuit with a single final check.
+    ///
+    /// The opening check of each proof is accumulated rather than decided:
+    /// the multi scalar multiplication of IPA and the pairing of KZG.
+    /// The accumulator is checked by the verifier, not in a circuit.
+    fn verify_batch(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        proofs: &[(&[Vec<Self::Scalar>], &[u8])],
+    ) -> Result<(), Error>;
+
+    fn verify(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        instances: &[Vec<Self::Scalar>],
+        proof: &[u8],
+    ) -> Result<(), Error> {
+        Self::verify_batch(params, vk, &[(instances, proof)])
+    }
+}
+
+/// KZG with SHPLONK over BN254. The setup is trusted, `setup` is for tests
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Kzg;
+
+/// IPA over the Pasta curves; commitments are on Vesta so circuits are over
+/// its scalar field. The setup is transparent.
+///
+/// Accumulation is native only: `verify_batch` defers the final MSM of each
+/// proof, but there is no in-circuit IPA verifier, so Halo style recursion
+/// over the Pasta cycle is not available on this backend
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Ipa;
+
+impl Backend for Kzg {
+    const NAME: &'static str = "kzg-bn254";
+
+    type Scalar = BnScalar;
+    type Curve = G1Affine;
+    type Params = ParamsKZG<Bn256>;
+
+    fn setup(k: u32, rng: impl RngCore) -> Self::Params {
+        ParamsKZG::<Bn256>::setup(k, rng)
+    }
+
+    fn k(params: &Self::Params) -> u32 {
+        params.k()
+    }
+
+    fn keygen<ConcreteCircuit: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        circuit: &ConcreteCircuit,
+    ) -> Result<ProvingKey<Self::Curve>, Error> {
+        let vk = keygen_vk(params, circuit)?;
+        keygen_pk(params, vk, circuit)
+    }
+
+    fn prove<ConcreteCircuit: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: ConcreteCircuit,
+        instances: &[Vec<Self::Scalar>],
+        rng: impl RngCore,
+    ) -> Result<Vec<u8>, Error> {
+        let instances: Vec<&[BnScalar]> = instances.iter().map(Vec::as_slice).collect();
+        let mut transcript = Blake2bWrite::<_, G1Affine, Challenge255<_>>::init(vec![]);
+        create_proof::<KZGCommitmentScheme<Bn256>, ProverSHPLONK<'_, Bn256>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[instances.as_slice()],
+            rng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    fn verify_batch(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        proofs: &[(&[Vec<Self::Scalar>], &[u8])],
+    ) -> Result<(), Error> {
+        let mut strategy = KzgAccumulatorStrategy::new(params);
+        for (instances, proof) in proofs.iter() {
+            let instances: Vec<&[BnScalar]> = instances.iter().map(Vec::as_slice).collect();
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(*proof);
+            strategy =
+                verify_proof::<KZGCommitmentScheme<Bn256>, VerifierSHPLONK<'_, Bn256>, _, _, _>(
+                    params,
+                    vk,
+                    strategy,
+                    &[instances.as_slice()],
+                    &mut transcript,
+                )?;
+        }
+        if strategy.finalize() {
+            Ok(())
+        } else {
+            Err(Error::ConstraintSystemFailure)
+        }
+    }
+}
+
+impl Backend for Ipa {
+    const NAME: &'static str = "ipa-pasta";
+
+    type Scalar = PastaFp;
+    type Curve = EqAffine;
+    type Params = ParamsIPA<EqAffine>;
+
+    fn setup(k: u32, _: impl RngCore) -> Self::Params {
+        ParamsIPA::<EqAffine>::new(k)
+    }
+
+    fn k(params: &Self::Params) -> u32 {
+        params.k()
+    }
+
+    fn keygen<ConcreteCircuit: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        circuit: &ConcreteCircuit,
+    ) -> Result<ProvingKey<Self::Curve>, Error> {
+        let vk = keygen_vk(params, circuit)?;
+        keygen_pk(params, vk, circuit)
+    }
+
+    fn prove<ConcreteCircuit: Circuit<Self::Scalar>>(
+        params: &Self::Params,
+        pk: &ProvingKey<Self::Curve>,
+        circuit: ConcreteCircuit,
+        instances: &[Vec<Self::Scalar>],
+        rng: impl RngCore,
+    ) -> Result<Vec<u8>, Error> {
+        let instances: Vec<&[PastaFp]> = instances.iter().map(Vec::as_slice).collect();
+        let mut transcript = Blake2bWrite::<_, EqAffine, Challenge255<_>>::init(vec![]);
+        create_proof::<IPACommitmentScheme<EqAffine>, ProverIPA<'_, EqAffine>, _, _, _, _>(
+            params,
+            pk,
+            &[circuit],
+            &[instances.as_slice()],
+            rng,
+            &mut transcript,
+        )?;
+        Ok(transcript.finalize())
+    }
+
+    fn verify_batch(
+        params: &Self::Params,
+        vk: &VerifyingKey<Self::Curve>,
+        proofs: &[(&[Vec<Self::Scalar>], &[u8])],
+    ) -> Result<(), Error> {
+        let mut strategy = IpaAccumulatorStrategy::new(params);
+        for (instances, proof) in proofs.iter() {
+            let instances: Vec<&[PastaFp]> = instances.iter().map(Vec::as_slice).collect();
+            let mut transcript = Blake2bRead::<_, _, Challenge255<_>>::init(*proof);
+            strategy =
+                verify_proof::<IPACommitmentScheme<EqAffine>, VerifierIPA<'_, EqAffine>, _, _, _>(
+                    params,
+                    vk,
+                    strategy,
+                    &[instances.as_slice()],
+                    &mut transcript,
+                )?;
+        }
+        if strategy.finalize() {
+            Ok(())
+        } else {
+            Err(Error::ConstraintSystemFailure)
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use clap::Parser;
+use recreds::service::{bind, Service};
+use recreds::wire::Wire;
+use recreds::{KeyStore, ParamsCache};
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+
+/// Local issuance and verification service with stand-in issuers
+#[derive(Debug, Parser)]
+#[command(name = "recreds-server", version)]
+struct Args {
+    /// Loopback address to listen on
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    addr: SocketAddr,
+    /// KZG params in the halo2 format or a .ptau file
+    #[arg(long)]
+    params: PathBuf,
+    /// Largest k of the circuits to verify; params are truncated to it
+    #[arg(long)]
+    max_k: u32,
+    /// Directory of the proving and verifying keys
+    #[arg(long, default_value = "keys")]
+    keys: PathBuf,
+    /// Directory of the issuer secret keys
+    #[arg(long, default_value = "issuers")]
+    issuers: PathBuf,
+    /// Stand-in issuers to create if they do not exist yet
+    #[arg(long = "issuer")]
+    create: Vec<String>,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let server = bind(args.addr)?;
+    let service = Service::new(
+        ParamsCache::open(&args.params, args.max_k)?,
+        KeyStore::new(&args.keys)?,
+        &args.issuers,
+    )?;
+    for id in args.create.iter() {
+        let pk = service.add_issuer(id)?;
+        println!("issuer {}: {}", id, pk.to_json());
+    }
+    println!("listening on http://{}", args.addr);
+    service.serve(&server);
+    Ok(())
+}
@@ -0,0 +1,508 @@
+use clap::{Args, Parser, Subcommand};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::halo2curves::ff::PrimeField;
+use halo2_proofs::plonk::{ProvingKey, VerifyingKey};
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use rand::rngs::OsRng;
+use recreds::params::{read_ptau, verify_params, write_params};
+use recreds::sizing::{estimate_k, report};
+use recreds::wire::{field_from_hex, field_to_hex};
+use recreds::{
+    present, verify_presentation, Backend, Credential, IssuerPublicKey, IssuerSecretKey, KeyStore,
+    Kzg, ParamsCache, Presentation, PresentationCircuit, PresentationPolicy, Schema,
+    SchemaRegistry, Wire,
+};
+use serde_json::Value;
+use std::error::Error;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Instant;
+
+type Result<T> = std::result::Result<T, Box<dyn Error>>;
+
+/// Issue, present and verify Re2creds credentials.
+///
+/// Artifacts are files, written as JSON when their name ends in `.json` and
+/// in the binary encoding otherwise. Proofs use the KZG backend
+#[derive(Debug, Parser)]
+#[command(name = "recreds", version)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Write KZG params, converted from a .ptau file or generated insecurely
+    /// for tests
+    Setup {
+        #[arg(long)]
+        k: u32,
+        #[arg(long)]
+        ptau: Option<PathBuf>,
+        #[arg(long)]
+        out: PathBuf,
+    },
+    #[command(subcommand)]
+    Schema(SchemaCommand),
+    #[command(subcommand)]
+    Issuer(IssuerCommand),
+    #[command(subcommand)]
+    Holder(HolderCommand),
+    #[command(subcommand)]
+    Verifier(VerifierCommand),
+    /// Decode any artifact and print it as JSON
+    Inspect { file: PathBuf },
+    /// Print the rows, columns, gates and lookups of a presentation circuit
+    Report {
+        #[arg(long, default_value_t = 4)]
+        attributes: usize,
+        /// Comma separated positions of the disclosed attributes
+        #[arg(long, value_delimiter = ',', default_value = "0")]
+        disclosed: Vec<usize>,
+        #[arg(long)]
+        json: bool,
+    },
+    /// Time key generation, proving and verification of a presentation
+    Bench {
+        #[command(flatten)]
+        keys: KeyArgs,
+        #[arg(long, default_value_t = 4)]
+        attributes: usize,
+        /// Comma separated positions of the disclosed attributes
+        #[arg(long, value_delimiter = ',', default_value = "0")]
+        disclosed: Vec<usize>,
+        #[arg(long, default_value_t = 3)]
+        runs: usize,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum SchemaCommand {
+    /// Register a schema, or a new version of a registered schema, and print
+    /// its hash
+    Register {
+        file: PathBuf,
+        #[arg(long, default_value = "schemas")]
+        registry: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum IssuerCommand {
+    /// Generate an issuer key pair
+    Keygen {
+        #[arg(long)]
+        out: PathBuf,
+        #[arg(long = "pub")]
+        public: PathBuf,
+    },
+    /// Issue a credential
+    Issue {
+        #[arg(long)]
+        key: PathBuf,
+        /// Schema of the credential, registered on issuance
+        #[arg(long)]
+        schema: PathBuf,
+        /// Attribute as `name=value`, once per attribute
+        #[arg(long = "attr")]
+        attrs: Vec<String>,
+        #[arg(long, default_value = "schemas")]
+        registry: PathBuf,
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum HolderCommand {
+    /// Present a credential to a verifier
+    Show {
+        #[command(flatten)]
+        keys: KeyArgs,
+        #[arg(long)]
+        credential: PathBuf,
+        /// Comma separated names of the disclosed attributes, from the schema
+        /// of the credential
+        #[arg(long, value_delimiter = ',', conflicts_with = "policy")]
+        disclose: Vec<String>,
+        /// Comma separated positions of the disclosed attributes, for
+        /// credentials without a registered schema
+        #[arg(long, value_delimiter = ',')]
+        policy: Vec<usize>,
+        #[arg(long, default_value = "schemas")]
+        registry: PathBuf,
+        /// Challenge of the verifier, decimal or canonical hex
+        #[arg(long)]
+        nonce: String,
+        #[arg(long)]
+        out: PathBuf,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum VerifierCommand {
+    /// Verify a presentation
+    Verify {
+        #[command(flatten)]
+        keys: KeyArgs,
+        #[arg(long)]
+        presentation: PathBuf,
+        /// Issuer public key the presentation must come from
+        #[arg(long)]
+        issuer: PathBuf,
+        /// Challenge the presentation must answer
+        #[arg(long)]
+        nonce: String,
+        /// Schemas used to name the disclosed attributes
+        #[arg(long, default_value = "schemas")]
+        registry: PathBuf,
+    },
+}
+
+#[derive(Debug, Args)]
+struct KeyArgs {
+    /// KZG params in the halo2 format or a .ptau file
+    #[arg(long)]
+    params: PathBuf,
+    /// Directory of the proving and verifying keys
+    #[arg(long, default_value = "keys")]
+    keys: PathBuf,
+}
+
+impl KeyArgs {
+    /// Params downsized to the circuit of `policy`, verified after loading
+    fn params(&self, policy: &PresentationPolicy) -> Result<Arc<ParamsKZG<Bn256>>> {
+        let k = estimate_k::<Fr>(policy, None)?.k;
+        Ok(ParamsCache::open(&self.params, k)?.get(k)?)
+    }
+
+    fn proving_key(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        policy: &PresentationPolicy,
+    ) -> Result<ProvingKey<G1Affine>> {
+        let circuit = PresentationCircuit::new(policy.clone());
+        Ok(KeyStore::new(&self.keys)?.load_or_generate(params, &policy.circuit_id(), &circuit)?)
+    }
+
+    fn verifying_key(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        policy: &PresentationPolicy,
+    ) -> Result<VerifyingKey<G1Affine>> {
+        let circuit = PresentationCircuit::new(policy.clone());
+        Ok(KeyStore::new(&self.keys)?.load_or_generate_vk(
+            params,
+            &policy.circuit_id(),
+            &circuit,
+        )?)
+    }
+}
+
+fn plonk_error(e: halo2_proofs::plonk::Error) -> Box<dyn Error> {
+    format!("proof system error: {:?}", e).into()
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .map_or(false, |extension| extension == "json")
+}
+
+fn write_artifact<T: Wire>(path: &Path, artifact: &T) -> Result<()> {
+    if is_json(path) {
+        fs::write(path, artifact.to_json())?;
+    } else {
+        fs::write(path, artifact.to_bytes())?;
+    }
+    Ok(())
+}
+
+/// Writes a secret artifact to a file that only the owner can read
+fn write_secret_artifact<T: Wire>(path: &Path, artifact: &T) -> Result<()> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path)?;
+    // the mode above only applies when the file is created
+    #[cfg(unix)]
+    file.set_permissions(std::os::unix::fs::PermissionsExt::from_mode(0o600))?;
+    if is_json(path) {
+        file.write_all(artifact.to_json().as_bytes())?;
+    } else {
+        file.write_all(&artifact.to_bytes())?;
+    }
+    Ok(())
+}
+
+fn read_artifact<T: Wire>(path: &Path) -> Result<T> {
+    let bytes = fs::read(path)?;
+    if bytes.starts_with(b"RCW") {
+        Ok(T::from_bytes(&bytes)?)
+    } else {
+        Ok(T::from_json(std::str::from_utf8(&bytes)?)?)
+    }
+}
+
+fn parse_field(value: &str) -> Result<Fr> {
+    if value.starts_with("0x") {
+        Ok(field_from_hex(value)?)
+    } else {
+        Fr::from_str_vartime(value).ok_or_else(|| format!("invalid field element {}", value).into())
+    }
+}
+
+fn print_json(json: &str) -> Result<()> {
+    let value: Value = serde_json::from_str(json)?;
+    println!("{}", serde_json::to_string_pretty(&value)?);
+    Ok(())
+}
+
+fn inspect(path: &Path) -> Result<()> {
+    let bytes = fs::read(path)?;
+    let kind = if bytes.starts_with(b"RCW") {
+        match bytes.get(4) {
+            Some(kind) if *kind == PresentationPolicy::KIND => PresentationPolicy::NAME,
+            Some(kind) if *kind == IssuerPublicKey::KIND => IssuerPublicKey::NAME,
+            Some(kind) if *kind == IssuerSecretKey::KIND => IssuerSecretKey::NAME,
+            Some(kind) if *kind == Credential::<Fr>::KIND => Credential::<Fr>::NAME,
+            Some(kind) if *kind == Presentation::<Fr>::KIND => Presentation::<Fr>::NAME,
+            Some(kind) if *kind == Schema::KIND => Schema::NAME,
+            _ => return Err("unknown artifact".into()),
+        }
+        .to_string()
+    } else {
+        let value: Value = serde_json::from_slice(&bytes)?;
+        value["type"].as_str().ok_or("not an artifact")?.to_string()
+    };
+
+    if kind == PresentationPolicy::NAME {
+        print_json(&read_artifact::<PresentationPolicy>(path)?.to_json())?;
+    } else if kind == IssuerPublicKey::NAME {
+        print_json(&read_artifact::<IssuerPublicKey>(path)?.to_json())?;
+    } else if kind == IssuerSecretKey::NAME {
+        read_artifact::<IssuerSecretKey>(path)?;
+        println!("issuer secret key, not printed");
+    } else if kind == Credential::<Fr>::NAME {
+        let credential = read_artifact::<Credential<Fr>>(path)?;
+        print_json(&credential.to_json())?;
+        println!("issuer signature valid: {}", credential.verify());
+    } else if kind == Presentation::<Fr>::NAME {
+        let presentation = read_artifact::<Presentation<Fr>>(path)?;
+        print_json(&presentation.to_json())?;
+        println!("circuit: {}", presentation.policy.circuit_id());
+        println!("public inputs: {}", presentation.public_inputs().len());
+        println!("proof size: {} bytes", presentation.proof.len());
+    } else if kind == Schema::NAME {
+        let schema = read_artifact::<Schema>(path)?;
+        print_json(&schema.to_json())?;
+        println!("hash: {}", field_to_hex(&schema.hash::<Fr>()));
+    } else {
+        return Err(format!("unknown artifact type {}", kind).into());
+    }
+    Ok(())
+}
+
+fn bench(keys: &KeyArgs, policy: PresentationPolicy, runs: usize) -> Result<()> {
+    let params = keys.params(&policy)?;
+    println!(
+        "circuit {} with k = {}",
+        policy.circuit_id(),
+        Kzg::k(&params)
+    );
+
+    let start = Instant::now();
+    let pk =
+        Kzg::keygen(&params, &PresentationCircuit::new(policy.clone())).map_err(plonk_error)?;
+    println!("keygen: {:?}", start.elapsed());
+
+    let sk = IssuerSecretKey::random(OsRng);
+    let attributes = (1..=policy.num_attributes as u64).map(Fr::from).collect();
+    let credential = Credential::issue(&sk, attributes, OsRng);
+    for run in 0..runs {
+        let start = Instant::now();
+        let presentation = present::<Kzg>(
+            &params,
+            &pk,
+            &credential,
+            &policy,
+            Fr::from(run as u64),
+            OsRng,
+        )
+        .map_err(plonk_error)?;
+        let proving = start.elapsed();
+        let start = Instant::now();
+        verify_presentation::<Kzg>(&params, pk.get_vk(), &presentation).map_err(plonk_error)?;
+        println!(
+            "run {}: prove {:?}, verify {:?}, proof size {} bytes",
+            run,
+            proving,
+            start.elapsed(),
+            presentation.proof.len()
+        );
+    }
+    Ok(())
+}
+
+pub fn run(cli: Cli) -> Result<()> {
+    match cli.command {
+        Command::Setup { k, ptau, out } => {
+            let params = match ptau {
+                Some(ptau) => read_ptau(ptau, k)?,
+                None => {
+                    eprintln!("warning: generating params with a known secret, for tests only");
+                    Kzg::setup(k, OsRng)
+                }
+            };
+            verify_params(&params)?;
+            write_params(&params, out)?;
+        }
+        Command::Issuer(IssuerCommand::Keygen { out, public }) => {
+            let sk = IssuerSecretKey::random(OsRng);
+            write_secret_artifact(&out, &sk)?;
+            write_artifact(&public, &sk.public_key())?;
+        }
+        Command::Schema(SchemaCommand::Register { file, registry }) => {
+            let schema: Schema = read_artifact(&file)?;
+            let hash = SchemaRegistry::new(&registry)?.register(&schema)?;
+            println!("{}", field_to_hex(&hash));
+        }
+        Command::Issuer(IssuerCommand::Issue {
+            key,
+            schema,
+            attrs,
+            registry,
+            out,
+        }) => {
+            let schema: Schema = read_artifact(&schema)?;
+            let values = attrs
+                .iter()
+                .map(|attribute| {
+                    attribute
+                        .split_once('=')
+                        .ok_or_else(|| format!("expected name=value, found {}", attribute).into())
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let sk: IssuerSecretKey = read_artifact(&key)?;
+            let attributes: Vec<Fr> = schema.encode(&values)?;
+            SchemaRegistry::new(&registry)?.register(&schema)?;
+            let credential = schema.issue(&sk, attributes, OsRng)?;
+            write_artifact(&out, &credential)?;
+        }
+        Command::Holder(HolderCommand::Show {
+            keys,
+            credential,
+            disclose,
+            policy,
+            registry,
+            nonce,
+            out,
+        }) => {
+            let credential: Credential<Fr> = read_artifact(&credential)?;
+            let policy = if policy.is_empty() {
+                let schema = match credential.attributes.first() {
+                    Some(hash) => SchemaRegistry::new(&registry)?.get(hash)?,
+                    None => return Err("credential has no attributes".into()),
+                };
+                schema.validate(&credential.attributes)?;
+                let names: Vec<&str> = disclose.iter().map(String::as_str).collect();
+                schema.policy(&names)?
+            } else {
+                let policy = PresentationPolicy {
+                    num_attributes: credential.attributes.len(),
+                    disclosed: policy,
+                };
+                if !policy.is_valid() {
+                    return Err("disclosed positions must be increasing and in range".into());
+                }
+                policy
+            };
+            let params = keys.params(&policy)?;
+            let pk = keys.proving_key(&params, &policy)?;
+            let presentation = present::<Kzg>(
+                &params,
+                &pk,
+                &credential,
+                &policy,
+                parse_field(&nonce)?,
+                OsRng,
+            )
+            .map_err(plonk_error)?;
+            write_artifact(&out, &presentation)?;
+        }
+        Command::Verifier(VerifierCommand::Verify {
+            keys,
+            presentation,
+            issuer,
+            nonce,
+            registry,
+        }) => {
+            let presentation: Presentation<Fr> = read_artifact(&presentation)?;
+            if read_artifact::<IssuerPublicKey>(&issuer)? != presentation.issuer {
+                return Err("presentation is from another issuer".into());
+            }
+            if parse_field(&nonce)? != presentation.nonce {
+                return Err("presentation answers another challenge".into());
+            }
+            let params = keys.params(&presentation.policy)?;
+            let vk = keys.verifying_key(&params, &presentation.policy)?;
+            verify_presentation::<Kzg>(&params, &vk, &presentation)
+                .map_err(|_| "invalid presentation")?;
+            println!("valid presentation");
+            let schema = SchemaRegistry::new(&registry)?.resolve(&presentation).ok();
+            if let Some(schema) = &schema {
+                println!("schema: {} version {}", schema.name, schema.version);
+            }
+            for (i, value) in presentation
+                .policy
+                .disclosed
+                .iter()
+                .zip(presentation.disclosed.iter())
+            {
+                match &schema {
+                    Some(schema) => println!("{}: {}", schema.names()[*i], field_to_hex(value)),
+                    None => println!("attribute {}: {}", i, field_to_hex(value)),
+                }
+            }
+        }
+        Command::Inspect { file } => inspect(&file)?,
+        Command::Report {
+            attributes,
+            disclosed,
+            json,
+        } => {
+            let policy = PresentationPolicy {
+                num_attributes: attributes,
+                disclosed,
+            };
+            if !policy.is_valid() {
+                return Err("disclosed positions must be increasing and in range".into());
+            }
+            let report = report(&PresentationCircuit::<Fr>::new(policy)).map_err(plonk_error)?;
+            if json {
+                println!("{}", serde_json::to_string_pretty(&report.to_json())?);
+            } else {
+                print!("{}", report);
+            }
+        }
+        Command::Bench {
+            keys,
+            attributes,
+            disclosed,
+            runs,
+        } => {
+            let policy = PresentationPolicy {
+                num_attributes: attributes,
+                disclosed,
+            };
+            if !policy.is_valid() {
+                return Err("disclosed positions must be increasing and in range".into());
+            }
+            bench(&keys, policy, runs)?;
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,467 @@
+use crate::backend::Backend;
+use ecc::integer::rns::Rns;
+use ecc::integer::{IntegerInstructions, Range};
+use ecc::maingate::{
+    MainGate, MainGateConfig, MainGateInstructions, RangeChip, RangeConfig, RangeInstructions,
+    RegionCtx,
+};
+use ecc::{EccConfig, GeneralEccChip, Point};
+use ecdsa::credential::EcdsaCredential;
+use ecdsa::credential::EcdsaCredentialChip;
+use ecdsa::ecdsa::{AssignedEcdsaSig, AssignedPublicKey};
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::ff::{Field, FromUniformBytes, PrimeField};
+use halo2_proofs::halo2curves::group::{Curve, Group};
+use halo2_proofs::halo2curves::secp256k1::{Fp as IssuerBase, Fq as IssuerScalar, Secp256k1Affine};
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error, ProvingKey, VerifyingKey};
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use rand_core::RngCore;
+use std::rc::Rc;
+
+#[cfg(test)]
+mod tests;
+
+/// Curve of the issuer keys, whose signatures are verified in circuit
+pub type IssuerCurve = Secp256k1Affine;
+
+pub const NUMBER_OF_LIMBS: usize = 4;
+pub const BIT_LEN_LIMB: usize = 68;
+const WINDOW_SIZE: usize = 4;
+
+/// Seed of the auxiliary generator of the in-circuit scalar multiplications
+const AUX_GENERATOR_SEED: [u8; 32] = *b"recreds presentation aux genera.";
+
+/// ECDSA secret key of an issuer
+#[derive(Clone, Debug)]
+pub struct IssuerSecretKey(pub IssuerScalar);
+
+/// ECDSA public key of an issuer
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IssuerPublicKey(pub IssuerCurve);
+
+impl IssuerSecretKey {
+    pub fn random(rng: impl RngCore) -> Self {
+        Self(IssuerScalar::random(rng))
+    }
+
+    pub fn public_key(&self) -> IssuerPublicKey {
+        IssuerPublicKey((IssuerCurve::generator() * self.0).to_affine())
+    }
+}
+
+impl IssuerPublicKey {
+    /// Limbs of x and then y, as exposed by presentation circuits over `N`
+    pub fn public_inputs<N: PrimeField>(&self) -> Vec<N> {
+        let rns = Rc::new(Rns::<IssuerBase, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::construct());
+        Point::new(rns, self.0).public()
+    }
+}
+
+/// A credential: attributes and the issuer signature over their Poseidon
+/// commitment.
+///
+/// Attributes live in the circuit field of a backend, so a credential can
+/// only be presented with the backend it was issued for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Credential<N: PrimeField> {
+    pub attributes: Vec<N>,
+    pub issuer: IssuerPublicKey,
+    pub signature: (IssuerScalar, IssuerScalar),
+}
+
+impl<N: FromUniformBytes<64>> Credential<N> {
+    /// Issue a credential over `attributes`
+    pub fn issue(sk: &IssuerSecretKey, attributes: Vec<N>, rng: impl RngCore) -> Self {
+        let credential = EcdsaCredential::<IssuerCurve, N>::issue(sk.0, attributes, rng);
+        Self {
+            attributes: credential.attributes,
+            issuer: IssuerPublicKey(credential.issuer),
+            signature: credential.signature,
+        }
+    }
+
+    /// Check the issuer signature natively
+    pub fn verify(&self) -> bool {
+        EcdsaCredential::<IssuerCurve, N> {
+            attributes: self.attributes.clone(),
+            issuer: self.issuer.0,
+            signature: self.signature,
+        }
+        .verify()
+    }
+}
+
+/// What a presentation reveals: the positions of the disclosed attributes
+/// among `num_attributes`. Presentations under one policy share a circuit
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PresentationPolicy {
+    pub num_attributes: usize,
+    pub disclosed: Vec<usize>,
+}
+
+impl PresentationPolicy {
+    pub fn new(num_attributes: usize, disclosed: Vec<usize>) -> Self {
+        assert!(
+            disclosed.windows(2).all(|w| w[0] < w[1]),
+            "disclosed attributes must be strictly increasing"
+        );
+        assert!(
+            disclosed.iter().all(|i| *i < num_attributes),
+            "disclosed attribute out of range"
+        );
+        Self {
+            num_attributes,
+            disclosed,
+        }
+    }
+
+    /// Identifier of the circuit of the policy, for key stores
+    pub fn circuit_id(&self) -> String {
+        let disclosed: Vec<String> = self.disclosed.iter().map(|i| i.to_string()).collect();
+        format!(
+            "presentation-{}-{}",
+            self.num_attributes,
+            disclosed.join(".")
+        )
+    }
+
+    /// Whether the disclosed positions are strictly increasing and in range,
+    /// for policies that were not built with `new`
+    pub fn is_valid(&self) -> bool {
+        self.disclosed.windows(2).all(|w| w[0] < w[1])
+            && self.disclosed.iter().all(|i| *i < self.num_attributes)
+    }
+}
+
+/// A proof of possession of a credential from `issuer` that discloses some
+/// of its attributes under `policy`.
+///
+/// The proof is bound to the verifier's `nonce`. Verifiers must check that
+/// they trust `issuer`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Presentation<N: PrimeField> {
+    pub policy: PresentationPolicy,
+    pub issuer: IssuerPublicKey,
+    pub nonce: N,
+    pub disclosed: Vec<N>,
+    pub proof: Vec<u8>,
+}
+
+impl<N: PrimeField> Presentation<N> {
+    /// Issuer key limbs, the nonce and the disclosed attributes
+    pub fn public_inputs(&self) -> Vec<N> {
+        let mut public_inputs = self.issuer.public_inputs();
+        public_inputs.push(self.nonce);
+        public_inputs.extend(self.disclosed.iter().copied());
+        public_inputs
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PresentationConfig {
+    main_gate_config: MainGateConfig,
+    range_config: RangeConfig,
+}
+
+impl PresentationConfig {
+    fn new<N: PrimeField>(meta: &mut ConstraintSystem<N>) -> Self {
+        let (rns_base, rns_scalar) =
+            GeneralEccChip::<IssuerCurve, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::rns();
+        let main_gate_config = MainGate::<N>::configure(meta);
+        let mut overflow_bit_lens: Vec<usize> = vec![];
+        overflow_bit_lens.extend(rns_base.overflow_lengths());
+        overflow_bit_lens.extend(rns_scalar.overflow_lengths());
+        let composition_bit_lens = vec![BIT_LEN_LIMB / NUMBER_OF_LIMBS];
+
+        let range_config = RangeChip::<N>::configure(
+            meta,
+            &main_gate_config,
+            composition_bit_lens,
+            overflow_bit_lens,
+        );
+        PresentationConfig {
+            main_gate_config,
+            range_config,
+        }
+    }
+
+    fn ecc_chip_config(&self) -> EccConfig {
+        EccConfig::new(self.range_config.clone(), self.main_gate_config.clone())
+    }
+
+    fn config_range<N: PrimeField>(&self, layouter: &mut impl Layouter<N>) -> Result<(), Error> {
+        let range_chip = RangeChip::<N>::new(self.range_config.clone());
+        range_chip.load_table(layouter)?;
+        Ok(())
+    }
+}
+
+/// Verifies the issuer signature over all attributes and exposes the issuer
+/// key, the nonce and the disclosed attributes, in the order of
+/// `Presentation::public_inputs`
+#[derive(Clone, Debug)]
+pub struct PresentationCircuit<N: PrimeField> {
+    policy: PresentationPolicy,
+    attributes: Vec<Value<N>>,
+    issuer: Value<IssuerCurve>,
+    signature: Value<(IssuerScalar, IssuerScalar)>,
+    nonce: Value<N>,
+}
+
+impl<N: PrimeField> PresentationCircuit<N> {
+    /// The circuit of `policy` without witnesses, for key generation
+    pub fn new(policy: PresentationPolicy) -> Self {
+        Self {
+            attributes: vec![Value::unknown(); policy.num_attributes],
+            policy,
+            issuer: Value::unknown(),
+            signature: Value::unknown(),
+            nonce: Value::unknown(),
+        }
+    }
+
+    pub fn from_credential(
+        credential: &Credential<N>,
+        policy: &PresentationPolicy,
+        nonce: N,
+    ) -> Self {
+        assert_eq!(credential.attributes.len(), policy.num_attributes);
+        Self {
+            policy: policy.clone(),
+            attributes: credential
+                .attributes
+                .iter()
+                .copied()
+                .map(Value::known)
+                .collect(),
+            issuer: Value::known(credential.issuer.0),
+            signature: Value::known(credential.signature),
+            nonce: Value::known(nonce),
+        }
+    }
+
+    pub fn policy(&self) -> &PresentationPolicy {
+        &self.policy
+    }
+}
+
+fn aux_generator() -> IssuerCurve {
+    let rng = ChaCha8Rng::from_seed(AUX_GENERATOR_SEED);
+    <IssuerCurve as CurveAffine>::CurveExt::random(rng).to_affine()
+}
+
+impl<N: FromUniformBytes<64> + Ord> Circuit<N> for PresentationCircuit<N> {
+    type Config = PresentationConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self::new(self.policy.clone())
+    }
+
+    fn configure(meta: &mut ConstraintSystem<N>) -> Self::Config {
+        PresentationConfig::new::<N>(meta)
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<N>,
+    ) -> Result<(), Error> {
+        let mut ecc_chip = GeneralEccChip::<IssuerCurve, N, NUMBER_OF_LIMBS, BIT_LEN_LIMB>::new(
+            config.ecc_chip_config(),
+        );
+
+        layouter.assign_region(
+            || "assign aux values",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+
+                ecc_chip.assign_aux_generator(ctx, Value::known(aux_generator()))?;
+                ecc_chip.assign_aux(ctx, WINDOW_SIZE, 2)?;
+                Ok(())
+            },
+        )?;
+
+        let credential_chip = EcdsaCredentialChip::new(ecc_chip.clone());
+        let scalar_chip = ecc_chip.scalar_field_chip();
+        let main_gate = scalar_chip.main_gate();
+
+        // One region per gadget, so that sizing reports the rows of each
+        let (attributes, nonce) = layouter.assign_region(
+            || "attributes",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+
+                let attributes = self
+                    .attributes
+                    .iter()
+                    .map(|attribute| main_gate.assign_value(ctx, *attribute))
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let nonce = main_gate.assign_value(ctx, self.nonce)?;
+                Ok((attributes, nonce))
+            },
+        )?;
+
+        let commitment = layouter.assign_region(
+            || "poseidon",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+                credential_chip.commit(ctx, &attributes)
+            },
+        )?;
+
+        let issuer = layouter.assign_region(
+            || "ecdsa",
+            |region| {
+                let offset = 0;
+                let ctx = &mut RegionCtx::new(region, offset);
+
+                let r = self.signature.map(|signature| signature.0);
+                let s = self.signature.map(|signature| signature.1);
+                let integer_r = ecc_chip.new_unassigned_scalar(r);
+                let integer_s = ecc_chip.new_unassigned_scalar(s);
+                let sig = AssignedEcdsaSig {
+                    r: scalar_chip.assign_integer(ctx, integer_r, Range::Remainder)?,
+                    s: scalar_chip.assign_integer(ctx, integer_s, Range::Remainder)?,
+                };
+                let issuer = ecc_chip.assign_point(ctx, self.issuer)?;
+                let pk = AssignedPublicKey {
+                    point: issuer.clone(),
+                };
+                credential_chip.verify_commitment(ctx, &commitment, &sig, &pk)?;
+                Ok(issuer)
+            },
+        )?;
+        let disclosed = self
+            .policy
+            .disclosed
+            .iter()
+            .map(|i| attributes[*i].clone())
+            .collect::<Vec<_>>();
+
+        config.config_range(&mut layouter)?;
+
+        let mut offset = 2 * NUMBER_OF_LIMBS;
+        ecc_chip.expose_public(layouter.namespace(|| "issuer"), issuer, 0)?;
+        main_gate.expose_public(layouter.namespace(|| "nonce"), nonce, offset)?;
+        for attribute in disclosed.into_iter() {
+            offset += 1;
+            main_gate.expose_public(layouter.namespace(|| "disclosed"), attribute, offset)?;
+        }
+        Ok(())
+    }
+}
+
+/// Generates the proving key of presentations under `policy`. Params of the
+/// `k` of `sizing::estimate_k` give the smallest domain
+pub fn keygen<B: Backend>(
+    params: &B::Params,
+    policy: &PresentationPolicy,
+) -> Result<ProvingKey<B::Curve>, Error> {
+    B::keygen(
+        params,
+        &PresentationCircuit::<B::Scalar>::new(policy.clone()),
+    )
+}
+
+/// Presents `credential` under `policy` to a verifier that sent `nonce`
+pub fn present<B: Backend>(
+    params: &B::Params,
+    pk: &ProvingKey<B::Curve>,
+    credential: &Credential<B::Scalar>,
+    policy: &PresentationPolicy,
+    nonce: B::Scalar,
+    rng: impl RngCore,
+) -> Result<Presentation<B::Scalar>, Error> {
+    let circuit = PresentationCircuit::from_credential(credential, policy, nonce);
+    let mut presentation = unproven(credential, policy, nonce);
+    presentation.proof = B::prove(params, pk, circuit, &[presentation.public_inputs()], rng)?;
+    Ok(presentation)
+}
+
+/// Presents each credential to the verifier that sent its nonce under one
+/// policy, proving in parallel with at most `max_in_flight` provers as in
+/// `Backend::prove_batch`. The results are in the order of `requests`
+pub fn present_batch<B: Backend>(
+    params: &B::Params,
+    pk: &ProvingKey<B::Curve>,
+    requests: &[(&Credential<B::Scalar>, B::Scalar)],
+    policy: &PresentationPolicy,
+    max_in_flight: usize,
+    rng: impl RngCore + Send,
+) -> Vec<Result<Presentation<B::Scalar>, Error>> {
+    let presentations: Vec<_> = requests
+        .iter()
+        .map(|(credential, nonce)| unproven(credential, policy, *nonce))
+        .collect();
+    let circuits =
+        requests
+            .iter()
+            .zip(presentations.iter())
+            .map(|((credential, nonce), presentation)| {
+                (
+                    PresentationCircuit::from_credential(credential, policy, *nonce),
+                    vec![presentation.public_inputs()],
+                )
+            });
+    let proofs = B::prove_batch(params, pk, circuits, max_in_flight, rng);
+    proofs
+        .into_iter()
+        .zip(presentations)
+        .map(|(proof, mut presentation)| {
+            presentation.proof = proof?;
+            Ok(presentation)
+        })
+        .collect()
+}
+
+/// The presentation of `credential` before its proof
+fn unproven<N: PrimeField>(
+    credential: &Credential<N>,
+    policy: &PresentationPolicy,
+    nonce: N,
+) -> Presentation<N> {
+    Presentation {
+        policy: policy.clone(),
+        issuer: credential.issuer,
+        nonce,
+        disclosed: policy
+            .disclosed
+            .iter()
+            .map(|i| credential.attributes[*i])
+            .collect(),
+        proof: vec![],
+    }
+}
+
+/// Verifies a presentation against the key of its policy
+pub fn verify_presentation<B: Backend>(
+    params: &B::Params,
+    vk: &VerifyingKey<B::Curve>,
+    presentation: &Presentation<B::Scalar>,
+) -> Result<(), Error> {
+    verify_presentations::<B>(params, vk, std::slice::from_ref(presentation))
+}
+
+/// Verifies presentations under one policy with a single final check
+pub fn verify_presentations<B: Backend>(
+    params: &B::Params,
+    vk: &VerifyingKey<B::Curve>,
+    presentations: &[Presentation<B::Scalar>],
+) -> Result<(), Error> {
+    let instances: Vec<_> = presentations
+        .iter()
+        .map(|presentation| vec![presentation.public_inputs()])
+        .collect();
+    let proofs: Vec<_> = instances
+        .iter()
+        .zip(presentations.iter())
+        .map(|(instances, presentation)| (instances.as_slice(), presentation.proof.as_slice()))
+        .collect();
+    B::verify_batch(params, vk, &proofs)
+}
@@ -0,0 +1,134 @@
+use crate::backend::{Backend, Ipa, Kzg};
+use crate::credential::{
+    keygen, present, present_batch, verify_presentation, verify_presentations, Credential,
+    IssuerSecretKey, PresentationPolicy,
+};
+use crate::sizing::estimate_k;
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::Field;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+fn end_to_end<B: Backend>() {
+    let mut rng = StdRng::seed_from_u64(0xc0ffee);
+
+    let sk = IssuerSecretKey::random(&mut rng);
+    let attributes: Vec<B::Scalar> = (0..4).map(|_| B::Scalar::random(&mut rng)).collect();
+    let credential = Credential::issue(&sk, attributes, &mut rng);
+    assert!(credential.verify());
+
+    let policy = PresentationPolicy::new(4, vec![1, 3]);
+    let nonce = B::Scalar::random(&mut rng);
+    let k = estimate_k::<B::Scalar>(&policy, None).unwrap().k;
+    println!("{} presentation k = {}", B::NAME, k);
+
+    let params = B::setup(k, &mut rng);
+    let pk = keygen::<B>(&params, &policy).unwrap();
+    let vk = pk.get_vk();
+
+    let presentation = present::<B>(&params, &pk, &credential, &policy, nonce, &mut rng).unwrap();
+    assert_eq!(
+        presentation.disclosed,
+        vec![credential.attributes[1], credential.attributes[3]]
+    );
+    verify_presentation::<B>(&params, vk, &presentation).unwrap();
+
+    // presentations of the same policy verify as a batch
+    let other = present::<B>(
+        &params,
+        &pk,
+        &credential,
+        &policy,
+        B::Scalar::random(&mut rng),
+        &mut rng,
+    )
+    .unwrap();
+    verify_presentations::<B>(&params, vk, &[presentation.clone(), other.clone()]).unwrap();
+
+    // Error case: wrong disclosed value
+    {
+        let mut tampered = presentation.clone();
+        tampered.disclosed[0] += B::Scalar::ONE;
+        assert!(verify_presentation::<B>(&params, vk, &tampered).is_err());
+        assert!(verify_presentations::<B>(&params, vk, &[other.clone(), tampered]).is_err());
+    }
+
+    // Error case: replayed to a verifier with another nonce
+    {
+        let mut tampered = presentation.clone();
+        tampered.nonce += B::Scalar::ONE;
+        assert!(verify_presentation::<B>(&params, vk, &tampered).is_err());
+    }
+
+    // Error case: claimed to come from another issuer
+    {
+        let mut tampered = presentation;
+        tampered.issuer = IssuerSecretKey::random(&mut rng).public_key();
+        assert!(verify_presentation::<B>(&params, vk, &tampered).is_err());
+    }
+}
+
+#[test]
+fn test_presentation_kzg() {
+    end_to_end::<Kzg>();
+}
+
+#[test]
+fn test_presentation_ipa() {
+    end_to_end::<Ipa>();
+}
+
+#[test]
+fn test_present_batch() {
+    let mut rng = StdRng::seed_from_u64(0xba7c4);
+
+    let sk = IssuerSecretKey::random(&mut rng);
+    let credentials: Vec<Credential<Fr>> = (1..=3u64)
+        .map(|i| Credential::issue(&sk, (i..i + 4).map(Fr::from).collect(), &mut rng))
+        .collect();
+    // Error case: a credential the issuer did not sign still gets a proof,
+    // which does not verify
+    let mut forged = credentials[2].clone();
+    forged.attributes[1] += Fr::ONE;
+
+    let policy = PresentationPolicy::new(4, vec![1, 3]);
+    let params = Kzg::setup(estimate_k::<Fr>(&policy, None).unwrap().k, &mut rng);
+    let pk = keygen::<Kzg>(&params, &policy).unwrap();
+    let requests: Vec<_> = credentials[..2]
+        .iter()
+        .chain(std::iter::once(&forged))
+        .zip(1u64..)
+        .map(|(credential, nonce)| (credential, Fr::from(nonce)))
+        .collect();
+
+    // Two provers share the three presentations, each taking the next
+    // request once its proof is done
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(2)
+        .build()
+        .unwrap();
+    let replay = rng.clone();
+    let presentations: Vec<_> = pool
+        .install(|| present_batch::<Kzg>(&params, &pk, &requests, &policy, 2, &mut rng))
+        .into_iter()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(presentations.len(), 3);
+
+    // Proofs draw their randomness in the order of the requests, whatever
+    // the order the provers finish in
+    let sequential = present_batch::<Kzg>(&params, &pk, &requests[..1], &policy, 1, replay);
+    assert_eq!(
+        sequential[0].as_ref().unwrap().proof,
+        presentations[0].proof
+    );
+    for ((credential, nonce), presentation) in requests.iter().zip(presentations.iter()) {
+        assert_eq!(presentation.nonce, *nonce);
+        assert_eq!(
+            presentation.disclosed,
+            vec![credential.attributes[1], credential.attributes[3]]
+        );
+    }
+    verify_presentations::<Kzg>(&params, pk.get_vk(), &presentations[..2]).unwrap();
+    assert!(verify_presentation::<Kzg>(&params, pk.get_vk(), &presentations[2]).is_err());
+}
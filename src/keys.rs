@@ -0,0 +1,328 @@
+use crate::backend::{Backend, Kzg};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr, G1Affine};
+use halo2_proofs::halo2curves::ff::PrimeField;
+use halo2_proofs::halo2curves::serde::SerdeObject;
+use halo2_proofs::plonk::{keygen_vk, Circuit, Error, ProvingKey, VerifyingKey};
+use halo2_proofs::poly::commitment::Params;
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_proofs::SerdeFormat;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const MAGIC: &[u8; 6] = b"RCKEYS";
+const VERSION: u8 = 1;
+const DIGEST_LEN: usize = 32;
+
+/// Key files carry a digest of the key, so points are read unchecked
+const FORMAT: SerdeFormat = SerdeFormat::RawBytesUnchecked;
+
+#[derive(Debug, Error)]
+pub enum KeyStoreError {
+    #[error("i/o error: {0}")]
+    Io(#[from] io::Error),
+    #[error("key generation failed: {0:?}")]
+    Keygen(Error),
+    #[error("not a key file")]
+    BadMagic,
+    #[error("unsupported key file version {0}")]
+    UnsupportedVersion(u8),
+    #[error("key file was generated for another {0}")]
+    Mismatch(&'static str),
+    #[error("key file is corrupted")]
+    Corrupted,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum KeyKind {
+    Verifying = 0,
+    Proving = 1,
+}
+
+impl KeyKind {
+    fn extension(&self) -> &'static str {
+        match self {
+            KeyKind::Verifying => "vk",
+            KeyKind::Proving => "pk",
+        }
+    }
+}
+
+/// Header of a key file. Keys are only loaded for the circuit identifier,
+/// the circuit, the `k` and the params they were generated with
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Header {
+    kind: KeyKind,
+    circuit_id: String,
+    k: u32,
+    circuit_digest: [u8; DIGEST_LEN],
+    params_digest: [u8; DIGEST_LEN],
+    key_digest: [u8; DIGEST_LEN],
+}
+
+impl Header {
+    fn write(&self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&[VERSION, self.kind as u8])?;
+        writer.write_all(&(self.circuit_id.len() as u16).to_le_bytes())?;
+        writer.write_all(self.circuit_id.as_bytes())?;
+        writer.write_all(&self.k.to_le_bytes())?;
+        writer.write_all(&self.circuit_digest)?;
+        writer.write_all(&self.params_digest)?;
+        writer.write_all(&self.key_digest)
+    }
+
+    fn read(reader: &mut impl Read) -> Result<Self, KeyStoreError> {
+        let mut magic = [0u8; 6];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err(KeyStoreError::BadMagic);
+        }
+        let mut bytes = [0u8; 2];
+        reader.read_exact(&mut bytes)?;
+        let [version, kind] = bytes;
+        if version != VERSION {
+            return Err(KeyStoreError::UnsupportedVersion(version));
+        }
+        let kind = match kind {
+            0 => KeyKind::Verifying,
+            1 => KeyKind::Proving,
+            _ => return Err(KeyStoreError::Corrupted),
+        };
+        reader.read_exact(&mut bytes)?;
+        let mut circuit_id = vec![0u8; u16::from_le_bytes(bytes) as usize];
+        reader.read_exact(&mut circuit_id)?;
+        let circuit_id = String::from_utf8(circuit_id).map_err(|_| KeyStoreError::Corrupted)?;
+        let mut k = [0u8; 4];
+        reader.read_exact(&mut k)?;
+        let mut digests = [[0u8; DIGEST_LEN]; 3];
+        for digest in digests.iter_mut() {
+            reader.read_exact(digest)?;
+        }
+        let [circuit_digest, params_digest, key_digest] = digests;
+        Ok(Self {
+            kind,
+            circuit_id,
+            k: u32::from_le_bytes(k),
+            circuit_digest,
+            params_digest,
+            key_digest,
+        })
+    }
+}
+
+fn digest(personal: &[u8], chunks: &[&[u8]]) -> [u8; DIGEST_LEN] {
+    let mut state = blake2b_simd::Params::new()
+        .hash_length(DIGEST_LEN)
+        .personal(personal)
+        .to_state();
+    for chunk in chunks.iter() {
+        state.update(chunk);
+    }
+    state.finalize().as_bytes().try_into().unwrap()
+}
+
+/// Digest of the circuit of `vk`. The transcript representation of the key
+/// covers the constraint system, the fixed columns and the permutation
+fn circuit_digest(vk: &VerifyingKey<G1Affine>) -> [u8; DIGEST_LEN] {
+    digest(
+        b"recreds-circuit",
+        &[vk.transcript_repr().to_repr().as_ref()],
+    )
+}
+
+/// Digest of the secret of the params, that is shared by all their
+/// downsized copies
+fn params_digest(params: &ParamsKZG<Bn256>) -> [u8; DIGEST_LEN] {
+    let mut bytes = vec![];
+    params.g2().write_raw(&mut bytes).unwrap();
+    params.s_g2().write_raw(&mut bytes).unwrap();
+    digest(b"recreds-params", &[&bytes])
+}
+
+/// Proving and verifying keys of the KZG backend stored in a directory, one
+/// file per circuit identifier, `k` and kind of key.
+///
+/// Circuit identifiers name the circuit type and whatever fixes its shape,
+/// such as the presentation policy
+#[derive(Clone, Debug)]
+pub struct KeyStore {
+    dir: PathBuf,
+}
+
+impl KeyStore {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, KeyStoreError> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path(&self, circuit_id: &str, k: u32, kind: KeyKind) -> PathBuf {
+        self.dir
+            .join(format!("{}-k{}.{}", circuit_id, k, kind.extension()))
+    }
+
+    fn header(
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        kind: KeyKind,
+        vk: &VerifyingKey<G1Affine>,
+        key: &[u8],
+    ) -> Header {
+        Header {
+            kind,
+            circuit_id: circuit_id.to_string(),
+            k: params.k(),
+            circuit_digest: circuit_digest(vk),
+            params_digest: params_digest(params),
+            key_digest: digest(b"recreds-key", &[key]),
+        }
+    }
+
+    fn store(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        kind: KeyKind,
+        vk: &VerifyingKey<G1Affine>,
+        key: &[u8],
+    ) -> Result<(), KeyStoreError> {
+        let header = Self::header(params, circuit_id, kind, vk, key);
+        let path = self.path(circuit_id, params.k(), kind);
+        // write then rename so that an interrupted write leaves no key file;
+        // the verifying and proving keys are written to distinct files
+        let tmp = path.with_extension(format!("{}.tmp", kind.extension()));
+        let mut file = io::BufWriter::new(fs::File::create(&tmp)?);
+        header.write(&mut file)?;
+        file.write_all(key)?;
+        file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(tmp, path)?;
+        Ok(())
+    }
+
+    /// Reads the key file of `circuit`. The verifying key of `circuit` is
+    /// generated to check the circuit digest, which is much cheaper than
+    /// generating a proving key
+    fn load<ConcreteCircuit: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        kind: KeyKind,
+        circuit: &ConcreteCircuit,
+    ) -> Result<Vec<u8>, KeyStoreError> {
+        let mut file = io::BufReader::new(fs::File::open(self.path(circuit_id, params.k(), kind))?);
+        let header = Header::read(&mut file)?;
+        let mut key = vec![];
+        file.read_to_end(&mut key)?;
+
+        let vk = keygen_vk(params, circuit).map_err(KeyStoreError::Keygen)?;
+        let expected = Self::header(params, circuit_id, kind, &vk, &key);
+        if header.kind != expected.kind {
+            Err(KeyStoreError::Mismatch("kind of key"))
+        } else if header.circuit_id != expected.circuit_id {
+            Err(KeyStoreError::Mismatch("circuit identifier"))
+        } else if header.k != expected.k {
+            Err(KeyStoreError::Mismatch("k"))
+        } else if header.params_digest != expected.params_digest {
+            Err(KeyStoreError::Mismatch("params"))
+        } else if header.circuit_digest != expected.circuit_digest {
+            Err(KeyStoreError::Mismatch("circuit"))
+        } else if header.key_digest != expected.key_digest {
+            Err(KeyStoreError::Corrupted)
+        } else {
+            Ok(key)
+        }
+    }
+
+    pub fn store_vk(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        vk: &VerifyingKey<G1Affine>,
+    ) -> Result<(), KeyStoreError> {
+        let mut key = vec![];
+        vk.write(&mut key, FORMAT)?;
+        self.store(params, circuit_id, KeyKind::Verifying, vk, &key)
+    }
+
+    pub fn store_pk(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        pk: &ProvingKey<G1Affine>,
+    ) -> Result<(), KeyStoreError> {
+        let mut key = vec![];
+        pk.write(&mut key, FORMAT)?;
+        self.store(params, circuit_id, KeyKind::Proving, pk.get_vk(), &key)
+    }
+
+    pub fn load_vk<ConcreteCircuit: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        circuit: &ConcreteCircuit,
+    ) -> Result<VerifyingKey<G1Affine>, KeyStoreError> {
+        let key = self.load(params, circuit_id, KeyKind::Verifying, circuit)?;
+        Ok(VerifyingKey::read::<_, ConcreteCircuit>(
+            &mut key.as_slice(),
+            FORMAT,
+        )?)
+    }
+
+    pub fn load_pk<ConcreteCircuit: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        circuit: &ConcreteCircuit,
+    ) -> Result<ProvingKey<G1Affine>, KeyStoreError> {
+        let key = self.load(params, circuit_id, KeyKind::Proving, circuit)?;
+        Ok(ProvingKey::read::<_, ConcreteCircuit>(
+            &mut key.as_slice(),
+            FORMAT,
+        )?)
+    }
+
+    /// Loads the proving key of `circuit`, or generates and stores it together
+    /// with its verifying key when there is none yet.
+    ///
+    /// Key files that do not match the circuit or the params are an error
+    /// rather than silently regenerated
+    pub fn load_or_generate<ConcreteCircuit: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        circuit: &ConcreteCircuit,
+    ) -> Result<ProvingKey<G1Affine>, KeyStoreError> {
+        match self.load_pk(params, circuit_id, circuit) {
+            Err(KeyStoreError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+                let pk = Kzg::keygen(params, circuit).map_err(KeyStoreError::Keygen)?;
+                self.store_vk(params, circuit_id, pk.get_vk())?;
+                self.store_pk(params, circuit_id, &pk)?;
+                Ok(pk)
+            }
+            result => result,
+        }
+    }
+
+    /// Loads the verifying key of `circuit`, or generates and stores both keys
+    /// when there is none yet
+    pub fn load_or_generate_vk<ConcreteCircuit: Circuit<Fr>>(
+        &self,
+        params: &ParamsKZG<Bn256>,
+        circuit_id: &str,
+        circuit: &ConcreteCircuit,
+    ) -> Result<VerifyingKey<G1Affine>, KeyStoreError> {
+        match self.load_vk(params, circuit_id, circuit) {
+            Err(KeyStoreError::Io(e)) if e.kind() == io::ErrorKind::NotFound => Ok(self
+                .load_or_generate(params, circuit_id, circuit)?
+                .get_vk()
+                .clone()),
+            result => result,
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use crate::backend::{Backend, Kzg};
+use crate::keys::{KeyStore, KeyStoreError};
+use ecc::maingate::{MainGate, MainGateConfig, MainGateInstructions, RegionCtx};
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fr};
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{Circuit, ConstraintSystem, Error};
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fs;
+
+/// Proves `c = a * b + shift` for a public `c`. `SQUARE` adds a column, which
+/// changes the constraint system, while `shift` only changes a fixed column
+#[derive(Clone, Default)]
+struct MulCircuit<const SQUARE: bool> {
+    a: Value<Fr>,
+    b: Value<Fr>,
+    shift: Fr,
+}
+
+impl<const SQUARE: bool> Circuit<Fr> for MulCircuit<SQUARE> {
+    type Config = MainGateConfig;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        Self {
+            shift: self.shift,
+            ..Self::default()
+        }
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        let config = MainGate::<Fr>::configure(meta);
+        if SQUARE {
+            // an extra column changes the constraint system
+            meta.advice_column();
+        }
+        config
+    }
+
+    fn synthesize(
+        &self,
+        config: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        let main_gate = MainGate::<Fr>::new(config);
+        let c = layouter.assign_region(
+            || "region 0",
+            |region| {
+                let ctx = &mut RegionCtx::new(region, 0);
+                let a = main_gate.assign_value(ctx, self.a)?;
+                let b = main_gate.assign_value(ctx, self.b)?;
+                let c = main_gate.mul(ctx, &a, &b)?;
+                main_gate.add_constant(ctx, &c, self.shift)
+            },
+        )?;
+        main_gate.expose_public(layouter.namespace(|| "c"), c, 0)
+    }
+}
+
+#[test]
+fn test_key_store() {
+    let dir = std::env::temp_dir().join(format!("recreds-keys-{}", std::process::id()));
+    let store = KeyStore::new(&dir).unwrap();
+    let mut rng = StdRng::seed_from_u64(0);
+    let params = ParamsKZG::<Bn256>::setup(5, &mut rng);
+    let circuit = MulCircuit::<false>::default();
+
+    let pk = store.load_or_generate(&params, "mul", &circuit).unwrap();
+    let loaded = store.load_or_generate(&params, "mul", &circuit).unwrap();
+    assert_eq!(
+        loaded.get_vk().transcript_repr(),
+        pk.get_vk().transcript_repr()
+    );
+    let vk = store.load_vk(&params, "mul", &circuit).unwrap();
+    assert_eq!(vk.transcript_repr(), pk.get_vk().transcript_repr());
+
+    // proofs of the loaded proving key verify with the loaded verifying key
+    let (a, b) = (Fr::from(3), Fr::from(5));
+    let circuit = MulCircuit::<false> {
+        a: Value::known(a),
+        b: Value::known(b),
+        shift: Fr::ZERO,
+    };
+    let instances = vec![vec![a * b]];
+    let proof = Kzg::prove(&params, &loaded, circuit, &instances, &mut rng).unwrap();
+    Kzg::verify(&params, &vk, &instances, &proof).unwrap();
+
+    // Error case: another constraint system under the same identifier
+    assert!(matches!(
+        store.load_pk(&params, "mul", &MulCircuit::<true>::default()),
+        Err(KeyStoreError::Mismatch("circuit"))
+    ));
+
+    // Error case: the same constraint system with other fixed assignments
+    {
+        let circuit = MulCircuit::<false> {
+            shift: Fr::ONE,
+            ..MulCircuit::default()
+        };
+        assert!(matches!(
+            store.load_pk(&params, "mul", &circuit),
+            Err(KeyStoreError::Mismatch("circuit"))
+        ));
+        assert!(matches!(
+            store.load_vk(&params, "mul", &circuit),
+            Err(KeyStoreError::Mismatch("circuit"))
+        ));
+    }
+
+    // Error case: params of another setup
+    {
+        let params = ParamsKZG::<Bn256>::setup(5, &mut rng);
+        assert!(matches!(
+            store.load_or_generate(&params, "mul", &MulCircuit::<false>::default()),
+            Err(KeyStoreError::Mismatch("params"))
+        ));
+    }
+
+    // Error case: renamed to another identifier
+    let path = |id: &str| dir.join(format!("{}-k5.pk", id));
+    fs::copy(path("mul"), path("other")).unwrap();
+    assert!(matches!(
+        store.load_pk(&params, "other", &MulCircuit::<false>::default()),
+        Err(KeyStoreError::Mismatch("circuit identifier"))
+    ));
+
+    // Error case: corrupted key
+    let mut bytes = fs::read(path("mul")).unwrap();
+    *bytes.last_mut().unwrap() ^= 1;
+    fs::write(path("mul"), &bytes).unwrap();
+    assert!(matches!(
+        store.load_pk(&params, "mul", &MulCircuit::<false>::default()),
+        Err(KeyStoreError::Corrupted)
+    ));
+
+    // Error case: unknown version
+    bytes[6] += 1;
+    fs::write(path("mul"), &bytes).unwrap();
+    assert!(matches!(
+        store.load_pk(&params, "mul", &MulCircuit::<false>::default()),
+        Err(KeyStoreError::UnsupportedVersion(2))
+    ));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
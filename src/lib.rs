@@ -0,0 +1,19 @@
+pub mod backend;
+pub mod credential;
+pub mod keys;
+pub mod params;
+pub mod schema;
+pub mod service;
+pub mod sizing;
+pub mod vc;
+pub mod wire;
+
+pub use backend::{Backend, Ipa, Kzg};
+pub use credential::{
+    keygen, present, present_batch, verify_presentation, verify_presentations, Credential,
+    IssuerPublicKey, IssuerSecretKey, Presentation, PresentationCircuit, PresentationPolicy,
+};
+pub use keys::{KeyStore, KeyStoreError};
+pub use params::{ParamsCache, ParamsError};
+pub use schema::{AttributeDef, AttributeType, Schema, SchemaError, SchemaRegistry};
+pub use wire::{Wire, WireError};
@@ -4,6 +4,13 @@ mod testECC;
 mod testGeneralECC;
 mod PoseidonSample;
 // mod PoseidonSample;
+mod cli;
+
+use clap::Parser;
+
 fn main() {
-    println!("Hello, world!");
+    if let Err(e) = cli::run(cli::Cli::parse()) {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
 }
\ No newline at end of file
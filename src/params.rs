@@ -0,0 +1,257 @@
+use halo2_proofs::arithmetic::{best_multiexp, g_to_lagrange};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine, G2Affine};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::halo2curves::group::prime::PrimeCurveAffine;
+use halo2_proofs::halo2curves::group::Curve;
+use halo2_proofs::halo2curves::pairing::Engine;
+use halo2_proofs::halo2curves::serde::SerdeObject;
+use halo2_proofs::poly::commitment::{Blind, Params, ParamsProver};
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use halo2_proofs::poly::EvaluationDomain;
+use halo2_proofs::SerdeFormat;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const PTAU_MAGIC: &[u8; 4] = b"ptau";
+const PTAU_HEADER: u32 = 1;
+const PTAU_TAU_G1: u32 = 2;
+const PTAU_TAU_G2: u32 = 3;
+
+/// Size in bytes of a base field element in `.ptau` files over BN254
+const N8: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum ParamsError {
+    #[error("i/o error: {0}")]
+    Io(#[from] io::Error),
+    #[error("malformed ptau file: {0}")]
+    Ptau(&'static str),
+    #[error("params for k = {requested} requested but only k = {available} is available")]
+    TooSmall { requested: u32, available: u32 },
+    #[error("params are not consistent powers of a single secret")]
+    Inconsistent,
+}
+
+/// Reads params in the halo2 format
+pub fn read_params(path: impl AsRef<Path>) -> Result<ParamsKZG<Bn256>, ParamsError> {
+    let mut reader = BufReader::new(File::open(path)?);
+    Ok(ParamsKZG::read(&mut reader)?)
+}
+
+/// Writes params in the halo2 format
+pub fn write_params(params: &ParamsKZG<Bn256>, path: impl AsRef<Path>) -> Result<(), ParamsError> {
+    let mut writer = BufWriter::new(File::create(path)?);
+    Ok(params.write(&mut writer)?)
+}
+
+/// Reads params of `2^k` rows from a Perpetual Powers of Tau file as written
+/// by snarkjs. Only the header and the tau powers in G1 and G2 are used
+pub fn read_ptau(path: impl AsRef<Path>, k: u32) -> Result<ParamsKZG<Bn256>, ParamsError> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0u8; 4];
+    reader.read_exact(&mut magic)?;
+    if &magic != PTAU_MAGIC {
+        return Err(ParamsError::Ptau("bad magic"));
+    }
+    let _version = read_u32(&mut reader)?;
+    let number_of_sections = read_u32(&mut reader)?;
+    let mut sections = BTreeMap::new();
+    for _ in 0..number_of_sections {
+        let section = read_u32(&mut reader)?;
+        let size = read_u64(&mut reader)?;
+        let offset = reader.stream_position()?;
+        sections.insert(section, (offset, size));
+        reader.seek(SeekFrom::Current(size as i64))?;
+    }
+    let seek_section = |section: u32, reader: &mut BufReader<File>| {
+        let (offset, size) = *sections
+            .get(&section)
+            .ok_or(ParamsError::Ptau("missing section"))?;
+        reader.seek(SeekFrom::Start(offset))?;
+        Ok::<_, ParamsError>(size)
+    };
+
+    seek_section(PTAU_HEADER, &mut reader)?;
+    if read_u32(&mut reader)? as usize != N8 {
+        return Err(ParamsError::Ptau("unexpected field size"));
+    }
+    let mut q = [0u8; N8];
+    reader.read_exact(&mut q)?;
+    if q != fq_modulus() {
+        return Err(ParamsError::Ptau("not over BN254"));
+    }
+    let power = read_u32(&mut reader)?;
+    if k > power {
+        return Err(ParamsError::TooSmall {
+            requested: k,
+            available: power,
+        });
+    }
+
+    let n = 1usize << k;
+    let size = seek_section(PTAU_TAU_G1, &mut reader)?;
+    if size < (n * 2 * N8) as u64 {
+        return Err(ParamsError::Ptau("truncated tau powers in G1"));
+    }
+    let mut buf = [0u8; 2 * N8];
+    let g = (0..n)
+        .map(|_| {
+            reader.read_exact(&mut buf)?;
+            G1Affine::from_raw_bytes(&buf).ok_or(ParamsError::Ptau("invalid point in G1"))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let size = seek_section(PTAU_TAU_G2, &mut reader)?;
+    if size < (2 * 4 * N8) as u64 {
+        return Err(ParamsError::Ptau("truncated tau powers in G2"));
+    }
+    let mut buf = [0u8; 4 * N8];
+    let mut read_g2 = || {
+        reader.read_exact(&mut buf)?;
+        G2Affine::from_raw_bytes(&buf).ok_or(ParamsError::Ptau("invalid point in G2"))
+    };
+    let g2 = read_g2()?;
+    let s_g2 = read_g2()?;
+
+    from_parts(k, g, g2, s_g2)
+}
+
+/// `ParamsKZG` has no public constructor from its parts, so they go through
+/// its raw serialization
+fn from_parts(
+    k: u32,
+    g: Vec<G1Affine>,
+    g2: G2Affine,
+    s_g2: G2Affine,
+) -> Result<ParamsKZG<Bn256>, ParamsError> {
+    let g_lagrange: Vec<G1Affine> = g_to_lagrange(g.iter().map(|g| g.to_curve()).collect(), k);
+
+    let mut bytes = k.to_le_bytes().to_vec();
+    for point in g.iter().chain(g_lagrange.iter()) {
+        point.write_raw(&mut bytes)?;
+    }
+    g2.write_raw(&mut bytes)?;
+    s_g2.write_raw(&mut bytes)?;
+    Ok(ParamsKZG::read_custom(
+        &mut bytes.as_slice(),
+        SerdeFormat::RawBytesUnchecked,
+    )?)
+}
+
+fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0u8; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0u8; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Little endian modulus of the base field, as in `.ptau` headers
+fn fq_modulus() -> [u8; N8] {
+    // the modulus is odd, so adding one to `-1` does not carry
+    let mut modulus = (-Fq::ONE).to_repr();
+    modulus[0] += 1;
+    modulus
+}
+
+/// Returns a copy of `params` for `2^k` rows
+pub fn downsize(params: &ParamsKZG<Bn256>, k: u32) -> Result<ParamsKZG<Bn256>, ParamsError> {
+    if k > params.k() {
+        return Err(ParamsError::TooSmall {
+            requested: k,
+            available: params.k(),
+        });
+    }
+    let mut params = params.clone();
+    if k < params.k() {
+        params.downsize(k);
+    }
+    Ok(params)
+}
+
+/// Checks that params are the powers of a single secret `s` over the
+/// standard generators, and that the Lagrange bases match them.
+///
+/// The powers are checked with a random linear combination, that is
+/// `e(sum r^i [s^(i + 1)], g2) = e(sum r^i [s^i], [s] g2)`
+pub fn verify_params(params: &ParamsKZG<Bn256>) -> Result<(), ParamsError> {
+    let g = params.get_g();
+    if g[0] != G1Affine::generator() || params.g2() != G2Affine::generator() {
+        return Err(ParamsError::Inconsistent);
+    }
+
+    let r = Fr::random(rand::thread_rng());
+    let powers: Vec<Fr> = std::iter::successors(Some(Fr::ONE), |power| Some(*power * r))
+        .take(g.len() - 1)
+        .collect();
+    let lhs = best_multiexp(&powers, &g[1..]).to_affine();
+    let rhs = best_multiexp(&powers, &g[..g.len() - 1]).to_affine();
+    if Bn256::pairing(&lhs, &params.g2()) != Bn256::pairing(&rhs, &params.s_g2()) {
+        return Err(ParamsError::Inconsistent);
+    }
+
+    // Lagrange polynomials sum to one
+    let domain = EvaluationDomain::<Fr>::new(1, params.k());
+    let one = domain.lagrange_from_vec(vec![Fr::ONE; g.len()]);
+    if params.commit_lagrange(&one, Blind::default()).to_affine() != g[0] {
+        return Err(ParamsError::Inconsistent);
+    }
+    Ok(())
+}
+
+/// Params loaded once and downsized on demand, one instance per `k`
+#[derive(Debug)]
+pub struct ParamsCache {
+    params: ParamsKZG<Bn256>,
+    downsized: Mutex<BTreeMap<u32, Arc<ParamsKZG<Bn256>>>>,
+}
+
+impl ParamsCache {
+    pub fn new(params: ParamsKZG<Bn256>) -> Self {
+        Self {
+            params,
+            downsized: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Loads and verifies params in the halo2 format, or from a `.ptau`
+    /// file truncated to `2^max_k` rows
+    pub fn open(path: impl AsRef<Path>, max_k: u32) -> Result<Self, ParamsError> {
+        let mut magic = [0u8; 4];
+        File::open(path.as_ref())?.read_exact(&mut magic)?;
+        let params = if &magic == PTAU_MAGIC {
+            read_ptau(path, max_k)?
+        } else {
+            downsize(&read_params(path)?, max_k)?
+        };
+        verify_params(&params)?;
+        Ok(Self::new(params))
+    }
+
+    /// Largest `k` available
+    pub fn max_k(&self) -> u32 {
+        self.params.k()
+    }
+
+    pub fn get(&self, k: u32) -> Result<Arc<ParamsKZG<Bn256>>, ParamsError> {
+        let mut downsized = self.downsized.lock().unwrap();
+        if let Some(params) = downsized.get(&k) {
+            return Ok(params.clone());
+        }
+        let params = Arc::new(downsize(&self.params, k)?);
+        downsized.insert(k, params.clone());
+        Ok(params)
+    }
+}
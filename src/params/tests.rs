@@ -0,0 +1,163 @@
+use crate::params::{
+    downsize, read_params, read_ptau, verify_params, write_params, ParamsCache, ParamsError, N8,
+};
+use halo2_proofs::halo2curves::bn256::{Bn256, Fq, Fr, G1Affine, G2Affine};
+use halo2_proofs::halo2curves::ff::{Field, PrimeField};
+use halo2_proofs::halo2curves::group::prime::PrimeCurveAffine;
+use halo2_proofs::halo2curves::group::Curve;
+use halo2_proofs::halo2curves::serde::SerdeObject;
+use halo2_proofs::poly::commitment::{Params, ParamsProver};
+use halo2_proofs::poly::kzg::commitment::ParamsKZG;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("recreds-{}-{}", std::process::id(), name))
+}
+
+/// Writes the header and tau sections of a `.ptau` file for the secret `tau`
+fn write_ptau(path: &PathBuf, tau: Fr, power: u32, tamper: bool) {
+    let section = |bytes: &mut Vec<u8>, section: u32, data: Vec<u8>| {
+        bytes.extend(section.to_le_bytes());
+        bytes.extend((data.len() as u64).to_le_bytes());
+        bytes.extend(data);
+    };
+    let powers =
+        |n: usize| std::iter::successors(Some(Fr::ONE), move |power| Some(*power * tau)).take(n);
+
+    let mut header = (N8 as u32).to_le_bytes().to_vec();
+    let mut modulus = (-Fq::ONE).to_repr();
+    modulus[0] += 1;
+    header.extend(modulus);
+    header.extend(power.to_le_bytes());
+    header.extend(power.to_le_bytes());
+
+    let mut tau_g1 = vec![];
+    for (i, power) in powers((1 << (power + 1)) - 1).enumerate() {
+        let power = if tamper && i == 3 {
+            power.double()
+        } else {
+            power
+        };
+        (G1Affine::generator() * power)
+            .to_affine()
+            .write_raw(&mut tau_g1)
+            .unwrap();
+    }
+    let mut tau_g2 = vec![];
+    for power in powers(1 << power) {
+        (G2Affine::generator() * power)
+            .to_affine()
+            .write_raw(&mut tau_g2)
+            .unwrap();
+    }
+
+    let mut bytes = b"ptau".to_vec();
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend(3u32.to_le_bytes());
+    section(&mut bytes, 1, header);
+    section(&mut bytes, 2, tau_g1);
+    section(&mut bytes, 3, tau_g2);
+    std::fs::write(path, bytes).unwrap();
+}
+
+#[test]
+fn test_native_params() {
+    let params = ParamsKZG::<Bn256>::setup(5, StdRng::seed_from_u64(0));
+    verify_params(&params).unwrap();
+
+    let path = temp_path("params.bin");
+    write_params(&params, &path).unwrap();
+    let read = read_params(&path).unwrap();
+    assert_eq!(read.get_g(), params.get_g());
+    assert_eq!(read.s_g2(), params.s_g2());
+
+    let cache = ParamsCache::open(&path, 4).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(cache.max_k(), 4);
+
+    let small = cache.get(3).unwrap();
+    assert_eq!(small.k(), 3);
+    assert_eq!(small.get_g(), downsize(&params, 3).unwrap().get_g());
+    verify_params(&small).unwrap();
+    assert!(Arc::ptr_eq(&small, &cache.get(3).unwrap()));
+
+    // Error case: larger than the loaded params
+    assert!(matches!(
+        cache.get(5),
+        Err(ParamsError::TooSmall {
+            requested: 5,
+            available: 4
+        })
+    ));
+}
+
+#[test]
+fn test_ptau_params() {
+    let tau = Fr::random(StdRng::seed_from_u64(1));
+
+    let path = temp_path("params.ptau");
+    write_ptau(&path, tau, 5, false);
+    let params = read_ptau(&path, 4).unwrap();
+    verify_params(&params).unwrap();
+    assert_eq!(params.k(), 4);
+    assert_eq!(params.s_g2(), (G2Affine::generator() * tau).to_affine());
+    assert_eq!(
+        params.get_g()[2],
+        (G1Affine::generator() * tau.square()).to_affine()
+    );
+
+    let cache = ParamsCache::open(&path, 5).unwrap();
+    assert_eq!(cache.get(2).unwrap().get_g(), &params.get_g()[..4]);
+
+    // Error case: more rows than the ceremony
+    assert!(matches!(
+        read_ptau(&path, 6),
+        Err(ParamsError::TooSmall { .. })
+    ));
+    std::fs::remove_file(&path).unwrap();
+
+    // Error case: a power that is not consistent with the others
+    write_ptau(&path, tau, 5, true);
+    let params = read_ptau(&path, 4).unwrap();
+    assert!(matches!(
+        verify_params(&params),
+        Err(ParamsError::Inconsistent)
+    ));
+    assert!(ParamsCache::open(&path, 4).is_err());
+    std::fs::remove_file(&path).unwrap();
+}
+
+/// `tests/fixtures/pot4.ptau` is laid out as the output of
+/// `snarkjs powersoftau new bn128 4`, with coordinates in Montgomery form as
+/// snarkjs writes them, for tau = 5. See `tests/fixtures/README.md`
+#[test]
+fn test_ptau_fixture() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/pot4.ptau");
+    let params = read_ptau(&path, 4).unwrap();
+    verify_params(&params).unwrap();
+
+    let tau = Fr::from(5);
+    assert_eq!(params.s_g2(), (G2Affine::generator() * tau).to_affine());
+    for (i, g) in params.get_g().iter().enumerate() {
+        assert_eq!(
+            *g,
+            (G1Affine::generator() * tau.pow([i as u64])).to_affine()
+        );
+    }
+
+    let cache = ParamsCache::open(&path, 3).unwrap();
+    assert_eq!(cache.max_k(), 3);
+    assert_eq!(cache.get(3).unwrap().get_g(), &params.get_g()[..8]);
+
+    // Error case: more rows than the ceremony
+    assert!(matches!(
+        read_ptau(&path, 5),
+        Err(ParamsError::TooSmall {
+            requested: 5,
+            available: 4
+        })
+    ));
+}
@@ -0,0 +1,452 @@
+use crate::credential::{Credential, IssuerSecretKey, Presentation, PresentationPolicy};
+use crate::wire::{field_to_hex, Wire, WireError};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::{Field, FromUniformBytes, PrimeField};
+use rand_core::RngCore;
+use std::collections::BTreeSet;
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+/// Name of the attribute that holds the schema hash, first in every
+/// credential issued under a schema
+pub const SCHEMA_ATTRIBUTE: &str = "schema";
+
+#[derive(Debug, Error)]
+pub enum SchemaError {
+    #[error("invalid schema: {0}")]
+    Invalid(&'static str),
+    #[error("unknown attribute {0}")]
+    UnknownAttribute(String),
+    #[error("missing attribute {0}")]
+    MissingAttribute(String),
+    #[error("invalid value of attribute {0}")]
+    InvalidValue(String),
+    #[error("attributes are not committed to this schema")]
+    WrongSchema,
+    #[error("schema does not evolve from the previous version: {0}")]
+    Evolution(&'static str),
+    #[error("no schema with hash {0}")]
+    NotFound(String),
+    #[error("i/o error: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Wire(#[from] WireError),
+}
+
+/// Type of an attribute, which fixes how its values are encoded as field
+/// elements.
+///
+/// Every encoding is non zero, zero stands for an absent optional attribute
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeType {
+    /// Hash of the UTF-8 bytes
+    String,
+    /// `YYYY-MM-DD`, encoded as the integer `YYYYMMDD` so that encodings
+    /// compare like dates
+    Date,
+    /// One of the variants, encoded as its position plus one
+    Enum(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeDef {
+    pub name: String,
+    pub kind: AttributeType,
+    pub optional: bool,
+}
+
+impl AttributeDef {
+    pub fn new(name: &str, kind: AttributeType) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            optional: false,
+        }
+    }
+
+    pub fn optional(name: &str, kind: AttributeType) -> Self {
+        Self {
+            optional: true,
+            ..Self::new(name, kind)
+        }
+    }
+}
+
+/// The attributes of a type of credential.
+///
+/// Credentials issued under a schema hold its hash as first attribute, so
+/// that the hash is signed within the attribute commitment, followed by the
+/// attributes of the schema in order. A later version of a schema keeps the
+/// attributes of the previous one and only appends optional attributes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    pub name: String,
+    pub version: u32,
+    pub attributes: Vec<AttributeDef>,
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_')
+}
+
+fn days_in_month(year: u32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// `YYYY-MM-DD` as the integer `YYYYMMDD`
+fn parse_date(date: &str) -> Option<u64> {
+    let bytes = date.as_bytes();
+    if bytes.len() != 10
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !bytes
+            .iter()
+            .enumerate()
+            .all(|(i, c)| i == 4 || i == 7 || c.is_ascii_digit())
+    {
+        return None;
+    }
+    let year: u32 = date[0..4].parse().ok()?;
+    let month: u32 = date[5..7].parse().ok()?;
+    let day: u32 = date[8..10].parse().ok()?;
+    let valid =
+        year > 0 && (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day);
+    valid.then(|| (year * 10000 + month * 100 + day) as u64)
+}
+
+fn is_date<N: PrimeField>(value: &N) -> bool {
+    let repr = value.to_repr();
+    let (low, high) = repr.as_ref().split_at(8);
+    if high.iter().any(|byte| *byte != 0) {
+        return false;
+    }
+    let date = u64::from_le_bytes(low.try_into().unwrap());
+    let (year, month, day) = (date / 10000, date / 100 % 100, date % 100);
+    (1..=9999).contains(&year)
+        && (1..=12).contains(&month)
+        && day >= 1
+        && day <= days_in_month(year as u32, month as u32) as u64
+}
+
+fn hash_to_field<N: FromUniformBytes<64>>(personal: &[u8], bytes: &[u8]) -> N {
+    let hash = blake2b_simd::Params::new()
+        .hash_length(64)
+        .personal(personal)
+        .hash(bytes);
+    N::from_uniform_bytes(hash.as_array())
+}
+
+impl AttributeType {
+    fn encode<N: FromUniformBytes<64>>(&self, value: &str) -> Option<N> {
+        match self {
+            AttributeType::String => Some(hash_to_field(b"recreds-string", value.as_bytes())),
+            AttributeType::Date => parse_date(value).map(N::from),
+            AttributeType::Enum(variants) => variants
+                .iter()
+                .position(|variant| variant == value)
+                .map(|i| N::from(i as u64 + 1)),
+        }
+    }
+
+    /// Whether `value` may encode an attribute of this type. String hashes
+    /// can not be told apart from other field elements
+    fn accepts<N: PrimeField>(&self, value: &N) -> bool {
+        match self {
+            AttributeType::String => !bool::from(value.is_zero()),
+            AttributeType::Date => is_date(value),
+            AttributeType::Enum(variants) => {
+                (1..=variants.len() as u64).any(|i| *value == N::from(i))
+            }
+        }
+    }
+}
+
+impl Schema {
+    pub fn new(
+        name: &str,
+        version: u32,
+        attributes: Vec<AttributeDef>,
+    ) -> Result<Self, SchemaError> {
+        let schema = Self {
+            name: name.to_string(),
+            version,
+            attributes,
+        };
+        schema.check()?;
+        Ok(schema)
+    }
+
+    /// Checks the definition, for schemas that were not built with `new`
+    pub fn check(&self) -> Result<(), SchemaError> {
+        if !is_identifier(&self.name) {
+            return Err(SchemaError::Invalid("schema name"));
+        }
+        let mut names = BTreeSet::from([SCHEMA_ATTRIBUTE]);
+        for attribute in self.attributes.iter() {
+            if !is_identifier(&attribute.name) {
+                return Err(SchemaError::Invalid("attribute name"));
+            }
+            if !names.insert(attribute.name.as_str()) {
+                return Err(SchemaError::Invalid("duplicate attribute"));
+            }
+            if let AttributeType::Enum(variants) = &attribute.kind {
+                let unique: BTreeSet<&String> = variants.iter().collect();
+                if variants.is_empty() || unique.len() != variants.len() {
+                    return Err(SchemaError::Invalid("enum variants"));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Hash of the body of the binary encoding of the schema
+    pub fn hash<N: FromUniformBytes<64>>(&self) -> N {
+        let mut bytes = vec![];
+        self.write_body(&mut bytes);
+        hash_to_field(b"recreds-schema", &bytes)
+    }
+
+    /// Number of attributes of the credentials, counting the schema hash
+    pub fn num_attributes(&self) -> usize {
+        self.attributes.len() + 1
+    }
+
+    /// Names of the attributes of the credentials, in order
+    pub fn names(&self) -> Vec<&str> {
+        std::iter::once(SCHEMA_ATTRIBUTE)
+            .chain(
+                self.attributes
+                    .iter()
+                    .map(|attribute| attribute.name.as_str()),
+            )
+            .collect()
+    }
+
+    /// Position of attribute `name` in the credentials
+    pub fn position(&self, name: &str) -> Result<usize, SchemaError> {
+        self.names()
+            .iter()
+            .position(|other| *other == name)
+            .ok_or_else(|| SchemaError::UnknownAttribute(name.to_string()))
+    }
+
+    /// Encodes the attributes of a credential from `(name, value)` pairs.
+    /// Absent optional attributes are zero
+    pub fn encode<N: FromUniformBytes<64>>(
+        &self,
+        values: &[(&str, &str)],
+    ) -> Result<Vec<N>, SchemaError> {
+        for (i, (name, _)) in values.iter().enumerate() {
+            if !self
+                .attributes
+                .iter()
+                .any(|attribute| attribute.name == *name)
+            {
+                return Err(SchemaError::UnknownAttribute(name.to_string()));
+            }
+            if values[..i].iter().any(|(other, _)| other == name) {
+                return Err(SchemaError::InvalidValue(name.to_string()));
+            }
+        }
+        let mut attributes = vec![self.hash()];
+        for attribute in self.attributes.iter() {
+            let value = values.iter().find(|(name, _)| *name == attribute.name);
+            attributes.push(match value {
+                Some((_, value)) => attribute
+                    .kind
+                    .encode(value)
+                    .ok_or_else(|| SchemaError::InvalidValue(attribute.name.clone()))?,
+                None if attribute.optional => N::ZERO,
+                None => return Err(SchemaError::MissingAttribute(attribute.name.clone())),
+            });
+        }
+        Ok(attributes)
+    }
+
+    /// Checks that `attributes` are committed to the schema and that every
+    /// value may encode its attribute
+    pub fn validate<N: FromUniformBytes<64>>(&self, attributes: &[N]) -> Result<(), SchemaError> {
+        if attributes.len() != self.num_attributes() || attributes[0] != self.hash() {
+            return Err(SchemaError::WrongSchema);
+        }
+        for (attribute, value) in self.attributes.iter().zip(attributes[1..].iter()) {
+            if bool::from(value.is_zero()) {
+                if !attribute.optional {
+                    return Err(SchemaError::MissingAttribute(attribute.name.clone()));
+                }
+            } else if !attribute.kind.accepts(value) {
+                return Err(SchemaError::InvalidValue(attribute.name.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Issues a credential over attributes that the schema validates
+    pub fn issue<N: FromUniformBytes<64>>(
+        &self,
+        sk: &IssuerSecretKey,
+        attributes: Vec<N>,
+        rng: impl RngCore,
+    ) -> Result<Credential<N>, SchemaError> {
+        self.validate(&attributes)?;
+        Ok(Credential::issue(sk, attributes, rng))
+    }
+
+    /// Policy that discloses the schema hash and the attributes `names`
+    pub fn policy(&self, names: &[&str]) -> Result<PresentationPolicy, SchemaError> {
+        let mut disclosed = names
+            .iter()
+            .map(|name| self.position(name))
+            .collect::<Result<BTreeSet<_>, _>>()?;
+        disclosed.insert(0);
+        Ok(PresentationPolicy::new(
+            self.num_attributes(),
+            disclosed.into_iter().collect(),
+        ))
+    }
+
+    /// Checks that `presentation` discloses a credential of the schema
+    pub fn check_presentation<N: FromUniformBytes<64>>(
+        &self,
+        presentation: &Presentation<N>,
+    ) -> Result<(), SchemaError> {
+        if presentation.policy.num_attributes != self.num_attributes()
+            || presentation.policy.disclosed.first() != Some(&0)
+            || presentation.disclosed.first() != Some(&self.hash())
+        {
+            return Err(SchemaError::WrongSchema);
+        }
+        Ok(())
+    }
+
+    /// Checks that `next` is a valid evolution of the schema: the same name,
+    /// a later version, the same attributes and only optional additions
+    pub fn check_evolution(&self, next: &Schema) -> Result<(), SchemaError> {
+        if next.name != self.name {
+            return Err(SchemaError::Evolution("another schema name"));
+        }
+        if next.version <= self.version {
+            return Err(SchemaError::Evolution("version is not later"));
+        }
+        if next.attributes.len() < self.attributes.len()
+            || next.attributes[..self.attributes.len()] != self.attributes[..]
+        {
+            return Err(SchemaError::Evolution("existing attributes changed"));
+        }
+        if !next.attributes[self.attributes.len()..]
+            .iter()
+            .all(|attribute| attribute.optional)
+        {
+            return Err(SchemaError::Evolution("added attributes must be optional"));
+        }
+        Ok(())
+    }
+}
+
+/// Schemas stored in a directory as JSON files named after their hash.
+///
+/// The versions of a schema name are registered in order, each one an
+/// evolution of the latest registered version
+#[derive(Clone, Debug)]
+pub struct SchemaRegistry {
+    dir: PathBuf,
+}
+
+impl SchemaRegistry {
+    pub fn new(dir: impl AsRef<Path>) -> Result<Self, SchemaError> {
+        fs::create_dir_all(dir.as_ref())?;
+        Ok(Self {
+            dir: dir.as_ref().to_path_buf(),
+        })
+    }
+
+    fn path(&self, hash: &Fr) -> PathBuf {
+        self.dir.join(format!("{}.json", &field_to_hex(hash)[2..]))
+    }
+
+    /// Registers `schema` and returns its hash. Registering a schema again
+    /// does nothing
+    pub fn register(&self, schema: &Schema) -> Result<Fr, SchemaError> {
+        schema.check()?;
+        let hash = schema.hash();
+        let path = self.path(&hash);
+        if path.exists() {
+            return Ok(hash);
+        }
+        if let Some(latest) = self.latest(&schema.name)? {
+            latest.check_evolution(schema)?;
+        }
+        // write then rename so that an interrupted write leaves no schema file
+        let tmp = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp)?;
+        file.write_all(schema.to_json().as_bytes())?;
+        file.sync_all()?;
+        fs::rename(tmp, path)?;
+        Ok(hash)
+    }
+
+    pub fn get(&self, hash: &Fr) -> Result<Schema, SchemaError> {
+        let json = match fs::read_to_string(self.path(hash)) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(SchemaError::NotFound(field_to_hex(hash)))
+            }
+            result => result?,
+        };
+        let schema = Schema::from_json(&json)?;
+        if schema.hash::<Fr>() != *hash {
+            return Err(SchemaError::Invalid("schema file does not match its hash"));
+        }
+        Ok(schema)
+    }
+
+    /// Registered versions of the schema `name`, oldest first
+    pub fn versions(&self, name: &str) -> Result<Vec<Schema>, SchemaError> {
+        let mut versions = vec![];
+        for entry in fs::read_dir(&self.dir)? {
+            let path = entry?.path();
+            if path
+                .extension()
+                .map_or(true, |extension| extension != "json")
+            {
+                continue;
+            }
+            let schema = Schema::from_json(&fs::read_to_string(path)?)?;
+            if schema.name == name {
+                versions.push(schema);
+            }
+        }
+        versions.sort_by_key(|schema| schema.version);
+        Ok(versions)
+    }
+
+    pub fn latest(&self, name: &str) -> Result<Option<Schema>, SchemaError> {
+        Ok(self.versions(name)?.pop())
+    }
+
+    /// Schema of the credential of `presentation`, from its disclosed hash
+    pub fn resolve(&self, presentation: &Presentation<Fr>) -> Result<Schema, SchemaError> {
+        let hash = match (
+            presentation.policy.disclosed.first(),
+            presentation.disclosed.first(),
+        ) {
+            (Some(0), Some(hash)) => hash,
+            _ => return Err(SchemaError::WrongSchema),
+        };
+        let schema = self.get(hash)?;
+        schema.check_presentation(presentation)?;
+        Ok(schema)
+    }
+}
@@ -0,0 +1,174 @@
+use crate::credential::{IssuerSecretKey, Presentation};
+use crate::schema::{AttributeDef, AttributeType, Schema, SchemaError, SchemaRegistry};
+use crate::wire::Wire;
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::Field;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fs;
+
+fn identity(version: u32) -> Schema {
+    let mut attributes = vec![
+        AttributeDef::new("name", AttributeType::String),
+        AttributeDef::new("dob", AttributeType::Date),
+        AttributeDef::new(
+            "country",
+            AttributeType::Enum(vec!["FR".to_string(), "DE".to_string()]),
+        ),
+    ];
+    if version > 1 {
+        attributes.push(AttributeDef::optional("expiry", AttributeType::Date));
+    }
+    Schema::new("identity", version, attributes).unwrap()
+}
+
+#[test]
+fn test_encode_and_validate() {
+    let schema = identity(1);
+    assert_eq!(
+        schema.to_json(),
+        Schema::from_json(&schema.to_json()).unwrap().to_json()
+    );
+    assert_eq!(Schema::from_bytes(&schema.to_bytes()).unwrap(), schema);
+    assert_ne!(schema.hash::<Fr>(), identity(2).hash::<Fr>());
+    assert_eq!(schema.names(), vec!["schema", "name", "dob", "country"]);
+
+    let attributes: Vec<Fr> = schema
+        .encode(&[("name", "Alice"), ("dob", "1990-02-28"), ("country", "DE")])
+        .unwrap();
+    assert_eq!(attributes[0], schema.hash());
+    assert_eq!(attributes[2], Fr::from(19900228));
+    assert_eq!(attributes[3], Fr::from(2));
+    assert!(schema.validate(&attributes).is_ok());
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let sk = IssuerSecretKey::random(&mut rng);
+    let credential = schema.issue(&sk, attributes.clone(), &mut rng).unwrap();
+    assert!(credential.verify());
+
+    // Absent optional attributes are zero
+    let next = identity(2);
+    let next_attributes: Vec<Fr> = next
+        .encode(&[("name", "Alice"), ("dob", "1990-02-28"), ("country", "DE")])
+        .unwrap();
+    assert_eq!(next_attributes[4], Fr::ZERO);
+    assert!(next.validate(&next_attributes).is_ok());
+
+    // Error cases: invalid values and attributes
+    for (values, expected) in [
+        (
+            vec![("name", "Alice"), ("dob", "1990-02-29"), ("country", "DE")],
+            "dob",
+        ),
+        (
+            vec![("name", "Alice"), ("dob", "1990-2-28"), ("country", "DE")],
+            "dob",
+        ),
+        (
+            vec![("name", "Alice"), ("dob", "1990-02-28"), ("country", "IT")],
+            "country",
+        ),
+    ] {
+        assert!(matches!(
+            schema.encode::<Fr>(&values),
+            Err(SchemaError::InvalidValue(name)) if name == expected
+        ));
+    }
+    assert!(matches!(
+        schema.encode::<Fr>(&[("name", "Alice"), ("country", "DE")]),
+        Err(SchemaError::MissingAttribute(name)) if name == "dob"
+    ));
+    let values = [
+        ("name", "Alice"),
+        ("dob", "1990-02-28"),
+        ("country", "DE"),
+        ("age", "34"),
+    ];
+    assert!(matches!(
+        schema.encode::<Fr>(&values),
+        Err(SchemaError::UnknownAttribute(name)) if name == "age"
+    ));
+
+    // Error cases: issued attributes that the schema does not validate
+    let mut invalid = attributes.clone();
+    invalid[3] = Fr::from(3);
+    assert!(matches!(
+        schema.issue(&sk, invalid, &mut rng),
+        Err(SchemaError::InvalidValue(name)) if name == "country"
+    ));
+    assert!(matches!(
+        next.validate(&attributes),
+        Err(SchemaError::WrongSchema)
+    ));
+
+    // Error case: invalid definitions
+    for attributes in [
+        vec![AttributeDef::new("schema", AttributeType::Date)],
+        vec![AttributeDef::new("a b", AttributeType::Date)],
+        vec![AttributeDef::new("a", AttributeType::Enum(vec![]))],
+    ] {
+        assert!(matches!(
+            Schema::new("identity", 1, attributes),
+            Err(SchemaError::Invalid(_))
+        ));
+    }
+}
+
+#[test]
+fn test_policy() {
+    let schema = identity(1);
+    let policy = schema.policy(&["country", "name"]).unwrap();
+    assert_eq!(policy.num_attributes, 4);
+    assert_eq!(policy.disclosed, vec![0, 1, 3]);
+
+    let attributes: Vec<Fr> = schema
+        .encode(&[("name", "Alice"), ("dob", "1990-02-28"), ("country", "DE")])
+        .unwrap();
+    let presentation = Presentation {
+        disclosed: policy.disclosed.iter().map(|i| attributes[*i]).collect(),
+        policy,
+        issuer: IssuerSecretKey::random(StdRng::seed_from_u64(0)).public_key(),
+        nonce: Fr::ONE,
+        proof: vec![],
+    };
+    assert!(schema.check_presentation(&presentation).is_ok());
+    assert!(matches!(
+        identity(2).check_presentation(&presentation),
+        Err(SchemaError::WrongSchema)
+    ));
+}
+
+#[test]
+fn test_registry() {
+    let dir = std::env::temp_dir().join(format!("recreds-schemas-{}", std::process::id()));
+    let registry = SchemaRegistry::new(&dir).unwrap();
+
+    let hash = registry.register(&identity(1)).unwrap();
+    assert_eq!(registry.register(&identity(1)).unwrap(), hash);
+    assert_eq!(registry.get(&hash).unwrap(), identity(1));
+    let next = registry.register(&identity(2)).unwrap();
+    assert_eq!(registry.get(&next).unwrap(), identity(2));
+    assert_eq!(registry.latest("identity").unwrap(), Some(identity(2)));
+    assert_eq!(registry.versions("identity").unwrap().len(), 2);
+
+    // Error cases: changed or required attributes, earlier version
+    let mut changed = identity(3);
+    changed.attributes[1].kind = AttributeType::String;
+    let mut required = identity(3);
+    required.attributes[3].optional = false;
+    let mut earlier = identity(2);
+    earlier.version = 1;
+    earlier.attributes[3].name = "valid_until".to_string();
+    for schema in [changed, required, earlier] {
+        assert!(matches!(
+            registry.register(&schema),
+            Err(SchemaError::Evolution(_))
+        ));
+    }
+    assert!(matches!(
+        registry.get(&Fr::ONE),
+        Err(SchemaError::NotFound(_))
+    ));
+
+    fs::remove_dir_all(&dir).unwrap();
+}
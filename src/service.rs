@@ -0,0 +1,250 @@
+use crate::backend::Kzg;
+use crate::credential::{
+    verify_presentation, Credential, IssuerPublicKey, IssuerSecretKey, Presentation,
+    PresentationCircuit,
+};
+use crate::keys::{KeyStore, KeyStoreError};
+use crate::params::ParamsCache;
+use crate::sizing::estimate_k;
+use crate::wire::{field_from_hex, field_from_json, field_to_json, Wire};
+use halo2_proofs::halo2curves::bn256::Fr;
+use rand::rngs::OsRng;
+use serde_json::{json, Value};
+use std::fs;
+use std::io::{self, Read};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Decides whether an issuer signs a credential over `attributes`. Hooks
+/// return the reason of a refusal
+pub trait ApprovalHook: Send + Sync {
+    fn approve(&self, issuer: &str, attributes: &[Fr]) -> Result<(), String>;
+}
+
+impl<F: Fn(&str, &[Fr]) -> Result<(), String> + Send + Sync> ApprovalHook for F {
+    fn approve(&self, issuer: &str, attributes: &[Fr]) -> Result<(), String> {
+        self(issuer, attributes)
+    }
+}
+
+/// Issuance and verification over HTTP, for integration tests.
+///
+/// - `POST /issue` with `{"issuer": id, "attributes": [hex]}` returns the
+///   JSON encoding of a credential signed by the stand-in issuer `id`, once
+///   every approval hook accepted it
+/// - `POST /verify` with `{"presentation": presentation, "issuer": id,
+///   "nonce": nonce}` returns the disclosed attributes of a valid
+///   presentation by the registered issuer `id` that answers `nonce`, under
+///   a policy of at most `MAX_ATTRIBUTES` attributes whose verifying key is
+///   already in the key store
+/// - `GET /issuer/{id}/pubkey` returns the JSON encoding of the issuer key
+///
+/// Credentials, presentations and keys use the JSON encoding of `Wire`, with
+/// its `"type"` and `"version"`
+///
+/// Issuer secret keys are files in the issuers directory, proofs use the KZG
+/// backend with keys from a key store. The service never generates keys, so
+/// the key store lists the policies it verifies
+pub struct Service {
+    params: ParamsCache,
+    keys: KeyStore,
+    issuers: PathBuf,
+    hooks: Vec<Box<dyn ApprovalHook>>,
+}
+
+/// Largest policy `/verify` sizes a circuit for, as the policy comes from the
+/// client
+pub const MAX_ATTRIBUTES: usize = 64;
+
+type Reply = (u16, Value);
+
+fn error(status: u16, message: impl ToString) -> Reply {
+    (status, json!({ "error": message.to_string() }))
+}
+
+fn get<'a>(value: &'a Value, name: &str) -> Result<&'a Value, Reply> {
+    value
+        .get(name)
+        .ok_or_else(|| error(400, format!("missing {}", name)))
+}
+
+impl Service {
+    pub fn new(params: ParamsCache, keys: KeyStore, issuers: impl AsRef<Path>) -> io::Result<Self> {
+        fs::create_dir_all(issuers.as_ref())?;
+        Ok(Self {
+            params,
+            keys,
+            issuers: issuers.as_ref().to_path_buf(),
+            hooks: vec![],
+        })
+    }
+
+    pub fn with_hook(mut self, hook: impl ApprovalHook + 'static) -> Self {
+        self.hooks.push(Box::new(hook));
+        self
+    }
+
+    fn issuer_path(&self, id: &str) -> Option<PathBuf> {
+        let valid = !id.is_empty()
+            && id
+                .bytes()
+                .all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_');
+        valid.then(|| self.issuers.join(format!("{}.sk", id)))
+    }
+
+    /// Creates the stand-in issuer `id` unless it exists
+    pub fn add_issuer(&self, id: &str) -> io::Result<IssuerPublicKey> {
+        let path = self
+            .issuer_path(id)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "invalid issuer id"))?;
+        if !path.exists() {
+            fs::write(&path, IssuerSecretKey::random(OsRng).to_bytes())?;
+        }
+        Ok(self.issuer(id).unwrap()?.public_key())
+    }
+
+    fn issuer(&self, id: &str) -> Option<io::Result<IssuerSecretKey>> {
+        let path = self.issuer_path(id)?;
+        match fs::read(path) {
+            Ok(bytes) => Some(
+                IssuerSecretKey::from_bytes(&bytes)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e.to_string())),
+            ),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+            Err(e) => Some(Err(e)),
+        }
+    }
+
+    fn secret_key(&self, id: &str) -> Result<IssuerSecretKey, Reply> {
+        match self.issuer(id) {
+            None => Err(error(404, format!("unknown issuer {}", id))),
+            Some(sk) => sk.map_err(|e| error(500, e)),
+        }
+    }
+
+    fn pubkey(&self, id: &str) -> Result<Reply, Reply> {
+        let pk = self.secret_key(id)?.public_key();
+        Ok((200, serde_json::from_str(&pk.to_json()).unwrap()))
+    }
+
+    fn issue(&self, body: &Value) -> Result<Reply, Reply> {
+        let id = get(body, "issuer")?
+            .as_str()
+            .ok_or_else(|| error(400, "issuer is not a string"))?;
+        let attributes = get(body, "attributes")?
+            .as_array()
+            .ok_or_else(|| error(400, "attributes are not an array"))?
+            .iter()
+            .map(field_from_json)
+            .collect::<Result<Vec<Fr>, _>>()
+            .map_err(|e| error(400, e))?;
+        let sk = self.secret_key(id)?;
+        for hook in self.hooks.iter() {
+            hook.approve(id, &attributes).map_err(|e| error(403, e))?;
+        }
+        let credential = Credential::issue(&sk, attributes, OsRng);
+        Ok((200, serde_json::from_str(&credential.to_json()).unwrap()))
+    }
+
+    fn verify(&self, body: &Value) -> Result<Reply, Reply> {
+        let presentation = Presentation::<Fr>::from_json(&get(body, "presentation")?.to_string())
+            .map_err(|e| error(400, e))?;
+        let id = get(body, "issuer")?
+            .as_str()
+            .ok_or_else(|| error(400, "issuer is not a string"))?;
+        if self.secret_key(id)?.public_key() != presentation.issuer {
+            return Err(error(422, "presentation is from another issuer"));
+        }
+        // the nonce is required, a presentation without one could be replayed
+        let nonce: Fr = get(body, "nonce")?
+            .as_str()
+            .ok_or_else(|| error(400, "nonce is not a string"))
+            .and_then(|nonce| field_from_hex(nonce).map_err(|e| error(400, e)))?;
+        if nonce != presentation.nonce {
+            return Err(error(422, "presentation answers another challenge"));
+        }
+
+        let policy = &presentation.policy;
+        if policy.num_attributes > MAX_ATTRIBUTES {
+            return Err(error(
+                422,
+                format!("policies have at most {} attributes", MAX_ATTRIBUTES),
+            ));
+        }
+        let k = estimate_k::<Fr>(policy, None).map_err(|e| error(500, e))?.k;
+        if k > self.params.max_k() {
+            return Err(error(422, format!("policy needs params of k = {}", k)));
+        }
+        let params = self.params.get(k).map_err(|e| error(500, e))?;
+        let vk = match self.keys.load_vk(
+            &params,
+            &policy.circuit_id(),
+            &PresentationCircuit::new(policy.clone()),
+        ) {
+            Err(KeyStoreError::Io(e)) if e.kind() == io::ErrorKind::NotFound => {
+                return Err(error(422, "no verifying key for this policy"))
+            }
+            result => result.map_err(|e| error(500, e))?,
+        };
+
+        verify_presentation::<Kzg>(&params, &vk, &presentation)
+            .map_err(|_| error(422, "invalid presentation"))?;
+        let disclosed: Value = policy
+            .disclosed
+            .iter()
+            .zip(presentation.disclosed.iter())
+            .map(|(i, value)| (i.to_string(), field_to_json(value)))
+            .collect::<serde_json::Map<_, _>>()
+            .into();
+        Ok((200, json!({ "valid": true, "disclosed": disclosed })))
+    }
+
+    /// Replies to a request, with a JSON body in both cases
+    pub fn handle(&self, method: &Method, url: &str, body: &str) -> Reply {
+        let path: Vec<&str> = url.trim_matches('/').split('/').collect();
+        let parse = || serde_json::from_str::<Value>(body).map_err(|e| error(400, e));
+        let reply = match (method, path.as_slice()) {
+            (Method::Post, ["issue"]) => parse().and_then(|body| self.issue(&body)),
+            (Method::Post, ["verify"]) => parse().and_then(|body| self.verify(&body)),
+            (Method::Get, ["issuer", id, "pubkey"]) => self.pubkey(id),
+            _ => Err(error(404, "not found")),
+        };
+        reply.unwrap_or_else(|reply| reply)
+    }
+
+    fn respond(&self, mut request: Request) -> io::Result<()> {
+        let mut body = String::new();
+        let (status, reply) = match request.as_reader().read_to_string(&mut body) {
+            Ok(_) => self.handle(request.method(), request.url(), &body),
+            Err(e) => error(400, e),
+        };
+        let content_type = Header::from_bytes(&b"Content-Type"[..], &b"application/json"[..]);
+        request.respond(
+            Response::from_string(reply.to_string())
+                .with_status_code(status)
+                .with_header(content_type.unwrap()),
+        )
+    }
+
+    /// Serves requests one at a time until the server is dropped
+    pub fn serve(&self, server: &Server) {
+        for request in server.incoming_requests() {
+            if let Err(e) = self.respond(request) {
+                eprintln!("failed to respond: {}", e);
+            }
+        }
+    }
+}
+
+/// Binds a server to a loopback address. The service has no authentication
+/// and is only meant to run locally
+pub fn bind(addr: SocketAddr) -> io::Result<Server> {
+    if !addr.ip().is_loopback() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the service only listens on loopback addresses",
+        ));
+    }
+    Server::http(addr).map_err(|e| io::Error::other(e.to_string()))
+}
@@ -0,0 +1,481 @@
+use crate::credential::{PresentationCircuit, PresentationPolicy};
+use crate::schema::Schema;
+use ecc::maingate::DimensionMeasurement;
+use halo2_proofs::circuit::Value;
+use halo2_proofs::halo2curves::ff::{FromUniformBytes, PrimeField};
+use halo2_proofs::plonk::{
+    Advice, Any, Assigned, Assignment, Challenge, Circuit, Column, ConstraintSystem, Error,
+    Expression, Fixed, FloorPlanner, Instance, Selector,
+};
+use serde_json::{json, Value as Json};
+use std::collections::BTreeMap;
+use std::fmt;
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Error)]
+pub enum SizingError {
+    #[error("policy has {policy} attributes but the schema has {schema}")]
+    SchemaMismatch { schema: usize, policy: usize },
+    #[error("circuit synthesis failed: {0:?}")]
+    Synthesis(Error),
+}
+
+/// Rows of the regions of one name, which are the regions of one chip or
+/// gadget. Names of regions assigned within namespaces are prefixed by the
+/// namespaces, as in `namespace/region`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegionRows {
+    pub name: String,
+    pub rows: usize,
+}
+
+/// Size of a circuit: the smallest `k` it fits in, its row usage and its
+/// columns
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitSize {
+    pub k: u32,
+    /// Rows used by advice, fixed and selector assignments, tables included
+    pub rows: usize,
+    pub instance_rows: usize,
+    /// Rows per region name, in the order of their first assignment
+    pub regions: Vec<RegionRows>,
+    pub blinding_factors: usize,
+    pub advice_columns: usize,
+    pub fixed_columns: usize,
+    pub instance_columns: usize,
+    pub selectors: usize,
+    pub lookups: usize,
+}
+
+/// Records the rows assigned within each region
+#[derive(Default)]
+struct RegionMeasurement {
+    regions: Vec<RegionRows>,
+    /// Name and first and last rows of the current region
+    current: Option<(String, Option<(usize, usize)>)>,
+    /// Namespaces the current region is assigned in
+    namespaces: Vec<String>,
+    rows: usize,
+    instance_rows: usize,
+    /// Rows assigned in each fixed column, which bound the lookup tables
+    fixed_rows: BTreeMap<usize, usize>,
+}
+
+impl RegionMeasurement {
+    fn update(&mut self, offset: usize) {
+        self.rows = self.rows.max(offset + 1);
+        if let Some((_, range)) = self.current.as_mut() {
+            *range = Some(match *range {
+                Some((first, last)) => (first.min(offset), last.max(offset)),
+                None => (offset, offset),
+            });
+        }
+    }
+
+    fn update_any(&mut self, column: Column<Any>, offset: usize) {
+        match column.column_type() {
+            Any::Instance => self.instance_rows = self.instance_rows.max(offset + 1),
+            _ => self.update(offset),
+        }
+    }
+}
+
+impl<F: PrimeField> Assignment<F> for RegionMeasurement {
+    fn enter_region<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        let name = self
+            .namespaces
+            .iter()
+            .cloned()
+            .chain([name().into()])
+            .collect::<Vec<_>>()
+            .join("/");
+        self.current = Some((name, None));
+    }
+
+    fn exit_region(&mut self) {
+        let (name, range) = self.current.take().unwrap();
+        let rows = range.map_or(0, |(first, last)| last - first + 1);
+        match self.regions.iter_mut().find(|region| region.name == name) {
+            Some(region) => region.rows += rows,
+            None => self.regions.push(RegionRows { name, rows }),
+        }
+    }
+
+    fn get_challenge(&self, _: Challenge) -> Value<F> {
+        Value::unknown()
+    }
+
+    fn enable_selector<A, AR>(&mut self, _: A, _: &Selector, offset: usize) -> Result<(), Error>
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update(offset);
+        Ok(())
+    }
+
+    fn query_instance(&self, _: Column<Instance>, _: usize) -> Result<Value<F>, Error> {
+        Ok(Value::unknown())
+    }
+
+    fn annotate_column<A, AR>(&mut self, _: A, _: Column<Any>)
+    where
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+    }
+
+    fn assign_advice<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        _: Column<Advice>,
+        offset: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        self.update(offset);
+        Ok(())
+    }
+
+    fn assign_fixed<V, VR, A, AR>(
+        &mut self,
+        _: A,
+        column: Column<Fixed>,
+        offset: usize,
+        _: V,
+    ) -> Result<(), Error>
+    where
+        V: FnOnce() -> Value<VR>,
+        VR: Into<Assigned<F>>,
+        A: FnOnce() -> AR,
+        AR: Into<String>,
+    {
+        let rows = self.fixed_rows.entry(column.index()).or_default();
+        *rows = (*rows).max(offset + 1);
+        self.update(offset);
+        Ok(())
+    }
+
+    fn copy(
+        &mut self,
+        lhs: Column<Any>,
+        offset_lhs: usize,
+        rhs: Column<Any>,
+        offset_rhs: usize,
+    ) -> Result<(), Error> {
+        self.update_any(lhs, offset_lhs);
+        self.update_any(rhs, offset_rhs);
+        Ok(())
+    }
+
+    fn fill_from_row(
+        &mut self,
+        _: Column<Fixed>,
+        offset: usize,
+        _: Value<Assigned<F>>,
+    ) -> Result<(), Error> {
+        self.update(offset);
+        Ok(())
+    }
+
+    fn push_namespace<NR, N>(&mut self, name: N)
+    where
+        NR: Into<String>,
+        N: FnOnce() -> NR,
+    {
+        self.namespaces.push(name().into());
+    }
+
+    fn pop_namespace(&mut self, _: Option<String>) {
+        self.namespaces.pop();
+    }
+
+    fn query_advice(&self, _: Column<Advice>, _: usize) -> Result<F, Error> {
+        Err(Error::Synthesis)
+    }
+
+    fn query_fixed(&self, _: Column<Fixed>, _: usize) -> Result<F, Error> {
+        Err(Error::Synthesis)
+    }
+}
+
+/// Assigns `circuit` without witnesses nor commitments
+fn dry_run<N: PrimeField, C: Circuit<N>>(
+    circuit: &C,
+) -> Result<(ConstraintSystem<N>, RegionMeasurement), Error> {
+    let mut cs = ConstraintSystem::default();
+    let config = C::configure(&mut cs);
+    let mut measurement = RegionMeasurement::default();
+    C::FloorPlanner::synthesize(&mut measurement, circuit, config, cs.constants().to_vec())?;
+    Ok((cs, measurement))
+}
+
+fn size<N: PrimeField, C: Circuit<N>>(
+    circuit: &C,
+    cs: &ConstraintSystem<N>,
+    measurement: &RegionMeasurement,
+) -> Result<CircuitSize, Error> {
+    let blinding_factors = cs.blinding_factors();
+    let needed = (measurement.rows.max(measurement.instance_rows) + blinding_factors + 1)
+        .max(cs.minimum_rows());
+    let k = DimensionMeasurement::measure(circuit)?
+        .k()
+        .max(needed.next_power_of_two().trailing_zeros());
+    Ok(CircuitSize {
+        k,
+        rows: measurement.rows,
+        instance_rows: measurement.instance_rows,
+        regions: measurement.regions.clone(),
+        blinding_factors,
+        advice_columns: cs.num_advice_columns(),
+        fixed_columns: cs.num_fixed_columns(),
+        instance_columns: cs.num_instance_columns(),
+        selectors: cs.num_selectors(),
+        lookups: cs.lookups().len(),
+    })
+}
+
+/// Measures `circuit` without proving it.
+///
+/// `k` is the one of `DimensionMeasurement`, raised when the used rows, the
+/// blinding rows and the last unusable row would not fit
+pub fn measure<N: PrimeField, C: Circuit<N>>(circuit: &C) -> Result<CircuitSize, Error> {
+    let (cs, measurement) = dry_run(circuit)?;
+    size(circuit, &cs, &measurement)
+}
+
+/// Size of the presentation circuit of `policy` over `N`, whose `k` is the
+/// one to set up params and generate keys with. The regions are the ones of
+/// its gadgets: attributes, Poseidon, ECDSA and the range tables.
+///
+/// Fails if `schema` does not have the attributes of `policy`
+pub fn estimate_k<N: FromUniformBytes<64> + Ord>(
+    policy: &PresentationPolicy,
+    schema: Option<&Schema>,
+) -> Result<CircuitSize, SizingError> {
+    if let Some(schema) = schema {
+        if schema.num_attributes() != policy.num_attributes {
+            return Err(SizingError::SchemaMismatch {
+                schema: schema.num_attributes(),
+                policy: policy.num_attributes,
+            });
+        }
+    }
+    measure(&PresentationCircuit::<N>::new(policy.clone())).map_err(SizingError::Synthesis)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GateStats {
+    pub name: String,
+    pub constraints: usize,
+    pub degree: usize,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LookupStats {
+    pub inputs: usize,
+    pub degree: usize,
+    /// Assigned rows of the fixed columns of the table, zero for tables of
+    /// advice columns
+    pub table_rows: usize,
+}
+
+/// Statistics of a circuit, to find which chip dominates its cost
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircuitReport {
+    pub size: CircuitSize,
+    pub gates: Vec<GateStats>,
+    pub lookups: Vec<LookupStats>,
+    pub permutation_columns: usize,
+    /// Degree of the constraint system, that sets the size of the quotient
+    pub max_degree: usize,
+}
+
+fn fixed_columns<N: PrimeField>(expression: &Expression<N>) -> Vec<usize> {
+    expression.evaluate(
+        &|_| vec![],
+        &|_| vec![],
+        &|query| vec![query.column_index()],
+        &|_| vec![],
+        &|_| vec![],
+        &|_| vec![],
+        &|a| a,
+        &|a, b| [a, b].concat(),
+        &|a, b| [a, b].concat(),
+        &|a, _| a,
+    )
+}
+
+/// Reports the statistics of `circuit` from a dry-run assignment
+pub fn report<N: PrimeField, C: Circuit<N>>(circuit: &C) -> Result<CircuitReport, Error> {
+    let (cs, measurement) = dry_run(circuit)?;
+    let gates = cs
+        .gates()
+        .iter()
+        .map(|gate| GateStats {
+            name: gate.name().to_string(),
+            constraints: gate.polynomials().len(),
+            degree: gate
+                .polynomials()
+                .iter()
+                .map(Expression::degree)
+                .max()
+                .unwrap_or(0),
+        })
+        .collect();
+    let lookups = cs
+        .lookups()
+        .iter()
+        .map(|lookup| LookupStats {
+            inputs: lookup.input_expressions().len(),
+            degree: lookup
+                .input_expressions()
+                .iter()
+                .chain(lookup.table_expressions().iter())
+                .map(Expression::degree)
+                .max()
+                .unwrap_or(0),
+            table_rows: lookup
+                .table_expressions()
+                .iter()
+                .flat_map(fixed_columns)
+                .map(|column| measurement.fixed_rows.get(&column).copied().unwrap_or(0))
+                .max()
+                .unwrap_or(0),
+        })
+        .collect();
+    Ok(CircuitReport {
+        size: size(circuit, &cs, &measurement)?,
+        gates,
+        lookups,
+        permutation_columns: cs.permutation().get_columns().len(),
+        max_degree: cs.degree(),
+    })
+}
+
+impl CircuitReport {
+    pub fn to_json(&self) -> Json {
+        let size = &self.size;
+        json!({
+            "k": size.k,
+            "rows": size.rows,
+            "instance_rows": size.instance_rows,
+            "blinding_factors": size.blinding_factors,
+            "columns": {
+                "advice": size.advice_columns,
+                "fixed": size.fixed_columns,
+                "instance": size.instance_columns,
+                "selectors": size.selectors,
+                "permutation": self.permutation_columns,
+            },
+            "max_degree": self.max_degree,
+            "regions": size
+                .regions
+                .iter()
+                .map(|region| json!({ "name": region.name, "rows": region.rows }))
+                .collect::<Vec<_>>(),
+            "gates": self
+                .gates
+                .iter()
+                .map(|gate| json!({
+                    "name": gate.name,
+                    "constraints": gate.constraints,
+                    "degree": gate.degree,
+                }))
+                .collect::<Vec<_>>(),
+            "lookups": self
+                .lookups
+                .iter()
+                .map(|lookup| json!({
+                    "inputs": lookup.inputs,
+                    "degree": lookup.degree,
+                    "table_rows": lookup.table_rows,
+                }))
+                .collect::<Vec<_>>(),
+        })
+    }
+}
+
+/// Prints the report as tables
+impl fmt::Display for CircuitReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let size = &self.size;
+        writeln!(
+            f,
+            "k = {}, {} of {} rows used, {} blinding rows",
+            size.k,
+            size.rows,
+            1usize << size.k,
+            size.blinding_factors
+        )?;
+        writeln!(
+            f,
+            "columns: {} advice, {} fixed, {} instance, {} selectors, {} in the permutation",
+            size.advice_columns,
+            size.fixed_columns,
+            size.instance_columns,
+            size.selectors,
+            self.permutation_columns
+        )?;
+        writeln!(f, "max degree: {}", self.max_degree)?;
+
+        let width = size
+            .regions
+            .iter()
+            .map(|region| region.name.len())
+            .chain(self.gates.iter().map(|gate| gate.name.len()))
+            .chain(["region".len(), "lookup".len()])
+            .max()
+            .unwrap();
+        writeln!(f)?;
+        writeln!(f, "{:<width$}  {:>10}  {:>6}", "region", "rows", "share")?;
+        for region in size.regions.iter() {
+            writeln!(
+                f,
+                "{:<width$}  {:>10}  {:>5.1}%",
+                region.name,
+                region.rows,
+                100.0 * region.rows as f64 / size.rows.max(1) as f64
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<width$}  {:>11}  {:>6}",
+            "gate", "constraints", "degree"
+        )?;
+        for gate in self.gates.iter() {
+            writeln!(
+                f,
+                "{:<width$}  {:>11}  {:>6}",
+                gate.name, gate.constraints, gate.degree
+            )?;
+        }
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{:<width$}  {:>6}  {:>6}  {:>10}",
+            "lookup", "inputs", "degree", "table rows"
+        )?;
+        for (i, lookup) in self.lookups.iter().enumerate() {
+            writeln!(
+                f,
+                "{:<width$}  {:>6}  {:>6}  {:>10}",
+                i, lookup.inputs, lookup.degree, lookup.table_rows
+            )?;
+        }
+        Ok(())
+    }
+}
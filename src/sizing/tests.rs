@@ -0,0 +1,147 @@
+use crate::backend::{Backend, Kzg};
+use crate::credential::{PresentationCircuit, PresentationPolicy};
+use crate::schema::{AttributeDef, AttributeType, Schema};
+use crate::sizing::{estimate_k, report, SizingError};
+use ecc::maingate::DimensionMeasurement;
+use halo2_proofs::circuit::{Layouter, SimpleFloorPlanner, Value};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::Field;
+use halo2_proofs::plonk::{Advice, Circuit, Column, ConstraintSystem, Error};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+#[test]
+fn test_estimate_k() {
+    let policy = PresentationPolicy::new(4, vec![1, 3]);
+    let size = estimate_k::<Fr>(&policy, None).unwrap();
+
+    let circuit = PresentationCircuit::<Fr>::new(policy.clone());
+    assert!(size.k >= DimensionMeasurement::measure(&circuit).unwrap().k());
+    assert!(size.rows + size.blinding_factors < 1 << size.k);
+    assert_eq!(size.instance_columns, 1);
+    assert_eq!(size.instance_rows, 2 * 4 + 1 + 2);
+    assert!(size.lookups > 0);
+    let names: Vec<&str> = size.regions.iter().map(|r| r.name.as_str()).collect();
+    assert!(names.contains(&"assign aux values"));
+    for gadget in ["attributes", "poseidon", "ecdsa"] {
+        assert!(names.contains(&gadget));
+    }
+    assert!(size.regions.iter().all(|r| r.rows <= size.rows));
+
+    // More attributes take more rows, but the columns do not change
+    let larger = estimate_k::<Fr>(&PresentationPolicy::new(16, vec![1, 3]), None).unwrap();
+    assert!(larger.rows > size.rows);
+    assert_eq!(larger.advice_columns, size.advice_columns);
+
+    let schema = Schema::new(
+        "identity",
+        1,
+        vec![
+            AttributeDef::new("name", AttributeType::String),
+            AttributeDef::new("dob", AttributeType::Date),
+            AttributeDef::new("country", AttributeType::String),
+        ],
+    )
+    .unwrap();
+    assert_eq!(estimate_k::<Fr>(&policy, Some(&schema)).unwrap(), size);
+    // ECDSA dominates the attributes
+    let rows = |name: &str| size.regions.iter().find(|r| r.name == name).unwrap().rows;
+    assert!(rows("ecdsa") > rows("poseidon"));
+    assert!(rows("poseidon") > rows("attributes"));
+
+    // Error case: a policy of another number of attributes
+    assert!(matches!(
+        estimate_k::<Fr>(&PresentationPolicy::new(3, vec![1]), Some(&schema)),
+        Err(SizingError::SchemaMismatch {
+            schema: 4,
+            policy: 3
+        })
+    ));
+
+    // Error case: one row less than the estimate
+    let params = Kzg::setup(size.k - 1, StdRng::seed_from_u64(0));
+    assert!(matches!(
+        Kzg::keygen(&params, &circuit),
+        Err(Error::NotEnoughRowsAvailable { .. })
+    ));
+}
+
+#[test]
+fn test_report() {
+    let policy = PresentationPolicy::new(4, vec![1, 3]);
+    let report = report(&PresentationCircuit::<Fr>::new(policy.clone())).unwrap();
+
+    assert_eq!(report.size, estimate_k::<Fr>(&policy, None).unwrap());
+    assert!(report
+        .gates
+        .iter()
+        .all(|gate| gate.constraints > 0 && gate.degree <= report.max_degree));
+    assert_eq!(report.lookups.len(), report.size.lookups);
+    assert!(report.lookups.iter().all(|lookup| lookup.table_rows > 0));
+    assert!(report.permutation_columns > 0);
+
+    let json = report.to_json();
+    assert_eq!(json["k"], report.size.k);
+    assert_eq!(json["columns"]["advice"], report.size.advice_columns);
+    assert_eq!(
+        json["regions"].as_array().unwrap().len(),
+        report.size.regions.len()
+    );
+    assert!(report.to_string().contains("ecdsa"));
+}
+
+/// Loads rows of an advice column, within a chip namespace and outside
+#[derive(Clone)]
+struct NamespacedCircuit;
+
+fn load(
+    layouter: &mut impl Layouter<Fr>,
+    column: Column<Advice>,
+    rows: usize,
+) -> Result<(), Error> {
+    layouter.assign_region(
+        || "load",
+        |mut region| {
+            for offset in 0..rows {
+                region.assign_advice(|| "row", column, offset, || Value::known(Fr::ONE))?;
+            }
+            Ok(())
+        },
+    )
+}
+
+impl Circuit<Fr> for NamespacedCircuit {
+    type Config = Column<Advice>;
+    type FloorPlanner = SimpleFloorPlanner;
+
+    fn without_witnesses(&self) -> Self {
+        self.clone()
+    }
+
+    fn configure(meta: &mut ConstraintSystem<Fr>) -> Self::Config {
+        meta.advice_column()
+    }
+
+    fn synthesize(
+        &self,
+        column: Self::Config,
+        mut layouter: impl Layouter<Fr>,
+    ) -> Result<(), Error> {
+        load(&mut layouter.namespace(|| "chip"), column, 3)?;
+        load(&mut layouter.namespace(|| "chip"), column, 1)?;
+        load(&mut layouter, column, 2)
+    }
+}
+
+#[test]
+fn test_report_namespaces() {
+    let report = report(&NamespacedCircuit).unwrap();
+    let regions: Vec<(&str, usize)> = report
+        .size
+        .regions
+        .iter()
+        .map(|region| (region.name.as_str(), region.rows))
+        .collect();
+    assert_eq!(regions, vec![("chip/load", 4), ("load", 2)]);
+    assert_eq!(report.size.rows, 6);
+}
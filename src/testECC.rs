@@ -202,9 +202,6 @@ mod testECC{
 
     #[test]
     fn test_ec_ops() {
-        
-        let k = 14;
-
         let mut rng = test_rng();
         let s = Fr::random(&mut rng);
         let p1 = G1::random(&mut rng).to_affine();
@@ -222,6 +219,7 @@ mod testECC{
                 p4,
                 p5,
             };
+            let k = recreds::sizing::measure(&circuit).unwrap().k;
             let params = ParamsKZG::<Bn256>::setup(k, &mut OsRng);
             let verifier_params = params.verifier_params();
             let vk = keygen_vk(&params, &circuit).expect("keygen_vk should not fail");
@@ -0,0 +1,271 @@
+use crate::backend::Backend;
+use crate::credential::{Credential, IssuerPublicKey, Presentation, PresentationPolicy};
+use crate::wire::{field_from_json, field_to_json, Wire, WireError};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use ecdsa::credential::attribute_commitment;
+use halo2_proofs::halo2curves::ff::{FromUniformBytes, PrimeField};
+use serde_json::{json, Map, Value};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+pub const CREDENTIALS_CONTEXT: &str = "https://www.w3.org/2018/credentials/v1";
+pub const RECREDS_CONTEXT: &str = "urn:recreds:context:v1";
+
+const CREDENTIAL_TYPE: &str = "Re2credsCredential";
+const PRESENTATION_TYPE: &str = "Re2credsPresentation";
+const SIGNATURE_TYPE: &str = "Re2credsEcdsaSecp256k1Signature";
+const PROOF_TYPE: &str = "Re2credsHalo2Presentation";
+
+/// Both contexts are bundled so that documents are produced, read and
+/// expanded without fetching anything
+const CREDENTIALS_CONTEXT_DOCUMENT: &str = include_str!("vc/credentials-v1.jsonld");
+const RECREDS_CONTEXT_DOCUMENT: &str = include_str!("vc/context.jsonld");
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum VcError {
+    #[error("unsupported @context, expected the credentials and Re2creds contexts")]
+    Context,
+    #[error("document is not of type {0}")]
+    Type(&'static str),
+    #[error("missing or malformed {0}")]
+    Malformed(&'static str),
+    #[error("{0} does not match the rest of the document")]
+    Mismatch(&'static str),
+    #[error(transparent)]
+    Wire(#[from] WireError),
+}
+
+/// Resolves the contexts of Re2creds documents from local copies, for a
+/// document loader of a JSON-LD processor
+pub fn context_document(url: &str) -> Option<Value> {
+    let document = match url {
+        CREDENTIALS_CONTEXT => CREDENTIALS_CONTEXT_DOCUMENT,
+        RECREDS_CONTEXT => RECREDS_CONTEXT_DOCUMENT,
+        _ => return None,
+    };
+    Some(serde_json::from_str(document).unwrap())
+}
+
+fn get<'a>(value: &'a Value, name: &'static str) -> Result<&'a Value, VcError> {
+    value.get(name).ok_or(VcError::Malformed(name))
+}
+
+/// Credentials must have an issuance date; it is not part of what is signed
+fn check_issuance_date(credential: &Value) -> Result<(), VcError> {
+    get(credential, "issuanceDate")?
+        .as_str()
+        .map(|_| ())
+        .ok_or(VcError::Malformed("issuanceDate"))
+}
+
+fn check_envelope(document: &Value, types: [&'static str; 2]) -> Result<(), VcError> {
+    if get(document, "@context")? != &json!([CREDENTIALS_CONTEXT, RECREDS_CONTEXT]) {
+        return Err(VcError::Context);
+    }
+    let document_types = get(document, "type")?
+        .as_array()
+        .ok_or(VcError::Malformed("type"))?;
+    for t in types.iter() {
+        if !document_types
+            .iter()
+            .any(|document_type| document_type == t)
+        {
+            return Err(VcError::Type(t));
+        }
+    }
+    Ok(())
+}
+
+/// Issuers are identified by their public key
+fn issuer_to_json(issuer: &IssuerPublicKey) -> Value {
+    let public_key = issuer.to_json_value();
+    json!({
+        "id": format!(
+            "urn:recreds:issuer:{}:{}",
+            public_key["x"].as_str().unwrap(),
+            public_key["y"].as_str().unwrap()
+        ),
+        "type": "Re2credsIssuer",
+        "publicKey": public_key,
+    })
+}
+
+fn issuer_from_json(value: &Value) -> Result<IssuerPublicKey, VcError> {
+    let issuer = IssuerPublicKey::from_json_value(get(value, "publicKey")?)?;
+    if get(value, "id")? != &issuer_to_json(&issuer)["id"] {
+        return Err(VcError::Mismatch("issuer id"));
+    }
+    Ok(issuer)
+}
+
+fn subject_to_json<N: PrimeField>(names: &[&str], values: &[N]) -> Result<Value, VcError> {
+    if names.len() != values.len() {
+        return Err(VcError::Mismatch("credentialSubject"));
+    }
+    let subject: Map<String, Value> = names
+        .iter()
+        .zip(values.iter())
+        .map(|(name, value)| (name.to_string(), field_to_json(value)))
+        .collect();
+    Ok(Value::Object(subject))
+}
+
+/// Reads exactly the attributes `names`, in their order
+fn subject_from_json<N: PrimeField>(value: &Value, names: &[&str]) -> Result<Vec<N>, VcError> {
+    let subject = value
+        .as_object()
+        .ok_or(VcError::Malformed("credentialSubject"))?;
+    if subject.len() != names.len() {
+        return Err(VcError::Mismatch("credentialSubject"));
+    }
+    names
+        .iter()
+        .map(|name| {
+            let value = subject
+                .get(*name)
+                .ok_or(VcError::Mismatch("credentialSubject"))?;
+            Ok(field_from_json(value)?)
+        })
+        .collect()
+}
+
+/// Wraps a credential in a verifiable credential whose subject has the
+/// attributes under `names`, and whose proof is the issuer signature over
+/// their commitment
+pub fn credential_to_vc<N: FromUniformBytes<64>>(
+    credential: &Credential<N>,
+    names: &[&str],
+    issuance_date: &str,
+) -> Result<Value, VcError> {
+    Ok(json!({
+        "@context": [CREDENTIALS_CONTEXT, RECREDS_CONTEXT],
+        "type": ["VerifiableCredential", CREDENTIAL_TYPE],
+        "issuer": issuer_to_json(&credential.issuer),
+        "issuanceDate": issuance_date,
+        "credentialSubject": subject_to_json(names, &credential.attributes)?,
+        "proof": {
+            "type": SIGNATURE_TYPE,
+            "attributeCommitment": field_to_json(&attribute_commitment(&credential.attributes)),
+            "r": field_to_json(&credential.signature.0),
+            "s": field_to_json(&credential.signature.1),
+        },
+    }))
+}
+
+pub fn credential_from_vc<N: FromUniformBytes<64>>(
+    vc: &Value,
+    names: &[&str],
+) -> Result<Credential<N>, VcError> {
+    check_envelope(vc, ["VerifiableCredential", CREDENTIAL_TYPE])?;
+    check_issuance_date(vc)?;
+    let proof = get(vc, "proof")?;
+    if get(proof, "type")? != SIGNATURE_TYPE {
+        return Err(VcError::Type(SIGNATURE_TYPE));
+    }
+    let credential = Credential {
+        attributes: subject_from_json(get(vc, "credentialSubject")?, names)?,
+        issuer: issuer_from_json(get(vc, "issuer")?)?,
+        signature: (
+            field_from_json(get(proof, "r")?)?,
+            field_from_json(get(proof, "s")?)?,
+        ),
+    };
+    let commitment: N = field_from_json(get(proof, "attributeCommitment")?)?;
+    if commitment != attribute_commitment(&credential.attributes) {
+        return Err(VcError::Mismatch("attributeCommitment"));
+    }
+    Ok(credential)
+}
+
+/// Wraps a presentation in a verifiable presentation. The derived credential
+/// only carries the disclosed attributes and the issuance date of the
+/// credential, and the proof carries the proof bytes and public inputs of
+/// backend `B`
+pub fn presentation_to_vp<B: Backend>(
+    presentation: &Presentation<B::Scalar>,
+    names: &[&str],
+    issuance_date: &str,
+) -> Result<Value, VcError> {
+    if names.len() != presentation.policy.num_attributes {
+        return Err(VcError::Mismatch("credentialSubject"));
+    }
+    let disclosed_names: Vec<&str> = presentation
+        .policy
+        .disclosed
+        .iter()
+        .map(|i| names[*i])
+        .collect();
+    Ok(json!({
+        "@context": [CREDENTIALS_CONTEXT, RECREDS_CONTEXT],
+        "type": ["VerifiablePresentation", PRESENTATION_TYPE],
+        "verifiableCredential": [{
+            "@context": [CREDENTIALS_CONTEXT, RECREDS_CONTEXT],
+            "type": ["VerifiableCredential", CREDENTIAL_TYPE],
+            "issuer": issuer_to_json(&presentation.issuer),
+            "issuanceDate": issuance_date,
+            "credentialSubject": subject_to_json(&disclosed_names, &presentation.disclosed)?,
+        }],
+        "proof": {
+            "type": PROOF_TYPE,
+            "backend": B::NAME,
+            "challenge": field_to_json(&presentation.nonce),
+            "policy": presentation.policy.to_json_value(),
+            "proofValue": BASE64.encode(&presentation.proof),
+            "publicInputs": presentation
+                .public_inputs()
+                .iter()
+                .map(field_to_json)
+                .collect::<Vec<_>>(),
+        },
+    }))
+}
+
+pub fn presentation_from_vp<B: Backend>(
+    vp: &Value,
+    names: &[&str],
+) -> Result<Presentation<B::Scalar>, VcError> {
+    check_envelope(vp, ["VerifiablePresentation", PRESENTATION_TYPE])?;
+    let credential = match get(vp, "verifiableCredential")?.as_array() {
+        Some(credentials) if credentials.len() == 1 => &credentials[0],
+        _ => return Err(VcError::Malformed("verifiableCredential")),
+    };
+    check_envelope(credential, ["VerifiableCredential", CREDENTIAL_TYPE])?;
+    check_issuance_date(credential)?;
+
+    let proof = get(vp, "proof")?;
+    if get(proof, "type")? != PROOF_TYPE {
+        return Err(VcError::Type(PROOF_TYPE));
+    }
+    if get(proof, "backend")? != B::NAME {
+        return Err(VcError::Mismatch("backend"));
+    }
+    let policy = PresentationPolicy::from_json_value(get(proof, "policy")?)?;
+    if names.len() != policy.num_attributes {
+        return Err(VcError::Mismatch("policy"));
+    }
+    let disclosed_names: Vec<&str> = policy.disclosed.iter().map(|i| names[*i]).collect();
+    let proof_value = get(proof, "proofValue")?
+        .as_str()
+        .and_then(|proof| BASE64.decode(proof).ok())
+        .ok_or(VcError::Malformed("proofValue"))?;
+
+    let presentation = Presentation {
+        disclosed: subject_from_json(get(credential, "credentialSubject")?, &disclosed_names)?,
+        issuer: issuer_from_json(get(credential, "issuer")?)?,
+        nonce: field_from_json(get(proof, "challenge")?)?,
+        policy,
+        proof: proof_value,
+    };
+    let public_inputs = presentation
+        .public_inputs()
+        .iter()
+        .map(field_to_json)
+        .collect::<Vec<_>>();
+    if get(proof, "publicInputs")? != &Value::Array(public_inputs) {
+        return Err(VcError::Mismatch("publicInputs"));
+    }
+    Ok(presentation)
+}
@@ -0,0 +1,145 @@
+use crate::backend::{Backend, Ipa, Kzg};
+use crate::credential::{Credential, IssuerSecretKey, Presentation, PresentationPolicy};
+use crate::vc::{
+    context_document, credential_from_vc, credential_to_vc, presentation_from_vp,
+    presentation_to_vp, VcError, CREDENTIALS_CONTEXT, PROOF_TYPE, RECREDS_CONTEXT, SIGNATURE_TYPE,
+};
+use halo2_proofs::halo2curves::ff::Field;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+const NAMES: [&str; 3] = ["name", "dateOfBirth", "country"];
+
+fn artifacts() -> (
+    Credential<<Kzg as Backend>::Scalar>,
+    Presentation<<Kzg as Backend>::Scalar>,
+) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let sk = IssuerSecretKey::random(&mut rng);
+    let attributes = (0..3).map(|_| Field::random(&mut rng)).collect();
+    let credential = Credential::issue(&sk, attributes, &mut rng);
+    let presentation = Presentation {
+        policy: PresentationPolicy::new(3, vec![2]),
+        issuer: credential.issuer,
+        nonce: Field::random(&mut rng),
+        disclosed: vec![credential.attributes[2]],
+        proof: vec![1, 2, 3, 4],
+    };
+    (credential, presentation)
+}
+
+#[test]
+fn test_context() {
+    let context = context_document(RECREDS_CONTEXT).unwrap();
+    let terms = context["@context"].as_object().unwrap();
+    assert!(terms.contains_key(SIGNATURE_TYPE));
+    assert!(terms.contains_key(PROOF_TYPE));
+
+    let credentials = context_document(CREDENTIALS_CONTEXT).unwrap();
+    let credentials = credentials["@context"].as_object().unwrap();
+    assert!(credentials["VerifiableCredential"]["@context"]
+        .get("issuanceDate")
+        .is_some());
+    assert!(credentials.contains_key("VerifiablePresentation"));
+    // both contexts are protected, so they must not define the same terms
+    assert!(terms
+        .keys()
+        .filter(|term| !term.starts_with('@'))
+        .all(|term| !credentials.contains_key(term)));
+
+    // Error case: contexts are not resolved remotely
+    assert!(context_document("https://example.org/other").is_none());
+}
+
+#[test]
+fn test_credential_vc() {
+    let (credential, _) = artifacts();
+    let vc = credential_to_vc(&credential, &NAMES, "2024-01-01T00:00:00Z").unwrap();
+    assert_eq!(
+        vc["credentialSubject"]["country"],
+        crate::wire::field_to_json(&credential.attributes[2])
+    );
+    assert_eq!(credential_from_vc(&vc, &NAMES), Ok(credential.clone()));
+
+    // Error case: attribute that is not the signed one
+    let mut tampered = vc.clone();
+    tampered["credentialSubject"]["country"] = vc["credentialSubject"]["name"].clone();
+    assert_eq!(
+        credential_from_vc::<<Kzg as Backend>::Scalar>(&tampered, &NAMES),
+        Err(VcError::Mismatch("attributeCommitment"))
+    );
+
+    // Error case: attribute names of another schema
+    assert_eq!(
+        credential_from_vc::<<Kzg as Backend>::Scalar>(&vc, &["name", "dateOfBirth", "city"]),
+        Err(VcError::Mismatch("credentialSubject"))
+    );
+
+    // Error case: fewer names than attributes
+    assert_eq!(
+        credential_to_vc(&credential, &NAMES[..2], "2024-01-01T00:00:00Z"),
+        Err(VcError::Mismatch("credentialSubject"))
+    );
+
+    // Error case: unknown context
+    let mut tampered = vc;
+    tampered["@context"][1] = "https://example.org/other".into();
+    assert_eq!(
+        credential_from_vc::<<Kzg as Backend>::Scalar>(&tampered, &NAMES),
+        Err(VcError::Context)
+    );
+}
+
+#[test]
+fn test_presentation_vp() {
+    let (credential, presentation) = artifacts();
+    let vp = presentation_to_vp::<Kzg>(&presentation, &NAMES, "2024-01-01T00:00:00Z").unwrap();
+    let credential_vc = &vp["verifiableCredential"][0];
+    let subject = credential_vc["credentialSubject"].as_object().unwrap();
+    assert_eq!(subject.keys().collect::<Vec<_>>(), vec!["country"]);
+    assert_eq!(credential_vc["issuanceDate"], "2024-01-01T00:00:00Z");
+    assert_eq!(
+        presentation_from_vp::<Kzg>(&vp, &NAMES),
+        Ok(presentation.clone())
+    );
+
+    // Error case: fewer names than attributes of the policy
+    assert_eq!(
+        presentation_to_vp::<Kzg>(&presentation, &NAMES[..2], "2024-01-01T00:00:00Z"),
+        Err(VcError::Mismatch("credentialSubject"))
+    );
+
+    // Error case: the credential has no issuance date
+    let mut tampered = vp.clone();
+    tampered["verifiableCredential"][0]
+        .as_object_mut()
+        .unwrap()
+        .remove("issuanceDate");
+    assert_eq!(
+        presentation_from_vp::<Kzg>(&tampered, &NAMES),
+        Err(VcError::Malformed("issuanceDate"))
+    );
+
+    // Error case: disclosed value that the public inputs do not commit to
+    let mut tampered = vp.clone();
+    tampered["verifiableCredential"][0]["credentialSubject"]["country"] =
+        crate::wire::field_to_json(&credential.attributes[0]);
+    assert_eq!(
+        presentation_from_vp::<Kzg>(&tampered, &NAMES),
+        Err(VcError::Mismatch("publicInputs"))
+    );
+
+    // Error case: proof of another backend
+    assert_eq!(
+        presentation_from_vp::<Ipa>(&vp, &NAMES),
+        Err(VcError::Mismatch("backend"))
+    );
+
+    // Error case: not a presentation
+    let mut tampered = vp;
+    tampered["type"] = serde_json::json!(["VerifiablePresentation"]);
+    assert_eq!(
+        presentation_from_vp::<Kzg>(&tampered, &NAMES),
+        Err(VcError::Type("Re2credsPresentation"))
+    );
+}
@@ -0,0 +1,639 @@
+use crate::credential::{
+    Credential, IssuerCurve, IssuerPublicKey, IssuerSecretKey, Presentation, PresentationPolicy,
+};
+use crate::schema::{AttributeDef, AttributeType, Schema};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use halo2_proofs::arithmetic::CurveAffine;
+use halo2_proofs::halo2curves::ff::PrimeField;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+#[cfg(test)]
+mod tests;
+
+const MAGIC: &[u8; 3] = b"RCW";
+pub const VERSION: u8 = 1;
+
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum WireError {
+    #[error("not an encoded artifact")]
+    BadMagic,
+    #[error("unknown encoding version {0}")]
+    UnknownVersion(u8),
+    #[error("expected a {expected} but found a {found}")]
+    UnexpectedKind {
+        expected: &'static str,
+        found: String,
+    },
+    #[error("input is truncated")]
+    Truncated,
+    #[error("{0} trailing bytes after the artifact")]
+    TrailingBytes(usize),
+    #[error("non canonical field element")]
+    NonCanonical,
+    #[error("point is not on the curve")]
+    InvalidPoint,
+    #[error("malformed artifact: {0}")]
+    Malformed(&'static str),
+    #[error("invalid json: {0}")]
+    Json(String),
+}
+
+/// Artifacts with a versioned binary encoding and a canonical JSON encoding.
+///
+/// The binary encoding is the magic `RCW`, the version, the kind and the
+/// body. Integers are little endian `u32`, field elements their canonical
+/// representation and byte strings are prefixed by their length. The JSON
+/// encoding is an object with sorted keys and no whitespace, field elements
+/// are big endian hex strings and byte strings are base64
+pub trait Wire: Sized {
+    /// Name of the kind of artifact in JSON
+    const NAME: &'static str;
+    /// Kind of artifact in the binary header
+    const KIND: u8;
+
+    fn write_body(&self, bytes: &mut Vec<u8>);
+
+    fn read_body(reader: &mut Reader<'_>) -> Result<Self, WireError>;
+
+    fn to_json_value(&self) -> Value;
+
+    fn from_json_value(value: &Value) -> Result<Self, WireError>;
+
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([VERSION, Self::KIND]);
+        self.write_body(&mut bytes);
+        bytes
+    }
+
+    fn from_bytes(bytes: &[u8]) -> Result<Self, WireError> {
+        let mut reader = Reader { bytes };
+        if reader.take(MAGIC.len())? != MAGIC {
+            return Err(WireError::BadMagic);
+        }
+        let version = reader.u8()?;
+        if version != VERSION {
+            return Err(WireError::UnknownVersion(version));
+        }
+        let kind = reader.u8()?;
+        if kind != Self::KIND {
+            return Err(WireError::UnexpectedKind {
+                expected: Self::NAME,
+                found: kind_name(kind).to_string(),
+            });
+        }
+        let artifact = Self::read_body(&mut reader)?;
+        match reader.bytes.len() {
+            0 => Ok(artifact),
+            n => Err(WireError::TrailingBytes(n)),
+        }
+    }
+
+    fn to_json(&self) -> String {
+        let mut value = self.to_json_value();
+        let object = value.as_object_mut().unwrap();
+        object.insert("type".to_string(), json!(Self::NAME));
+        object.insert("version".to_string(), json!(VERSION));
+        value.to_string()
+    }
+
+    fn from_json(json: &str) -> Result<Self, WireError> {
+        let value: Value =
+            serde_json::from_str(json).map_err(|e| WireError::Json(e.to_string()))?;
+        let version = field(&value, "version")?
+            .as_u64()
+            .ok_or(WireError::Malformed("version"))?;
+        if version != VERSION as u64 {
+            return Err(WireError::UnknownVersion(version.min(u8::MAX as u64) as u8));
+        }
+        let kind = field(&value, "type")?
+            .as_str()
+            .ok_or(WireError::Malformed("type"))?;
+        if kind != Self::NAME {
+            return Err(WireError::UnexpectedKind {
+                expected: Self::NAME,
+                found: kind.to_string(),
+            });
+        }
+        Self::from_json_value(&value)
+    }
+}
+
+const POLICY: u8 = 1;
+const ISSUER: u8 = 2;
+const CREDENTIAL: u8 = 3;
+const PRESENTATION: u8 = 4;
+const ISSUER_SECRET_KEY: u8 = 5;
+const SCHEMA: u8 = 6;
+
+fn kind_name(kind: u8) -> &'static str {
+    match kind {
+        POLICY => "policy",
+        ISSUER => "issuer",
+        CREDENTIAL => "credential",
+        PRESENTATION => "presentation",
+        ISSUER_SECRET_KEY => "issuer-secret-key",
+        SCHEMA => "schema",
+        _ => "unknown artifact",
+    }
+}
+
+/// Reads the body of a binary artifact
+#[derive(Debug)]
+pub struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], WireError> {
+        if self.bytes.len() < n {
+            return Err(WireError::Truncated);
+        }
+        let (head, tail) = self.bytes.split_at(n);
+        self.bytes = tail;
+        Ok(head)
+    }
+
+    fn u8(&mut self) -> Result<u8, WireError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, WireError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn field<F: PrimeField>(&mut self) -> Result<F, WireError> {
+        let mut repr = F::Repr::default();
+        let len = repr.as_ref().len();
+        repr.as_mut().copy_from_slice(self.take(len)?);
+        Option::from(F::from_repr(repr)).ok_or(WireError::NonCanonical)
+    }
+
+    fn fields<F: PrimeField>(&mut self) -> Result<Vec<F>, WireError> {
+        let n = self.u32()?;
+        (0..n).map(|_| self.field()).collect()
+    }
+
+    fn string(&mut self) -> Result<String, WireError> {
+        let n = self.u32()? as usize;
+        String::from_utf8(self.take(n)?.to_vec()).map_err(|_| WireError::Malformed("string"))
+    }
+}
+
+fn write_u32(bytes: &mut Vec<u8>, n: usize) {
+    bytes.extend((n as u32).to_le_bytes());
+}
+
+fn write_string(bytes: &mut Vec<u8>, string: &str) {
+    write_u32(bytes, string.len());
+    bytes.extend(string.as_bytes());
+}
+
+fn write_fields<F: PrimeField>(bytes: &mut Vec<u8>, fields: &[F]) {
+    write_u32(bytes, fields.len());
+    for field in fields.iter() {
+        bytes.extend(field.to_repr().as_ref());
+    }
+}
+
+fn field<'a>(value: &'a Value, name: &'static str) -> Result<&'a Value, WireError> {
+    value.get(name).ok_or(WireError::Malformed(name))
+}
+
+/// Big endian hex of a field element, as in the JSON encoding
+pub fn field_to_hex<F: PrimeField>(field: &F) -> String {
+    let hex: String = field
+        .to_repr()
+        .as_ref()
+        .iter()
+        .rev()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("0x{}", hex)
+}
+
+/// Accepts exactly the output of `field_to_hex`
+pub fn field_from_hex<F: PrimeField>(hex: &str) -> Result<F, WireError> {
+    let mut repr = F::Repr::default();
+    let len = repr.as_ref().len();
+    let digits = hex
+        .strip_prefix("0x")
+        .filter(|digits| digits.len() == 2 * len)
+        .filter(|digits| {
+            digits
+                .bytes()
+                .all(|c| matches!(c, b'0'..=b'9' | b'a'..=b'f'))
+        })
+        .ok_or(WireError::NonCanonical)?;
+    for (i, byte) in repr.as_mut().iter_mut().rev().enumerate() {
+        *byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).unwrap();
+    }
+    Option::from(F::from_repr(repr)).ok_or(WireError::NonCanonical)
+}
+
+pub(crate) fn field_to_json<F: PrimeField>(field: &F) -> Value {
+    Value::String(field_to_hex(field))
+}
+
+pub(crate) fn field_from_json<F: PrimeField>(value: &Value) -> Result<F, WireError> {
+    field_from_hex(
+        value
+            .as_str()
+            .ok_or(WireError::Malformed("field element"))?,
+    )
+}
+
+pub(crate) fn fields_from_json<F: PrimeField>(value: &Value) -> Result<Vec<F>, WireError> {
+    value
+        .as_array()
+        .ok_or(WireError::Malformed("field elements"))?
+        .iter()
+        .map(field_from_json)
+        .collect()
+}
+
+impl Wire for PresentationPolicy {
+    const NAME: &'static str = "policy";
+    const KIND: u8 = POLICY;
+
+    fn write_body(&self, bytes: &mut Vec<u8>) {
+        write_u32(bytes, self.num_attributes);
+        write_u32(bytes, self.disclosed.len());
+        for i in self.disclosed.iter() {
+            write_u32(bytes, *i);
+        }
+    }
+
+    fn read_body(reader: &mut Reader<'_>) -> Result<Self, WireError> {
+        let num_attributes = reader.u32()? as usize;
+        let n = reader.u32()?;
+        let disclosed = (0..n)
+            .map(|_| Ok(reader.u32()? as usize))
+            .collect::<Result<Vec<_>, WireError>>()?;
+        let policy = PresentationPolicy {
+            num_attributes,
+            disclosed,
+        };
+        if !policy.is_valid() {
+            return Err(WireError::Malformed("policy"));
+        }
+        Ok(policy)
+    }
+
+    fn to_json_value(&self) -> Value {
+        json!({
+            "num_attributes": self.num_attributes,
+            "disclosed": self.disclosed,
+        })
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self, WireError> {
+        let index = |value: &Value| {
+            value
+                .as_u64()
+                .filter(|i| *i <= u32::MAX as u64)
+                .map(|i| i as usize)
+                .ok_or(WireError::Malformed("policy"))
+        };
+        let policy = PresentationPolicy {
+            num_attributes: index(field(value, "num_attributes")?)?,
+            disclosed: field(value, "disclosed")?
+                .as_array()
+                .ok_or(WireError::Malformed("policy"))?
+                .iter()
+                .map(index)
+                .collect::<Result<_, _>>()?,
+        };
+        if !policy.is_valid() {
+            return Err(WireError::Malformed("policy"));
+        }
+        Ok(policy)
+    }
+}
+
+impl Wire for IssuerPublicKey {
+    const NAME: &'static str = "issuer";
+    const KIND: u8 = ISSUER;
+
+    fn write_body(&self, bytes: &mut Vec<u8>) {
+        let coordinates = self.0.coordinates().unwrap();
+        bytes.extend(coordinates.x().to_repr().as_ref());
+        bytes.extend(coordinates.y().to_repr().as_ref());
+    }
+
+    fn read_body(reader: &mut Reader<'_>) -> Result<Self, WireError> {
+        let x = reader.field()?;
+        let y = reader.field()?;
+        Option::from(IssuerCurve::from_xy(x, y))
+            .map(IssuerPublicKey)
+            .ok_or(WireError::InvalidPoint)
+    }
+
+    fn to_json_value(&self) -> Value {
+        let coordinates = self.0.coordinates().unwrap();
+        json!({
+            "x": field_to_json(coordinates.x()),
+            "y": field_to_json(coordinates.y()),
+        })
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self, WireError> {
+        let x = field_from_json(field(value, "x")?)?;
+        let y = field_from_json(field(value, "y")?)?;
+        Option::from(IssuerCurve::from_xy(x, y))
+            .map(IssuerPublicKey)
+            .ok_or(WireError::InvalidPoint)
+    }
+}
+
+/// Secret keys are encoded like other artifacts so that tools can store them,
+/// they must never be sent
+impl Wire for IssuerSecretKey {
+    const NAME: &'static str = "issuer-secret-key";
+    const KIND: u8 = ISSUER_SECRET_KEY;
+
+    fn write_body(&self, bytes: &mut Vec<u8>) {
+        bytes.extend(self.0.to_repr().as_ref());
+    }
+
+    fn read_body(reader: &mut Reader<'_>) -> Result<Self, WireError> {
+        Ok(IssuerSecretKey(reader.field()?))
+    }
+
+    fn to_json_value(&self) -> Value {
+        json!({ "sk": field_to_json(&self.0) })
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self, WireError> {
+        Ok(IssuerSecretKey(field_from_json(field(value, "sk")?)?))
+    }
+}
+
+impl<N: PrimeField> Wire for Credential<N> {
+    const NAME: &'static str = "credential";
+    const KIND: u8 = CREDENTIAL;
+
+    fn write_body(&self, bytes: &mut Vec<u8>) {
+        self.issuer.write_body(bytes);
+        bytes.extend(self.signature.0.to_repr().as_ref());
+        bytes.extend(self.signature.1.to_repr().as_ref());
+        write_fields(bytes, &self.attributes);
+    }
+
+    fn read_body(reader: &mut Reader<'_>) -> Result<Self, WireError> {
+        let issuer = IssuerPublicKey::read_body(reader)?;
+        let signature = (reader.field()?, reader.field()?);
+        let attributes = reader.fields()?;
+        Ok(Credential {
+            attributes,
+            issuer,
+            signature,
+        })
+    }
+
+    fn to_json_value(&self) -> Value {
+        json!({
+            "issuer": self.issuer.to_json_value(),
+            "signature": {
+                "r": field_to_json(&self.signature.0),
+                "s": field_to_json(&self.signature.1),
+            },
+            "attributes": self.attributes.iter().map(field_to_json).collect::<Vec<_>>(),
+        })
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self, WireError> {
+        let signature = field(value, "signature")?;
+        Ok(Credential {
+            attributes: fields_from_json(field(value, "attributes")?)?,
+            issuer: IssuerPublicKey::from_json_value(field(value, "issuer")?)?,
+            signature: (
+                field_from_json(field(signature, "r")?)?,
+                field_from_json(field(signature, "s")?)?,
+            ),
+        })
+    }
+}
+
+impl<N: PrimeField> Wire for Presentation<N> {
+    const NAME: &'static str = "presentation";
+    const KIND: u8 = PRESENTATION;
+
+    fn write_body(&self, bytes: &mut Vec<u8>) {
+        self.policy.write_body(bytes);
+        self.issuer.write_body(bytes);
+        bytes.extend(self.nonce.to_repr().as_ref());
+        write_fields(bytes, &self.disclosed);
+        write_u32(bytes, self.proof.len());
+        bytes.extend(self.proof.iter());
+    }
+
+    fn read_body(reader: &mut Reader<'_>) -> Result<Self, WireError> {
+        let policy = PresentationPolicy::read_body(reader)?;
+        let issuer = IssuerPublicKey::read_body(reader)?;
+        let nonce = reader.field()?;
+        let disclosed = reader.fields()?;
+        let n = reader.u32()? as usize;
+        let proof = reader.take(n)?.to_vec();
+        presentation(policy, issuer, nonce, disclosed, proof)
+    }
+
+    fn to_json_value(&self) -> Value {
+        json!({
+            "policy": self.policy.to_json_value(),
+            "issuer": self.issuer.to_json_value(),
+            "nonce": field_to_json(&self.nonce),
+            "disclosed": self.disclosed.iter().map(field_to_json).collect::<Vec<_>>(),
+            "proof": BASE64.encode(&self.proof),
+        })
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self, WireError> {
+        let proof = field(value, "proof")?
+            .as_str()
+            .ok_or(WireError::Malformed("proof"))?;
+        let proof = BASE64
+            .decode(proof)
+            .map_err(|_| WireError::Malformed("proof"))?;
+        presentation(
+            PresentationPolicy::from_json_value(field(value, "policy")?)?,
+            IssuerPublicKey::from_json_value(field(value, "issuer")?)?,
+            field_from_json(field(value, "nonce")?)?,
+            fields_from_json(field(value, "disclosed")?)?,
+            proof,
+        )
+    }
+}
+
+fn presentation<N: PrimeField>(
+    policy: PresentationPolicy,
+    issuer: IssuerPublicKey,
+    nonce: N,
+    disclosed: Vec<N>,
+    proof: Vec<u8>,
+) -> Result<Presentation<N>, WireError> {
+    if disclosed.len() != policy.disclosed.len() {
+        return Err(WireError::Malformed(
+            "disclosed attributes do not match the policy",
+        ));
+    }
+    Ok(Presentation {
+        policy,
+        issuer,
+        nonce,
+        disclosed,
+        proof,
+    })
+}
+
+const STRING: u8 = 0;
+const DATE: u8 = 1;
+const ENUM: u8 = 2;
+
+fn checked_schema(schema: Schema) -> Result<Schema, WireError> {
+    schema.check().map_err(|_| WireError::Malformed("schema"))?;
+    Ok(schema)
+}
+
+impl Wire for Schema {
+    const NAME: &'static str = "schema";
+    const KIND: u8 = SCHEMA;
+
+    fn write_body(&self, bytes: &mut Vec<u8>) {
+        write_string(bytes, &self.name);
+        write_u32(bytes, self.version as usize);
+        write_u32(bytes, self.attributes.len());
+        for attribute in self.attributes.iter() {
+            write_string(bytes, &attribute.name);
+            match &attribute.kind {
+                AttributeType::String => bytes.push(STRING),
+                AttributeType::Date => bytes.push(DATE),
+                AttributeType::Enum(variants) => {
+                    bytes.push(ENUM);
+                    write_u32(bytes, variants.len());
+                    for variant in variants.iter() {
+                        write_string(bytes, variant);
+                    }
+                }
+            }
+            bytes.push(attribute.optional as u8);
+        }
+    }
+
+    fn read_body(reader: &mut Reader<'_>) -> Result<Self, WireError> {
+        let name = reader.string()?;
+        let version = reader.u32()?;
+        let n = reader.u32()?;
+        let attributes = (0..n)
+            .map(|_| {
+                let name = reader.string()?;
+                let kind = match reader.u8()? {
+                    STRING => AttributeType::String,
+                    DATE => AttributeType::Date,
+                    ENUM => {
+                        let n = reader.u32()?;
+                        AttributeType::Enum(
+                            (0..n).map(|_| reader.string()).collect::<Result<_, _>>()?,
+                        )
+                    }
+                    _ => return Err(WireError::Malformed("attribute type")),
+                };
+                let optional = match reader.u8()? {
+                    0 => false,
+                    1 => true,
+                    _ => return Err(WireError::Malformed("optional")),
+                };
+                Ok(AttributeDef {
+                    name,
+                    kind,
+                    optional,
+                })
+            })
+            .collect::<Result<_, WireError>>()?;
+        checked_schema(Schema {
+            name,
+            version,
+            attributes,
+        })
+    }
+
+    fn to_json_value(&self) -> Value {
+        let attributes: Vec<Value> = self
+            .attributes
+            .iter()
+            .map(|attribute| {
+                let mut value = json!({
+                    "name": attribute.name,
+                    "optional": attribute.optional,
+                });
+                let kind = match &attribute.kind {
+                    AttributeType::String => "string",
+                    AttributeType::Date => "date",
+                    AttributeType::Enum(variants) => {
+                        value["values"] = json!(variants);
+                        "enum"
+                    }
+                };
+                value["kind"] = json!(kind);
+                value
+            })
+            .collect();
+        json!({
+            "name": self.name,
+            "schema_version": self.version,
+            "attributes": attributes,
+        })
+    }
+
+    fn from_json_value(value: &Value) -> Result<Self, WireError> {
+        let string = |value: &Value, name: &'static str| {
+            field(value, name)?
+                .as_str()
+                .map(str::to_string)
+                .ok_or(WireError::Malformed(name))
+        };
+        let version = field(value, "schema_version")?
+            .as_u64()
+            .filter(|version| *version <= u32::MAX as u64)
+            .ok_or(WireError::Malformed("schema_version"))?;
+        let attributes = field(value, "attributes")?
+            .as_array()
+            .ok_or(WireError::Malformed("attributes"))?
+            .iter()
+            .map(|attribute| {
+                let kind = match string(attribute, "kind")?.as_str() {
+                    "string" => AttributeType::String,
+                    "date" => AttributeType::Date,
+                    "enum" => AttributeType::Enum(
+                        field(attribute, "values")?
+                            .as_array()
+                            .ok_or(WireError::Malformed("values"))?
+                            .iter()
+                            .map(|variant| {
+                                variant
+                                    .as_str()
+                                    .map(str::to_string)
+                                    .ok_or(WireError::Malformed("values"))
+                            })
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    _ => return Err(WireError::Malformed("kind")),
+                };
+                Ok(AttributeDef {
+                    name: string(attribute, "name")?,
+                    kind,
+                    optional: field(attribute, "optional")?
+                        .as_bool()
+                        .ok_or(WireError::Malformed("optional"))?,
+                })
+            })
+            .collect::<Result<_, WireError>>()?;
+        checked_schema(Schema {
+            name: string(value, "name")?,
+            version: version as u32,
+            attributes,
+        })
+    }
+}
@@ -0,0 +1,140 @@
+use crate::credential::{
+    Credential, IssuerPublicKey, IssuerSecretKey, Presentation, PresentationPolicy,
+};
+use crate::schema::{AttributeDef, AttributeType, Schema};
+use crate::wire::{Wire, WireError};
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::Field;
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+use std::fmt::Debug;
+
+fn round_trip<T: Wire + PartialEq + Debug>(artifact: &T) {
+    let bytes = artifact.to_bytes();
+    assert_eq!(&T::from_bytes(&bytes).unwrap(), artifact);
+    let json = artifact.to_json();
+    assert_eq!(&T::from_json(&json).unwrap(), artifact);
+    assert_eq!(T::from_json(&json).unwrap().to_json(), json);
+
+    // Error case: every strict prefix is truncated
+    for n in 5..bytes.len() {
+        assert_eq!(T::from_bytes(&bytes[..n]), Err(WireError::Truncated));
+    }
+
+    // Error case: trailing bytes
+    let mut extended = bytes.clone();
+    extended.push(0);
+    assert_eq!(T::from_bytes(&extended), Err(WireError::TrailingBytes(1)));
+
+    // Error case: unknown version
+    let mut bumped = bytes;
+    bumped[3] += 1;
+    assert_eq!(T::from_bytes(&bumped), Err(WireError::UnknownVersion(2)));
+    let json = json.replace("\"version\":1", "\"version\":2");
+    assert_eq!(T::from_json(&json), Err(WireError::UnknownVersion(2)));
+}
+
+fn artifacts() -> (PresentationPolicy, Credential<Fr>, Presentation<Fr>) {
+    let mut rng = StdRng::seed_from_u64(0);
+    let sk = IssuerSecretKey::random(&mut rng);
+    let attributes = (0..4).map(|_| Fr::random(&mut rng)).collect();
+    let credential = Credential::issue(&sk, attributes, &mut rng);
+    let policy = PresentationPolicy::new(4, vec![0, 2]);
+    let presentation = Presentation {
+        policy: policy.clone(),
+        issuer: credential.issuer,
+        nonce: Fr::random(&mut rng),
+        disclosed: vec![credential.attributes[0], credential.attributes[2]],
+        proof: (0..=255).collect(),
+    };
+    (policy, credential, presentation)
+}
+
+#[test]
+fn test_round_trip() {
+    let (policy, credential, presentation) = artifacts();
+    round_trip(&policy);
+    round_trip(&credential.issuer);
+    round_trip(&credential);
+    round_trip(&presentation);
+    round_trip(
+        &Schema::new(
+            "identity",
+            2,
+            vec![
+                AttributeDef::new("dob", AttributeType::Date),
+                AttributeDef::optional("country", AttributeType::Enum(vec!["FR".to_string()])),
+            ],
+        )
+        .unwrap(),
+    );
+    assert!(Credential::<Fr>::from_bytes(&credential.to_bytes())
+        .unwrap()
+        .verify());
+}
+
+#[test]
+fn test_invalid_artifacts() {
+    let (policy, credential, presentation) = artifacts();
+
+    // Error case: another kind of artifact
+    assert_eq!(
+        Presentation::<Fr>::from_bytes(&credential.to_bytes()),
+        Err(WireError::UnexpectedKind {
+            expected: "presentation",
+            found: "credential".to_string()
+        })
+    );
+    assert!(matches!(
+        PresentationPolicy::from_json(&credential.to_json()),
+        Err(WireError::UnexpectedKind { .. })
+    ));
+    assert_eq!(
+        PresentationPolicy::from_bytes(b"not an artifact"),
+        Err(WireError::BadMagic)
+    );
+
+    // Error case: attribute at or above the modulus, the last field element
+    // of the encoding
+    let mut bytes = credential.to_bytes();
+    let n = bytes.len();
+    bytes[n - 32..].copy_from_slice(&[0xff; 32]);
+    assert_eq!(
+        Credential::<Fr>::from_bytes(&bytes),
+        Err(WireError::NonCanonical)
+    );
+
+    // Error case: non canonical hex in JSON
+    let nonce = presentation.to_json_value()["nonce"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    for non_canonical in [
+        nonce.to_uppercase().replace("0X", "0x"),
+        nonce.replacen("0x", "0x0", 1),
+        nonce.trim_start_matches("0x").to_string(),
+        format!("0x{}", "f".repeat(64)),
+    ] {
+        let json = presentation.to_json().replace(&nonce, &non_canonical);
+        assert_eq!(
+            Presentation::<Fr>::from_json(&json),
+            Err(WireError::NonCanonical)
+        );
+    }
+
+    // Error case: issuer key off the curve
+    let mut bytes = credential.issuer.to_bytes();
+    bytes[5] ^= 1;
+    assert_eq!(
+        IssuerPublicKey::from_bytes(&bytes),
+        Err(WireError::InvalidPoint)
+    );
+
+    // Error case: disclosed positions out of order
+    let mut bytes = policy.to_bytes();
+    bytes.swap(13, 17);
+    assert!(matches!(
+        PresentationPolicy::from_bytes(&bytes),
+        Err(WireError::Malformed(_))
+    ));
+}
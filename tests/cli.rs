@@ -0,0 +1,263 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+fn recreds(dir: &Path, args: &[&str]) -> Output {
+    let output = Command::new(env!("CARGO_BIN_EXE_recreds"))
+        .current_dir(dir)
+        .args(args)
+        .output()
+        .unwrap();
+    println!("{}", String::from_utf8_lossy(&output.stdout));
+    eprintln!("{}", String::from_utf8_lossy(&output.stderr));
+    output
+}
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("recreds-cli-{}-{}", name, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+const SCHEMA: &str = r#"{
+    "type": "schema",
+    "version": 1,
+    "name": "identity",
+    "schema_version": 1,
+    "attributes": [
+        { "name": "name", "kind": "string", "optional": false },
+        { "name": "dob", "kind": "date", "optional": false },
+        { "name": "country", "kind": "enum", "values": ["FR", "DE"], "optional": false }
+    ]
+}"#;
+
+#[test]
+fn test_issue_and_inspect() {
+    let dir = temp_dir("issue");
+    std::fs::write(dir.join("schema.json"), SCHEMA).unwrap();
+    let run = |args: &[&str]| recreds(&dir, args);
+
+    assert!(run(&[
+        "issuer",
+        "keygen",
+        "--out",
+        "issuer.sk",
+        "--pub",
+        "issuer.pub.json"
+    ])
+    .status
+    .success());
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let metadata = std::fs::metadata(dir.join("issuer.sk")).unwrap();
+        assert_eq!(metadata.permissions().mode() & 0o777, 0o600);
+    }
+    for out in ["cred.bin", "cred.json"] {
+        let output = run(&[
+            "issuer",
+            "issue",
+            "--key",
+            "issuer.sk",
+            "--schema",
+            "schema.json",
+            "--attr",
+            "name=Alice Doe",
+            "--attr",
+            "dob=1990-01-01",
+            "--attr",
+            "country=DE",
+            "--out",
+            out,
+        ]);
+        assert!(output.status.success());
+
+        let output = run(&["inspect", out]);
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("\"type\": \"credential\""));
+        assert!(stdout.contains("issuer signature valid: true"));
+    }
+
+    let output = run(&["inspect", "issuer.pub.json"]);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("\"type\": \"issuer\""));
+
+    // The schema was registered on issuance
+    let hash = String::from_utf8(run(&["schema", "register", "schema.json"]).stdout).unwrap();
+    let output = run(&["inspect", "schema.json"]);
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains(&format!("hash: {}", hash.trim())));
+    assert!(dir
+        .join("schemas")
+        .join(format!("{}.json", &hash.trim()[2..]))
+        .exists());
+
+    // Error cases: missing attribute, invalid date
+    for attrs in [
+        ["--attr", "name=Alice", "--attr", "dob=1990-01-01"],
+        ["--attr", "name=Alice", "--attr", "dob=1990-13-01"],
+    ] {
+        let mut args = vec![
+            "issuer",
+            "issue",
+            "--key",
+            "issuer.sk",
+            "--schema",
+            "schema.json",
+            "--attr",
+            "country=FR",
+            "--out",
+            "bad.bin",
+        ];
+        args.extend(attrs);
+        assert!(!run(&args).status.success());
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn test_report() {
+    let dir = temp_dir("report");
+    let output = recreds(&dir, &["report", "--attributes", "3", "--disclosed", "0,2"]);
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)
+        .unwrap()
+        .contains("max degree"));
+
+    let output = recreds(&dir, &["report", "--json"]);
+    assert!(output.status.success());
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert!(report["k"].as_u64().unwrap() > 0);
+
+    // Error case: disclosed position out of range
+    let output = recreds(&dir, &["report", "--attributes", "2", "--disclosed", "2"]);
+    assert!(!output.status.success());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Runs the whole flow with insecure params, as a shell script would
+#[test]
+fn test_show_and_verify() {
+    let dir = temp_dir("flow");
+    let run = |args: &[&str]| {
+        let output = recreds(&dir, args);
+        assert!(output.status.success(), "recreds {:?} failed", args);
+        output
+    };
+
+    std::fs::write(dir.join("schema.json"), SCHEMA).unwrap();
+    // Smallest params for the schema hash, dob and country out of 4
+    let output = run(&[
+        "report",
+        "--attributes",
+        "4",
+        "--disclosed",
+        "0,2,3",
+        "--json",
+    ]);
+    let report: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    let k = report["k"].as_u64().unwrap().to_string();
+    run(&["setup", "--k", &k, "--out", "params.bin"]);
+    run(&[
+        "issuer",
+        "keygen",
+        "--out",
+        "issuer.sk",
+        "--pub",
+        "issuer.pub",
+    ]);
+    run(&[
+        "issuer",
+        "issue",
+        "--key",
+        "issuer.sk",
+        "--schema",
+        "schema.json",
+        "--attr",
+        "name=Alice",
+        "--attr",
+        "dob=1990-01-01",
+        "--attr",
+        "country=FR",
+        "--out",
+        "cred.bin",
+    ]);
+    run(&[
+        "holder",
+        "show",
+        "--params",
+        "params.bin",
+        "--credential",
+        "cred.bin",
+        "--disclose",
+        "dob,country",
+        "--nonce",
+        "42",
+        "--out",
+        "pres.json",
+    ]);
+    let output = run(&[
+        "verifier",
+        "verify",
+        "--params",
+        "params.bin",
+        "--presentation",
+        "pres.json",
+        "--issuer",
+        "issuer.pub",
+        "--nonce",
+        "42",
+    ]);
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("valid presentation"));
+    assert!(stdout.contains("schema: identity version 1"));
+    assert!(stdout.contains("dob: 0x"));
+
+    // Error cases: another challenge, another issuer, no issuer
+    run(&[
+        "issuer",
+        "keygen",
+        "--out",
+        "other.sk",
+        "--pub",
+        "other.pub",
+    ]);
+    for (issuer, nonce) in [("issuer.pub", "43"), ("other.pub", "42")] {
+        let output = recreds(
+            &dir,
+            &[
+                "verifier",
+                "verify",
+                "--params",
+                "params.bin",
+                "--presentation",
+                "pres.json",
+                "--issuer",
+                issuer,
+                "--nonce",
+                nonce,
+            ],
+        );
+        assert!(!output.status.success());
+    }
+    let output = recreds(
+        &dir,
+        &[
+            "verifier",
+            "verify",
+            "--params",
+            "params.bin",
+            "--presentation",
+            "pres.json",
+            "--nonce",
+            "42",
+        ],
+    );
+    assert!(!output.status.success());
+
+    run(&["inspect", "pres.json"]);
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,171 @@
+use halo2_proofs::halo2curves::bn256::Fr;
+use halo2_proofs::halo2curves::ff::Field;
+use rand::rngs::OsRng;
+use recreds::service::{bind, Service, MAX_ATTRIBUTES};
+use recreds::sizing::estimate_k;
+use recreds::wire::{field_to_hex, Wire};
+use recreds::{
+    present, Backend, Credential, IssuerPublicKey, KeyStore, Kzg, ParamsCache, PresentationCircuit,
+    PresentationPolicy,
+};
+use serde_json::{json, Value};
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+
+/// Sends one request and returns the status and JSON body of the reply
+fn request(addr: &str, method: &str, path: &str, body: &Value) -> (u16, Value) {
+    let body = body.to_string();
+    let mut stream = TcpStream::connect(addr).unwrap();
+    write!(
+        stream,
+        "{} {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        method,
+        path,
+        addr,
+        body.len(),
+        body
+    )
+    .unwrap();
+    let mut reply = String::new();
+    stream.read_to_string(&mut reply).unwrap();
+    let (head, body) = reply.split_once("\r\n\r\n").unwrap();
+    let status = head.split(' ').nth(1).unwrap().parse().unwrap();
+    (status, serde_json::from_str(body).unwrap())
+}
+
+#[test]
+fn test_issue_and_verify() {
+    let dir = std::env::temp_dir().join(format!("recreds-server-{}", std::process::id()));
+    let policy = PresentationPolicy::new(4, vec![1, 3]);
+    let k = estimate_k::<Fr>(&policy, None).unwrap().k;
+    let params = Kzg::setup(k, OsRng);
+    let keys = KeyStore::new(dir.join("keys")).unwrap();
+
+    // The stand-in issuer refuses credentials whose first attribute is zero
+    let service = Service::new(ParamsCache::new(params.clone()), keys, dir.join("issuers"))
+        .unwrap()
+        .with_hook(|_: &str, attributes: &[Fr]| {
+            if attributes.first() == Some(&Fr::ZERO) {
+                Err("first attribute must be set".to_string())
+            } else {
+                Ok(())
+            }
+        });
+    let issuer = service.add_issuer("dmv").unwrap();
+
+    let server = bind("127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = server.server_addr().to_ip().unwrap().to_string();
+    let service = Arc::new(service);
+    std::thread::spawn({
+        let service = service.clone();
+        move || service.serve(&server)
+    });
+
+    let (status, body) = request(&addr, "GET", "/issuer/dmv/pubkey", &Value::Null);
+    assert_eq!(status, 200);
+    assert_eq!(
+        IssuerPublicKey::from_json(&body.to_string()).unwrap(),
+        issuer
+    );
+
+    let attributes: Vec<Fr> = (1..=4).map(Fr::from).collect();
+    let (status, body) = request(
+        &addr,
+        "POST",
+        "/issue",
+        &json!({
+            "issuer": "dmv",
+            "attributes": attributes.iter().map(field_to_hex).collect::<Vec<_>>(),
+        }),
+    );
+    assert_eq!(status, 200);
+    let credential = Credential::<Fr>::from_json(&body.to_string()).unwrap();
+    assert_eq!(credential.attributes, attributes);
+    assert_eq!(credential.issuer, issuer);
+    assert!(credential.verify());
+
+    // Error cases: refused by the hook, unknown issuer
+    let refused = json!({ "issuer": "dmv", "attributes": [field_to_hex(&Fr::ZERO)] });
+    assert_eq!(request(&addr, "POST", "/issue", &refused).0, 403);
+    let unknown = json!({ "issuer": "registry", "attributes": [field_to_hex(&Fr::ONE)] });
+    assert_eq!(request(&addr, "POST", "/issue", &unknown).0, 404);
+
+    // The holder proves with the keys the service verifies with
+    let pk = KeyStore::new(dir.join("keys"))
+        .unwrap()
+        .load_or_generate(
+            &params,
+            &policy.circuit_id(),
+            &PresentationCircuit::new(policy.clone()),
+        )
+        .unwrap();
+    let nonce = Fr::from(42);
+    let presentation = present::<Kzg>(&params, &pk, &credential, &policy, nonce, OsRng).unwrap();
+    let encoded: Value = serde_json::from_str(&presentation.to_json()).unwrap();
+
+    let (status, body) = request(
+        &addr,
+        "POST",
+        "/verify",
+        &json!({
+            "presentation": encoded,
+            "issuer": "dmv",
+            "nonce": field_to_hex(&nonce),
+        }),
+    );
+    assert_eq!(status, 200);
+    assert_eq!(body["valid"], true);
+    assert_eq!(body["disclosed"]["1"], field_to_hex(&attributes[1]));
+    assert_eq!(body["disclosed"]["3"], field_to_hex(&attributes[3]));
+
+    // Error cases: another challenge, no nonce, no issuer, unknown issuer
+    let verify = |body: Value| request(&addr, "POST", "/verify", &body).0;
+    let nonce = field_to_hex(&nonce);
+    assert_eq!(
+        verify(json!({
+            "presentation": encoded,
+            "issuer": "dmv",
+            "nonce": field_to_hex(&Fr::from(43)),
+        })),
+        422
+    );
+    assert_eq!(
+        verify(json!({ "presentation": encoded, "issuer": "dmv" })),
+        400
+    );
+    assert_eq!(
+        verify(json!({ "presentation": encoded, "nonce": nonce })),
+        400
+    );
+    assert_eq!(
+        verify(json!({ "presentation": encoded, "issuer": "registry", "nonce": nonce })),
+        404
+    );
+
+    // Error case: a policy too large to size a circuit for
+    let mut oversized = encoded.clone();
+    oversized["policy"]["num_attributes"] = json!(MAX_ATTRIBUTES + 1);
+    assert_eq!(
+        verify(json!({ "presentation": oversized, "issuer": "dmv", "nonce": nonce })),
+        422
+    );
+
+    // Error case: a policy without a verifying key, which the service does not generate
+    let mut unknown = encoded.clone();
+    unknown["policy"]["disclosed"] = json!([0, 2]);
+    assert_eq!(
+        verify(json!({ "presentation": unknown, "issuer": "dmv", "nonce": nonce })),
+        422
+    );
+    let other = PresentationPolicy::new(4, vec![0, 2]);
+    assert!(!std::fs::read_dir(dir.join("keys"))
+        .unwrap()
+        .any(|entry| entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with(&other.circuit_id())));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}